@@ -5,6 +5,7 @@
 
 module Client
 ( createCacClient
+, createCacClientWithApiKey
 , getCacClient
 , getFullConfigStateWithFilter
 , getCacLastModified
@@ -35,7 +36,7 @@ type Tenant = String
 type Error = String
 
 foreign import ccall unsafe "cac_new_client"
-    c_new_cac_client :: CTenant -> CULong -> CString -> IO CInt
+    c_new_cac_client :: CTenant -> CULong -> CString -> CString -> IO CInt
 
 foreign import ccall unsafe "&cac_free_client"
     c_free_cac_client :: FunPtr (Ptr CacClient -> IO ())
@@ -81,12 +82,16 @@ cleanup :: [Ptr a] -> IO ()
 cleanup items = mapM free items $> ()
 
 createCacClient:: Tenant -> Integer -> String -> IO (Either Error ())
-createCacClient tenant frequency hostname = do
+createCacClient tenant frequency hostname = createCacClientWithApiKey tenant frequency hostname Nothing
+
+createCacClientWithApiKey:: Tenant -> Integer -> String -> Maybe String -> IO (Either Error ())
+createCacClientWithApiKey tenant frequency hostname apiKey = do
     let duration = fromInteger frequency
     cTenant   <- newCAString tenant
     cHostname <- newCAString hostname
-    resp      <- c_new_cac_client cTenant duration cHostname
-    _         <- cleanup [cTenant, cHostname]
+    cApiKey   <- maybe (pure nullPtr) newCAString apiKey
+    resp      <- c_new_cac_client cTenant duration cHostname cApiKey
+    _         <- cleanup [cTenant, cHostname, cApiKey]
     case resp of
         0 -> pure $ Right ()
         _ -> Left <$> getError
@@ -7,7 +7,10 @@ module Client
 ( expStartPolling
 , getExpClient
 , createExpClient
+, createExpClientWithApiKey
+, createExpClientWithKeyPrefixes
 , getApplicableVariants
+, tossOf
 , getSatisfiedExperiments
 , getFilteredSatisfiedExperiments
 , getRunningExperiments
@@ -15,12 +18,14 @@ module Client
 
 import           Data.Aeson.Types
 import           Data.Functor          (($>))
+import           Data.List             (intercalate)
 import           Foreign               (FunPtr, Ptr)
 import           Foreign.C             (CInt (..), CShort (..), CULong (..))
 import           Foreign.C.String
 import           Foreign.ForeignPtr
-import           Foreign.Marshal.Alloc (free)
+import           Foreign.Marshal.Alloc (alloca, free)
 import           Foreign.Ptr           (nullPtr)
+import           Foreign.Storable      (peek)
 import           Prelude
 
 data Arc_Client
@@ -33,7 +38,7 @@ type Tenant = String
 type Error = String
 
 foreign import ccall unsafe "expt_new_client"
-    c_new_expt_client :: CTenant -> CULong -> CString -> IO CInt
+    c_new_expt_client :: CTenant -> CULong -> CString -> CString -> CString -> IO CInt
 
 foreign import ccall unsafe "&expt_free_client"
     c_free_expt_client :: FunPtr (Ptr ExpClient -> IO ())
@@ -53,6 +58,9 @@ foreign import ccall unsafe "expt_start_polling_update"
 foreign import ccall unsafe "expt_get_applicable_variant"
     c_get_applicable_variants :: Ptr ExpClient -> CString -> CShort -> IO CString
 
+foreign import ccall unsafe "expt_toss_of"
+    c_toss_of :: CString -> Ptr CShort -> IO CInt
+
 foreign import ccall unsafe "expt_get_satisfied_experiments"
     c_get_satisfied_experiments :: Ptr ExpClient -> CString -> CString -> IO CString
 
@@ -77,12 +85,20 @@ cleanup :: [Ptr a] -> IO ()
 cleanup items = mapM free items $> ()
 
 createExpClient:: Tenant -> Integer -> String -> IO (Either Error ())
-createExpClient tenant frequency hostname = do
+createExpClient tenant frequency hostname = createExpClientWithApiKey tenant frequency hostname Nothing
+
+createExpClientWithApiKey:: Tenant -> Integer -> String -> Maybe String -> IO (Either Error ())
+createExpClientWithApiKey tenant frequency hostname apiKey = createExpClientWithKeyPrefixes tenant frequency hostname apiKey Nothing
+
+createExpClientWithKeyPrefixes:: Tenant -> Integer -> String -> Maybe String -> Maybe [String] -> IO (Either Error ())
+createExpClientWithKeyPrefixes tenant frequency hostname apiKey keyPrefixes = do
     let duration = fromInteger frequency
-    cTenant   <- newCAString tenant
-    cHostname <- newCAString hostname
-    resp      <- c_new_expt_client cTenant duration cHostname
-    _         <- cleanup [cTenant, cHostname]
+    cTenant      <- newCAString tenant
+    cHostname    <- newCAString hostname
+    cApiKey      <- maybe (pure nullPtr) newCAString apiKey
+    cKeyPrefixes <- maybe (pure nullPtr) (newCAString . intercalate ",") keyPrefixes
+    resp         <- c_new_expt_client cTenant duration cHostname cApiKey cKeyPrefixes
+    _            <- cleanup [cTenant, cHostname, cApiKey, cKeyPrefixes]
     case resp of
         0 -> pure $ Right ()
         _ -> Left <$> getError
@@ -111,6 +127,17 @@ getApplicableVariants client query toss = do
                     -- Error s     -> Left s
                     -- Success vec -> Right vec
 
+tossOf :: String -> IO (Either Error Integer)
+tossOf identifier = do
+    cIdentifier <- newCAString identifier
+    toss        <- alloca $ \cToss -> do
+        resp <- c_toss_of cIdentifier cToss
+        case resp of
+            0 -> Right . toInteger <$> peek cToss
+            _ -> Left <$> getError
+    _           <- cleanup [cIdentifier]
+    pure toss
+
 getSatisfiedExperiments :: ForeignPtr ExpClient -> String -> Maybe String -> IO (Either Error Value)
 getSatisfiedExperiments client query mbPrefix = do
     context     <- newCAString query
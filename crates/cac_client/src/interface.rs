@@ -102,15 +102,21 @@ pub extern "C" fn cac_new_client(
     tenant: *const c_char,
     update_frequency: c_ulong,
     hostname: *const c_char,
+    api_key: *const c_char,
 ) -> c_int {
     let duration = Duration::new(update_frequency, 0);
     let tenant = unwrap_safe!(cstring_to_rstring(tenant), return 1);
     let hostname = unwrap_safe!(cstring_to_rstring(hostname), return 1);
+    // the api key is optional, NULL means no key
+    let api_key = match api_key.is_null() {
+        true => None,
+        false => Some(unwrap_safe!(cstring_to_rstring(api_key), return 1)),
+    };
 
     // println!("Creating cac client thread for tenant {tenant}");
     CAC_RUNTIME.block_on(async move {
         match CLIENT_FACTORY
-            .create_client(tenant.clone(), duration, hostname)
+            .create_client(tenant.clone(), duration, hostname, api_key)
             .await
         {
             Ok(_) => 0,
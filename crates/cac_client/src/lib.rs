@@ -89,12 +89,16 @@ impl Client {
         tenant: String,
         polling_interval: Duration,
         hostname: String,
+        api_key: Option<String>,
     ) -> Result<Self, String> {
         let reqw_client = reqwest::Client::builder().build().map_err_to_string()?;
         let cac_endpoint = format!("{hostname}/config");
-        let reqw = reqw_client
+        let mut reqw = reqw_client
             .get(cac_endpoint)
             .header("x-tenant", tenant.to_string());
+        if let Some(api_key) = api_key {
+            reqw = reqw.header("x-api-key", api_key);
+        }
 
         let reqwc = clone_reqw(&reqw)?;
         let resp = reqwc.send().await.map_err_to_string()?;
@@ -233,6 +237,7 @@ impl ClientFactory {
         tenant: String,
         polling_interval: Duration,
         hostname: String,
+        api_key: Option<String>,
     ) -> Result<Arc<Client>, String> {
         let mut factory = self.write().await;
 
@@ -240,8 +245,9 @@ impl ClientFactory {
             return Ok(client.clone());
         }
 
-        let client =
-            Arc::new(Client::new(tenant.to_string(), polling_interval, hostname).await?);
+        let client = Arc::new(
+            Client::new(tenant.to_string(), polling_interval, hostname, api_key).await?,
+        );
         factory.insert(tenant.to_string(), client.clone());
         Ok(client.clone())
     }
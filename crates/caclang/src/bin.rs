@@ -1,51 +1,135 @@
-use std::fs;
+use std::{fs, process::ExitCode};
 
-use caclang::{ContextAwareConfig, DataType};
-use clap::Parser;
-use evalexpr::{ContextWithMutableVariables, HashMapContext};
-use inquire::{CustomType, Text};
+use anyhow::anyhow;
+use caclang::{
+    diagnostics::{lint, validate, Diagnostic, Severity},
+    sync::{compile, plan, Credentials, SyncClient},
+    ContextAwareConfig, DataType,
+};
+use clap::{Parser, Subcommand, ValueEnum};
+use inquire::{Confirm, CustomType, Text};
+use serde_json::{json, Map, Value};
 
 #[derive(Parser, Debug)]
 #[command(version, about)]
 struct Args {
-    #[arg(short, long)]
-    file: String,
+    #[command(subcommand)]
+    command: Command,
 }
 
-pub fn main() -> anyhow::Result<()> {
-    env_logger::init();
-    let args = Args::parse();
-    let config = fs::read_to_string(args.file)?;
-    let cac = ContextAwareConfig::parse(config)?;
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum DumpFormat {
+    Json,
+    Yaml,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Interactively evaluate the config for dimension values
+    Eval {
+        #[arg(short, long)]
+        file: String,
+        /// Only print this config key
+        #[arg(short, long)]
+        key: Option<String>,
+    },
+    /// Print the config resolved for the given dimension values
+    Resolve {
+        #[arg(short, long)]
+        file: String,
+        /// Dimension value in the form dimension=value, can be repeated
+        #[arg(long = "ctx", value_name = "DIMENSION=VALUE")]
+        ctx: Vec<String>,
+        /// Only print this config key
+        #[arg(short, long)]
+        key: Option<String>,
+    },
+    /// Report every error in the config file
+    Validate {
+        #[arg(short, long)]
+        file: String,
+    },
+    /// Warn about unreachable contexts, shadowed overrides and unused default configs
+    Lint {
+        #[arg(short, long)]
+        file: String,
+        /// Exit with a failure status when there are warnings
+        #[arg(long)]
+        deny_warnings: bool,
+    },
+    /// Print the config in the format the CAC server serves it
+    Dump {
+        #[arg(short, long)]
+        file: String,
+        #[arg(long, value_enum, default_value = "json")]
+        format: DumpFormat,
+    },
+    /// Diff the config against a tenant and optionally apply the changes
+    Sync {
+        #[arg(short, long)]
+        file: String,
+        /// Base URL of the superposition server
+        #[arg(long, default_value = "http://localhost:8080")]
+        host: String,
+        #[arg(short, long)]
+        tenant: String,
+        /// API key to authenticate with
+        #[arg(long, env = "CAC_API_KEY", conflicts_with = "token")]
+        api_key: Option<String>,
+        /// Bearer token to authenticate with
+        #[arg(long, env = "CAC_TOKEN")]
+        token: Option<String>,
+        /// Apply the plan instead of only printing it
+        #[arg(long)]
+        apply: bool,
+        /// Skip the confirmation prompt before applying
+        #[arg(long)]
+        auto_approve: bool,
+    },
+}
+
+fn load(file: &str) -> anyhow::Result<ContextAwareConfig> {
+    ContextAwareConfig::parse(fs::read_to_string(file)?)
+}
+
+fn print_resolved(
+    cac: &ContextAwareConfig,
+    query: &Map<String, Value>,
+    key: &Option<String>,
+) -> anyhow::Result<()> {
+    let resolved = cac.resolve(query)?;
+    let output = match key {
+        Some(key) => resolved
+            .get(key)
+            .cloned()
+            .ok_or(anyhow!("{key} is not present in default-configs section"))?,
+        None => Value::Object(resolved),
+    };
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn eval(cac: ContextAwareConfig, key: Option<String>) -> anyhow::Result<()> {
     loop {
-        let mut context = HashMapContext::new();
+        let mut query = Map::new();
         for (dimension, props) in cac.dimensions.iter() {
-            if props.data_type == DataType::String {
-                if let Some(user_value) = Text::new(
-                    format!("Value for {dimension}, hit Esc key to skip").as_str(),
-                )
-                .prompt_skippable()?
-                {
-                    context.set_value(dimension.clone(), user_value.into())?;
-                }
-            } else if props.data_type == DataType::Number {
-                if let Some(user_value) = CustomType::<i64>::new(
-                    format!("Value for {dimension}, hit Esc key to skip").as_str(),
-                )
-                .prompt_skippable()?
-                {
-                    context.set_value(dimension.clone(), user_value.into())?;
+            let prompt = format!("Value for {dimension}, hit Esc key to skip");
+            let value = match props.data_type {
+                DataType::String => {
+                    Text::new(&prompt).prompt_skippable()?.map(Value::from)
                 }
-            } else if let Some(user_value) = CustomType::<bool>::new(
-                format!("Value for {dimension}, hit Esc key to skip").as_str(),
-            )
-            .prompt_skippable()?
-            {
-                context.set_value(dimension.clone(), user_value.into())?;
+                DataType::Number => CustomType::<i64>::new(&prompt)
+                    .prompt_skippable()?
+                    .map(Value::from),
+                DataType::Boolean => CustomType::<bool>::new(&prompt)
+                    .prompt_skippable()?
+                    .map(Value::from),
             };
+            if let Some(value) = value {
+                query.insert(dimension.clone(), value);
+            }
         }
-        let v = cac.get_config("android_hyperpay_configuration", &context)?;
-        println!("config value: {v}");
+        print_resolved(&cac, &query, &key)?;
         if CustomType::<bool>::new("Hit Esc to stop, enter to continue")
             .prompt_skippable()?
             .is_none()
@@ -54,3 +138,127 @@ pub fn main() -> anyhow::Result<()> {
         }
     }
 }
+
+fn resolve(
+    cac: ContextAwareConfig,
+    ctx: Vec<String>,
+    key: Option<String>,
+) -> anyhow::Result<()> {
+    let mut query = Map::new();
+    for item in ctx.iter() {
+        let (dimension, value) = item
+            .split_once('=')
+            .ok_or(anyhow!("invalid context {item}, expected dimension=value"))?;
+        let props = cac.dimensions.get(dimension).ok_or(anyhow!(
+            "Dimension {dimension} is not defined in dimensions section"
+        ))?;
+        let value = match props.data_type {
+            DataType::String => json!(value),
+            DataType::Number => value
+                .parse::<i64>()
+                .map(Value::from)
+                .or_else(|_| value.parse::<f64>().map(Value::from))
+                .map_err(|_| anyhow!("{dimension} expects a number, found {value}"))?,
+            DataType::Boolean => value
+                .parse::<bool>()
+                .map(Value::from)
+                .map_err(|_| anyhow!("{dimension} expects a boolean, found {value}"))?,
+        };
+        query.insert(dimension.to_string(), value);
+    }
+    print_resolved(&cac, &query, &key)
+}
+
+fn report(file: &str, diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics.iter() {
+        eprintln!("{file}:{diagnostic}");
+    }
+}
+
+fn dump(cac: ContextAwareConfig, format: DumpFormat) -> anyhow::Result<()> {
+    let config = cac.to_config()?;
+    match format {
+        DumpFormat::Json => println!("{}", serde_json::to_string_pretty(&config)?),
+        DumpFormat::Yaml => print!("{}", serde_yaml::to_string(&config)?),
+    }
+    Ok(())
+}
+
+fn sync(
+    cac: ContextAwareConfig,
+    host: String,
+    tenant: String,
+    credentials: Option<Credentials>,
+    apply: bool,
+    auto_approve: bool,
+) -> anyhow::Result<()> {
+    let local = compile(&cac)?;
+    let client = SyncClient::new(host, tenant, credentials);
+    let remote = client.fetch()?;
+    let plan = plan(&local, &remote);
+    println!("{plan}");
+    if !apply || plan.is_empty() {
+        return Ok(());
+    }
+    if !auto_approve
+        && !Confirm::new("Do you want to apply these changes?")
+            .with_default(false)
+            .prompt()?
+    {
+        println!("Apply cancelled.");
+        return Ok(());
+    }
+    client.apply(&plan)?;
+    let (add, change, destroy) = plan.summary();
+    println!("\nApply complete! {add} added, {change} changed, {destroy} destroyed.");
+    Ok(())
+}
+
+pub fn main() -> anyhow::Result<ExitCode> {
+    env_logger::init();
+    let args = Args::parse();
+    match args.command {
+        Command::Eval { file, key } => eval(load(&file)?, key)?,
+        Command::Resolve { file, ctx, key } => resolve(load(&file)?, ctx, key)?,
+        Command::Validate { file } => {
+            let diagnostics = validate(&fs::read_to_string(&file)?);
+            report(&file, &diagnostics);
+            if !diagnostics.is_empty() {
+                return Ok(ExitCode::FAILURE);
+            }
+            println!("{file} is valid");
+        }
+        Command::Lint {
+            file,
+            deny_warnings,
+        } => {
+            let source = fs::read_to_string(&file)?;
+            let errors = validate(&source);
+            if !errors.is_empty() {
+                report(&file, &errors);
+                return Ok(ExitCode::FAILURE);
+            }
+            let warnings = lint(&ContextAwareConfig::parse(source.clone())?, &source);
+            report(&file, &warnings);
+            if deny_warnings && warnings.iter().any(|w| w.severity == Severity::Warning) {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::Dump { file, format } => dump(load(&file)?, format)?,
+        Command::Sync {
+            file,
+            host,
+            tenant,
+            api_key,
+            token,
+            apply,
+            auto_approve,
+        } => {
+            let credentials = api_key
+                .map(Credentials::ApiKey)
+                .or(token.map(Credentials::Token));
+            sync(load(&file)?, host, tenant, credentials, apply, auto_approve)?
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,120 @@
+use anyhow::anyhow;
+use evalexpr::{Node, Operator, Value as ExprValue};
+use serde_json::{json, Value};
+
+/// Parenthesised sub expressions show up as root nodes with a single child.
+fn unwrap_root(node: &Node) -> &Node {
+    match (node.operator(), node.children()) {
+        (Operator::RootNode, [child]) => unwrap_root(child),
+        _ => node,
+    }
+}
+
+fn to_json_value(value: &ExprValue) -> anyhow::Result<Value> {
+    match value {
+        ExprValue::String(s) => Ok(Value::String(s.clone())),
+        ExprValue::Int(i) => Ok(json!(i)),
+        ExprValue::Float(f) => Ok(json!(f)),
+        ExprValue::Boolean(b) => Ok(Value::Bool(*b)),
+        ExprValue::Tuple(items) => Ok(Value::Array(
+            items
+                .iter()
+                .map(to_json_value)
+                .collect::<anyhow::Result<Vec<Value>>>()?,
+        )),
+        ExprValue::Empty => Err(anyhow!("empty values cannot be used in contexts")),
+    }
+}
+
+fn compile_operand(node: &Node) -> anyhow::Result<Value> {
+    let node = unwrap_root(node);
+    match node.operator() {
+        Operator::VariableIdentifierRead { identifier } => {
+            Ok(json!({ "var": identifier }))
+        }
+        Operator::Const { value } => to_json_value(value),
+        Operator::Neg => match node.children() {
+            [child] => match compile_operand(child)? {
+                Value::Number(n) if n.is_i64() => Ok(json!(-n.as_i64().unwrap_or(0))),
+                Value::Number(n) => Ok(json!(-n.as_f64().unwrap_or(0.0))),
+                _ => Err(anyhow!("only numbers can be negated in contexts")),
+            },
+            _ => Err(anyhow!("negation expects a single operand")),
+        },
+        Operator::Tuple => Ok(Value::Array(
+            node.children()
+                .iter()
+                .map(compile_operand)
+                .collect::<anyhow::Result<Vec<Value>>>()?,
+        )),
+        operator => Err(anyhow!("unsupported operand {operator:?} in context")),
+    }
+}
+
+fn compile_and(node: &Node, conditions: &mut Vec<Value>) -> anyhow::Result<()> {
+    let node = unwrap_root(node);
+    match node.operator() {
+        Operator::And => {
+            for child in node.children() {
+                compile_and(child, conditions)?;
+            }
+            Ok(())
+        }
+        _ => {
+            conditions.push(compile_rule(node)?);
+            Ok(())
+        }
+    }
+}
+
+fn compile_rule(node: &Node) -> anyhow::Result<Value> {
+    let node = unwrap_root(node);
+    let binary = |op: &str| -> anyhow::Result<Value> {
+        match node.children() {
+            [lhs, rhs] => {
+                Ok(json!({ op: [compile_operand(lhs)?, compile_operand(rhs)?] }))
+            }
+            _ => Err(anyhow!("{op} expects exactly two operands")),
+        }
+    };
+    match node.operator() {
+        Operator::Eq => binary("=="),
+        Operator::Neq => binary("!="),
+        Operator::Gt => binary(">"),
+        Operator::Geq => binary(">="),
+        Operator::Lt => binary("<"),
+        Operator::Leq => binary("<="),
+        Operator::FunctionIdentifier { identifier } if identifier == "contains" => {
+            let arguments = node
+                .children()
+                .first()
+                .map(unwrap_root)
+                .ok_or(anyhow!("contains expects a list and a dimension"))?;
+            match (arguments.operator(), arguments.children()) {
+                (Operator::Tuple, [list, dimension]) => {
+                    let list = match compile_operand(list)? {
+                        Value::Array(items) => Value::Array(items),
+                        item => Value::Array(vec![item]),
+                    };
+                    Ok(json!({ "in": [compile_operand(dimension)?, list] }))
+                }
+                _ => Err(anyhow!("contains expects a list and a dimension")),
+            }
+        }
+        Operator::And => {
+            let mut conditions = Vec::new();
+            compile_and(node, &mut conditions)?;
+            Ok(json!({ "and": conditions }))
+        }
+        operator => Err(anyhow!("unsupported operator {operator:?} in context")),
+    }
+}
+
+/// Compiles a caclang context expression into the JSONLogic condition the CAC
+/// server and `cac_client` evaluate. Conditions are always wrapped in an
+/// `and`, the same shape contexts created from the dashboard have.
+pub fn compile_condition(expr: &Node) -> anyhow::Result<Value> {
+    let mut conditions = Vec::new();
+    compile_and(expr, &mut conditions)?;
+    Ok(json!({ "and": conditions }))
+}
@@ -0,0 +1,376 @@
+use std::{collections::HashMap, fmt};
+
+use serde_json::Value;
+use toml::Table;
+
+use crate::{
+    helpers::{compute_cac_hash, extract_optional_section, extract_section},
+    resolve_named_overrides, Context, ContextAwareConfig, DimensionProperties,
+    Dimensions,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub position: Option<Position>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(Position { line, column }) = self.position {
+            write!(f, "{line}:{column}: ")?;
+        }
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+fn position_at(source: &str, offset: usize) -> Position {
+    let prefix = &source[..offset.min(source.len())];
+    let line = prefix.matches('\n').count() + 1;
+    let column = prefix.len() - prefix.rfind('\n').map_or(0, |i| i + 1) + 1;
+    Position { line, column }
+}
+
+/// Finds where a key is declared, quoted keys are looked up as is and bare
+/// keys at the start of a line.
+fn locate(source: &str, key: &str) -> Option<Position> {
+    if let Some(offset) = source.find(&format!("\"{key}\"")) {
+        return Some(position_at(source, offset));
+    }
+    let mut offset = 0;
+    for line in source.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if let Some(rest) = trimmed.strip_prefix(key) {
+            if rest.trim_start().starts_with('=') {
+                return Some(position_at(source, offset + line.len() - trimmed.len()));
+            }
+        }
+        offset += line.len();
+    }
+    None
+}
+
+struct Collector<'a> {
+    source: &'a str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Collector<'a> {
+    fn push(&mut self, severity: Severity, message: String, key: Option<&str>) {
+        let position = key.and_then(|key| locate(self.source, key));
+        self.diagnostics.push(Diagnostic {
+            severity,
+            message,
+            position,
+        });
+    }
+
+    fn error(&mut self, err: anyhow::Error, key: Option<&str>) {
+        self.push(Severity::Error, err.to_string(), key);
+    }
+
+    fn warn(&mut self, message: String, key: Option<&str>) {
+        self.push(Severity::Warning, message, key);
+    }
+}
+
+/// Validates a config file, unlike `ContextAwareConfig::parse` this does not
+/// stop at the first error.
+pub fn validate(source: &str) -> Vec<Diagnostic> {
+    let mut collector = Collector {
+        source,
+        diagnostics: Vec::new(),
+    };
+
+    let mut cac = match source.parse::<Table>() {
+        Ok(cac) => cac,
+        Err(err) => {
+            return vec![Diagnostic {
+                severity: Severity::Error,
+                message: err.message().to_string(),
+                position: err.span().map(|span| position_at(source, span.start)),
+            }]
+        }
+    };
+
+    let mut dimensions = HashMap::new();
+    match extract_section(&mut cac, "dimensions") {
+        Ok(section) => {
+            for (dimension, props) in section.iter() {
+                let properties = props.as_table().ok_or(anyhow::anyhow!(
+                    "invalid properties provided for {dimension}"
+                ));
+                match properties.and_then(DimensionProperties::from) {
+                    Ok(properties) => {
+                        dimensions.insert(dimension.clone(), properties);
+                    }
+                    Err(err) => collector.error(err, Some(dimension.as_str())),
+                }
+            }
+        }
+        Err(err) => collector.error(err, None),
+    }
+    let dimensions = Dimensions(dimensions);
+
+    let default_config =
+        extract_section(&mut cac, "default-configs").unwrap_or_else(|err| {
+            collector.error(err, None);
+            Table::new()
+        });
+    let named_overrides =
+        extract_optional_section(&mut cac, "overrides").unwrap_or_else(|err| {
+            collector.error(err, Some("overrides"));
+            Table::new()
+        });
+    let referenced =
+        extract_optional_section(&mut cac, "contexts").unwrap_or_else(|err| {
+            collector.error(err, Some("contexts"));
+            Table::new()
+        });
+
+    let mut contexts = Vec::new();
+    for (ctx, override_names) in referenced.iter() {
+        match resolve_named_overrides(ctx, override_names, &named_overrides) {
+            Ok(overrides) => contexts.push((ctx.clone(), overrides)),
+            Err(err) => collector.error(err, Some(ctx.as_str())),
+        }
+    }
+    for (ctx, overrides) in cac.iter() {
+        match overrides.as_table() {
+            Some(overrides) => contexts.push((ctx.clone(), overrides.clone())),
+            None => collector.error(
+                anyhow::anyhow!("invalid overrides provided for {ctx}"),
+                Some(ctx.as_str()),
+            ),
+        }
+    }
+
+    let mut seen: HashMap<String, String> = HashMap::new();
+    for (ctx, overrides) in contexts.into_iter() {
+        match compute_cac_hash(&ctx) {
+            Ok(ctx_hash) => {
+                if let Some(collided_ctx) = seen.get(&ctx_hash) {
+                    collector.error(
+                        anyhow::anyhow!("{ctx} is a logical duplicate of {collided_ctx}"),
+                        Some(ctx.as_str()),
+                    );
+                    continue;
+                }
+                seen.insert(ctx_hash, ctx.clone());
+            }
+            Err(err) => {
+                collector.error(err, Some(ctx.as_str()));
+                continue;
+            }
+        }
+        if let Err(err) =
+            Context::from(&dimensions, &default_config, ctx.clone(), &overrides)
+        {
+            collector.error(err, Some(ctx.as_str()));
+        }
+    }
+
+    collector.diagnostics
+}
+
+fn rules(ctx: &Context) -> Vec<Value> {
+    ctx.condition
+        .get("and")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn rule_parts(rule: &Value) -> Option<(&str, &str, &Value)> {
+    let (op, operands) = rule.as_object()?.iter().next()?;
+    let operands = operands.as_array()?;
+    let dimension = operands.first()?.get("var")?.as_str()?;
+    Some((op.as_str(), dimension, operands.get(1)?))
+}
+
+/// Whether a set of rules can never be true at the same time, only equality
+/// and membership rules on the same dimension are compared.
+fn is_contradictory(rules: &[Value]) -> bool {
+    let mut allowed: HashMap<&str, Vec<&Value>> = HashMap::new();
+    let mut excluded: Vec<(&str, &Value)> = Vec::new();
+    for rule in rules.iter() {
+        let Some((op, dimension, value)) = rule_parts(rule) else {
+            continue;
+        };
+        let values = match (op, value) {
+            ("==", value) => vec![value],
+            ("in", Value::Array(items)) => items.iter().collect(),
+            ("!=", value) => {
+                excluded.push((dimension, value));
+                continue;
+            }
+            _ => continue,
+        };
+        let entry = allowed.entry(dimension).or_insert_with(|| values.clone());
+        entry.retain(|v| values.contains(v));
+        if entry.is_empty() {
+            return true;
+        }
+    }
+    excluded.into_iter().any(|(dimension, value)| {
+        allowed
+            .get(dimension)
+            .is_some_and(|values| values.iter().all(|v| *v == value))
+    })
+}
+
+/// Warns about contexts that can never apply, overrides which are hidden by
+/// another context of the same priority and default configs nothing
+/// overrides.
+pub fn lint(cac: &ContextAwareConfig, source: &str) -> Vec<Diagnostic> {
+    let mut collector = Collector {
+        source,
+        diagnostics: Vec::new(),
+    };
+    let mut contexts = cac.contexts.values().collect::<Vec<&Context>>();
+    contexts.sort_by(|a, b| a.context.cmp(&b.context));
+
+    for ctx in contexts.iter() {
+        if is_contradictory(&rules(ctx)) {
+            collector.warn(
+                format!(
+                    "context {} is unreachable, its rules contradict each other",
+                    ctx.context
+                ),
+                Some(ctx.context.as_str()),
+            );
+        } else if ctx.overrides.is_empty() {
+            collector.warn(
+                format!("context {} has no overrides", ctx.context),
+                Some(ctx.context.as_str()),
+            );
+        }
+    }
+
+    for (i, a) in contexts.iter().enumerate() {
+        for b in contexts.iter().skip(i + 1) {
+            if a.calculated_priority != b.calculated_priority {
+                continue;
+            }
+            let mut combined = rules(a);
+            combined.extend(rules(b));
+            if is_contradictory(&combined) {
+                continue;
+            }
+            let mut shared = a
+                .overrides
+                .keys()
+                .filter(|key| b.overrides.contains_key(*key))
+                .collect::<Vec<&String>>();
+            shared.sort();
+            for key in shared {
+                collector.warn(
+                    format!(
+                        "override of {key} in context {} shadows the one in context {} as both have priority {}",
+                        a.context, b.context, a.calculated_priority
+                    ),
+                    Some(b.context.as_str()),
+                );
+            }
+        }
+    }
+
+    for key in cac.default_config.keys() {
+        if !contexts.iter().any(|ctx| ctx.overrides.contains_key(key)) {
+            collector.warn(
+                format!("default config {key} is not overridden by any context"),
+                Some(key.as_str()),
+            );
+        }
+    }
+
+    collector.diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"[dimensions]
+os = { type = "string", pattern = "(android|ios|web)", priority = 1 }
+tier = { type = "number", priority = 2 }
+scope = { type = "string", priority = 2 }
+
+[default-configs]
+version = "1.0"
+theme = "light"
+retries = 3
+
+["os is android"]
+version = "1.1"
+
+["os is android and os is ios"]
+version = "1.2"
+
+["tier is 1"]
+theme = "dark"
+
+["scope is beta"]
+theme = "blue"
+"#;
+
+    #[test]
+    fn test_validate_reports_all_errors() {
+        let config = CONFIG
+            .replace(r#"version = "1.1""#, "version = 11")
+            .replace("tier is 1", "tier is one");
+        let diagnostics = validate(&config);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity == Severity::Error));
+        assert_eq!(
+            diagnostics
+                .iter()
+                .filter_map(|diagnostic| diagnostic.position)
+                .map(|position| position.line)
+                .collect::<Vec<usize>>(),
+            vec![11, 17]
+        );
+    }
+
+    #[test]
+    fn test_lint() {
+        assert!(validate(CONFIG).is_empty());
+        let cac = ContextAwareConfig::parse(CONFIG.to_string()).unwrap();
+        let messages = lint(&cac, CONFIG)
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect::<Vec<String>>();
+        assert_eq!(
+            messages,
+            vec![
+                "context os is android and os is ios is unreachable, its rules contradict each other",
+                "override of theme in context scope is beta shadows the one in context tier is 1 as both have priority 2",
+                "default config retries is not overridden by any context",
+            ]
+        );
+    }
+}
@@ -20,6 +20,18 @@ pub(crate) fn extract_section(
     Ok(section.clone())
 }
 
+pub(crate) fn extract_optional_section(
+    item: &mut Table,
+    section_name: &'static str,
+) -> anyhow::Result<Table> {
+    match item.remove(section_name) {
+        Some(section) => section.as_table().cloned().ok_or(anyhow!(
+            "The formatting of the {section_name} is incorrect. Please check the docs"
+        )),
+        None => Ok(Table::new()),
+    }
+}
+
 pub fn compute_cac_hash(ctx: &String) -> anyhow::Result<String> {
     let tokens = ctx.split(AND_TOKEN).collect::<Vec<&str>>();
     let mut final_tokens: Vec<String> = Vec::new();
@@ -79,7 +91,15 @@ pub(crate) fn parse_and_validate_ctx(
             "Dimension {dimension} is not defined in dimensions section"
         ))?;
 
-        let validator = |value| -> anyhow::Result<()> {
+        let validator = |value: &str| -> anyhow::Result<()> {
+            let valid_type = match props.data_type {
+                DataType::String => true,
+                DataType::Number => value.parse::<f64>().is_ok(),
+                DataType::Boolean => value.parse::<bool>().is_ok(),
+            };
+            if !valid_type {
+                return Err(anyhow!("Invalid value for dimension {dimension} in context {ctx}, expected a value of type {:?}", props.data_type));
+            }
             if !props.pattern.is_match(value) {
                 return Err(anyhow!("Invalid value for dimension {dimension} in context {ctx}, the pattern defined does not match with the value provided"));
             }
@@ -156,3 +176,22 @@ pub(crate) fn parse_and_validate_ctx(
     let expr = build_operator_tree(expr_ctx.join(JOIN_TOKEN).as_str())?;
     Ok((expr, priority))
 }
+
+/// Overrides have to be of the same type as the default value they override,
+/// integers are accepted where floats are expected.
+pub(crate) fn validate_override_type(
+    ctx: &str,
+    key: &str,
+    default_value: &toml::Value,
+    value: &toml::Value,
+) -> anyhow::Result<()> {
+    match (default_value, value) {
+        (toml::Value::Float(_), toml::Value::Integer(_)) => Ok(()),
+        (d, v) if d.same_type(v) => Ok(()),
+        (d, v) => Err(anyhow!(
+            "The override for {key} in the context {ctx} should be of type {}, found {}",
+            d.type_str(),
+            v.type_str()
+        )),
+    }
+}
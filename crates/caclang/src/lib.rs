@@ -2,14 +2,24 @@ use std::{
     collections::{BTreeMap, HashMap},
     str::FromStr,
 };
+pub mod condition;
+pub mod diagnostics;
 mod helpers;
+pub mod sync;
 
 use anyhow::anyhow;
+use cac_client::{eval_cac, MergeStrategy};
+use condition::compile_condition;
 use derive_more::Deref;
 use evalexpr::Node;
-use helpers::{compute_cac_hash, extract_section, parse_and_validate_ctx};
+use helpers::{
+    compute_cac_hash, extract_optional_section, extract_section, parse_and_validate_ctx,
+    validate_override_type,
+};
 use regex::Regex;
+use serde_json::{json, Map, Value};
 use strum_macros::EnumString;
+use superposition_types::hashing::hash_json;
 use toml::Table;
 
 pub type HashMapContext = evalexpr::HashMapContext;
@@ -23,11 +33,23 @@ pub enum DataType {
     #[strum(ascii_case_insensitive)]
     Boolean,
 }
+
+impl DataType {
+    fn schema_type(&self) -> &'static str {
+        match self {
+            DataType::String => "string",
+            DataType::Number => "number",
+            DataType::Boolean => "boolean",
+        }
+    }
+}
 #[derive(Debug, Clone)]
 pub struct Context {
     pub context: String,
     pub expr: Node,
-    pub overrides: HashMap<String, String>,
+    /// the context rendered as a JSONLogic condition, the way the CAC server stores it
+    pub condition: Value,
+    pub overrides: HashMap<String, toml::Value>,
     pub calculated_priority: u64,
 }
 
@@ -39,47 +61,97 @@ impl Context {
         o: &Table,
     ) -> anyhow::Result<Self> {
         let (expr, calculated_priority) = parse_and_validate_ctx(dimensions, &ctx)?;
+        let condition = compile_condition(&expr)?;
         let mut overrides = HashMap::new();
         for (config_key, ov) in o.into_iter() {
-            default_config.get(config_key).ok_or(anyhow!("The override key {config_key} for the context {ctx} is not present in default-configs section"))?;
-            let ove = ov.as_str().ok_or(anyhow!("Invalid syntax for override for the context {ctx} and config key {config_key}"))?.to_string();
-            overrides.insert(config_key.into(), ove);
+            let default_value = default_config.get(config_key).ok_or(anyhow!("The override key {config_key} for the context {ctx} is not present in default-configs section"))?;
+            validate_override_type(&ctx, config_key, default_value, ov)?;
+            overrides.insert(config_key.into(), ov.clone());
         }
         Ok(Self {
             context: ctx,
             expr,
+            condition,
             overrides,
             calculated_priority,
         })
     }
 }
 
+/// Merges the `[overrides]` blocks a context in the `[contexts]` section
+/// refers to, later blocks win.
+pub(crate) fn resolve_named_overrides(
+    ctx: &str,
+    override_names: &toml::Value,
+    named_overrides: &Table,
+) -> anyhow::Result<Table> {
+    let override_names = override_names.as_array().ok_or(anyhow!(
+        "overrides for {ctx} in the contexts section should be a list of override names"
+    ))?;
+    let mut overrides = Table::new();
+    for name in override_names.iter() {
+        let name = name
+            .as_str()
+            .ok_or(anyhow!("invalid override name {name} for {ctx}"))?;
+        let block = named_overrides
+            .get(name)
+            .ok_or(anyhow!(
+                "The override {name} for the context {ctx} is not present in overrides section"
+            ))?
+            .as_table()
+            .ok_or(anyhow!("invalid overrides provided for {name}"))?;
+        overrides.extend(block.clone());
+    }
+    Ok(overrides)
+}
+
 #[derive(Debug, Clone, Deref)]
 pub struct Contexts(pub HashMap<String, Context>);
 
 impl Contexts {
+    fn insert(
+        items: &mut HashMap<String, Context>,
+        dimensions: &Dimensions,
+        default_config: &Table,
+        ctx: String,
+        overrides: &Table,
+    ) -> anyhow::Result<()> {
+        let ctx_hash = compute_cac_hash(&ctx)?;
+        if let Some(collided_ctx) = items.get(&ctx_hash) {
+            return Err(anyhow!(
+                "{ctx} is a logical duplicate of {}",
+                collided_ctx.context
+            ));
+        }
+        items.insert(
+            ctx_hash,
+            Context::from(dimensions, default_config, ctx, overrides)?,
+        );
+        Ok(())
+    }
+
+    /// Builds contexts from both supported styles, inline tables of overrides
+    /// keyed by the context and the `[contexts]` section which references
+    /// blocks from the `[overrides]` section by name. When a context
+    /// references multiple blocks, later blocks win.
     pub fn from(
         dimensions: &Dimensions,
         default_config: &Table,
-        value: Table,
+        named_overrides: &Table,
+        referenced: Table,
+        inline: Table,
     ) -> anyhow::Result<Self> {
         let mut items = HashMap::new();
-        for (ctx, overrides) in value.into_iter() {
-            let ctx_hash = compute_cac_hash(&ctx)?;
-            if items.contains_key(&ctx_hash) {
-                let collided_ctx: &Context = items.get(&ctx_hash).ok_or(anyhow!(""))?;
-                return Err(anyhow!(
-                    "{ctx} is a logical duplicate of {}",
-                    collided_ctx.context
-                ));
-            }
+        for (ctx, override_names) in referenced.into_iter() {
+            let overrides =
+                resolve_named_overrides(&ctx, &override_names, named_overrides)?;
+            Self::insert(&mut items, dimensions, default_config, ctx, &overrides)?;
+        }
+        for (ctx, overrides) in inline.into_iter() {
             let overrides = overrides
                 .as_table()
                 .ok_or(anyhow!("invalid overrides provided for {ctx}"))?;
-            items.insert(
-                ctx_hash,
-                Context::from(dimensions, default_config, ctx, overrides)?,
-            );
+            Self::insert(&mut items, dimensions, default_config, ctx, overrides)?;
         }
         Ok(Contexts(items))
     }
@@ -90,22 +162,41 @@ pub struct DimensionProperties {
     pub data_type: DataType,
     pub pattern: Regex,
     pub priority: u64,
+    /// the dimension as the JSON schema the CAC server validates it with
+    pub schema: Value,
 }
 
 impl DimensionProperties {
-    fn from(value: &Table) -> anyhow::Result<Self> {
-        let data_type = DataType::from_str(
-            value
-                .get("type")
-                .ok_or(anyhow!("type is missing from dimension"))?
-                .as_str()
-                .ok_or(anyhow!(
-                    "Invalid type, allowed values are string, number and boolean"
-                ))?,
-        )
-        .unwrap();
+    pub(crate) fn from(value: &Table) -> anyhow::Result<Self> {
+        let data_type = value
+            .get("type")
+            .ok_or(anyhow!("type is missing from dimension"))?
+            .as_str()
+            .and_then(|t| DataType::from_str(t).ok())
+            .ok_or(anyhow!(
+                "Invalid type, allowed values are string, number and boolean"
+            ))?;
+        let mut schema = json!({ "type": data_type.schema_type() });
         let pattern = if let Some(p) = value.get("pattern") {
-            Regex::new(p.as_str().ok_or(anyhow!("Invalid regex expression"))?)?
+            let p = p.as_str().ok_or(anyhow!("Invalid regex expression"))?;
+            schema["pattern"] = json!(p);
+            Regex::new(p)?
+        } else if let Some(e) = value.get("enum") {
+            let values = e
+                .as_array()
+                .ok_or(anyhow!("enum should be a list of values"))?;
+            schema["enum"] = serde_json::to_value(values)?;
+            let variants = values
+                .iter()
+                .map(|v| match v {
+                    toml::Value::String(s) => Ok(regex::escape(s)),
+                    toml::Value::Integer(_)
+                    | toml::Value::Float(_)
+                    | toml::Value::Boolean(_) => Ok(regex::escape(&v.to_string())),
+                    _ => Err(anyhow!("Invalid enum value {v}")),
+                })
+                .collect::<anyhow::Result<Vec<String>>>()?;
+            Regex::new(&format!("^({})$", variants.join("|")))?
         } else {
             Regex::new(".*")?
         };
@@ -118,6 +209,7 @@ impl DimensionProperties {
             data_type,
             pattern,
             priority,
+            schema,
         })
     }
 }
@@ -129,7 +221,9 @@ impl Dimensions {
     pub fn from(value: Table) -> anyhow::Result<Self> {
         let mut dimension_map = HashMap::new();
         for (dimension, props) in value.into_iter() {
-            let properties: &Table = props.as_table().unwrap();
+            let properties: &Table = props
+                .as_table()
+                .ok_or(anyhow!("invalid properties provided for {dimension}"))?;
             dimension_map.insert(dimension, DimensionProperties::from(properties)?);
         }
         Ok(Dimensions(dimension_map))
@@ -147,7 +241,15 @@ impl ContextAwareConfig {
         let mut cac: Table = config.parse::<Table>()?;
         let dimensions = Dimensions::from(extract_section(&mut cac, "dimensions")?)?;
         let default_config = extract_section(&mut cac, "default-configs")?;
-        let contexts = Contexts::from(&dimensions, &default_config, cac)?;
+        let named_overrides = extract_optional_section(&mut cac, "overrides")?;
+        let referenced = extract_optional_section(&mut cac, "contexts")?;
+        let contexts = Contexts::from(
+            &dimensions,
+            &default_config,
+            &named_overrides,
+            referenced,
+            cac,
+        )?;
         Ok(Self {
             dimensions,
             default_config,
@@ -155,12 +257,65 @@ impl ContextAwareConfig {
         })
     }
 
+    /// Builds the `Config` the CAC server would serve for this file, so it can
+    /// be evaluated with `cac_client::eval_cac`.
+    pub fn to_config(&self) -> anyhow::Result<cac_client::Config> {
+        let default_configs = self
+            .default_config
+            .iter()
+            .map(|(key, value)| Ok((key.clone(), serde_json::to_value(value)?)))
+            .collect::<anyhow::Result<Map<String, Value>>>()?;
+
+        let mut sorted_contexts = self.contexts.values().collect::<Vec<&Context>>();
+        sorted_contexts.sort_by_key(|ctx| ctx.calculated_priority);
+
+        let mut contexts = Vec::new();
+        let mut overrides = Map::new();
+        for ctx in sorted_contexts.into_iter() {
+            let override_ = ctx
+                .overrides
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), serde_json::to_value(value)?)))
+                .collect::<anyhow::Result<Map<String, Value>>>()?;
+            let override_ = Value::Object(override_);
+            let override_id = hash_json(&override_);
+            overrides.insert(override_id.clone(), override_);
+            contexts.push(cac_client::Context {
+                condition: ctx.condition.clone(),
+                override_with_keys: [override_id],
+            });
+        }
+
+        Ok(cac_client::Config {
+            contexts,
+            overrides,
+            default_configs,
+        })
+    }
+
+    /// Resolves the full config for `query` the same way `cac_client` does.
+    pub fn resolve(
+        &self,
+        query: &Map<String, Value>,
+    ) -> anyhow::Result<Map<String, Value>> {
+        let config = self.to_config()?;
+        eval_cac(
+            config.default_configs,
+            &config.contexts,
+            &config.overrides,
+            query,
+            MergeStrategy::MERGE,
+        )
+        .map_err(|err| anyhow!(err))
+    }
+
     pub fn get_config(
         &self,
-        key: &'static str,
+        key: &str,
         context: &HashMapContext,
-    ) -> anyhow::Result<String> {
-        let mut sorted_map: BTreeMap<u64, &HashMap<String, String>> = BTreeMap::new();
+    ) -> anyhow::Result<toml::Value> {
+        let mut sorted_map: BTreeMap<u64, &HashMap<String, toml::Value>> =
+            BTreeMap::new();
         for (_, ctx) in self.contexts.iter() {
             let eval = ctx.expr.eval_with_context(context);
             if eval.is_ok() && eval?.as_boolean()? {
@@ -173,7 +328,7 @@ impl ContextAwareConfig {
                 sorted_map.insert(ctx.calculated_priority, &ctx.overrides);
             }
         }
-        let mut fin_override: HashMap<String, String> = HashMap::new();
+        let mut fin_override: HashMap<String, toml::Value> = HashMap::new();
         for (_, ov) in sorted_map.into_iter() {
             fin_override.extend(ov.to_owned());
         }
@@ -187,3 +342,160 @@ impl ContextAwareConfig {
             .to_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use evalexpr::ContextWithMutableVariables;
+    use serde_json::json;
+
+    const CONFIG: &str = r#"
+[dimensions]
+os = { type = "string", pattern = "(android|ios|web)", priority = 1 }
+clientId = { type = "string", priority = 2 }
+toss = { type = "number", priority = 4 }
+
+[default-configs]
+version = "1.0"
+theme = "light"
+
+["os is android"]
+version = "1.1"
+
+["os is android and clientId in [meesho,zee5]"]
+version = "1.2"
+theme = "dark"
+
+["os in [web,ios] and toss is -1"]
+theme = "contrast"
+
+["toss > 50"]
+theme = "blue"
+"#;
+
+    const KEYS: [&str; 2] = ["version", "theme"];
+
+    fn to_expr_context(query: &Map<String, Value>) -> HashMapContext {
+        let mut context = HashMapContext::new();
+        for (dimension, value) in query.iter() {
+            let value = match value {
+                Value::String(s) => evalexpr::Value::String(s.clone()),
+                Value::Number(n) => evalexpr::Value::Int(n.as_i64().unwrap()),
+                Value::Bool(b) => evalexpr::Value::Boolean(*b),
+                _ => panic!("unsupported dimension value {value}"),
+            };
+            context.set_value(dimension.clone(), value).unwrap();
+        }
+        context
+    }
+
+    #[test]
+    fn test_compile_condition() {
+        let cac = ContextAwareConfig::parse(CONFIG.to_string()).unwrap();
+        let conditions = cac
+            .contexts
+            .values()
+            .map(|ctx| (ctx.context.as_str(), ctx.condition.clone()))
+            .collect::<HashMap<&str, Value>>();
+
+        assert_eq!(
+            conditions["os is android and clientId in [meesho,zee5]"],
+            json!({ "and": [
+                { "==": [{ "var": "os" }, "android"] },
+                { "in": [{ "var": "clientId" }, ["meesho", "zee5"]] }
+            ]})
+        );
+        assert_eq!(
+            conditions["os in [web,ios] and toss is -1"],
+            json!({ "and": [
+                { "in": [{ "var": "os" }, ["web", "ios"]] },
+                { "==": [{ "var": "toss" }, -1] }
+            ]})
+        );
+        assert_eq!(
+            conditions["toss > 50"],
+            json!({ "and": [{ ">": [{ "var": "toss" }, 50] }] })
+        );
+    }
+
+    #[test]
+    fn test_round_trip_with_eval_cac() {
+        let cac = ContextAwareConfig::parse(CONFIG.to_string()).unwrap();
+        let config = cac.to_config().unwrap();
+        let queries = [
+            json!({ "os": "android" }),
+            json!({ "os": "android", "clientId": "zee5" }),
+            json!({ "os": "android", "clientId": "cac", "toss": 60 }),
+            json!({ "os": "ios", "toss": -1 }),
+            json!({ "os": "web", "toss": 60 }),
+            json!({ "os": "web", "toss": 10 }),
+        ];
+
+        for query in queries.iter() {
+            let query = query.as_object().unwrap();
+            let resolved = eval_cac(
+                config.default_configs.clone(),
+                &config.contexts,
+                &config.overrides,
+                query,
+                MergeStrategy::MERGE,
+            )
+            .unwrap();
+            let context = to_expr_context(query);
+            for key in KEYS {
+                let expected = match cac.get_config(key, &context) {
+                    Ok(value) => serde_json::to_value(value).unwrap(),
+                    // caclang does not fall back to defaults
+                    Err(_) => config.default_configs[key].clone(),
+                };
+                assert_eq!(resolved[key], expected, "{key} for {query:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_named_overrides() {
+        let cac =
+            ContextAwareConfig::parse(include_str!("../cac_example.toml").to_string())
+                .unwrap();
+        let mut context = HashMapContext::new();
+        context
+            .set_value("breed".into(), "golden_retriever".into())
+            .unwrap();
+
+        assert_eq!(
+            cac.get_config("name", &context).unwrap(),
+            toml::Value::String("blondie".into())
+        );
+        assert_eq!(
+            cac.get_config("owner", &context).unwrap(),
+            toml::Value::String("Saurav".into())
+        );
+        assert_eq!(
+            cac.get_config("vaccinated", &context).unwrap(),
+            toml::Value::Boolean(true)
+        );
+    }
+
+    #[test]
+    fn test_override_type_validation() {
+        let config = r#"
+[dimensions]
+os = { type = "string", priority = 1 }
+toss = { type = "number", priority = 2 }
+
+[default-configs]
+retries = 3
+
+["os is android"]
+retries = "five"
+"#;
+        assert!(ContextAwareConfig::parse(config.to_string()).is_err());
+
+        let config = config.replace(r#"retries = "five""#, "retries = 5");
+        assert!(ContextAwareConfig::parse(config.clone()).is_ok());
+
+        let config = config.replace("os is android", "toss is high");
+        assert!(ContextAwareConfig::parse(config).is_err());
+    }
+}
@@ -0,0 +1,649 @@
+use std::{collections::BTreeMap, fmt};
+
+use anyhow::anyhow;
+use reqwest::blocking::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use superposition_types::hashing::{hash_json, json_to_sorted_string};
+
+use crate::ContextAwareConfig;
+
+/// The state of a tenant as described by the CAC API payloads, contexts are
+/// keyed by the same hash the server uses as the context id.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigState {
+    pub dimensions: BTreeMap<String, DimensionState>,
+    pub default_configs: BTreeMap<String, Value>,
+    pub contexts: BTreeMap<String, ContextState>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DimensionState {
+    pub priority: i32,
+    pub schema: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextState {
+    pub label: String,
+    pub condition: Value,
+    pub overrides: Map<String, Value>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Change {
+    CreateDimension {
+        name: String,
+        dimension: DimensionState,
+    },
+    UpdateDimension {
+        name: String,
+        old: DimensionState,
+        new: DimensionState,
+    },
+    CreateDefaultConfig {
+        key: String,
+        value: Value,
+    },
+    UpdateDefaultConfig {
+        key: String,
+        old: Value,
+        new: Value,
+    },
+    DeleteDefaultConfig {
+        key: String,
+    },
+    CreateContext {
+        id: String,
+        context: ContextState,
+    },
+    UpdateContext {
+        id: String,
+        old: Map<String, Value>,
+        context: ContextState,
+    },
+    DeleteContext {
+        id: String,
+        context: ContextState,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::CreateDimension { name, dimension } => {
+                write!(
+                    f,
+                    "  + dimension {name} (priority {}) {}",
+                    dimension.priority, dimension.schema
+                )
+            }
+            Change::UpdateDimension { name, old, new } => {
+                write!(f, "  ~ dimension {name}")?;
+                if old.priority != new.priority {
+                    write!(
+                        f,
+                        "\n      ~ priority = {} -> {}",
+                        old.priority, new.priority
+                    )?;
+                }
+                if old.schema != new.schema {
+                    write!(f, "\n      ~ schema = {} -> {}", old.schema, new.schema)?;
+                }
+                Ok(())
+            }
+            Change::CreateDefaultConfig { key, value } => {
+                write!(f, "  + default-config {key} = {value}")
+            }
+            Change::UpdateDefaultConfig { key, old, new } => {
+                write!(f, "  ~ default-config {key} = {old} -> {new}")
+            }
+            Change::DeleteDefaultConfig { key } => {
+                write!(f, "  - default-config {key}")
+            }
+            Change::CreateContext { context, .. } => {
+                write!(f, "  + context \"{}\"", context.label)?;
+                for (key, value) in context.overrides.iter() {
+                    write!(f, "\n      + {key} = {value}")?;
+                }
+                Ok(())
+            }
+            Change::UpdateContext { old, context, .. } => {
+                write!(f, "  ~ context \"{}\"", context.label)?;
+                for (key, value) in context.overrides.iter() {
+                    match old.get(key) {
+                        None => write!(f, "\n      + {key} = {value}")?,
+                        Some(old_value) if old_value != value => {
+                            write!(f, "\n      ~ {key} = {old_value} -> {value}")?
+                        }
+                        Some(_) => (),
+                    }
+                }
+                for key in old.keys().filter(|k| !context.overrides.contains_key(*k)) {
+                    write!(f, "\n      - {key}")?;
+                }
+                Ok(())
+            }
+            Change::DeleteContext { context, .. } => {
+                write!(f, "  - context \"{}\"", context.label)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Plan(pub Vec<Change>);
+
+impl Plan {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Counts of (additions, changes, deletions) in the plan.
+    pub fn summary(&self) -> (usize, usize, usize) {
+        self.0
+            .iter()
+            .fold((0, 0, 0), |(add, change, destroy), item| match item {
+                Change::CreateDimension { .. }
+                | Change::CreateDefaultConfig { .. }
+                | Change::CreateContext { .. } => (add + 1, change, destroy),
+                Change::UpdateDimension { .. }
+                | Change::UpdateDefaultConfig { .. }
+                | Change::UpdateContext { .. } => (add, change + 1, destroy),
+                Change::DeleteDefaultConfig { .. } | Change::DeleteContext { .. } => {
+                    (add, change, destroy + 1)
+                }
+            })
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "No changes. The tenant is up-to-date.");
+        }
+        for change in self.0.iter() {
+            writeln!(f, "{change}")?;
+        }
+        let (add, change, destroy) = self.summary();
+        write!(
+            f,
+            "\nPlan: {add} to add, {change} to change, {destroy} to destroy."
+        )
+    }
+}
+
+pub fn context_id(condition: &Value) -> String {
+    hash_json(condition)
+}
+
+fn infer_schema(value: &Value) -> Value {
+    match value {
+        Value::String(_) => json!({ "type": "string" }),
+        Value::Number(n) if n.is_i64() || n.is_u64() => json!({ "type": "integer" }),
+        Value::Number(_) => json!({ "type": "number" }),
+        Value::Bool(_) => json!({ "type": "boolean" }),
+        Value::Array(_) => json!({ "type": "array" }),
+        Value::Object(_) => json!({ "type": "object" }),
+        Value::Null => json!({ "type": "null" }),
+    }
+}
+
+/// Compiles a parsed TOML config into the payloads the CAC API accepts.
+pub fn compile(cac: &ContextAwareConfig) -> anyhow::Result<ConfigState> {
+    let mut dimensions = BTreeMap::new();
+    for (name, properties) in cac.dimensions.iter() {
+        dimensions.insert(
+            name.clone(),
+            DimensionState {
+                priority: i32::try_from(properties.priority)?,
+                schema: properties.schema.clone(),
+            },
+        );
+    }
+
+    let mut default_configs = BTreeMap::new();
+    for (key, value) in cac.default_config.iter() {
+        default_configs.insert(key.clone(), serde_json::to_value(value)?);
+    }
+
+    let mut contexts = BTreeMap::new();
+    for context in cac.contexts.values() {
+        let overrides = context
+            .overrides
+            .iter()
+            .map(|(key, value)| Ok((key.clone(), serde_json::to_value(value)?)))
+            .collect::<anyhow::Result<Map<String, Value>>>()?;
+        contexts.insert(
+            context_id(&context.condition),
+            ContextState {
+                label: context.context.clone(),
+                condition: context.condition.clone(),
+                overrides,
+            },
+        );
+    }
+
+    Ok(ConfigState {
+        dimensions,
+        default_configs,
+        contexts,
+    })
+}
+
+/// Whether `condition` references the `variantIds` dimension, which only the
+/// contexts of experiment variants do.
+fn is_experiment_context(condition: &Value) -> bool {
+    match condition {
+        Value::Object(map) => map.iter().any(|(operator, operand)| {
+            (operator == "var" && operand.as_str() == Some("variantIds"))
+                || is_experiment_context(operand)
+        }),
+        Value::Array(operands) => operands.iter().any(is_experiment_context),
+        _ => false,
+    }
+}
+
+/// Computes the changes needed to move `remote` to `local`. Dimensions are
+/// only ever created or updated, the server has no way to delete them.
+/// Contexts of experiments are managed through the experiments and left alone.
+pub fn plan(local: &ConfigState, remote: &ConfigState) -> Plan {
+    let mut changes = Vec::new();
+    let remote_contexts = remote
+        .contexts
+        .iter()
+        .filter(|(_, context)| !is_experiment_context(&context.condition))
+        .collect::<BTreeMap<&String, &ContextState>>();
+
+    for (name, dimension) in local.dimensions.iter() {
+        match remote.dimensions.get(name) {
+            None => changes.push(Change::CreateDimension {
+                name: name.clone(),
+                dimension: dimension.clone(),
+            }),
+            Some(old) if old != dimension => changes.push(Change::UpdateDimension {
+                name: name.clone(),
+                old: old.clone(),
+                new: dimension.clone(),
+            }),
+            Some(_) => (),
+        }
+    }
+
+    for (key, value) in local.default_configs.iter() {
+        match remote.default_configs.get(key) {
+            None => changes.push(Change::CreateDefaultConfig {
+                key: key.clone(),
+                value: value.clone(),
+            }),
+            Some(old) if old != value => changes.push(Change::UpdateDefaultConfig {
+                key: key.clone(),
+                old: old.clone(),
+                new: value.clone(),
+            }),
+            Some(_) => (),
+        }
+    }
+
+    for (id, context) in local.contexts.iter() {
+        match remote_contexts.get(id) {
+            None => changes.push(Change::CreateContext {
+                id: id.clone(),
+                context: context.clone(),
+            }),
+            Some(old) if old.overrides != context.overrides => {
+                changes.push(Change::UpdateContext {
+                    id: id.clone(),
+                    old: old.overrides.clone(),
+                    context: context.clone(),
+                })
+            }
+            Some(_) => (),
+        }
+    }
+
+    for (id, context) in remote_contexts {
+        if !local.contexts.contains_key(id) {
+            changes.push(Change::DeleteContext {
+                id: id.clone(),
+                context: context.clone(),
+            });
+        }
+    }
+
+    for key in remote.default_configs.keys() {
+        if !local.default_configs.contains_key(key) {
+            changes.push(Change::DeleteDefaultConfig { key: key.clone() });
+        }
+    }
+
+    Plan(changes)
+}
+
+#[derive(Deserialize)]
+struct RemoteDimension {
+    dimension: String,
+    priority: i32,
+    schema: Value,
+}
+
+#[derive(Deserialize)]
+struct RemoteContext {
+    id: String,
+    condition: Value,
+    override_with_keys: [String; 1],
+}
+
+#[derive(Deserialize)]
+struct RemoteConfig {
+    contexts: Vec<RemoteContext>,
+    overrides: Map<String, Value>,
+    default_configs: Map<String, Value>,
+}
+
+/// How the client authenticates with the superposition server.
+#[derive(Debug, Clone)]
+pub enum Credentials {
+    /// an API key, sent in the `x-api-key` header
+    ApiKey(String),
+    /// a bearer token, like a JWT
+    Token(String),
+}
+
+pub struct SyncClient {
+    host: String,
+    tenant: String,
+    credentials: Option<Credentials>,
+    http: Client,
+}
+
+impl SyncClient {
+    pub fn new(host: String, tenant: String, credentials: Option<Credentials>) -> Self {
+        Self {
+            host: host.trim_end_matches('/').to_string(),
+            tenant,
+            credentials,
+            http: Client::new(),
+        }
+    }
+
+    fn request(&self, builder: RequestBuilder) -> anyhow::Result<Value> {
+        let builder = match &self.credentials {
+            Some(Credentials::ApiKey(key)) => builder.header("x-api-key", key.as_str()),
+            Some(Credentials::Token(token)) => builder.bearer_auth(token),
+            None => builder,
+        };
+        let response = builder.header("x-tenant", self.tenant.as_str()).send()?;
+        let status = response.status();
+        let body = response.text()?;
+        if !status.is_success() {
+            return Err(anyhow!("request failed with status {status}: {body}"));
+        }
+        if body.is_empty() {
+            return Ok(Value::Null);
+        }
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    pub fn fetch(&self) -> anyhow::Result<ConfigState> {
+        let url = format!("{}/dimension", self.host);
+        let dimensions: Vec<RemoteDimension> =
+            serde_json::from_value(self.request(self.http.get(url))?)?;
+        let dimensions = dimensions
+            .into_iter()
+            .map(|d| {
+                (
+                    d.dimension,
+                    DimensionState {
+                        priority: d.priority,
+                        schema: d.schema,
+                    },
+                )
+            })
+            .collect();
+
+        let url = format!("{}/config", self.host);
+        let config: RemoteConfig =
+            serde_json::from_value(self.request(self.http.get(url))?)?;
+
+        let mut contexts = BTreeMap::new();
+        for context in config.contexts.into_iter() {
+            let [override_id] = context.override_with_keys;
+            let overrides = config
+                .overrides
+                .get(&override_id)
+                .and_then(Value::as_object)
+                .cloned()
+                .ok_or(anyhow!(
+                    "override {override_id} of context {} not found",
+                    context.id
+                ))?;
+            contexts.insert(
+                context.id,
+                ContextState {
+                    label: json_to_sorted_string(&context.condition),
+                    condition: context.condition,
+                    overrides,
+                },
+            );
+        }
+
+        Ok(ConfigState {
+            dimensions,
+            default_configs: config.default_configs.into_iter().collect(),
+            contexts,
+        })
+    }
+
+    fn put_context(&self, context: &ContextState) -> anyhow::Result<()> {
+        let url = format!("{}/context/overrides", self.host);
+        let body = json!({
+            "context": context.condition,
+            "override": context.overrides,
+        });
+        self.request(self.http.put(url).json(&body))?;
+        Ok(())
+    }
+
+    /// Applies a plan in dependency order, dimensions and default configs are
+    /// created before the contexts using them, default configs are deleted after.
+    pub fn apply(&self, plan: &Plan) -> anyhow::Result<()> {
+        for change in plan.0.iter() {
+            match change {
+                Change::CreateDimension {
+                    name,
+                    dimension: new,
+                }
+                | Change::UpdateDimension { name, new, .. } => {
+                    let url = format!("{}/dimension", self.host);
+                    let body = json!({
+                        "dimension": name,
+                        "priority": new.priority,
+                        "schema": new.schema,
+                    });
+                    self.request(self.http.put(url).json(&body))?;
+                }
+                Change::CreateDefaultConfig { key, value } => {
+                    let url = format!("{}/default-config/{key}", self.host);
+                    let body = json!({ "value": value, "schema": infer_schema(value) });
+                    self.request(self.http.put(url).json(&body))?;
+                }
+                Change::UpdateDefaultConfig { key, new, .. } => {
+                    let url = format!("{}/default-config/{key}", self.host);
+                    self.request(self.http.put(url).json(&json!({ "value": new })))?;
+                }
+                _ => continue,
+            }
+            println!("{change}");
+        }
+        for change in plan.0.iter() {
+            match change {
+                Change::CreateContext { context, .. }
+                | Change::UpdateContext { context, .. } => self.put_context(context)?,
+                Change::DeleteContext { id, .. } => {
+                    let url = format!("{}/context/{id}", self.host);
+                    self.request(self.http.delete(url))?;
+                }
+                _ => continue,
+            }
+            println!("{change}");
+        }
+        for change in plan.0.iter() {
+            if let Change::DeleteDefaultConfig { key } = change {
+                let url = format!("{}/default-config/{key}", self.host);
+                self.request(self.http.delete(url))?;
+                println!("{change}");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(
+        label: &str,
+        condition: Value,
+        overrides: Value,
+    ) -> (String, ContextState) {
+        (
+            context_id(&condition),
+            ContextState {
+                label: label.to_string(),
+                condition,
+                overrides: overrides.as_object().cloned().unwrap(),
+            },
+        )
+    }
+
+    fn dimension(priority: i32) -> DimensionState {
+        DimensionState {
+            priority,
+            schema: json!({ "type": "string" }),
+        }
+    }
+
+    fn local() -> ConfigState {
+        ConfigState {
+            dimensions: BTreeMap::from([
+                ("os".to_string(), dimension(1)),
+                ("city".to_string(), dimension(2)),
+            ]),
+            default_configs: BTreeMap::from([
+                ("theme".to_string(), json!("light")),
+                ("retries".to_string(), json!(3)),
+            ]),
+            contexts: BTreeMap::from([
+                context(
+                    "os is android",
+                    json!({ "==": [{ "var": "os" }, "android"] }),
+                    json!({ "theme": "dark" }),
+                ),
+                context(
+                    "city is Bangalore",
+                    json!({ "==": [{ "var": "city" }, "Bangalore"] }),
+                    json!({ "retries": 5 }),
+                ),
+            ]),
+        }
+    }
+
+    fn remote() -> ConfigState {
+        ConfigState {
+            dimensions: BTreeMap::from([
+                ("os".to_string(), dimension(3)),
+                ("tier".to_string(), dimension(4)),
+            ]),
+            default_configs: BTreeMap::from([
+                ("theme".to_string(), json!("blue")),
+                ("timeout".to_string(), json!(10)),
+            ]),
+            contexts: BTreeMap::from([
+                context(
+                    "os is android",
+                    json!({ "==": [{ "var": "os" }, "android"] }),
+                    json!({ "theme": "blue", "timeout": 5 }),
+                ),
+                context(
+                    "os is ios",
+                    json!({ "==": [{ "var": "os" }, "ios"] }),
+                    json!({ "theme": "dark" }),
+                ),
+                context(
+                    "os is ios and variantIds in [exp-test]",
+                    json!({ "and": [
+                        { "==": [{ "var": "os" }, "ios"] },
+                        { "in": ["exp-test", { "var": "variantIds" }] }
+                    ]}),
+                    json!({ "theme": "contrast" }),
+                ),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_plan() {
+        let changes = plan(&local(), &remote())
+            .0
+            .iter()
+            .map(|change| change.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            changes,
+            vec![
+                "  + dimension city (priority 2) {\"type\":\"string\"}",
+                "  ~ dimension os\n      ~ priority = 3 -> 1",
+                "  + default-config retries = 3",
+                "  ~ default-config theme = \"blue\" -> \"light\"",
+                "  ~ context \"os is android\"\n      ~ theme = \"blue\" -> \"dark\"\n      - timeout",
+                "  + context \"city is Bangalore\"\n      + retries = 5",
+                "  - context \"os is ios\"",
+                "  - default-config timeout",
+            ]
+        );
+        assert_eq!(plan(&local(), &local()).0.len(), 0);
+    }
+
+    #[test]
+    fn test_plan_leaves_experiment_contexts() {
+        let remote = remote();
+        let experiment_contexts = remote
+            .contexts
+            .iter()
+            .filter(|(_, context)| is_experiment_context(&context.condition))
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<String>>();
+        assert_eq!(experiment_contexts.len(), 1);
+
+        let plan = plan(&ConfigState::default(), &remote);
+        assert!(plan.0.iter().all(|change| !matches!(
+            change,
+            Change::DeleteContext { id, .. } if experiment_contexts.contains(id)
+        )));
+        assert_eq!(plan.summary(), (0, 0, 4));
+    }
+
+    #[test]
+    fn test_plan_display() {
+        assert_eq!(
+            Plan::default().to_string(),
+            "No changes. The tenant is up-to-date."
+        );
+        let mut local = local();
+        local.dimensions.clear();
+        local.contexts.clear();
+        let remote = ConfigState {
+            default_configs: BTreeMap::from([("theme".to_string(), json!("blue"))]),
+            ..ConfigState::default()
+        };
+        assert_eq!(
+            plan(&local, &remote).to_string(),
+            "  + default-config retries = 3\n  \
+             ~ default-config theme = \"blue\" -> \"light\"\n\
+             \nPlan: 1 to add, 1 to change, 0 to destroy."
+        );
+    }
+}
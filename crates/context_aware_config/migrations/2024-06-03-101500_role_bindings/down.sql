@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS role_bindings_audit ON public.role_bindings;
+DROP INDEX IF EXISTS role_bindings_subject_index;
+DROP TABLE IF EXISTS public.role_bindings;
@@ -0,0 +1,17 @@
+-- Your SQL goes here
+-- Name: role_bindings; Type: TABLE; Schema: public; Owner: -
+--
+CREATE TABLE public.role_bindings (
+    id uuid DEFAULT uuid_generate_v4() PRIMARY KEY,
+    subject text NOT NULL,
+    role text NOT NULL CHECK (role IN ('viewer', 'editor', 'experiment-owner', 'admin')),
+    resource text CHECK (resource IN ('dimension', 'default-config', 'context', 'function', 'type', 'experiment', 'role-binding')),
+    key_prefix text,
+    created_by text NOT NULL,
+    created_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+CREATE INDEX IF NOT EXISTS role_bindings_subject_index ON public.role_bindings(subject);
+--
+-- Name: role_bindings role_bindings_audit; Type: TRIGGER; Schema: public; Owner: -
+--
+CREATE TRIGGER role_bindings_audit AFTER INSERT OR DELETE OR UPDATE ON public.role_bindings FOR EACH ROW EXECUTE FUNCTION public.event_logger();
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE public.role_bindings DROP CONSTRAINT IF EXISTS role_bindings_resource_check;
+ALTER TABLE public.role_bindings ADD CONSTRAINT role_bindings_resource_check CHECK (resource IN ('dimension', 'default-config', 'context', 'function', 'type', 'experiment', 'role-binding'));
+DROP TRIGGER IF EXISTS api_keys_audit ON public.api_keys;
+DROP TABLE IF EXISTS public.api_keys;
@@ -0,0 +1,23 @@
+-- Your SQL goes here
+-- Name: api_keys; Type: TABLE; Schema: public; Owner: -
+--
+CREATE TABLE public.api_keys (
+    id uuid DEFAULT uuid_generate_v4() PRIMARY KEY,
+    name text NOT NULL,
+    key_hash text NOT NULL UNIQUE,
+    scopes text[] NOT NULL,
+    created_by text NOT NULL,
+    created_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    expires_at timestamp with time zone,
+    last_used_at timestamp with time zone,
+    revoked_at timestamp with time zone
+);
+--
+-- Name: api_keys api_keys_audit; Type: TRIGGER; Schema: public; Owner: -
+--
+CREATE TRIGGER api_keys_audit AFTER INSERT OR DELETE OR UPDATE OF name, scopes, expires_at, revoked_at ON public.api_keys FOR EACH ROW EXECUTE FUNCTION public.event_logger();
+--
+-- api keys are managed by admins, bindings can grant it like other resources
+--
+ALTER TABLE public.role_bindings DROP CONSTRAINT IF EXISTS role_bindings_resource_check;
+ALTER TABLE public.role_bindings ADD CONSTRAINT role_bindings_resource_check CHECK (resource IN ('dimension', 'default-config', 'context', 'function', 'type', 'experiment', 'role-binding', 'api-key'));
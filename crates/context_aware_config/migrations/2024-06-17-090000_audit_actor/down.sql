@@ -0,0 +1,49 @@
+-- This file should undo anything in `up.sql`
+CREATE OR REPLACE FUNCTION public.event_logger() RETURNS trigger
+    LANGUAGE plpgsql
+    AS $$
+DECLARE
+    old_data json;
+    new_data json;
+BEGIN
+    IF (TG_OP = 'UPDATE') THEN
+        old_data := row_to_json(OLD);
+        new_data := row_to_json(NEW);
+        INSERT INTO public.event_log
+            (table_name, user_name, action, original_data, new_data, query)
+            VALUES (
+                TG_TABLE_NAME::TEXT,
+                session_user::TEXT,
+                TG_OP,
+                old_data,
+                new_data,
+                current_query()
+            );
+    ELSIF (TG_OP = 'DELETE') THEN
+        old_data := row_to_json(OLD);
+        INSERT INTO public.event_log
+            (table_name, user_name, action, original_data, query)
+            VALUES (
+                TG_TABLE_NAME::TEXT,
+                session_user::TEXT,
+                TG_OP,
+                old_data,
+                current_query()
+            );
+    ELSIF (TG_OP = 'INSERT') THEN
+        new_data = row_to_json(NEW);
+        INSERT INTO public.event_log
+            (table_name, user_name, action, new_data, query)
+            VALUES (
+                TG_TABLE_NAME::TEXT,
+                session_user::TEXT,
+                TG_OP,
+                new_data,
+                current_query()
+            );
+    END IF;
+    RETURN NULL;
+END;
+$$;
+DROP INDEX IF EXISTS event_log_user_name_index;
+ALTER TABLE public.event_log DROP COLUMN IF EXISTS request_id;
@@ -0,0 +1,62 @@
+-- Your SQL goes here
+ALTER TABLE public.event_log ADD COLUMN IF NOT EXISTS request_id text;
+CREATE INDEX IF NOT EXISTS event_log_user_name_index ON public.event_log (user_name, "timestamp");
+--
+-- Name: event_logger(); Type: FUNCTION; Schema: public; Owner: -
+-- Writes record the acting user and request through transaction local
+-- settings, changes made outside the service fall back to the session user.
+--
+CREATE OR REPLACE FUNCTION public.event_logger() RETURNS trigger
+    LANGUAGE plpgsql
+    AS $$
+DECLARE
+    old_data json;
+    new_data json;
+    actor text := COALESCE(
+        NULLIF(current_setting('superposition.user_email', true), ''),
+        session_user::TEXT
+    );
+    request text := NULLIF(current_setting('superposition.request_id', true), '');
+BEGIN
+    IF (TG_OP = 'UPDATE') THEN
+        old_data := row_to_json(OLD);
+        new_data := row_to_json(NEW);
+        INSERT INTO public.event_log
+            (table_name, user_name, action, original_data, new_data, query, request_id)
+            VALUES (
+                TG_TABLE_NAME::TEXT,
+                actor,
+                TG_OP,
+                old_data,
+                new_data,
+                current_query(),
+                request
+            );
+    ELSIF (TG_OP = 'DELETE') THEN
+        old_data := row_to_json(OLD);
+        INSERT INTO public.event_log
+            (table_name, user_name, action, original_data, query, request_id)
+            VALUES (
+                TG_TABLE_NAME::TEXT,
+                actor,
+                TG_OP,
+                old_data,
+                current_query(),
+                request
+            );
+    ELSIF (TG_OP = 'INSERT') THEN
+        new_data = row_to_json(NEW);
+        INSERT INTO public.event_log
+            (table_name, user_name, action, new_data, query, request_id)
+            VALUES (
+                TG_TABLE_NAME::TEXT,
+                actor,
+                TG_OP,
+                new_data,
+                current_query(),
+                request
+            );
+    END IF;
+    RETURN NULL;
+END;
+$$;
@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE public.role_bindings DROP CONSTRAINT IF EXISTS role_bindings_resource_check;
+ALTER TABLE public.role_bindings ADD CONSTRAINT role_bindings_resource_check CHECK (resource IN ('dimension', 'default-config', 'context', 'function', 'type', 'experiment', 'role-binding', 'api-key'));
+DROP TRIGGER IF EXISTS config_versions_webhook ON public.config_versions;
+DROP TRIGGER IF EXISTS default_configs_webhook ON public.default_configs;
+DROP TRIGGER IF EXISTS contexts_webhook ON public.contexts;
+DROP FUNCTION IF EXISTS public.enqueue_config_version_webhook_event();
+DROP FUNCTION IF EXISTS public.enqueue_webhook_event();
+DROP TABLE IF EXISTS public.webhook_outbox;
+DROP TABLE IF EXISTS public.webhook_deliveries;
+DROP TABLE IF EXISTS public.webhooks;
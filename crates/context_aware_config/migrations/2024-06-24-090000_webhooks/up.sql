@@ -0,0 +1,82 @@
+-- Your SQL goes here
+-- Name: webhooks; Type: TABLE; Schema: public; Owner: -
+--
+CREATE TABLE public.webhooks (
+    id uuid DEFAULT uuid_generate_v4() PRIMARY KEY,
+    name text NOT NULL,
+    url text NOT NULL,
+    events text[] NOT NULL,
+    secret text NOT NULL,
+    enabled boolean DEFAULT true NOT NULL,
+    created_by text NOT NULL,
+    created_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    last_modified_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+--
+-- Name: webhook_deliveries; Type: TABLE; Schema: public; Owner: -
+--
+CREATE TABLE public.webhook_deliveries (
+    id uuid DEFAULT uuid_generate_v4() PRIMARY KEY,
+    webhook_id uuid NOT NULL REFERENCES public.webhooks(id) ON DELETE CASCADE,
+    event text NOT NULL,
+    payload json NOT NULL,
+    status text DEFAULT 'pending' NOT NULL CHECK (status IN ('pending', 'delivered', 'failed')),
+    attempts integer DEFAULT 0 NOT NULL,
+    next_attempt_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    last_attempt_at timestamp with time zone,
+    response_status integer,
+    last_error text,
+    created_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+CREATE INDEX IF NOT EXISTS webhook_deliveries_due_index ON public.webhook_deliveries(next_attempt_at) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS webhook_deliveries_webhook_index ON public.webhook_deliveries(webhook_id, created_at);
+--
+-- Name: webhook_outbox; Type: TABLE; Schema: public; Owner: -
+--
+-- Events are written by triggers in the transaction making the change, and
+-- moved to webhook_deliveries by the dispatcher.
+--
+CREATE TABLE IF NOT EXISTS public.webhook_outbox (
+    id bigserial PRIMARY KEY,
+    event text NOT NULL,
+    payload json NOT NULL,
+    created_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+--
+-- Name: enqueue_webhook_event(); Type: FUNCTION; Schema: public; Owner: -
+--
+-- The entity name is passed as the trigger argument, events are named
+-- <entity>.created, <entity>.updated and <entity>.deleted.
+--
+CREATE OR REPLACE FUNCTION public.enqueue_webhook_event() RETURNS trigger
+    LANGUAGE plpgsql
+    AS $$
+BEGIN
+    INSERT INTO public.webhook_outbox (event, payload) VALUES (
+        TG_ARGV[0] || '.' || CASE TG_OP WHEN 'INSERT' THEN 'created' WHEN 'UPDATE' THEN 'updated' ELSE 'deleted' END,
+        CASE TG_OP WHEN 'DELETE' THEN row_to_json(OLD) ELSE row_to_json(NEW) END
+    );
+    RETURN NULL;
+END;
+$$;
+--
+-- Name: enqueue_config_version_webhook_event(); Type: FUNCTION; Schema: public; Owner: -
+--
+-- Subscribers fetch the config themselves, it is left out of the payload.
+--
+CREATE OR REPLACE FUNCTION public.enqueue_config_version_webhook_event() RETURNS trigger
+    LANGUAGE plpgsql
+    AS $$
+BEGIN
+    INSERT INTO public.webhook_outbox (event, payload) VALUES (
+        'config.version_created',
+        json_build_object('id', NEW.id::TEXT, 'config_hash', NEW.config_hash, 'tags', NEW.tags, 'created_at', NEW.created_at)
+    );
+    RETURN NULL;
+END;
+$$;
+CREATE TRIGGER contexts_webhook AFTER INSERT OR DELETE OR UPDATE ON public.contexts FOR EACH ROW EXECUTE FUNCTION public.enqueue_webhook_event('context');
+CREATE TRIGGER default_configs_webhook AFTER INSERT OR DELETE OR UPDATE ON public.default_configs FOR EACH ROW EXECUTE FUNCTION public.enqueue_webhook_event('default_config');
+CREATE TRIGGER config_versions_webhook AFTER INSERT ON public.config_versions FOR EACH ROW EXECUTE FUNCTION public.enqueue_config_version_webhook_event();
+ALTER TABLE public.role_bindings DROP CONSTRAINT IF EXISTS role_bindings_resource_check;
+ALTER TABLE public.role_bindings ADD CONSTRAINT role_bindings_resource_check CHECK (resource IN ('dimension', 'default-config', 'context', 'function', 'type', 'experiment', 'role-binding', 'api-key', 'webhook'));
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS webhooks_audit ON public.webhooks;
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+--
+-- Secrets are encrypted by the service, rewriting them is left out of the
+-- event_log.
+--
+DROP TRIGGER IF EXISTS webhooks_audit ON public.webhooks;
+CREATE TRIGGER webhooks_audit AFTER INSERT OR DELETE OR UPDATE OF name, url, events, enabled ON public.webhooks FOR EACH ROW EXECUTE FUNCTION public.event_logger();
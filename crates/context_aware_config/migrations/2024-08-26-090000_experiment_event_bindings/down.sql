@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE public.role_bindings DROP CONSTRAINT IF EXISTS role_bindings_resource_check;
+ALTER TABLE public.role_bindings ADD CONSTRAINT role_bindings_resource_check CHECK (resource IN ('dimension', 'default-config', 'context', 'function', 'type', 'experiment', 'role-binding', 'api-key', 'webhook'));
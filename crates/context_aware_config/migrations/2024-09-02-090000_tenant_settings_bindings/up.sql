@@ -0,0 +1,6 @@
+-- Your SQL goes here
+--
+-- Bindings can be narrowed to the experimentation policy of the tenant.
+--
+ALTER TABLE public.role_bindings DROP CONSTRAINT IF EXISTS role_bindings_resource_check;
+ALTER TABLE public.role_bindings ADD CONSTRAINT role_bindings_resource_check CHECK (resource IN ('dimension', 'default-config', 'context', 'function', 'type', 'experiment', 'experiment-event', 'tenant-settings', 'role-binding', 'api-key', 'webhook'));
@@ -1,7 +1,10 @@
+pub mod api_keys;
 pub mod audit_log;
 pub mod config;
 pub mod context;
 pub mod default_config;
 pub mod dimension;
 pub mod functions;
+pub mod rbac;
 pub mod type_templates;
+pub mod webhooks;
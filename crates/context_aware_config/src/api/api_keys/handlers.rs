@@ -0,0 +1,134 @@
+use actix_web::{
+    delete, get, post,
+    web::{Json, Path},
+    HttpResponse, Scope,
+};
+use chrono::Utc;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use rand::{distributions::Alphanumeric, Rng};
+use service_utils::{
+    db::audit::AuditActor,
+    middlewares::auth::hash_api_key,
+    rbac::{Action, ApiKeyScope, Authorizer, Resource},
+    service::types::DbConnection,
+};
+use superposition_macros::{bad_argument, not_found};
+use superposition_types::{result as superposition, SuperpositionUser, User};
+
+use crate::{
+    api::api_keys::types::{CreateApiKeyReq, CreateApiKeyResponse},
+    db::{models::ApiKey, schema::api_keys},
+};
+
+const API_KEY_PREFIX: &str = "sp_";
+const API_KEY_LENGTH: usize = 40;
+
+pub fn endpoints() -> Scope {
+    Scope::new("")
+        .service(list_api_keys)
+        .service(create_api_key)
+        .service(revoke_api_key)
+}
+
+fn generate_api_key() -> String {
+    let secret = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(API_KEY_LENGTH)
+        .map(char::from)
+        .collect::<String>();
+    format!("{API_KEY_PREFIX}{secret}")
+}
+
+#[get("")]
+async fn list_api_keys(
+    auth: Authorizer,
+    db_conn: DbConnection,
+) -> superposition::Result<Json<Vec<ApiKey>>> {
+    auth.require(Resource::ApiKey, Action::Read)?;
+    let DbConnection(mut conn) = db_conn;
+
+    let keys = api_keys::table
+        .order(api_keys::created_at.asc())
+        .load::<ApiKey>(&mut conn)?;
+    Ok(Json(keys))
+}
+
+#[post("")]
+async fn create_api_key(
+    req: Json<CreateApiKeyReq>,
+    auth: Authorizer,
+    db_conn: DbConnection,
+    user: User,
+    actor: AuditActor,
+) -> superposition::Result<HttpResponse> {
+    auth.require(Resource::ApiKey, Action::Write)?;
+    let DbConnection(mut conn) = db_conn;
+    let req = req.into_inner();
+
+    if req.name.trim().is_empty() {
+        return Err(bad_argument!("name cannot be empty"));
+    }
+    if req.scopes.is_empty() {
+        return Err(bad_argument!("an api key needs at least one scope"));
+    }
+    if req
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(bad_argument!("expires_at must be in the future"));
+    }
+
+    let scopes = req
+        .scopes
+        .iter()
+        .map(|scope| {
+            scope
+                .parse::<ApiKeyScope>()
+                .map(|scope| scope.to_string())
+                .map_err(|_| bad_argument!("unknown api key scope {}", scope))
+        })
+        .collect::<superposition::Result<Vec<String>>>()?;
+
+    let key = generate_api_key();
+    let api_key = actor.transaction(&mut conn, |transaction_conn| {
+        diesel::insert_into(api_keys::table)
+            .values((
+                api_keys::name.eq(req.name),
+                api_keys::key_hash.eq(hash_api_key(&key)),
+                api_keys::scopes.eq(scopes),
+                api_keys::created_by.eq(user.get_email()),
+                api_keys::expires_at.eq(req.expires_at),
+            ))
+            .get_result::<ApiKey>(transaction_conn)
+    })?;
+    log::info!("api key {} created by {}", api_key.name, user.get_email());
+    Ok(HttpResponse::Created().json(CreateApiKeyResponse { key, api_key }))
+}
+
+#[delete("/{id}")]
+async fn revoke_api_key(
+    path: Path<uuid::Uuid>,
+    auth: Authorizer,
+    db_conn: DbConnection,
+    user: User,
+    actor: AuditActor,
+) -> superposition::Result<HttpResponse> {
+    auth.require(Resource::ApiKey, Action::Write)?;
+    let DbConnection(mut conn) = db_conn;
+    let id = path.into_inner();
+
+    let revoked = actor.transaction(&mut conn, |transaction_conn| {
+        diesel::update(
+            api_keys::table
+                .find(id)
+                .filter(api_keys::revoked_at.is_null()),
+        )
+        .set(api_keys::revoked_at.eq(Utc::now()))
+        .execute(transaction_conn)
+    })?;
+    if revoked == 0 {
+        return Err(not_found!("active api key {} doesn't exist", id));
+    }
+    log::info!("api key {id} revoked by {}", user.get_email());
+    Ok(HttpResponse::NoContent().finish())
+}
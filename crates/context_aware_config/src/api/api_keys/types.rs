@@ -0,0 +1,20 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::db::models::ApiKey;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyReq {
+    pub name: String,
+    /// parsed by the handler, so unknown scopes are reported by name
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// The key itself is only returned when it is created.
+#[derive(Debug, Serialize)]
+pub struct CreateApiKeyResponse {
+    pub key: String,
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+}
@@ -1,15 +1,28 @@
-use crate::{api::audit_log::types::AuditQueryFilters, db::models::EventLog};
-use actix_web::{get, web::Query, HttpResponse, Scope};
+use crate::{
+    api::audit_log::types::{AuditEntity, AuditHistoryEntry, AuditQueryFilters},
+    db::models::EventLog,
+};
+use actix_web::{
+    get,
+    web::{Json, Path, Query},
+    HttpResponse, Scope,
+};
 use chrono::{Duration, Utc};
-use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use diesel::{
+    dsl::sql,
+    sql_types::{Bool, Text},
+    ExpressionMethods, QueryDsl, RunQueryDsl,
+};
 use serde_json::json;
-use service_utils::service::types::DbConnection;
+use service_utils::{db::audit::field_changes, service::types::DbConnection};
 use superposition_types::result as superposition;
 
 use crate::db::schema::event_log::dsl as event_log;
 
 pub fn endpoints() -> Scope {
-    Scope::new("").service(get_audit_logs)
+    Scope::new("")
+        .service(get_audit_logs)
+        .service(get_entity_history)
 }
 
 #[get("")]
@@ -30,6 +43,9 @@ async fn get_audit_logs(
         if let Some(username) = filters.username.clone() {
             builder = builder.filter(event_log::user_name.eq(username));
         }
+        if let Some(request_id) = filters.request_id.clone() {
+            builder = builder.filter(event_log::request_id.eq(request_id));
+        }
         let now = Utc::now().naive_utc();
         builder
             .filter(
@@ -61,3 +77,36 @@ async fn get_audit_logs(
         "data": logs
     })))
 }
+
+/// Every change made to one row, most recent first. Rows are matched on the
+/// primary key in either the old or the new data so deletes are included.
+#[get("/entity/{table}/{id}")]
+async fn get_entity_history(
+    path: Path<(AuditEntity, String)>,
+    db_conn: DbConnection,
+) -> superposition::Result<Json<Vec<AuditHistoryEntry>>> {
+    let DbConnection(mut conn) = db_conn;
+    let (entity, id) = path.into_inner();
+    let primary_key = entity.primary_key();
+
+    let logs: Vec<EventLog> = event_log::event_log
+        .filter(event_log::table_name.eq(entity.table_name()))
+        .filter(
+            sql::<Bool>(&format!("(original_data ->> '{primary_key}' = "))
+                .bind::<Text, _>(id.clone())
+                .sql(&format!(" OR new_data ->> '{primary_key}' = "))
+                .bind::<Text, _>(id)
+                .sql(")"),
+        )
+        .order(event_log::timestamp.desc())
+        .load(&mut conn)?;
+
+    let history = logs
+        .into_iter()
+        .map(|log| AuditHistoryEntry {
+            changes: field_changes(log.original_data.as_ref(), log.new_data.as_ref()),
+            log,
+        })
+        .collect();
+    Ok(Json(history))
+}
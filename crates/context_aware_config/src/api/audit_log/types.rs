@@ -1,6 +1,8 @@
 use chrono::NaiveDateTime;
-use serde::Deserialize;
-use service_utils::helpers::deserialize_stringified_list;
+use serde::{Deserialize, Serialize};
+use service_utils::{db::audit::FieldChange, helpers::deserialize_stringified_list};
+
+use crate::db::models::EventLog;
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct StringArgs(
@@ -13,7 +15,46 @@ pub struct AuditQueryFilters {
     pub to_date: Option<NaiveDateTime>,
     pub table: Option<StringArgs>,
     pub action: Option<StringArgs>,
+    /// Email of the user who made the change
     pub username: Option<String>,
+    pub request_id: Option<String>,
     pub count: Option<i64>,
     pub page: Option<i64>,
 }
+
+/// Tables whose rows have a change history, named as in `event_log`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEntity {
+    Contexts,
+    DefaultConfigs,
+    Dimensions,
+    Functions,
+}
+
+impl AuditEntity {
+    pub fn table_name(&self) -> &'static str {
+        match self {
+            AuditEntity::Contexts => "contexts",
+            AuditEntity::DefaultConfigs => "default_configs",
+            AuditEntity::Dimensions => "dimensions",
+            AuditEntity::Functions => "functions",
+        }
+    }
+
+    pub fn primary_key(&self) -> &'static str {
+        match self {
+            AuditEntity::Contexts => "id",
+            AuditEntity::DefaultConfigs => "key",
+            AuditEntity::Dimensions => "dimension",
+            AuditEntity::Functions => "function_name",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditHistoryEntry {
+    #[serde(flatten)]
+    pub log: EventLog,
+    pub changes: Vec<FieldChange>,
+}
@@ -1,26 +1,34 @@
 use std::{collections::HashMap, str::FromStr};
 
-use super::helpers::{filter_config_by_dimensions, filter_config_by_prefix};
-use super::types::{Config, Context};
+use super::helpers::{
+    experiment_variant_ids, export_config_bundle, filter_config_by_dimensions,
+    filter_config_by_prefix, import_config_bundle,
+};
+use super::types::{
+    Config, ConfigBundle, Context, ExperimentResolveResponse, ImportQuery,
+};
 use crate::api::context::{
     delete_context_api, hash, put, validate_dimensions_and_calculate_priority, PutReq,
 };
 use crate::api::dimension::get_all_dimension_schema_map;
 use crate::{
     db::schema::{config_versions::dsl as config_versions, event_log::dsl as event_log},
-    helpers::generate_cac,
+    helpers::{add_config_version, generate_cac},
 };
 use actix_http::header::HeaderValue;
 use actix_web::{
-    get, put, web, web::Query, HttpRequest, HttpResponse, HttpResponseBuilder, Scope,
+    get, post, put,
+    web::{self, Data, Json, Query},
+    HttpRequest, HttpResponse, HttpResponseBuilder, Scope,
 };
 use cac_client::{eval_cac, eval_cac_with_reasoning, MergeStrategy};
 use chrono::{DateTime, NaiveDateTime, TimeZone, Timelike, Utc};
 use diesel::{
     dsl::max,
     r2d2::{ConnectionManager, PooledConnection},
-    ExpressionMethods, PgConnection, QueryDsl, RunQueryDsl,
+    Connection, ExpressionMethods, PgConnection, QueryDsl, RunQueryDsl,
 };
+use experimentation_platform::api::experiments::bucketing::{toss_of, BucketingCache};
 use serde_json::{json, Map, Value};
 use superposition_macros::{bad_argument, db_error, unexpected_error};
 use superposition_types::{result as superposition, User};
@@ -28,8 +36,10 @@ use superposition_types::{result as superposition, User};
 use itertools::Itertools;
 use jsonschema::JSONSchema;
 use service_utils::{
-    helpers::extract_dimensions,
-    service::types::{AppHeader, DbConnection},
+    db::{audit::AuditActor, utils::experimentation_namespace},
+    helpers::{extract_dimensions, parse_config_tags},
+    rbac::{Action, Authorizer, Resource},
+    service::types::{AppHeader, AppState, CustomHeaders, DbConnection, Tenant},
 };
 use uuid::Uuid;
 
@@ -37,7 +47,10 @@ pub fn endpoints() -> Scope {
     Scope::new("")
         .service(get)
         .service(get_resolved_config)
+        .service(resolve_with_experiments)
         .service(reduce_config)
+        .service(export_config)
+        .service(import_config)
 }
 
 fn validate_version_in_params(
@@ -338,6 +351,7 @@ fn construct_new_payload(req_payload: &Map<String, Value>) -> web::Json<PutReq>
 #[allow(clippy::too_many_arguments)]
 async fn reduce_config_key(
     user: User,
+    actor: &AuditActor,
     conn: &mut PooledConnection<ConnectionManager<PgConnection>>,
     mut og_contexts: Vec<Context>,
     mut og_overrides: Map<String, Value>,
@@ -409,14 +423,26 @@ async fn reduce_config_key(
             ) => {
                 if *to_be_deleted {
                     if is_approve {
-                        let _ = delete_context_api(cid.clone(), user.clone(), conn);
+                        let _ = actor.transaction(conn, |transaction_conn| {
+                            delete_context_api(
+                                cid.clone(),
+                                user.clone(),
+                                transaction_conn,
+                            )
+                        });
                     }
                     og_contexts.retain(|x| x.id != *cid);
                 } else {
                     if is_approve {
-                        let _ = delete_context_api(cid.clone(), user.clone(), conn);
-                        let put_req = construct_new_payload(request_payload);
-                        let _ = put(put_req, conn, false, &user);
+                        let _ = actor.transaction(conn, |transaction_conn| {
+                            delete_context_api(
+                                cid.clone(),
+                                user.clone(),
+                                transaction_conn,
+                            )?;
+                            let put_req = construct_new_payload(request_payload);
+                            put(put_req, transaction_conn, true, &user)
+                        });
                     }
 
                     let new_id = hash(override_val);
@@ -457,6 +483,8 @@ async fn reduce_config_key(
 async fn reduce_config(
     req: HttpRequest,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
     db_conn: DbConnection,
 ) -> superposition::Result<HttpResponse> {
     let DbConnection(mut conn) = db_conn;
@@ -465,6 +493,9 @@ async fn reduce_config(
         .get("x-approve")
         .and_then(|value| value.to_str().ok().and_then(|s| s.parse::<bool>().ok()))
         .unwrap_or(false);
+    if is_approve {
+        auth.require(Resource::Context, Action::Write)?;
+    }
 
     let dimensions_schema_map = get_all_dimension_schema_map(&mut conn)?;
     let mut config = generate_cac(&mut conn)?;
@@ -475,6 +506,7 @@ async fn reduce_config(
         let default_config = config.default_configs;
         config = reduce_config_key(
             user.clone(),
+            &actor,
             &mut conn,
             contexts.clone(),
             overrides.clone(),
@@ -552,28 +584,55 @@ async fn get(
     Ok(response.json(config))
 }
 
+fn resolve_query_params(
+    req: &HttpRequest,
+) -> superposition::Result<HashMap<String, String>> {
+    Query::<HashMap<String, String>>::from_query(req.query_string())
+        .map(Query::into_inner)
+        .map_err(|err| {
+            log::error!("failed to parse query params with err: {}", err);
+            bad_argument!("error getting query params")
+        })
+}
+
+/// Dimension values of the query params, integers where they parse as one.
+fn query_data(params: HashMap<String, String>) -> Map<String, Value> {
+    params
+        .into_iter()
+        .map(|(key, value)| {
+            let value = value
+                .parse::<i32>()
+                .map_or_else(|_| json!(value), |int_val| json!(int_val));
+            (key, value)
+        })
+        .collect()
+}
+
+fn merge_strategy(req: &HttpRequest) -> MergeStrategy {
+    req.headers()
+        .get("x-merge-strategy")
+        .and_then(|header_value: &HeaderValue| header_value.to_str().ok())
+        .and_then(|val| MergeStrategy::from_str(val).ok())
+        .unwrap_or_default()
+}
+
+fn cac_client_contexts(contexts: Vec<Context>) -> Vec<cac_client::Context> {
+    contexts
+        .into_iter()
+        .map(|val| cac_client::Context {
+            condition: val.condition,
+            override_with_keys: val.override_with_keys,
+        })
+        .collect()
+}
+
 #[get("/resolve")]
 async fn get_resolved_config(
     req: HttpRequest,
     db_conn: DbConnection,
 ) -> superposition::Result<HttpResponse> {
     let DbConnection(mut conn) = db_conn;
-    let params = Query::<HashMap<String, String>>::from_query(req.query_string())
-        .map_err(|err| {
-            log::error!("failed to parse query params with err: {}", err);
-            bad_argument!("error getting query params")
-        })?;
-
-    let mut query_params_map: serde_json::Map<String, Value> = Map::new();
-
-    for item in params.0.into_iter() {
-        query_params_map.insert(
-            item.0,
-            item.1
-                .parse::<i32>()
-                .map_or_else(|_| json!(item.1), |int_val| json!(int_val)),
-        );
-    }
+    let mut query_params_map = query_data(resolve_query_params(&req)?);
 
     let max_created_at = get_max_created_at(&mut conn)
         .map_err(|e| log::error!("failed to fetch max timestamp from event_log : {e}"))
@@ -588,21 +647,8 @@ async fn get_resolved_config(
     let mut config_version = validate_version_in_params(&mut query_params_map)?;
     let config = generate_config_from_version(&mut config_version, &mut conn)?;
 
-    let cac_client_contexts = config
-        .contexts
-        .into_iter()
-        .map(|val| cac_client::Context {
-            condition: val.condition,
-            override_with_keys: val.override_with_keys,
-        })
-        .collect::<Vec<_>>();
-
-    let merge_strategy = req
-        .headers()
-        .get("x-merge-strategy")
-        .and_then(|header_value: &HeaderValue| header_value.to_str().ok())
-        .and_then(|val| MergeStrategy::from_str(val).ok())
-        .unwrap_or_default();
+    let cac_client_contexts = cac_client_contexts(config.contexts);
+    let merge_strategy = merge_strategy(&req);
 
     let response = if let Some(Value::String(_)) = query_params_map.get("show_reasoning")
     {
@@ -637,3 +683,149 @@ async fn get_resolved_config(
 
     Ok(resp.json(response))
 }
+
+/// Resolves the latest config of a context with the variants of the
+/// experiments it is in, chosen for the unit `identifier` names the way the
+/// experimentation client chooses them.
+#[get("/resolve-with-experiments")]
+async fn resolve_with_experiments(
+    req: HttpRequest,
+    state: Data<AppState>,
+    cache: Data<BucketingCache>,
+    tenant: Tenant,
+    db_conn: DbConnection,
+) -> superposition::Result<HttpResponse> {
+    let DbConnection(mut conn) = db_conn;
+    let mut params = resolve_query_params(&req)?;
+    let identifier = params.remove("identifier").ok_or_else(|| {
+        bad_argument!("identifier to bucket the request with is missing")
+    })?;
+    // experiments are only known as they are now, and the params are the
+    // dimensions they are matched with
+    for param in ["version", "show_reasoning"] {
+        if params.contains_key(param) {
+            return Err(bad_argument!(
+                "{} is not supported when resolving with experiments",
+                param
+            ));
+        }
+    }
+    let toss = toss_of(&identifier);
+    let mut query_params_map = query_data(params);
+
+    let mut config_version = None;
+    let config = generate_config_from_version(&mut config_version, &mut conn)?;
+
+    // experiments live in their own pool
+    let namespace =
+        experimentation_namespace(state.enable_tenant_and_scope, Some(tenant.0.as_str()))
+            .ok_or_else(|| bad_argument!("x-tenant header not set"))?;
+    let mut experiment_conn =
+        state.db_pool.get_conn(namespace.clone()).map_err(|err| {
+            log::error!("failed to get an experimentation connection: {err}");
+            unexpected_error!("Something went wrong, failed to load experiments")
+        })?;
+    let variant_ids = experiment_variant_ids(
+        &cache,
+        &mut experiment_conn,
+        &namespace,
+        &query_params_map,
+        toss,
+    )?;
+    query_params_map.insert("variantIds".to_string(), json!(variant_ids));
+
+    let config = eval_cac(
+        config.default_configs,
+        &cac_client_contexts(config.contexts),
+        &config.overrides,
+        &query_params_map,
+        merge_strategy(&req),
+    )
+    .map_err(|err| {
+        log::error!("failed to eval cac with err: {}", err);
+        unexpected_error!("cac eval failed")
+    })?;
+    let mut resp = HttpResponse::Ok();
+    add_audit_id_to_header(&mut conn, &mut resp);
+    add_config_version_to_header(&config_version, &mut resp);
+
+    Ok(resp.json(ExperimentResolveResponse {
+        config,
+        variant_ids,
+        toss,
+    }))
+}
+
+#[get("/export")]
+async fn export_config(
+    db_conn: DbConnection,
+    auth: Authorizer,
+) -> superposition::Result<HttpResponse> {
+    // an export reads every part of the config
+    for resource in [
+        Resource::Function,
+        Resource::Type,
+        Resource::Dimension,
+        Resource::DefaultConfig,
+        Resource::Context,
+    ] {
+        auth.require(resource, Action::Read)?;
+    }
+    let DbConnection(mut conn) = db_conn;
+    let bundle = export_config_bundle(&mut conn)?;
+    Ok(HttpResponse::Ok().json(bundle))
+}
+
+#[post("/import")]
+async fn import_config(
+    state: Data<AppState>,
+    custom_headers: CustomHeaders,
+    query: Query<ImportQuery>,
+    req: Json<ConfigBundle>,
+    db_conn: DbConnection,
+    user: User,
+    actor: AuditActor,
+    auth: Authorizer,
+) -> superposition::Result<HttpResponse> {
+    // an import can touch every part of the config
+    for resource in [
+        Resource::Function,
+        Resource::Type,
+        Resource::Dimension,
+        Resource::DefaultConfig,
+        Resource::Context,
+    ] {
+        auth.require(resource, Action::Write)?;
+    }
+    let DbConnection(mut conn) = db_conn;
+    let tags = parse_config_tags(custom_headers.config_tags)?;
+    let mode = query.mode.unwrap_or_default();
+    let dry_run = query.dry_run.unwrap_or(false);
+    let bundle = req.into_inner();
+
+    conn.transaction::<_, superposition::AppError, _>(|transaction_conn| {
+        actor.record(transaction_conn)?;
+        if dry_run {
+            diesel::sql_query("SAVEPOINT import_dry_run").execute(transaction_conn)?;
+        }
+        let mut report =
+            import_config_bundle(&state, &bundle, mode, &user, transaction_conn)
+                .map_err(|err| {
+                    log::info!("config import failed with error: {:?}", err);
+                    err
+                })?;
+        let mut http_resp = HttpResponse::Ok();
+        if dry_run {
+            diesel::sql_query("ROLLBACK TO SAVEPOINT import_dry_run")
+                .execute(transaction_conn)?;
+            report.dry_run = true;
+        } else {
+            let version_id = add_config_version(&state, tags, transaction_conn)?;
+            http_resp.insert_header((
+                AppHeader::XConfigVersion.to_string(),
+                version_id.to_string(),
+            ));
+        }
+        Ok(http_resp.json(report))
+    })
+}
@@ -1,8 +1,47 @@
-use super::types::{Config, Context};
+extern crate base64;
+use std::collections::{HashMap, HashSet};
 
+use super::types::{
+    BundleContext, BundleDefaultConfig, BundleDimension, BundleFunction,
+    BundleTypeTemplate, Config, ConfigBundle, Context, ImportMode, ImportReport,
+    ImportSummary,
+};
+use crate::{
+    api::{
+        context::{hash, override_helper, PutReq},
+        default_config::KEY_NAME_REGEX,
+        functions::helpers::{decode_function, get_published_function_code},
+    },
+    db::{
+        models::{self, DefaultConfig, Dimension, Function, TypeTemplates},
+        schema::{contexts, default_configs, dimensions, functions, type_templates},
+    },
+    helpers::validate_jsonschema,
+    validation_functions::compile_fn,
+};
+use actix_web::web::{Data, Json};
+use base64::prelude::*;
+use chrono::Utc;
+use diesel::{
+    r2d2::{ConnectionManager, PooledConnection},
+    ExpressionMethods, PgConnection, QueryDsl, RunQueryDsl,
+};
+use experimentation_platform::api::experiments::bucketing::{
+    choose_variant, BucketingCache,
+};
+use jsonschema::{Draft, JSONSchema, ValidationError};
+use regex::Regex;
 use serde_json::{json, Map, Value};
-use superposition_macros::unexpected_error;
-use superposition_types::result as superposition;
+use service_utils::{
+    helpers::{extract_dimensions, validation_err_to_str},
+    service::types::AppState,
+};
+use superposition_macros::{bad_argument, unexpected_error, validation_error};
+use superposition_types::{result as superposition, SuperpositionUser, User};
+
+use crate::api::context::helpers::validate_value_with_function;
+
+type DBConnection = PooledConnection<ConnectionManager<PgConnection>>;
 
 pub fn filter_context(
     contexts: &[Context],
@@ -20,6 +59,39 @@ pub fn filter_context(
         .collect()
 }
 
+/// Variants the unit of `toss` is served in the experiments `dimension_data`
+/// satisfies, chosen like the experimentation client chooses them. The
+/// experiments are those of `namespace`, `conn` is a connection to it.
+pub fn experiment_variant_ids(
+    cache: &BucketingCache,
+    conn: &mut PgConnection,
+    namespace: &str,
+    dimension_data: &Map<String, Value>,
+    toss: i8,
+) -> superposition::Result<Vec<String>> {
+    let bucketed = cache.get(conn, namespace)?;
+    let buckets = bucketed.buckets;
+    let context = json!(dimension_data);
+    let variant_ids = bucketed
+        .experiments
+        .iter()
+        .filter(|(experiment, _)| {
+            let is_empty = experiment
+                .context
+                .as_object()
+                .map_or(false, |context| context.is_empty());
+            is_empty
+                || jsonlogic::apply(&experiment.context, &context)
+                    == Ok(Value::Bool(true))
+        })
+        .filter_map(|(experiment, variants)| {
+            choose_variant(experiment, variants, toss, buckets)
+                .map(|variant| variant.id.clone())
+        })
+        .collect();
+    Ok(variant_ids)
+}
+
 pub fn filter_config_by_prefix(
     config: &Config,
     prefix_list: &Vec<String>,
@@ -96,3 +168,568 @@ pub fn filter_config_by_dimensions(
 
     Ok(filtered_config)
 }
+
+/// Whether `condition` pins `variantIds`, which makes it the context of an
+/// experiment variant. Those are managed through experiments, never bundles.
+fn is_experiment_context(condition: &Value) -> superposition::Result<bool> {
+    Ok(extract_dimensions(condition)?.contains_key("variantIds"))
+}
+
+pub fn export_config_bundle(
+    conn: &mut DBConnection,
+) -> superposition::Result<ConfigBundle> {
+    let dimensions = dimensions::table
+        .order(dimensions::dimension.asc())
+        .load::<Dimension>(conn)?
+        .into_iter()
+        .map(|dimension| BundleDimension {
+            dimension: dimension.dimension,
+            priority: dimension.priority,
+            schema: dimension.schema,
+            function_name: dimension.function_name,
+        })
+        .collect();
+
+    let default_configs = default_configs::table
+        .order(default_configs::key.asc())
+        .load::<DefaultConfig>(conn)?
+        .into_iter()
+        .map(|config| BundleDefaultConfig {
+            key: config.key,
+            value: config.value,
+            schema: config.schema,
+            function_name: config.function_name,
+        })
+        .collect();
+
+    let contexts = contexts::table
+        .order((contexts::priority.asc(), contexts::created_at.asc()))
+        .load::<models::Context>(conn)?
+        .into_iter()
+        .filter_map(|context| match is_experiment_context(&context.value) {
+            Ok(true) => None,
+            Ok(false) => Some(match (context.value, context.override_) {
+                (Value::Object(condition), Value::Object(r#override)) => {
+                    Ok(BundleContext {
+                        condition,
+                        r#override,
+                    })
+                }
+                _ => {
+                    log::error!("context {} is not a valid JSON object", context.id);
+                    Err(unexpected_error!("failed to decode context {}", context.id))
+                }
+            }),
+            Err(err) => Some(Err(err)),
+        })
+        .collect::<superposition::Result<Vec<BundleContext>>>()?;
+
+    let mut function_list = functions::table
+        .order(functions::function_name.asc())
+        .load::<Function>(conn)?;
+    for function in function_list.iter_mut() {
+        decode_function(function)?;
+    }
+    let functions = function_list
+        .into_iter()
+        .map(|function| BundleFunction {
+            function_name: function.function_name,
+            function_description: function.function_description,
+            draft_code: function.draft_code,
+            draft_runtime_version: function.draft_runtime_version,
+            published_code: function.published_code,
+            published_runtime_version: function.published_runtime_version,
+        })
+        .collect();
+
+    let type_templates = type_templates::table
+        .order(type_templates::type_name.asc())
+        .load::<TypeTemplates>(conn)?
+        .into_iter()
+        .map(|type_template| BundleTypeTemplate {
+            type_name: type_template.type_name,
+            type_schema: type_template.type_schema,
+        })
+        .collect();
+
+    Ok(ConfigBundle {
+        dimensions,
+        default_configs,
+        contexts,
+        functions,
+        type_templates,
+    })
+}
+
+/// Records whether `incoming` creates, updates or leaves `key` untouched, and
+/// returns `true` when the item has to be written.
+fn classify<T: PartialEq>(
+    existing: &HashMap<String, T>,
+    key: &str,
+    incoming: &T,
+    summary: &mut ImportSummary,
+) -> bool {
+    match existing.get(key) {
+        None => {
+            summary.created.push(key.to_string());
+            true
+        }
+        Some(current) if current == incoming => {
+            summary.unchanged.push(key.to_string());
+            false
+        }
+        Some(_) => {
+            summary.updated.push(key.to_string());
+            true
+        }
+    }
+}
+
+fn stale_keys<T>(
+    existing: &HashMap<String, T>,
+    incoming: &HashSet<String>,
+) -> Vec<String> {
+    let mut keys: Vec<String> = existing
+        .keys()
+        .filter(|key| !incoming.contains(*key))
+        .cloned()
+        .collect();
+    keys.sort();
+    keys
+}
+
+fn compile_schema(schema: &Value, entity: &str) -> superposition::Result<JSONSchema> {
+    JSONSchema::options()
+        .with_draft(Draft::Draft7)
+        .compile(schema)
+        .map_err(|err| {
+            log::error!("failed to compile schema for {entity}: {err}");
+            bad_argument!("Invalid JSON schema (failed to compile) for {}", entity)
+        })
+}
+
+fn validate_bundle_default_config(
+    state: &Data<AppState>,
+    config: &BundleDefaultConfig,
+    key_regex: &Regex,
+    conn: &mut DBConnection,
+) -> superposition::Result<()> {
+    if !key_regex.is_match(&config.key) {
+        return Err(bad_argument!(
+            "The key name {} is invalid, it should obey the regex {}",
+            config.key,
+            KEY_NAME_REGEX
+        ));
+    }
+    validate_jsonschema(&state.default_config_validation_schema, &config.schema)?;
+    let jschema = compile_schema(&config.schema, &config.key)?;
+    if let Err(e) = jschema.validate(&config.value) {
+        let verrors = e.collect::<Vec<ValidationError>>();
+        log::info!(
+            "({}) default config validation error: {:?}",
+            config.key,
+            verrors
+        );
+        return Err(validation_error!(
+            "Schema validation failed for {}: {}",
+            config.key,
+            validation_err_to_str(verrors)
+                .first()
+                .unwrap_or(&String::new())
+        ));
+    }
+    if let Some(f_name) = &config.function_name {
+        let function_code = get_published_function_code(conn, f_name.to_string())
+            .map_err(|e| {
+                log::info!("Function not found with error : {e}");
+                bad_argument!("Function {} doesn't exists.", f_name)
+            })?;
+        if let Some(f_code) = function_code {
+            validate_value_with_function(f_name, &f_code, &config.key, &config.value)?;
+        }
+    }
+    Ok(())
+}
+
+fn import_functions(
+    bundle: &ConfigBundle,
+    mode: ImportMode,
+    user: &User,
+    conn: &mut DBConnection,
+    report: &mut ImportReport,
+) -> superposition::Result<HashSet<String>> {
+    let mut existing_functions = functions::table.load::<Function>(conn)?;
+    for function in existing_functions.iter_mut() {
+        decode_function(function)?;
+    }
+    let existing: HashMap<String, BundleFunction> = existing_functions
+        .into_iter()
+        .map(|f| {
+            (
+                f.function_name.clone(),
+                BundleFunction {
+                    function_name: f.function_name,
+                    function_description: f.function_description,
+                    draft_code: f.draft_code,
+                    draft_runtime_version: f.draft_runtime_version,
+                    published_code: f.published_code,
+                    published_runtime_version: f.published_runtime_version,
+                },
+            )
+        })
+        .collect();
+
+    for function in bundle.functions.iter() {
+        if !classify(
+            &existing,
+            &function.function_name,
+            function,
+            &mut report.functions,
+        ) {
+            continue;
+        }
+        compile_fn(&function.draft_code)?;
+        if let Some(code) = &function.published_code {
+            compile_fn(code)?;
+        }
+        let now = Utc::now().naive_utc();
+        let is_published = function.published_code.is_some();
+        let new_function = Function {
+            function_name: function.function_name.clone(),
+            function_description: function.function_description.clone(),
+            draft_code: BASE64_STANDARD.encode(&function.draft_code),
+            draft_runtime_version: function.draft_runtime_version.clone(),
+            draft_edited_by: user.get_email(),
+            draft_edited_at: now,
+            published_code: function
+                .published_code
+                .as_ref()
+                .map(|code| BASE64_STANDARD.encode(code)),
+            published_runtime_version: function.published_runtime_version.clone(),
+            published_by: is_published.then(|| user.get_email()),
+            published_at: is_published.then_some(now),
+        };
+        diesel::insert_into(functions::table)
+            .values(&new_function)
+            .on_conflict(functions::function_name)
+            .do_update()
+            .set(&new_function)
+            .execute(conn)?;
+    }
+
+    let incoming: HashSet<String> = bundle
+        .functions
+        .iter()
+        .map(|f| f.function_name.clone())
+        .collect();
+    Ok(match mode {
+        ImportMode::Replace => stale_keys(&existing, &incoming).into_iter().collect(),
+        ImportMode::Merge => HashSet::new(),
+    })
+}
+
+fn import_type_templates(
+    bundle: &ConfigBundle,
+    mode: ImportMode,
+    user: &User,
+    conn: &mut DBConnection,
+    report: &mut ImportReport,
+) -> superposition::Result<()> {
+    let existing: HashMap<String, BundleTypeTemplate> = type_templates::table
+        .load::<TypeTemplates>(conn)?
+        .into_iter()
+        .map(|t| {
+            (
+                t.type_name.clone(),
+                BundleTypeTemplate {
+                    type_name: t.type_name,
+                    type_schema: t.type_schema,
+                },
+            )
+        })
+        .collect();
+
+    for type_template in bundle.type_templates.iter() {
+        if !classify(
+            &existing,
+            &type_template.type_name,
+            type_template,
+            &mut report.type_templates,
+        ) {
+            continue;
+        }
+        compile_schema(&type_template.type_schema, &type_template.type_name)?;
+        let now = Utc::now().naive_utc();
+        diesel::insert_into(type_templates::table)
+            .values((
+                type_templates::type_name.eq(&type_template.type_name),
+                type_templates::type_schema.eq(&type_template.type_schema),
+                type_templates::created_by.eq(user.get_email()),
+            ))
+            .on_conflict(type_templates::type_name)
+            .do_update()
+            .set((
+                type_templates::type_schema.eq(&type_template.type_schema),
+                type_templates::last_modified.eq(now),
+            ))
+            .execute(conn)?;
+    }
+
+    if mode == ImportMode::Replace {
+        let incoming: HashSet<String> = bundle
+            .type_templates
+            .iter()
+            .map(|t| t.type_name.clone())
+            .collect();
+        let stale = stale_keys(&existing, &incoming);
+        diesel::delete(
+            type_templates::table.filter(type_templates::type_name.eq_any(&stale)),
+        )
+        .execute(conn)?;
+        report.type_templates.deleted = stale;
+    }
+    Ok(())
+}
+
+fn import_dimensions(
+    state: &Data<AppState>,
+    bundle: &ConfigBundle,
+    user: &User,
+    conn: &mut DBConnection,
+    report: &mut ImportReport,
+) -> superposition::Result<HashMap<String, BundleDimension>> {
+    let existing: HashMap<String, BundleDimension> = dimensions::table
+        .load::<Dimension>(conn)?
+        .into_iter()
+        .map(|d| {
+            (
+                d.dimension.clone(),
+                BundleDimension {
+                    dimension: d.dimension,
+                    priority: d.priority,
+                    schema: d.schema,
+                    function_name: d.function_name,
+                },
+            )
+        })
+        .collect();
+
+    for dimension in bundle.dimensions.iter() {
+        if !classify(
+            &existing,
+            &dimension.dimension,
+            dimension,
+            &mut report.dimensions,
+        ) {
+            continue;
+        }
+        if dimension.priority <= 0 {
+            return Err(bad_argument!(
+                "Priority of dimension {} should be greater than 0",
+                dimension.dimension
+            ));
+        }
+        validate_jsonschema(&state.meta_schema, &dimension.schema)?;
+        compile_schema(&dimension.schema, &dimension.dimension)?;
+        let new_dimension = Dimension {
+            dimension: dimension.dimension.clone(),
+            priority: dimension.priority,
+            schema: dimension.schema.clone(),
+            function_name: dimension.function_name.clone(),
+            created_by: user.get_email(),
+            created_at: Utc::now(),
+        };
+        diesel::insert_into(dimensions::table)
+            .values(&new_dimension)
+            .on_conflict(dimensions::dimension)
+            .do_update()
+            .set((
+                dimensions::priority.eq(new_dimension.priority),
+                dimensions::schema.eq(&new_dimension.schema),
+                dimensions::function_name.eq(&new_dimension.function_name),
+            ))
+            .execute(conn)?;
+    }
+    Ok(existing)
+}
+
+fn import_default_configs(
+    state: &Data<AppState>,
+    bundle: &ConfigBundle,
+    user: &User,
+    conn: &mut DBConnection,
+    report: &mut ImportReport,
+) -> superposition::Result<HashMap<String, BundleDefaultConfig>> {
+    let key_regex = Regex::new(KEY_NAME_REGEX).map_err(|err| {
+        unexpected_error!("could not parse regex due to: {}", err.to_string())
+    })?;
+    let existing: HashMap<String, BundleDefaultConfig> = default_configs::table
+        .load::<DefaultConfig>(conn)?
+        .into_iter()
+        .map(|c| {
+            (
+                c.key.clone(),
+                BundleDefaultConfig {
+                    key: c.key,
+                    value: c.value,
+                    schema: c.schema,
+                    function_name: c.function_name,
+                },
+            )
+        })
+        .collect();
+
+    for config in bundle.default_configs.iter() {
+        if !classify(&existing, &config.key, config, &mut report.default_configs) {
+            continue;
+        }
+        validate_bundle_default_config(state, config, &key_regex, conn)?;
+        let new_config = DefaultConfig {
+            key: config.key.clone(),
+            value: config.value.clone(),
+            schema: config.schema.clone(),
+            function_name: config.function_name.clone(),
+            created_by: user.get_email(),
+            created_at: Utc::now(),
+        };
+        diesel::insert_into(default_configs::table)
+            .values(&new_config)
+            .on_conflict(default_configs::key)
+            .do_update()
+            .set((
+                default_configs::value.eq(&new_config.value),
+                default_configs::schema.eq(&new_config.schema),
+                default_configs::function_name.eq(&new_config.function_name),
+            ))
+            .execute(conn)?;
+    }
+    Ok(existing)
+}
+
+fn import_contexts(
+    bundle: &ConfigBundle,
+    user: &User,
+    conn: &mut DBConnection,
+    report: &mut ImportReport,
+) -> superposition::Result<()> {
+    let existing: HashMap<String, Value> = contexts::table
+        .select((contexts::id, contexts::override_))
+        .load::<(String, Value)>(conn)?
+        .into_iter()
+        .collect();
+
+    let mut incoming = HashSet::new();
+    for context in bundle.contexts.iter() {
+        let context_id = hash(&Value::Object(context.condition.clone()));
+        if !incoming.insert(context_id.clone()) {
+            return Err(bad_argument!(
+                "context {} is present more than once in the bundle",
+                Value::Object(context.condition.clone())
+            ));
+        }
+        let r#override = Value::Object(context.r#override.clone());
+        if !classify(&existing, &context_id, &r#override, &mut report.contexts) {
+            continue;
+        }
+        override_helper(
+            Json(PutReq {
+                context: context.condition.clone(),
+                r#override: context.r#override.clone(),
+            }),
+            conn,
+            true,
+            user,
+        )?;
+    }
+    Ok(())
+}
+
+/// Applies `bundle` to the tenant behind `conn`. Must be called inside a
+/// transaction, nothing is committed here.
+pub fn import_config_bundle(
+    state: &Data<AppState>,
+    bundle: &ConfigBundle,
+    mode: ImportMode,
+    user: &User,
+    conn: &mut DBConnection,
+) -> superposition::Result<ImportReport> {
+    let mut report = ImportReport::default();
+
+    // experiment contexts need experiment permissions the import is not
+    // authorized with
+    for context in bundle.contexts.iter() {
+        let condition = Value::Object(context.condition.clone());
+        if is_experiment_context(&condition)? {
+            return Err(bad_argument!(
+                "context {} belongs to an experiment and can not be imported",
+                condition
+            ));
+        }
+    }
+
+    // functions and types come first since dimensions and default configs
+    // can reference them, contexts come last as they are validated against both
+    let stale_functions = import_functions(bundle, mode, user, conn, &mut report)?;
+    import_type_templates(bundle, mode, user, conn, &mut report)?;
+
+    if mode == ImportMode::Replace {
+        // contexts are dropped before the keys and dimensions they use, the
+        // contexts of experiments are left to the experiments
+        let incoming: HashSet<String> = bundle
+            .contexts
+            .iter()
+            .map(|context| hash(&Value::Object(context.condition.clone())))
+            .collect();
+        let mut stale = Vec::new();
+        for (id, condition) in contexts::table
+            .select((contexts::id, contexts::value))
+            .load::<(String, Value)>(conn)?
+        {
+            if !incoming.contains(&id) && !is_experiment_context(&condition)? {
+                stale.push(id);
+            }
+        }
+        stale.sort();
+        diesel::delete(contexts::table.filter(contexts::id.eq_any(&stale)))
+            .execute(conn)?;
+        report.contexts.deleted = stale;
+    }
+
+    let existing_dimensions = import_dimensions(state, bundle, user, conn, &mut report)?;
+    let existing_configs =
+        import_default_configs(state, bundle, user, conn, &mut report)?;
+
+    if mode == ImportMode::Replace {
+        let incoming: HashSet<String> = bundle
+            .default_configs
+            .iter()
+            .map(|c| c.key.clone())
+            .collect();
+        let stale = stale_keys(&existing_configs, &incoming);
+        diesel::delete(
+            default_configs::table.filter(default_configs::key.eq_any(&stale)),
+        )
+        .execute(conn)?;
+        report.default_configs.deleted = stale;
+
+        let incoming: HashSet<String> = bundle
+            .dimensions
+            .iter()
+            .map(|d| d.dimension.clone())
+            .collect();
+        let stale = stale_keys(&existing_dimensions, &incoming);
+        diesel::delete(dimensions::table.filter(dimensions::dimension.eq_any(&stale)))
+            .execute(conn)?;
+        report.dimensions.deleted = stale;
+
+        let mut stale: Vec<String> = stale_functions.into_iter().collect();
+        stale.sort();
+        diesel::delete(functions::table.filter(functions::function_name.eq_any(&stale)))
+            .execute(conn)?;
+        report.functions.deleted = stale;
+    }
+
+    import_contexts(bundle, user, conn, &mut report)?;
+
+    Ok(report)
+}
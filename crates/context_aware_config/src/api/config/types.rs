@@ -15,3 +15,98 @@ pub struct Context {
     pub priority: i32,
     pub override_with_keys: [String; 1],
 }
+
+/// Config resolved with the variants the unit is served, and the toss it was
+/// bucketed with.
+#[derive(Serialize)]
+pub struct ExperimentResolveResponse {
+    pub config: Map<String, Value>,
+    pub variant_ids: Vec<String>,
+    pub toss: i8,
+}
+
+/********** Config Bundle (import/export) Types ************/
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BundleDimension {
+    pub dimension: String,
+    pub priority: i32,
+    pub schema: Value,
+    pub function_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BundleDefaultConfig {
+    pub key: String,
+    pub value: Value,
+    pub schema: Value,
+    pub function_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BundleContext {
+    pub condition: Map<String, Value>,
+    pub r#override: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BundleFunction {
+    pub function_name: String,
+    pub function_description: String,
+    pub draft_code: String,
+    pub draft_runtime_version: String,
+    pub published_code: Option<String>,
+    pub published_runtime_version: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BundleTypeTemplate {
+    pub type_name: String,
+    pub type_schema: Value,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ConfigBundle {
+    #[serde(default)]
+    pub dimensions: Vec<BundleDimension>,
+    #[serde(default)]
+    pub default_configs: Vec<BundleDefaultConfig>,
+    #[serde(default)]
+    pub contexts: Vec<BundleContext>,
+    #[serde(default)]
+    pub functions: Vec<BundleFunction>,
+    #[serde(default)]
+    pub type_templates: Vec<BundleTypeTemplate>,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    #[default]
+    Merge,
+    Replace,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ImportQuery {
+    pub mode: Option<ImportMode>,
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct ImportSummary {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub deleted: Vec<String>,
+    pub unchanged: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub functions: ImportSummary,
+    pub type_templates: ImportSummary,
+    pub dimensions: ImportSummary,
+    pub default_configs: ImportSummary,
+    pub contexts: ImportSummary,
+}
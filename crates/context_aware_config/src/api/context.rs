@@ -1,9 +1,12 @@
 mod handlers;
 pub mod helpers;
 mod types;
+pub use handlers::bulk_operations_api;
 pub use handlers::delete_context_api;
 pub use handlers::endpoints;
 pub use handlers::hash;
+pub use handlers::override_helper;
 pub use handlers::put;
 pub use handlers::validate_dimensions_and_calculate_priority;
+pub use types::ContextAction;
 pub use types::PutReq;
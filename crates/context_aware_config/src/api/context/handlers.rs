@@ -2,8 +2,7 @@ extern crate base64;
 use std::str;
 
 use crate::helpers::{
-    add_config_version, calculate_context_priority, json_to_sorted_string,
-    validate_context_jsonschema,
+    add_config_version, calculate_context_priority, validate_context_jsonschema,
 };
 use crate::{
     api::{
@@ -22,7 +21,11 @@ use crate::{
     },
 };
 use actix_web::web::Data;
-use service_utils::service::types::{AppHeader, AppState, CustomHeaders};
+use service_utils::{
+    db::audit::AuditActor,
+    rbac::{Action, Authorizer, Resource},
+    service::types::{AppHeader, AppState, CustomHeaders},
+};
 
 use actix_web::{
     delete, get, post, put,
@@ -42,10 +45,11 @@ use serde_json::{from_value, json, Map, Value};
 use service_utils::helpers::{parse_config_tags, validation_err_to_str};
 use service_utils::service::types::DbConnection;
 use std::collections::HashMap;
-use superposition_types::{SuperpositionUser, User};
+use superposition_types::{hashing::hash_json, SuperpositionUser, User};
 
 use super::helpers::{
-    validate_condition_with_functions, validate_override_with_functions,
+    authorize_context, authorize_context_actions, validate_condition_with_functions,
+    validate_override_with_functions,
 };
 
 use superposition_macros::{
@@ -225,8 +229,7 @@ fn create_ctx_from_put_req(
 }
 
 pub fn hash(val: &Value) -> String {
-    let sorted_str: String = json_to_sorted_string(val);
-    blake3::hash(sorted_str.as_bytes()).to_string()
+    hash_json(val)
 }
 
 fn update_override_of_existing_ctx(
@@ -257,17 +260,16 @@ fn replace_override_of_existing_ctx(
     ctx: Context,
 ) -> superposition::Result<PutResp> {
     use contexts::dsl;
-    let new_override = ctx.override_;
-    let new_override_id = hash(&new_override);
-    let new_ctx = Context {
-        override_: new_override,
-        override_id: new_override_id,
-        ..ctx
-    };
-    diesel::update(dsl::contexts)
-        .filter(dsl::id.eq(&new_ctx.id))
-        .set(&new_ctx)
-        .execute(conn)?;
+    let new_override_id = hash(&ctx.override_);
+    // the creation fields of the existing context are kept
+    let new_ctx: Context = diesel::update(dsl::contexts)
+        .filter(dsl::id.eq(&ctx.id))
+        .set((
+            dsl::override_.eq(&ctx.override_),
+            dsl::override_id.eq(&new_override_id),
+            dsl::priority.eq(ctx.priority),
+        ))
+        .get_result(conn)?;
     Ok(get_put_resp(new_ctx))
 }
 
@@ -315,9 +317,17 @@ async fn put_handler(
     req: Json<PutReq>,
     mut db_conn: DbConnection,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
 ) -> superposition::Result<HttpResponse> {
+    authorize_context(
+        &auth,
+        &Value::Object(req.context.clone()),
+        req.r#override.keys(),
+    )?;
     let tags = parse_config_tags(custom_headers.config_tags)?;
     db_conn.transaction::<_, superposition::AppError, _>(|transaction_conn| {
+        actor.record(transaction_conn)?;
         let put_response = put(req, transaction_conn, true, &user).map_err(
             |err: superposition::AppError| {
                 log::info!("context put failed with error: {:?}", err);
@@ -335,7 +345,7 @@ async fn put_handler(
     })
 }
 
-fn override_helper(
+pub fn override_helper(
     req: Json<PutReq>,
     conn: &mut PooledConnection<ConnectionManager<PgConnection>>,
     already_under_txn: bool,
@@ -370,9 +380,17 @@ async fn update_override_handler(
     req: Json<PutReq>,
     mut db_conn: DbConnection,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
 ) -> superposition::Result<HttpResponse> {
+    authorize_context(
+        &auth,
+        &Value::Object(req.context.clone()),
+        req.r#override.keys(),
+    )?;
     let tags = parse_config_tags(custom_headers.config_tags)?;
     db_conn.transaction::<_, superposition::AppError, _>(|transaction_conn| {
+        actor.record(transaction_conn)?;
         let override_resp = override_helper(req, transaction_conn, true, &user).map_err(
             |err: superposition::AppError| {
                 log::info!("context put failed with error: {:?}", err);
@@ -478,9 +496,14 @@ async fn move_handler(
     req: Json<MoveReq>,
     mut db_conn: DbConnection,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
 ) -> superposition::Result<HttpResponse> {
+    let move_action = ContextAction::Move((path.to_string(), req.0.clone()));
+    authorize_context_actions(&auth, &[move_action], &mut db_conn)?;
     let tags = parse_config_tags(custom_headers.config_tags)?;
     db_conn.transaction::<_, superposition::AppError, _>(|transaction_conn| {
+        actor.record(transaction_conn)?;
         let move_reponse = r#move(path.into_inner(), req, transaction_conn, true, &user)
             .map_err(|err| {
                 log::info!("move api failed with error: {:?}", err);
@@ -589,11 +612,16 @@ async fn delete_context(
     path: Path<String>,
     custom_headers: CustomHeaders,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
     mut db_conn: DbConnection,
 ) -> superposition::Result<HttpResponse> {
     let ctx_id = path.into_inner();
+    let delete_action = ContextAction::Delete(ctx_id.clone());
+    authorize_context_actions(&auth, &[delete_action], &mut db_conn)?;
     let tags = parse_config_tags(custom_headers.config_tags)?;
     db_conn.transaction::<_, superposition::AppError, _>(|transaction_conn| {
+        actor.record(transaction_conn)?;
         delete_context_api(ctx_id, user, transaction_conn)?;
         let version_id = add_config_version(&state, tags, transaction_conn)?;
         Ok(HttpResponse::NoContent()
@@ -605,6 +633,62 @@ async fn delete_context(
     })
 }
 
+/// Applies `actions` in order in the transaction of `conn`, returning the
+/// result of each. The caller adds the config version.
+pub fn bulk_operations_api(
+    actions: Vec<ContextAction>,
+    user: &User,
+    conn: &mut PooledConnection<ConnectionManager<PgConnection>>,
+) -> superposition::Result<Vec<ContextBulkResponse>> {
+    use contexts::dsl::contexts;
+    let mut response = Vec::<ContextBulkResponse>::new();
+    for action in actions.into_iter() {
+        match action {
+            ContextAction::Put(put_req) => {
+                let put_resp = put(Json(put_req), conn, true, user).map_err(|err| {
+                    log::error!("Failed at insert into contexts due to {:?}", err);
+                    err
+                })?;
+                response.push(ContextBulkResponse::Put(put_resp));
+            }
+            ContextAction::Delete(ctx_id) => {
+                let deleted_row = delete(contexts.filter(id.eq(&ctx_id))).execute(conn);
+                let email: String = user.get_email();
+                match deleted_row {
+                    // Any kind of error would rollback the tranction but explicitly returning rollback tranction allows you to rollback from any point in transaction.
+                    Ok(0) => {
+                        return Err(bad_argument!("context with id {} not found", ctx_id))
+                    }
+                    Ok(_) => {
+                        log::info!("{ctx_id} context deleted by {email}");
+                        response.push(ContextBulkResponse::Delete(format!(
+                            "{ctx_id} deleted succesfully"
+                        )))
+                    }
+                    Err(e) => {
+                        log::error!("Delete context failed due to {:?}", e);
+                        return Err(db_error!(e));
+                    }
+                };
+            }
+            ContextAction::Move((old_ctx_id, move_req)) => {
+                let move_context_resp =
+                    r#move(old_ctx_id, Json(move_req), conn, true, user).map_err(
+                        |err| {
+                            log::error!(
+                                "Failed at moving context reponse due to {:?}",
+                                err
+                            );
+                            err
+                        },
+                    )?;
+                response.push(ContextBulkResponse::Move(move_context_resp));
+            }
+        }
+    }
+    Ok(response)
+}
+
 #[put("/bulk-operations")]
 async fn bulk_operations(
     state: Data<AppState>,
@@ -612,65 +696,17 @@ async fn bulk_operations(
     reqs: Json<Vec<ContextAction>>,
     db_conn: DbConnection,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
 ) -> superposition::Result<HttpResponse> {
-    use contexts::dsl::contexts;
     let DbConnection(mut conn) = db_conn;
     let tags = parse_config_tags(custom_headers.config_tags)?;
 
-    let mut response = Vec::<ContextBulkResponse>::new();
-    conn.transaction::<_, superposition::AppError, _>(|transaction_conn| {
-        for action in reqs.into_inner().into_iter() {
-            match action {
-                ContextAction::Put(put_req) => {
-                    let put_resp = put(Json(put_req), transaction_conn, true, &user)
-                        .map_err(|err| {
-                            log::error!(
-                                "Failed at insert into contexts due to {:?}",
-                                err
-                            );
-                            err
-                        })?;
-                    response.push(ContextBulkResponse::Put(put_resp));
-                }
-                ContextAction::Delete(ctx_id) => {
-                    let deleted_row =
-                        delete(contexts.filter(id.eq(&ctx_id))).execute(transaction_conn);
-                    let email: String = user.get_email();
-                    match deleted_row {
-                        // Any kind of error would rollback the tranction but explicitly returning rollback tranction allows you to rollback from any point in transaction.
-                        Ok(0) => {
-                            return Err(bad_argument!(
-                                "context with id {} not found",
-                                ctx_id
-                            ))
-                        }
-                        Ok(_) => {
-                            log::info!("{ctx_id} context deleted by {email}");
-                            response.push(ContextBulkResponse::Delete(format!(
-                                "{ctx_id} deleted succesfully"
-                            )))
-                        }
-                        Err(e) => {
-                            log::error!("Delete context failed due to {:?}", e);
-                            return Err(db_error!(e));
-                        }
-                    };
-                }
-                ContextAction::Move((old_ctx_id, move_req)) => {
-                    let move_context_resp =
-                        r#move(old_ctx_id, Json(move_req), transaction_conn, true, &user)
-                            .map_err(|err| {
-                                log::error!(
-                                    "Failed at moving context reponse due to {:?}",
-                                    err
-                                );
-                                err
-                            })?;
-                    response.push(ContextBulkResponse::Move(move_context_resp));
-                }
-            }
-        }
+    authorize_context_actions(&auth, &reqs, &mut conn)?;
 
+    conn.transaction::<_, superposition::AppError, _>(|transaction_conn| {
+        actor.record(transaction_conn)?;
+        let response = bulk_operations_api(reqs.into_inner(), &user, transaction_conn)?;
         let version_id = add_config_version(&state, tags, transaction_conn)?;
 
         let mut http_resp = HttpResponse::Ok();
@@ -690,7 +726,10 @@ async fn priority_recompute(
     custom_headers: CustomHeaders,
     db_conn: DbConnection,
     _user: User,
+    auth: Authorizer,
+    actor: AuditActor,
 ) -> superposition::Result<HttpResponse> {
+    auth.require(Resource::Context, Action::Write)?;
     use crate::db::schema::contexts::dsl::*;
     let DbConnection(mut conn) = db_conn;
 
@@ -737,6 +776,7 @@ async fn priority_recompute(
 
     let config_versin_id =
         conn.transaction::<_, superposition::AppError, _>(|transaction_conn| {
+            actor.record(transaction_conn)?;
             let insert = diesel::insert_into(contexts)
                 .values(&update_contexts)
                 .on_conflict(id)
@@ -1,15 +1,19 @@
 extern crate base64;
 use base64::prelude::*;
-use service_utils::helpers::extract_dimensions;
+use service_utils::{
+    helpers::extract_dimensions,
+    rbac::{Action, Authorizer, Resource},
+};
 use std::str;
-use superposition_macros::{unexpected_error, validation_error};
+use superposition_macros::{db_error, not_found, unexpected_error, validation_error};
 use superposition_types::result as superposition;
 
 use crate::api::functions::helpers::get_published_functions_by_names;
 use crate::validation_functions::execute_fn;
 use crate::{
-    api::context::types::FunctionsInfo,
+    api::context::types::{ContextAction, FunctionsInfo},
     db::schema::{
+        contexts,
         default_configs::dsl,
         dimensions::{self},
     },
@@ -22,6 +26,80 @@ use serde_json::{Map, Value};
 use std::collections::HashMap;
 type DBConnection = PooledConnection<ConnectionManager<PgConnection>>;
 
+/// Resource the writes of a context are authorized as. The contexts of
+/// experiment variants pin `variantIds` and are managed through experiments,
+/// every other context needs context permissions.
+pub fn context_resource(condition: &Value) -> superposition::Result<Resource> {
+    if extract_dimensions(condition)?.contains_key("variantIds") {
+        Ok(Resource::Experiment)
+    } else {
+        Ok(Resource::Context)
+    }
+}
+
+/// Checks the caller can write the `keys` of the context with `condition`.
+pub fn authorize_context<I, K>(
+    auth: &Authorizer,
+    condition: &Value,
+    keys: I,
+) -> superposition::Result<()>
+where
+    I: IntoIterator<Item = K>,
+    K: AsRef<str>,
+{
+    auth.require_keys(context_resource(condition)?, Action::Write, keys)
+}
+
+/// Condition and overridden keys of a stored context.
+fn stored_context(
+    ctx_id: &str,
+    conn: &mut DBConnection,
+) -> superposition::Result<(Value, Vec<String>)> {
+    let (condition, override_) = contexts::table
+        .filter(contexts::id.eq(ctx_id))
+        .select((contexts::value, contexts::override_))
+        .first::<(Value, Value)>(conn)
+        .map_err(|err| match err {
+            diesel::result::Error::NotFound => {
+                not_found!("Context Id `{}` doesn't exists", ctx_id)
+            }
+            err => db_error!(err),
+        })?;
+    let keys = override_
+        .as_object()
+        .map(|overrides| overrides.keys().cloned().collect())
+        .unwrap_or_default();
+    Ok((condition, keys))
+}
+
+/// Checks the caller can apply `actions`, contexts which are deleted or moved
+/// are checked as they are stored and moves also as they end up.
+pub fn authorize_context_actions(
+    auth: &Authorizer,
+    actions: &[ContextAction],
+    conn: &mut DBConnection,
+) -> superposition::Result<()> {
+    for action in actions.iter() {
+        match action {
+            ContextAction::Put(put_req) => authorize_context(
+                auth,
+                &Value::Object(put_req.context.clone()),
+                put_req.r#override.keys(),
+            )?,
+            ContextAction::Delete(ctx_id) => {
+                let (condition, keys) = stored_context(ctx_id, conn)?;
+                authorize_context(auth, &condition, keys)?;
+            }
+            ContextAction::Move((ctx_id, move_req)) => {
+                let (condition, keys) = stored_context(ctx_id, conn)?;
+                authorize_context(auth, &condition, &keys)?;
+                authorize_context(auth, &Value::Object(move_req.context.clone()), keys)?;
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn validate_condition_with_functions(
     conn: &mut DBConnection,
     context: &Value,
@@ -1,3 +1,6 @@
 mod handlers;
 mod types;
 pub use handlers::endpoints;
+pub(crate) use handlers::upsert_default_config;
+pub(crate) use handlers::KEY_NAME_REGEX;
+pub(crate) use types::CreateReq;
@@ -1,7 +1,9 @@
 extern crate base64;
 use super::types::CreateReq;
 use service_utils::{
+    db::audit::AuditActor,
     helpers::{parse_config_tags, validation_err_to_str},
+    rbac::{Action, Authorizer, Resource},
     service::types::{AppHeader, AppState, CustomHeaders, DbConnection},
 };
 
@@ -35,7 +37,8 @@ use jsonschema::{Draft, JSONSchema, ValidationError};
 use regex::Regex;
 use serde_json::{from_value, json, Map, Value};
 
-const KEY_NAME_REGEX: &str = "^[a-zA-Z0-9-_]([a-zA-Z0-9-_.]{0,254}[a-zA-Z0-9-_])?$";
+pub(crate) const KEY_NAME_REGEX: &str =
+    "^[a-zA-Z0-9-_]([a-zA-Z0-9-_.]{0,254}[a-zA-Z0-9-_])?$";
 
 pub fn endpoints() -> Scope {
     Scope::new("").service(create).service(get).service(delete)
@@ -49,12 +52,42 @@ async fn create(
     request: web::Json<CreateReq>,
     db_conn: DbConnection,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
 ) -> superposition::Result<HttpResponse> {
     let DbConnection(mut conn) = db_conn;
     let req = request.into_inner();
     let key = key.into_inner();
+    auth.require_keys(Resource::DefaultConfig, Action::Write, [&key])?;
     let tags = parse_config_tags(custom_headers.config_tags)?;
 
+    let version_id =
+        conn.transaction::<_, superposition::AppError, _>(|transaction_conn| {
+            actor.record(transaction_conn)?;
+            upsert_default_config(&state, key, req, &user, transaction_conn)?;
+            add_config_version(&state, tags, transaction_conn)
+        })?;
+
+    let mut http_resp = HttpResponse::Ok();
+
+    http_resp.insert_header((
+        AppHeader::XConfigVersion.to_string(),
+        version_id.to_string(),
+    ));
+    Ok(http_resp.json(json!({
+        "message": "DefaultConfig created/updated successfully."
+    })))
+}
+
+/// Creates or updates the default config `key` on `conn` after validating it
+/// against its schema and function. The caller adds the config version.
+pub fn upsert_default_config(
+    state: &Data<AppState>,
+    key: String,
+    req: CreateReq,
+    user: &User,
+    conn: &mut PooledConnection<ConnectionManager<PgConnection>>,
+) -> superposition::Result<()> {
     let regex = Regex::new(KEY_NAME_REGEX).map_err(|err| {
         unexpected_error!("could not parse regex due to: {}", err.to_string())
     })?;
@@ -84,7 +117,7 @@ async fn create(
         }
     };
 
-    let result = fetch_default_key(&key, &mut conn);
+    let result = fetch_default_key(&key, conn);
 
     let (value, schema, function_name) = match result {
         Ok((val, schema, f_name)) => {
@@ -152,7 +185,7 @@ async fn create(
     }
 
     if let Some(f_name) = &default_config.function_name {
-        let function_code = get_published_function_code(&mut conn, f_name.to_string())
+        let function_code = get_published_function_code(conn, f_name.to_string())
             .map_err(|e| {
                 log::info!("Function not found with error : {e}");
                 bad_argument!("Function {} doesn't exists.", f_name)
@@ -166,35 +199,21 @@ async fn create(
             )?;
         }
     }
-    let version_id =
-        conn.transaction::<_, superposition::AppError, _>(|transaction_conn| {
-            let upsert = diesel::insert_into(default_configs)
-                .values(&default_config)
-                .on_conflict(db::schema::default_configs::key)
-                .do_update()
-                .set(&default_config)
-                .execute(transaction_conn);
-            let version_id = add_config_version(&state, tags, transaction_conn)?;
-            match upsert {
-                Ok(_) => Ok(version_id),
-                Err(e) => {
-                    log::info!("DefaultConfig creation failed with error: {e}");
-                    Err(unexpected_error!(
-                        "Something went wrong, failed to create DefaultConfig"
-                    ))
-                }
-            }
-        })?;
-
-    let mut http_resp = HttpResponse::Ok();
-
-    http_resp.insert_header((
-        AppHeader::XConfigVersion.to_string(),
-        version_id.to_string(),
-    ));
-    Ok(http_resp.json(json!({
-        "message": "DefaultConfig created/updated successfully."
-    })))
+    let upsert = diesel::insert_into(default_configs)
+        .values(&default_config)
+        .on_conflict(db::schema::default_configs::key)
+        .do_update()
+        .set(&default_config)
+        .execute(conn);
+    match upsert {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            log::info!("DefaultConfig creation failed with error: {e}");
+            Err(unexpected_error!(
+                "Something went wrong, failed to create DefaultConfig"
+            ))
+        }
+    }
 }
 
 fn fetch_default_key(
@@ -249,16 +268,20 @@ async fn delete(
     custom_headers: CustomHeaders,
     db_conn: DbConnection,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
 ) -> superposition::Result<HttpResponse> {
     let DbConnection(mut conn) = db_conn;
     let tags = parse_config_tags(custom_headers.config_tags)?;
 
     let key = path.into_inner();
+    auth.require_keys(Resource::DefaultConfig, Action::Write, [&key])?;
     fetch_default_key(&key, &mut conn)?;
     let context_ids = get_key_usage_context_ids(&key, &mut conn)
         .map_err(|_| unexpected_error!("Something went wrong"))?;
     if context_ids.is_empty() {
         conn.transaction::<_, superposition::AppError, _>(|transaction_conn| {
+            actor.record(transaction_conn)?;
             let deleted_row = diesel::delete(
                 default_configs.filter(db::schema::default_configs::key.eq(&key)),
             )
@@ -15,7 +15,11 @@ use serde_json::Value;
 use superposition_macros::{bad_argument, unexpected_error};
 use superposition_types::{result as superposition, SuperpositionUser, User};
 
-use service_utils::service::types::{AppState, DbConnection};
+use service_utils::{
+    db::audit::AuditActor,
+    rbac::{Action, Authorizer, Resource},
+    service::types::{AppState, DbConnection},
+};
 
 pub fn endpoints() -> Scope {
     Scope::new("").service(create).service(get)
@@ -26,8 +30,11 @@ async fn create(
     state: Data<AppState>,
     req: web::Json<CreateReq>,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
     db_conn: DbConnection,
 ) -> superposition::Result<HttpResponse> {
+    auth.require(Resource::Dimension, Action::Write)?;
     let DbConnection(mut conn) = db_conn;
 
     if req.priority <= 0 {
@@ -70,12 +77,14 @@ async fn create(
         function_name: fun_name.clone(),
     };
 
-    let upsert = diesel::insert_into(dimensions)
-        .values(&new_dimension)
-        .on_conflict(dimension)
-        .do_update()
-        .set(&new_dimension)
-        .get_result::<Dimension>(&mut conn);
+    let upsert = actor.transaction(&mut conn, |transaction_conn| {
+        diesel::insert_into(dimensions)
+            .values(&new_dimension)
+            .on_conflict(dimension)
+            .do_update()
+            .set(&new_dimension)
+            .get_result::<Dimension>(transaction_conn)
+    });
 
     match upsert {
         Ok(upserted_dimension) => Ok(HttpResponse::Created().json(upserted_dimension)),
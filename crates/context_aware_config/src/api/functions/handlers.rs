@@ -20,7 +20,11 @@ use actix_web::{
 use chrono::Utc;
 use diesel::{delete, ExpressionMethods, QueryDsl, RunQueryDsl};
 use serde_json::json;
-use service_utils::service::types::DbConnection;
+use service_utils::{
+    db::audit::AuditActor,
+    rbac::{Action, Authorizer, Resource},
+    service::types::DbConnection,
+};
 
 use superposition_macros::{bad_argument, not_found, unexpected_error};
 use superposition_types::{result as superposition, SuperpositionUser, User};
@@ -45,7 +49,10 @@ async fn create(
     request: web::Json<CreateFunctionRequest>,
     db_conn: DbConnection,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
 ) -> superposition::Result<Json<Function>> {
+    auth.require(Resource::Function, Action::Write)?;
     let DbConnection(mut conn) = db_conn;
     let req = request.into_inner();
 
@@ -64,9 +71,12 @@ async fn create(
         function_description: req.description,
     };
 
-    let insert: Result<Function, diesel::result::Error> = diesel::insert_into(functions)
-        .values(&function)
-        .get_result(&mut conn);
+    let insert: Result<Function, diesel::result::Error> =
+        actor.transaction(&mut conn, |transaction_conn| {
+            diesel::insert_into(functions)
+                .values(&function)
+                .get_result(transaction_conn)
+        });
 
     match insert {
         Ok(mut res) => {
@@ -101,7 +111,10 @@ async fn update(
     request: web::Json<UpdateFunctionRequest>,
     db_conn: DbConnection,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
 ) -> superposition::Result<Json<Function>> {
+    auth.require(Resource::Function, Action::Write)?;
     let DbConnection(mut conn) = db_conn;
     let req = request.into_inner();
     let f_name = params.into_inner();
@@ -141,10 +154,12 @@ async fn update(
         published_runtime_version: result.published_runtime_version,
     };
 
-    let mut updated_function = diesel::update(functions)
-        .filter(db::schema::functions::function_name.eq(f_name))
-        .set(new_function)
-        .get_result::<Function>(&mut conn)?;
+    let mut updated_function = actor.transaction(&mut conn, |transaction_conn| {
+        diesel::update(functions)
+            .filter(db::schema::functions::function_name.eq(f_name))
+            .set(new_function)
+            .get_result::<Function>(transaction_conn)
+    })?;
 
     decode_function(&mut updated_function)?;
     Ok(Json(updated_function))
@@ -180,12 +195,16 @@ async fn delete_function(
     params: web::Path<String>,
     db_conn: DbConnection,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
 ) -> superposition::Result<HttpResponse> {
+    auth.require(Resource::Function, Action::Write)?;
     let DbConnection(mut conn) = db_conn;
     let f_name = params.into_inner();
 
-    let deleted_row =
-        delete(functions.filter(function_name.eq(&f_name))).execute(&mut conn);
+    let deleted_row = actor.transaction(&mut conn, |transaction_conn| {
+        delete(functions.filter(function_name.eq(&f_name))).execute(transaction_conn)
+    });
     match deleted_row {
         Ok(0) => Err(not_found!("Function {} doesn't exists", f_name)),
         Ok(_) => {
@@ -252,7 +271,10 @@ async fn publish(
     params: web::Path<String>,
     db_conn: DbConnection,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
 ) -> superposition::Result<Json<Function>> {
+    auth.require(Resource::Function, Action::Write)?;
     let DbConnection(mut conn) = db_conn;
     let fun_name = params.into_inner();
 
@@ -270,16 +292,18 @@ async fn publish(
         }
     };
 
-    let updated_function = diesel::update(functions)
-        .filter(dsl::function_name.eq(fun_name.clone()))
-        .set((
-            dsl::published_code.eq(Some(function.draft_code.clone())),
-            dsl::published_runtime_version
-                .eq(Some(function.draft_runtime_version.clone())),
-            dsl::published_by.eq(Some(user.get_email())),
-            dsl::published_at.eq(Some(Utc::now().naive_utc())),
-        ))
-        .get_result::<Function>(&mut conn)?;
+    let updated_function = actor.transaction(&mut conn, |transaction_conn| {
+        diesel::update(functions)
+            .filter(dsl::function_name.eq(fun_name.clone()))
+            .set((
+                dsl::published_code.eq(Some(function.draft_code.clone())),
+                dsl::published_runtime_version
+                    .eq(Some(function.draft_runtime_version.clone())),
+                dsl::published_by.eq(Some(user.get_email())),
+                dsl::published_at.eq(Some(Utc::now().naive_utc())),
+            ))
+            .get_result::<Function>(transaction_conn)
+    })?;
 
     Ok(Json(updated_function))
 }
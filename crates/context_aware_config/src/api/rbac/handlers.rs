@@ -0,0 +1,110 @@
+use actix_web::{
+    delete, get, post,
+    web::{Json, Path, Query},
+    HttpResponse, Scope,
+};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use service_utils::{
+    db::audit::AuditActor,
+    rbac::{Action, Authorizer, Resource},
+    service::types::DbConnection,
+};
+use superposition_macros::{bad_argument, not_found};
+use superposition_types::{result as superposition, SuperpositionUser, User};
+
+use crate::{
+    api::rbac::types::{CreateRoleBindingReq, RoleBindingFilters},
+    db::{models::RoleBinding, schema::role_bindings},
+};
+
+pub fn endpoints() -> Scope {
+    Scope::new("/bindings")
+        .service(list_bindings)
+        .service(create_binding)
+        .service(delete_binding)
+}
+
+#[get("")]
+async fn list_bindings(
+    filters: Query<RoleBindingFilters>,
+    auth: Authorizer,
+    db_conn: DbConnection,
+) -> superposition::Result<Json<Vec<RoleBinding>>> {
+    auth.require(Resource::RoleBinding, Action::Read)?;
+    let DbConnection(mut conn) = db_conn;
+
+    let mut builder = role_bindings::table
+        .order(role_bindings::created_at.asc())
+        .into_boxed();
+    if let Some(subject) = filters.into_inner().subject {
+        builder = builder.filter(role_bindings::subject.eq(subject));
+    }
+    Ok(Json(builder.load::<RoleBinding>(&mut conn)?))
+}
+
+#[post("")]
+async fn create_binding(
+    req: Json<CreateRoleBindingReq>,
+    auth: Authorizer,
+    db_conn: DbConnection,
+    user: User,
+    actor: AuditActor,
+) -> superposition::Result<HttpResponse> {
+    auth.require(Resource::RoleBinding, Action::Write)?;
+    let DbConnection(mut conn) = db_conn;
+    let req = req.into_inner();
+
+    if req.subject.trim().is_empty() {
+        return Err(bad_argument!("subject cannot be empty"));
+    }
+    if req
+        .key_prefix
+        .as_ref()
+        .is_some_and(|prefix| prefix.is_empty())
+    {
+        return Err(bad_argument!(
+            "key_prefix cannot be empty, leave it out to allow every key"
+        ));
+    }
+
+    let binding = actor.transaction(&mut conn, |transaction_conn| {
+        diesel::insert_into(role_bindings::table)
+            .values((
+                role_bindings::subject.eq(req.subject),
+                role_bindings::role.eq(req.role.to_string()),
+                role_bindings::resource.eq(req.resource.map(|r| r.to_string())),
+                role_bindings::key_prefix.eq(req.key_prefix),
+                role_bindings::created_by.eq(user.get_email()),
+            ))
+            .get_result::<RoleBinding>(transaction_conn)
+    })?;
+    log::info!(
+        "role {} granted to {} by {}",
+        binding.role,
+        binding.subject,
+        user.get_email()
+    );
+    Ok(HttpResponse::Created().json(binding))
+}
+
+#[delete("/{id}")]
+async fn delete_binding(
+    path: Path<uuid::Uuid>,
+    auth: Authorizer,
+    db_conn: DbConnection,
+    user: User,
+    actor: AuditActor,
+) -> superposition::Result<HttpResponse> {
+    auth.require(Resource::RoleBinding, Action::Write)?;
+    let DbConnection(mut conn) = db_conn;
+    let id = path.into_inner();
+
+    let deleted = actor.transaction(&mut conn, |transaction_conn| {
+        diesel::delete(role_bindings::table.find(id)).execute(transaction_conn)
+    })?;
+    if deleted == 0 {
+        return Err(not_found!("role binding {} doesn't exist", id));
+    }
+    log::info!("role binding {id} deleted by {}", user.get_email());
+    Ok(HttpResponse::NoContent().finish())
+}
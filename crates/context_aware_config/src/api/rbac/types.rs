@@ -0,0 +1,15 @@
+use serde::Deserialize;
+use service_utils::rbac::{Resource, Role};
+
+#[derive(Debug, Deserialize)]
+pub struct CreateRoleBindingReq {
+    pub subject: String,
+    pub role: Role,
+    pub resource: Option<Resource>,
+    pub key_prefix: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RoleBindingFilters {
+    pub subject: Option<String>,
+}
@@ -9,7 +9,11 @@ use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
 use jsonschema::JSONSchema;
 use regex::Regex;
 use serde_json::{json, Value};
-use service_utils::service::types::DbConnection;
+use service_utils::{
+    db::audit::AuditActor,
+    rbac::{Action, Authorizer, Resource},
+    service::types::DbConnection,
+};
 use superposition_macros::{bad_argument, db_error, unexpected_error};
 use superposition_types::{result as superposition, User};
 
@@ -30,7 +34,10 @@ async fn create_type(
     request: Json<TypeTemplateRequest>,
     db_conn: DbConnection,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
 ) -> superposition::Result<HttpResponse> {
+    auth.require(Resource::Type, Action::Write)?;
     let DbConnection(mut conn) = db_conn;
     let _ = JSONSchema::compile(&request.type_schema).map_err(|err| {
         log::error!(
@@ -54,13 +61,16 @@ async fn create_type(
             TYPE_NAME_REGEX
         ));
     }
-    let type_template = diesel::insert_into(type_templates::table)
-        .values((
-            type_templates::type_schema.eq(request.type_schema.clone()),
-            type_templates::type_name.eq(type_name),
-            type_templates::created_by.eq(user.email.clone()),
-        ))
-        .get_result::<TypeTemplates>(&mut conn)
+    let type_template = actor
+        .transaction(&mut conn, |transaction_conn| {
+            diesel::insert_into(type_templates::table)
+                .values((
+                    type_templates::type_schema.eq(request.type_schema.clone()),
+                    type_templates::type_name.eq(type_name),
+                    type_templates::created_by.eq(user.email.clone()),
+                ))
+                .get_result::<TypeTemplates>(transaction_conn)
+        })
         .map_err(|err| {
             log::error!("failed to insert custom type with error: {}", err);
             db_error!(err)
@@ -74,7 +84,10 @@ async fn update_type(
     path: Path<String>,
     db_conn: DbConnection,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
 ) -> superposition::Result<HttpResponse> {
+    auth.require(Resource::Type, Action::Write)?;
     let DbConnection(mut conn) = db_conn;
     let _ = JSONSchema::compile(&request).map_err(|err| {
         log::error!(
@@ -99,14 +112,17 @@ async fn update_type(
         ));
     }
     let timestamp = Utc::now().naive_utc();
-    let updated_type = diesel::update(type_templates::table)
-        .filter(type_templates::type_name.eq(type_name))
-        .set((
-            type_templates::type_schema.eq(request.clone()),
-            type_templates::created_by.eq(user.email),
-            type_templates::last_modified.eq(timestamp),
-        ))
-        .get_result::<TypeTemplates>(&mut conn)
+    let updated_type = actor
+        .transaction(&mut conn, |transaction_conn| {
+            diesel::update(type_templates::table)
+                .filter(type_templates::type_name.eq(type_name))
+                .set((
+                    type_templates::type_schema.eq(request.clone()),
+                    type_templates::created_by.eq(user.email),
+                    type_templates::last_modified.eq(timestamp),
+                ))
+                .get_result::<TypeTemplates>(transaction_conn)
+        })
         .map_err(|err| {
             log::error!("failed to insert custom type with error: {}", err);
             db_error!(err)
@@ -118,12 +134,16 @@ async fn update_type(
 async fn delete_type(
     path: Path<String>,
     db_conn: DbConnection,
+    auth: Authorizer,
+    actor: AuditActor,
 ) -> superposition::Result<HttpResponse> {
+    auth.require(Resource::Type, Action::Write)?;
     let DbConnection(mut conn) = db_conn;
     let type_name = path.into_inner();
-    let deleted_type =
+    let deleted_type = actor.transaction(&mut conn, |transaction_conn| {
         diesel::delete(dsl::type_templates.filter(dsl::type_name.eq(type_name)))
-            .get_result::<TypeTemplates>(&mut conn)?;
+            .get_result::<TypeTemplates>(transaction_conn)
+    })?;
     Ok(HttpResponse::Ok().json(deleted_type))
 }
 
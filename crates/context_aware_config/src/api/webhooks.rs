@@ -0,0 +1,5 @@
+pub mod delivery;
+mod handlers;
+pub mod secrets;
+pub mod types;
+pub use handlers::endpoints;
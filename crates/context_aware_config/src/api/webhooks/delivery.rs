@@ -0,0 +1,433 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use actix_web::{rt, web};
+use anyhow::anyhow;
+use chrono::Utc;
+use diesel::{
+    sql_types::{BigInt, Json, Text},
+    Connection, ExpressionMethods, PgConnection, QueryDsl, QueryableByName, RunQueryDsl,
+    SelectableHelper, TextExpressionMethods,
+};
+use futures::future::join_all;
+use hmac::{Hmac, Mac, NewMac};
+use serde_json::{json, Value};
+use service_utils::{
+    db::{pgschema_manager::PgSchemaManager, utils::tenant_namespaces},
+    helpers::get_from_env_or_default,
+};
+use sha2::Sha256;
+
+use crate::{
+    api::webhooks::{
+        secrets::{WebhookSecretKey, ENCRYPTED_PREFIX},
+        types::DeliveryStatus,
+    },
+    db::{
+        models::{Webhook, WebhookDelivery},
+        schema::{webhook_deliveries, webhooks},
+    },
+};
+
+pub const EVENT_HEADER: &str = "x-superposition-event";
+pub const DELIVERY_HEADER: &str = "x-superposition-delivery";
+pub const TIMESTAMP_HEADER: &str = "x-superposition-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-superposition-signature";
+
+const FAN_OUT_BATCH_SIZE: i64 = 500;
+const DELIVERY_BATCH_SIZE: i64 = 50;
+
+#[derive(Clone, Debug)]
+pub struct DeliveryPolicy {
+    /// Attempts after which a delivery is marked failed
+    pub max_attempts: i32,
+    /// Wait before the first retry, doubled on every retry after it
+    pub retry_base: Duration,
+    pub max_retry_wait: Duration,
+    pub timeout: Duration,
+    /// How often outboxes are drained and due deliveries are sent
+    pub interval: Duration,
+}
+
+impl DeliveryPolicy {
+    pub fn from_env() -> Self {
+        DeliveryPolicy {
+            max_attempts: get_from_env_or_default("WEBHOOK_MAX_ATTEMPTS", 8),
+            retry_base: Duration::from_secs(get_from_env_or_default(
+                "WEBHOOK_RETRY_BASE_SECS",
+                30,
+            )),
+            max_retry_wait: Duration::from_secs(6 * 60 * 60),
+            timeout: Duration::from_secs(get_from_env_or_default(
+                "WEBHOOK_TIMEOUT_SECS",
+                10,
+            )),
+            interval: Duration::from_secs(get_from_env_or_default(
+                "WEBHOOK_DISPATCH_INTERVAL_SECS",
+                5,
+            )),
+        }
+    }
+
+    /// Wait before the next attempt of a delivery which failed `attempts` times.
+    pub fn retry_wait(&self, attempts: i32) -> Duration {
+        let exponent = attempts.clamp(1, 16) as u32 - 1;
+        self.retry_base
+            .saturating_mul(2u32.pow(exponent))
+            .min(self.max_retry_wait)
+    }
+}
+
+/// Hex encoded HMAC-SHA256 of `<timestamp>.<body>` with the webhook secret,
+/// sent as `sha256=<signature>` in the signature header.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC takes keys of any size");
+    mac.update(format!("{timestamp}.{body}").as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[derive(QueryableByName)]
+struct OutboxEvent {
+    #[diesel(sql_type = BigInt)]
+    id: i64,
+    #[diesel(sql_type = Text)]
+    event: String,
+    #[diesel(sql_type = Json)]
+    payload: Value,
+}
+
+/// Moves the events in the outbox of `schema` to deliveries of the enabled
+/// webhooks subscribed to them, in one transaction so no event is lost or
+/// delivered twice.
+fn fan_out(conn: &mut PgConnection, schema: &str) -> anyhow::Result<usize> {
+    conn.transaction::<_, anyhow::Error, _>(|transaction_conn| {
+        let mut events = diesel::sql_query(format!(
+            "DELETE FROM {schema}.webhook_outbox WHERE id IN (
+                SELECT id FROM {schema}.webhook_outbox ORDER BY id LIMIT $1 FOR UPDATE SKIP LOCKED
+            ) RETURNING id, event, payload"
+        ))
+        .bind::<BigInt, _>(FAN_OUT_BATCH_SIZE)
+        .load::<OutboxEvent>(transaction_conn)?;
+        if events.is_empty() {
+            return Ok(0);
+        }
+        events.sort_by_key(|event| event.id);
+
+        let subscriptions = webhooks::table
+            .filter(webhooks::enabled.eq(true))
+            .select((webhooks::id, webhooks::events))
+            .load::<(uuid::Uuid, Vec<String>)>(transaction_conn)?;
+        let deliveries = events
+            .iter()
+            .flat_map(|event| {
+                subscriptions
+                    .iter()
+                    .filter(|(_, subscribed)| subscribed.contains(&event.event))
+                    .map(|(webhook_id, _)| {
+                        (
+                            webhook_deliveries::webhook_id.eq(*webhook_id),
+                            webhook_deliveries::event.eq(event.event.clone()),
+                            webhook_deliveries::payload.eq(event.payload.clone()),
+                        )
+                    })
+            })
+            .collect::<Vec<_>>();
+        if !deliveries.is_empty() {
+            diesel::insert_into(webhook_deliveries::table)
+                .values(&deliveries)
+                .execute(transaction_conn)?;
+        }
+        Ok(events.len())
+    })
+}
+
+/// Picks the due deliveries of enabled webhooks and pushes their next attempt
+/// past the send timeout, so other replicas leave them alone while they are
+/// being sent.
+fn claim_due_deliveries(
+    conn: &mut PgConnection,
+    policy: &DeliveryPolicy,
+) -> anyhow::Result<Vec<(WebhookDelivery, Webhook)>> {
+    let lease = chrono::Duration::from_std(policy.timeout * 2)?;
+    conn.transaction::<_, anyhow::Error, _>(|transaction_conn| {
+        let due = webhook_deliveries::table
+            .inner_join(webhooks::table)
+            .filter(webhooks::enabled.eq(true))
+            .filter(webhook_deliveries::status.eq(DeliveryStatus::Pending.to_string()))
+            .filter(webhook_deliveries::next_attempt_at.le(Utc::now()))
+            .order(webhook_deliveries::next_attempt_at.asc())
+            .limit(DELIVERY_BATCH_SIZE)
+            .for_update()
+            .skip_locked()
+            .select((WebhookDelivery::as_select(), Webhook::as_select()))
+            .load::<(WebhookDelivery, Webhook)>(transaction_conn)?;
+        let ids = due
+            .iter()
+            .map(|(delivery, _)| delivery.id)
+            .collect::<Vec<uuid::Uuid>>();
+        diesel::update(webhook_deliveries::table)
+            .filter(webhook_deliveries::id.eq_any(ids))
+            .set(webhook_deliveries::next_attempt_at.eq(Utc::now() + lease))
+            .execute(transaction_conn)?;
+        Ok(due)
+    })
+}
+
+/// Encrypts the webhook secrets stored before secrets were encrypted at rest.
+fn encrypt_plaintext_secrets(
+    conn: &mut PgConnection,
+    secret_key: &WebhookSecretKey,
+) -> anyhow::Result<()> {
+    let plaintext = webhooks::table
+        .filter(webhooks::secret.not_like(format!("{ENCRYPTED_PREFIX}%")))
+        .select((webhooks::id, webhooks::secret))
+        .load::<(uuid::Uuid, String)>(conn)?;
+    for (id, secret) in plaintext.into_iter() {
+        diesel::update(webhooks::table.find(id))
+            .filter(webhooks::secret.eq(&secret))
+            .set(webhooks::secret.eq(secret_key.encrypt(&secret)))
+            .execute(conn)?;
+        log::info!("encrypted the stored secret of webhook {id}");
+    }
+    Ok(())
+}
+
+struct DeliveryOutcome {
+    response_status: Option<i32>,
+    error: Option<String>,
+}
+
+async fn send(
+    client: &reqwest::Client,
+    tenant: Option<&str>,
+    delivery: &WebhookDelivery,
+    webhook: &Webhook,
+    secret_key: &WebhookSecretKey,
+    timeout: Duration,
+) -> DeliveryOutcome {
+    let secret = match secret_key.decrypt(&webhook.secret) {
+        Ok(secret) => secret,
+        Err(e) => {
+            return DeliveryOutcome {
+                response_status: None,
+                error: Some(format!("failed to read the webhook secret: {e}")),
+            }
+        }
+    };
+    let body = json!({
+        "id": delivery.id,
+        "event": delivery.event,
+        "tenant": tenant,
+        "created_at": delivery.created_at,
+        "data": delivery.payload,
+    })
+    .to_string();
+    let timestamp = Utc::now().timestamp();
+    let signature = sign(&secret, timestamp, &body);
+    let response = client
+        .post(&webhook.url)
+        .timeout(timeout)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, &delivery.event)
+        .header(DELIVERY_HEADER, delivery.id.to_string())
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(SIGNATURE_HEADER, format!("sha256={signature}"))
+        .body(body)
+        .send()
+        .await;
+    match response {
+        Ok(response) => {
+            let status = response.status();
+            DeliveryOutcome {
+                response_status: Some(status.as_u16() as i32),
+                error: (!status.is_success())
+                    .then(|| format!("webhook responded with {status}")),
+            }
+        }
+        Err(e) => DeliveryOutcome {
+            response_status: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+fn record_attempt(
+    conn: &mut PgConnection,
+    delivery: &WebhookDelivery,
+    outcome: &DeliveryOutcome,
+    policy: &DeliveryPolicy,
+) -> anyhow::Result<()> {
+    let attempts = delivery.attempts + 1;
+    let now = Utc::now();
+    let (status, next_attempt_at) = match outcome.error {
+        None => (DeliveryStatus::Delivered, now),
+        Some(_) if attempts >= policy.max_attempts => (DeliveryStatus::Failed, now),
+        Some(_) => (
+            DeliveryStatus::Pending,
+            now + chrono::Duration::from_std(policy.retry_wait(attempts))?,
+        ),
+    };
+    diesel::update(webhook_deliveries::table.find(delivery.id))
+        .set((
+            webhook_deliveries::status.eq(status.to_string()),
+            webhook_deliveries::attempts.eq(attempts),
+            webhook_deliveries::next_attempt_at.eq(next_attempt_at),
+            webhook_deliveries::last_attempt_at.eq(now),
+            webhook_deliveries::response_status.eq(outcome.response_status),
+            webhook_deliveries::last_error.eq(outcome.error.clone()),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Where the webhooks of a tenant are registered and the schemas whose
+/// outboxes feed them.
+struct WebhookTarget {
+    tenant: Option<String>,
+    namespace: String,
+    outbox_schemas: Vec<String>,
+}
+
+fn webhook_targets(
+    enable_tenant_and_scope: bool,
+    tenants: &HashSet<String>,
+) -> Vec<WebhookTarget> {
+    if !enable_tenant_and_scope {
+        // both services share the public schema without tenancy
+        return vec![WebhookTarget {
+            tenant: None,
+            namespace: "cac_v1".to_string(),
+            outbox_schemas: vec!["public".to_string()],
+        }];
+    }
+    tenants
+        .iter()
+        .map(|tenant| {
+            let [cac_schema, experimentation_schema] = tenant_namespaces(tenant);
+            WebhookTarget {
+                tenant: Some(tenant.clone()),
+                namespace: cac_schema.clone(),
+                outbox_schemas: vec![cac_schema, experimentation_schema],
+            }
+        })
+        .collect()
+}
+
+async fn dispatch(
+    schema_manager: &PgSchemaManager,
+    target: WebhookTarget,
+    client: &reqwest::Client,
+    secret_key: &WebhookSecretKey,
+    policy: &DeliveryPolicy,
+) -> anyhow::Result<()> {
+    let (manager, namespace, claim_policy, claim_key) = (
+        schema_manager.clone(),
+        target.namespace.clone(),
+        policy.clone(),
+        secret_key.clone(),
+    );
+    let outbox_schemas = target.outbox_schemas;
+    let claimed = web::block(move || {
+        let mut conn = manager.get_conn(namespace)?;
+        encrypt_plaintext_secrets(&mut conn, &claim_key)?;
+        for schema in outbox_schemas.iter() {
+            fan_out(&mut conn, schema)?;
+        }
+        claim_due_deliveries(&mut conn, &claim_policy)
+    })
+    .await
+    .map_err(|e| anyhow!("{e}"))??;
+    if claimed.is_empty() {
+        return Ok(());
+    }
+
+    let tenant = target.tenant.as_deref();
+    let outcomes = join_all(claimed.iter().map(|(delivery, webhook)| {
+        send(
+            client,
+            tenant,
+            delivery,
+            webhook,
+            secret_key,
+            policy.timeout,
+        )
+    }))
+    .await;
+
+    let (manager, namespace, record_policy) =
+        (schema_manager.clone(), target.namespace, policy.clone());
+    web::block(move || {
+        let mut conn = manager.get_conn(namespace)?;
+        for ((delivery, _), outcome) in claimed.iter().zip(outcomes.iter()) {
+            if let Some(error) = outcome.error.as_ref() {
+                log::warn!("webhook delivery {} failed: {error}", delivery.id);
+            }
+            record_attempt(&mut conn, delivery, outcome, &record_policy)?;
+        }
+        Ok::<(), anyhow::Error>(())
+    })
+    .await
+    .map_err(|e| anyhow!("{e}"))?
+}
+
+/// Background task draining the webhook outboxes of every tenant and sending
+/// the deliveries which are due.
+pub async fn run_webhook_dispatcher(
+    schema_manager: PgSchemaManager,
+    tenants: Arc<RwLock<HashSet<String>>>,
+    enable_tenant_and_scope: bool,
+    secret_key: WebhookSecretKey,
+    policy: DeliveryPolicy,
+) {
+    let client = reqwest::Client::new();
+    let mut interval = rt::time::interval(policy.interval);
+    loop {
+        interval.tick().await;
+        let targets = match tenants.read() {
+            Ok(tenants) => webhook_targets(enable_tenant_and_scope, &tenants),
+            Err(e) => {
+                log::error!("failed to read the active tenants: {e}");
+                continue;
+            }
+        };
+        for target in targets {
+            let namespace = target.namespace.clone();
+            if let Err(e) =
+                dispatch(&schema_manager, target, &client, &secret_key, &policy).await
+            {
+                log::error!("webhook dispatch failed for {namespace}: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign() {
+        assert_eq!(
+            sign("whsec_test", 1718000000, r#"{"event":"context.created"}"#),
+            "de842433d506f5a87ce9edaf5e84661f2955f815d59626f42ae78fb3a1724f9d"
+        );
+    }
+
+    #[test]
+    fn test_retry_wait() {
+        let policy = DeliveryPolicy {
+            max_attempts: 8,
+            retry_base: Duration::from_secs(30),
+            max_retry_wait: Duration::from_secs(600),
+            timeout: Duration::from_secs(10),
+            interval: Duration::from_secs(5),
+        };
+        assert_eq!(policy.retry_wait(1), Duration::from_secs(30));
+        assert_eq!(policy.retry_wait(3), Duration::from_secs(120));
+        assert_eq!(policy.retry_wait(7), Duration::from_secs(600));
+    }
+}
@@ -0,0 +1,263 @@
+use actix_web::{
+    delete, get, post, put,
+    web::{Data, Json, Path, Query},
+    HttpResponse, Scope,
+};
+use chrono::{DateTime, Utc};
+use diesel::{AsChangeset, ExpressionMethods, QueryDsl, RunQueryDsl};
+use rand::{distributions::Alphanumeric, Rng};
+use serde_json::json;
+use service_utils::{
+    db::audit::AuditActor,
+    rbac::{Action, Authorizer, Resource},
+    service::types::DbConnection,
+};
+use superposition_macros::{bad_argument, not_found};
+use superposition_types::{result as superposition, SuperpositionUser, User};
+
+use crate::{
+    api::webhooks::{
+        secrets::WebhookSecretKey,
+        types::{
+            CreateWebhookReq, CreateWebhookResponse, DeliveryFilters, UpdateWebhookReq,
+            WebhookEvent,
+        },
+    },
+    db::{
+        models::{Webhook, WebhookDelivery},
+        schema::{webhook_deliveries, webhooks},
+    },
+};
+
+const SECRET_PREFIX: &str = "whsec_";
+const SECRET_LENGTH: usize = 32;
+
+pub fn endpoints() -> Scope {
+    Scope::new("")
+        .service(list_webhooks)
+        .service(create_webhook)
+        .service(update_webhook)
+        .service(delete_webhook)
+        .service(list_deliveries)
+        .service(redeliver)
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = webhooks)]
+struct WebhookChangeset {
+    name: Option<String>,
+    url: Option<String>,
+    events: Option<Vec<String>>,
+    enabled: Option<bool>,
+    last_modified_at: DateTime<Utc>,
+}
+
+fn generate_secret() -> String {
+    let secret = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(SECRET_LENGTH)
+        .map(char::from)
+        .collect::<String>();
+    format!("{SECRET_PREFIX}{secret}")
+}
+
+fn validate_url(url: &str) -> superposition::Result<()> {
+    match reqwest::Url::parse(url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(()),
+        _ => Err(bad_argument!("{} is not a valid http(s) url", url)),
+    }
+}
+
+fn event_names(events: &[WebhookEvent]) -> superposition::Result<Vec<String>> {
+    if events.is_empty() {
+        return Err(bad_argument!("a webhook needs at least one event"));
+    }
+    Ok(events.iter().map(|event| event.to_string()).collect())
+}
+
+#[get("")]
+async fn list_webhooks(
+    auth: Authorizer,
+    db_conn: DbConnection,
+) -> superposition::Result<Json<Vec<Webhook>>> {
+    auth.require(Resource::Webhook, Action::Read)?;
+    let DbConnection(mut conn) = db_conn;
+
+    let result = webhooks::table
+        .order(webhooks::created_at.asc())
+        .load::<Webhook>(&mut conn)?;
+    Ok(Json(result))
+}
+
+#[post("")]
+async fn create_webhook(
+    req: Json<CreateWebhookReq>,
+    auth: Authorizer,
+    db_conn: DbConnection,
+    user: User,
+    actor: AuditActor,
+    secret_key: Data<WebhookSecretKey>,
+) -> superposition::Result<HttpResponse> {
+    auth.require(Resource::Webhook, Action::Write)?;
+    let DbConnection(mut conn) = db_conn;
+    let req = req.into_inner();
+
+    if req.name.trim().is_empty() {
+        return Err(bad_argument!("name cannot be empty"));
+    }
+    validate_url(&req.url)?;
+    let events = event_names(&req.events)?;
+
+    // the secret is only ever shown here, it is stored encrypted
+    let secret = generate_secret();
+    let webhook = actor.transaction(&mut conn, |transaction_conn| {
+        diesel::insert_into(webhooks::table)
+            .values((
+                webhooks::name.eq(req.name),
+                webhooks::url.eq(req.url),
+                webhooks::events.eq(events),
+                webhooks::secret.eq(secret_key.encrypt(&secret)),
+                webhooks::created_by.eq(user.get_email()),
+            ))
+            .get_result::<Webhook>(transaction_conn)
+    })?;
+    log::info!("webhook {} created by {}", webhook.name, user.get_email());
+    Ok(HttpResponse::Created().json(CreateWebhookResponse { secret, webhook }))
+}
+
+#[put("/{id}")]
+async fn update_webhook(
+    path: Path<uuid::Uuid>,
+    req: Json<UpdateWebhookReq>,
+    auth: Authorizer,
+    db_conn: DbConnection,
+    actor: AuditActor,
+) -> superposition::Result<Json<Webhook>> {
+    auth.require(Resource::Webhook, Action::Write)?;
+    let DbConnection(mut conn) = db_conn;
+    let id = path.into_inner();
+    let req = req.into_inner();
+
+    if req.name.as_ref().is_some_and(|name| name.trim().is_empty()) {
+        return Err(bad_argument!("name cannot be empty"));
+    }
+    if let Some(url) = req.url.as_ref() {
+        validate_url(url)?;
+    }
+    let changeset = WebhookChangeset {
+        name: req.name,
+        url: req.url,
+        events: req.events.as_deref().map(event_names).transpose()?,
+        enabled: req.enabled,
+        last_modified_at: Utc::now(),
+    };
+    let webhook = actor
+        .transaction(&mut conn, |transaction_conn| {
+            diesel::update(webhooks::table.find(id))
+                .set(changeset)
+                .get_result::<Webhook>(transaction_conn)
+        })
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => not_found!("webhook {} doesn't exist", id),
+            e => e.into(),
+        })?;
+    Ok(Json(webhook))
+}
+
+#[delete("/{id}")]
+async fn delete_webhook(
+    path: Path<uuid::Uuid>,
+    auth: Authorizer,
+    db_conn: DbConnection,
+    user: User,
+    actor: AuditActor,
+) -> superposition::Result<HttpResponse> {
+    auth.require(Resource::Webhook, Action::Write)?;
+    let DbConnection(mut conn) = db_conn;
+    let id = path.into_inner();
+
+    let deleted = actor.transaction(&mut conn, |transaction_conn| {
+        diesel::delete(webhooks::table.find(id)).execute(transaction_conn)
+    })?;
+    if deleted == 0 {
+        return Err(not_found!("webhook {} doesn't exist", id));
+    }
+    log::info!("webhook {id} deleted by {}", user.get_email());
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get("/{id}/deliveries")]
+async fn list_deliveries(
+    path: Path<uuid::Uuid>,
+    filters: Query<DeliveryFilters>,
+    auth: Authorizer,
+    db_conn: DbConnection,
+) -> superposition::Result<HttpResponse> {
+    auth.require(Resource::Webhook, Action::Read)?;
+    let DbConnection(mut conn) = db_conn;
+    let id = path.into_inner();
+
+    let query_builder = |filters: &DeliveryFilters| {
+        let mut builder = webhook_deliveries::table
+            .filter(webhook_deliveries::webhook_id.eq(id))
+            .into_boxed();
+        if let Some(status) = filters.status {
+            builder = builder.filter(webhook_deliveries::status.eq(status.to_string()));
+        }
+        builder
+    };
+    let total_items: i64 = query_builder(&filters).count().get_result(&mut conn)?;
+    let limit = filters.count.unwrap_or(10);
+    let offset = (filters.page.unwrap_or(1) - 1) * limit;
+    let deliveries = query_builder(&filters)
+        .order(webhook_deliveries::created_at.desc())
+        .limit(limit)
+        .offset(offset)
+        .load::<WebhookDelivery>(&mut conn)?;
+    let total_pages = (total_items as f64 / limit as f64).ceil() as i64;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "total_items": total_items,
+        "total_pages": total_pages,
+        "data": deliveries
+    })))
+}
+
+/// Queues a new delivery of the same event, the original stays in the log.
+#[post("/{id}/deliveries/{delivery_id}/redeliver")]
+async fn redeliver(
+    path: Path<(uuid::Uuid, uuid::Uuid)>,
+    auth: Authorizer,
+    db_conn: DbConnection,
+    user: User,
+    actor: AuditActor,
+) -> superposition::Result<HttpResponse> {
+    auth.require(Resource::Webhook, Action::Write)?;
+    let DbConnection(mut conn) = db_conn;
+    let (id, delivery_id) = path.into_inner();
+
+    let original = webhook_deliveries::table
+        .find(delivery_id)
+        .filter(webhook_deliveries::webhook_id.eq(id))
+        .first::<WebhookDelivery>(&mut conn)
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => {
+                not_found!("delivery {} of webhook {} doesn't exist", delivery_id, id)
+            }
+            e => e.into(),
+        })?;
+    let delivery = actor.transaction(&mut conn, |transaction_conn| {
+        diesel::insert_into(webhook_deliveries::table)
+            .values((
+                webhook_deliveries::webhook_id.eq(original.webhook_id),
+                webhook_deliveries::event.eq(original.event),
+                webhook_deliveries::payload.eq(original.payload),
+            ))
+            .get_result::<WebhookDelivery>(transaction_conn)
+    })?;
+    log::info!(
+        "delivery {delivery_id} of webhook {id} requeued by {}",
+        user.get_email()
+    );
+    Ok(HttpResponse::Accepted().json(delivery))
+}
@@ -0,0 +1,108 @@
+use anyhow::anyhow;
+use base64::prelude::*;
+use rand::RngCore;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use service_utils::{
+    aws::kms,
+    helpers::{get_from_env_or_default, get_from_env_unsafe},
+};
+
+/// Stored secrets encrypted with the key, secrets stored before they were
+/// encrypted have no prefix.
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+const KEY_ENV: &str = "WEBHOOK_SECRET_KEY";
+const KEY_LENGTH: usize = 32;
+
+/// AES-256-GCM key webhook secrets are encrypted with at rest, so the
+/// database alone is not enough to sign deliveries.
+#[derive(Clone)]
+pub struct WebhookSecretKey([u8; KEY_LENGTH]);
+
+impl WebhookSecretKey {
+    pub fn new(encoded: &str) -> anyhow::Result<Self> {
+        let bytes = BASE64_STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| anyhow!("{KEY_ENV} is not valid base64: {e}"))?;
+        let key = <[u8; KEY_LENGTH]>::try_from(bytes.as_slice())
+            .map_err(|_| anyhow!("{KEY_ENV} has to be {KEY_LENGTH} bytes"))?;
+        Ok(WebhookSecretKey(key))
+    }
+
+    /// Reads the base64 encoded key from `WEBHOOK_SECRET_KEY`, decrypted with
+    /// KMS outside of DEV and TEST.
+    pub async fn from_env() -> anyhow::Result<Self> {
+        let app_env: String = get_from_env_or_default("APP_ENV", "TEST".into());
+        let encoded = if app_env.as_str() == "DEV" || app_env.as_str() == "TEST" {
+            get_from_env_unsafe::<String>(KEY_ENV)
+                .map_err(|e| anyhow!("{KEY_ENV} is not set: {e:?}"))?
+        } else {
+            kms::decrypt(kms::new_client(), KEY_ENV).await
+        };
+        Self::new(&encoded)
+    }
+
+    fn aead_key(&self) -> LessSafeKey {
+        let key = UnboundKey::new(&AES_256_GCM, &self.0)
+            .expect("AES-256-GCM keys are 32 bytes");
+        LessSafeKey::new(key)
+    }
+
+    /// `secret` as it is stored, a random nonce followed by the ciphertext.
+    pub fn encrypt(&self, secret: &str) -> String {
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let mut sealed = secret.as_bytes().to_vec();
+        self.aead_key()
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::empty(),
+                &mut sealed,
+            )
+            .expect("secrets are far below the AES-GCM size limit");
+        let mut stored = nonce.to_vec();
+        stored.append(&mut sealed);
+        format!("{ENCRYPTED_PREFIX}{}", BASE64_STANDARD.encode(stored))
+    }
+
+    /// Secret a webhook signs deliveries with, from its stored form.
+    pub fn decrypt(&self, stored: &str) -> anyhow::Result<String> {
+        let Some(encoded) = stored.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(stored.to_string());
+        };
+        let mut sealed = BASE64_STANDARD.decode(encoded)?;
+        if sealed.len() < NONCE_LEN {
+            return Err(anyhow!("stored webhook secret is truncated"));
+        }
+        let mut ciphertext = sealed.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&sealed)
+            .map_err(|_| anyhow!("stored webhook secret has an invalid nonce"))?;
+        let secret = self
+            .aead_key()
+            .open_in_place(nonce, Aad::empty(), &mut ciphertext)
+            .map_err(|_| {
+                anyhow!("stored webhook secret was not encrypted with this key")
+            })?;
+        Ok(String::from_utf8(secret.to_vec())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_round_trip() {
+        let key =
+            WebhookSecretKey::new(&BASE64_STANDARD.encode([7u8; KEY_LENGTH])).unwrap();
+        let stored = key.encrypt("whsec_test");
+        assert!(stored.starts_with(ENCRYPTED_PREFIX));
+        assert!(!stored.contains("whsec_test"));
+        assert_eq!(key.decrypt(&stored).unwrap(), "whsec_test");
+        assert_eq!(key.decrypt("whsec_legacy").unwrap(), "whsec_legacy");
+
+        let other =
+            WebhookSecretKey::new(&BASE64_STANDARD.encode([8u8; KEY_LENGTH])).unwrap();
+        assert!(other.decrypt(&stored).is_err());
+        assert!(WebhookSecretKey::new("c2hvcnQ=").is_err());
+    }
+}
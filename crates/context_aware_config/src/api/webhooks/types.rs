@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+use crate::db::models::Webhook;
+
+/// Events webhooks can subscribe to, named `<entity>.<change>`. They are
+/// written to the `webhook_outbox` of a schema by triggers.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+pub enum WebhookEvent {
+    #[serde(rename = "context.created")]
+    #[strum(serialize = "context.created")]
+    ContextCreated,
+    #[serde(rename = "context.updated")]
+    #[strum(serialize = "context.updated")]
+    ContextUpdated,
+    #[serde(rename = "context.deleted")]
+    #[strum(serialize = "context.deleted")]
+    ContextDeleted,
+    #[serde(rename = "default_config.created")]
+    #[strum(serialize = "default_config.created")]
+    DefaultConfigCreated,
+    #[serde(rename = "default_config.updated")]
+    #[strum(serialize = "default_config.updated")]
+    DefaultConfigUpdated,
+    #[serde(rename = "default_config.deleted")]
+    #[strum(serialize = "default_config.deleted")]
+    DefaultConfigDeleted,
+    #[serde(rename = "config.version_created")]
+    #[strum(serialize = "config.version_created")]
+    ConfigVersionCreated,
+    #[serde(rename = "experiment.created")]
+    #[strum(serialize = "experiment.created")]
+    ExperimentCreated,
+    #[serde(rename = "experiment.ramped")]
+    #[strum(serialize = "experiment.ramped")]
+    ExperimentRamped,
+    #[serde(rename = "experiment.concluded")]
+    #[strum(serialize = "experiment.concluded")]
+    ExperimentConcluded,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    /// Given up after the maximum number of attempts
+    Failed,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookReq {
+    pub name: String,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateWebhookReq {
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub events: Option<Vec<WebhookEvent>>,
+    pub enabled: Option<bool>,
+}
+
+/// The signing secret is only returned when the webhook is created.
+#[derive(Debug, Serialize)]
+pub struct CreateWebhookResponse {
+    pub secret: String,
+    #[serde(flatten)]
+    pub webhook: Webhook,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeliveryFilters {
+    pub status: Option<DeliveryStatus>,
+    pub count: Option<i64>,
+    pub page: Option<i64>,
+}
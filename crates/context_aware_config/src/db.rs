@@ -1,2 +1,3 @@
+pub mod migrations;
 pub mod models;
 pub mod schema;
@@ -0,0 +1,82 @@
+use service_utils::db::migrations::SchemaMigration;
+
+/// Migrations applied to every tenant schema of this service, in order. The
+/// diesel initial setup migration is left out, it only matters for `public`.
+/// Migrations added under `migrations/` have to be listed here too.
+pub const MIGRATIONS: &[SchemaMigration] = &[
+    SchemaMigration {
+        version: "2023-10-16-133815_context-aware-config-init",
+        up_sql: include_str!(
+            "../../migrations/2023-10-16-133815_context-aware-config-init/up.sql"
+        ),
+        marker: Some("contexts"),
+    },
+    SchemaMigration {
+        version: "2024-01-23-123559_audit_log_partitions",
+        up_sql: include_str!(
+            "../../migrations/2024-01-23-123559_audit_log_partitions/up.sql"
+        ),
+        marker: None,
+    },
+    SchemaMigration {
+        version: "2024-02-19-125126_functions",
+        up_sql: include_str!("../../migrations/2024-02-19-125126_functions/up.sql"),
+        marker: Some("functions"),
+    },
+    SchemaMigration {
+        version: "2024-03-05-122806_dimensions_functions_ref",
+        up_sql: include_str!(
+            "../../migrations/2024-03-05-122806_dimensions_functions_ref/up.sql"
+        ),
+        marker: Some("dimensions.function_name"),
+    },
+    SchemaMigration {
+        version: "2024-04-22-122806_config_verions",
+        up_sql: include_str!("../../migrations/2024-04-22-122806_config_verions/up.sql"),
+        marker: Some("config_versions"),
+    },
+    SchemaMigration {
+        version: "2024-05-06-133756_type_templates",
+        up_sql: include_str!("../../migrations/2024-05-06-133756_type_templates/up.sql"),
+        marker: Some("type_templates"),
+    },
+    SchemaMigration {
+        version: "2024-06-03-101500_role_bindings",
+        up_sql: include_str!("../../migrations/2024-06-03-101500_role_bindings/up.sql"),
+        marker: Some("role_bindings"),
+    },
+    SchemaMigration {
+        version: "2024-06-10-093000_api_keys",
+        up_sql: include_str!("../../migrations/2024-06-10-093000_api_keys/up.sql"),
+        marker: Some("api_keys"),
+    },
+    SchemaMigration {
+        version: "2024-06-17-090000_audit_actor",
+        up_sql: include_str!("../../migrations/2024-06-17-090000_audit_actor/up.sql"),
+        marker: Some("event_log.request_id"),
+    },
+    SchemaMigration {
+        version: "2024-06-24-090000_webhooks",
+        up_sql: include_str!("../../migrations/2024-06-24-090000_webhooks/up.sql"),
+        marker: Some("webhooks"),
+    },
+    SchemaMigration {
+        version: "2024-08-19-090000_webhooks_audit",
+        up_sql: include_str!("../../migrations/2024-08-19-090000_webhooks_audit/up.sql"),
+        marker: None,
+    },
+    SchemaMigration {
+        version: "2024-08-26-090000_experiment_event_bindings",
+        up_sql: include_str!(
+            "../../migrations/2024-08-26-090000_experiment_event_bindings/up.sql"
+        ),
+        marker: None,
+    },
+    SchemaMigration {
+        version: "2024-09-02-090000_tenant_settings_bindings",
+        up_sql: include_str!(
+            "../../migrations/2024-09-02-090000_tenant_settings_bindings/up.sql"
+        ),
+        marker: None,
+    },
+];
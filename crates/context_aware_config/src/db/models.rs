@@ -1,6 +1,6 @@
 use crate::db::schema::{
-    config_versions, contexts, default_configs, dimensions, event_log, functions,
-    type_templates,
+    api_keys, config_versions, contexts, default_configs, dimensions, event_log,
+    functions, role_bindings, type_templates, webhook_deliveries, webhooks,
 };
 use chrono::{offset::Utc, DateTime, NaiveDateTime};
 use diesel::{AsChangeset, Insertable, Queryable, Selectable};
@@ -76,6 +76,7 @@ pub struct EventLog {
     pub original_data: Option<Value>,
     pub new_data: Option<Value>,
     pub query: String,
+    pub request_id: Option<String>,
 }
 
 #[derive(Queryable, Selectable, Insertable, Serialize, Clone, Debug)]
@@ -100,3 +101,69 @@ pub struct TypeTemplates {
     pub created_at: NaiveDateTime,
     pub last_modified: NaiveDateTime,
 }
+
+#[derive(Queryable, Selectable, Insertable, Clone, Serialize, Debug)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = role_bindings)]
+#[diesel(primary_key(id))]
+pub struct RoleBinding {
+    pub id: uuid::Uuid,
+    pub subject: String,
+    pub role: String,
+    pub resource: Option<String>,
+    pub key_prefix: Option<String>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Queryable, Selectable, Insertable, Clone, Serialize, Debug)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = api_keys)]
+#[diesel(primary_key(id))]
+pub struct ApiKey {
+    pub id: uuid::Uuid,
+    pub name: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Queryable, Selectable, Insertable, Clone, Serialize, Debug)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = webhooks)]
+#[diesel(primary_key(id))]
+pub struct Webhook {
+    pub id: uuid::Uuid,
+    pub name: String,
+    pub url: String,
+    pub events: Vec<String>,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub enabled: bool,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub last_modified_at: DateTime<Utc>,
+}
+
+#[derive(Queryable, Selectable, Insertable, Clone, Serialize, Debug)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = webhook_deliveries)]
+#[diesel(primary_key(id))]
+pub struct WebhookDelivery {
+    pub id: uuid::Uuid,
+    pub webhook_id: uuid::Uuid,
+    pub event: String,
+    pub payload: Value,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
@@ -10,6 +10,20 @@ pub mod sql_types {
     pub struct NotNullText;
 }
 
+diesel::table! {
+    api_keys (id) {
+        id -> Uuid,
+        name -> Text,
+        key_hash -> Text,
+        scopes -> Array<Text>,
+        created_by -> Text,
+        created_at -> Timestamptz,
+        expires_at -> Nullable<Timestamptz>,
+        last_used_at -> Nullable<Timestamptz>,
+        revoked_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     config_versions (id) {
         id -> Int8,
@@ -65,641 +79,125 @@ diesel::table! {
         original_data -> Nullable<Json>,
         new_data -> Nullable<Json>,
         query -> Text,
+        request_id -> Nullable<Text>,
     }
 }
 
 diesel::table! {
-    event_log_y2023m08 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2023m09 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2023m10 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2023m11 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2023m12 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m01 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m02 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m03 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m04 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m05 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m06 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m07 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m08 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
+    use diesel::sql_types::*;
+    use super::sql_types::NotNullText;
+    use super::sql_types::ExperimentStatusType;
 
-diesel::table! {
-    event_log_y2024m09 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
+    experiments (id) {
+        id -> Int8,
+        created_at -> Timestamptz,
+        created_by -> Text,
+        last_modified -> Timestamptz,
+        name -> Text,
+        override_keys -> Array<Nullable<NotNullText>>,
+        status -> ExperimentStatusType,
+        traffic_percentage -> Int4,
+        context -> Json,
+        variants -> Json,
+        last_modified_by -> Text,
+        chosen_variant -> Nullable<Text>,
     }
 }
 
 diesel::table! {
-    event_log_y2024m10 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
+    functions (function_name) {
+        function_name -> Text,
+        published_code -> Nullable<Text>,
+        draft_code -> Text,
+        function_description -> Text,
+        #[max_length = 16]
+        published_runtime_version -> Nullable<Varchar>,
+        #[max_length = 16]
+        draft_runtime_version -> Varchar,
+        published_at -> Nullable<Timestamp>,
+        draft_edited_at -> Timestamp,
+        published_by -> Nullable<Text>,
+        draft_edited_by -> Text,
     }
 }
 
 diesel::table! {
-    event_log_y2024m11 (id, timestamp) {
+    role_bindings (id) {
         id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
+        subject -> Text,
+        role -> Text,
+        resource -> Nullable<Text>,
+        key_prefix -> Nullable<Text>,
+        created_by -> Text,
+        created_at -> Timestamptz,
     }
 }
 
 diesel::table! {
-    event_log_y2024m12 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
+    type_templates (type_name) {
+        type_name -> Text,
+        type_schema -> Json,
+        created_by -> Text,
+        created_at -> Timestamp,
+        last_modified -> Timestamp,
     }
 }
 
 diesel::table! {
-    event_log_y2025m01 (id, timestamp) {
+    webhook_deliveries (id) {
         id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
+        webhook_id -> Uuid,
+        event -> Text,
+        payload -> Json,
+        status -> Text,
+        attempts -> Int4,
+        next_attempt_at -> Timestamptz,
+        last_attempt_at -> Nullable<Timestamptz>,
+        response_status -> Nullable<Int4>,
+        last_error -> Nullable<Text>,
+        created_at -> Timestamptz,
     }
 }
 
 diesel::table! {
-    event_log_y2025m02 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
+    webhook_outbox (id) {
+        id -> Int8,
+        event -> Text,
+        payload -> Json,
+        created_at -> Timestamptz,
     }
 }
 
 diesel::table! {
-    event_log_y2025m03 (id, timestamp) {
+    webhooks (id) {
         id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m04 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m05 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m06 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m07 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m08 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m09 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m10 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m11 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m12 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m01 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m02 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m03 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m04 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m05 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m06 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m07 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m08 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m09 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m10 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m11 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m12 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    use diesel::sql_types::*;
-    use super::sql_types::NotNullText;
-    use super::sql_types::ExperimentStatusType;
-
-    experiments (id) {
-        id -> Int8,
-        created_at -> Timestamptz,
-        created_by -> Text,
-        last_modified -> Timestamptz,
         name -> Text,
-        override_keys -> Array<Nullable<NotNullText>>,
-        status -> ExperimentStatusType,
-        traffic_percentage -> Int4,
-        context -> Json,
-        variants -> Json,
-        last_modified_by -> Text,
-        chosen_variant -> Nullable<Text>,
-    }
-}
-
-diesel::table! {
-    functions (function_name) {
-        function_name -> Text,
-        published_code -> Nullable<Text>,
-        draft_code -> Text,
-        function_description -> Text,
-        #[max_length = 16]
-        published_runtime_version -> Nullable<Varchar>,
-        #[max_length = 16]
-        draft_runtime_version -> Varchar,
-        published_at -> Nullable<Timestamp>,
-        draft_edited_at -> Timestamp,
-        published_by -> Nullable<Text>,
-        draft_edited_by -> Text,
-    }
-}
-
-diesel::table! {
-    type_templates (type_name) {
-        type_name -> Text,
-        type_schema -> Json,
+        url -> Text,
+        events -> Array<Text>,
+        secret -> Text,
+        enabled -> Bool,
         created_by -> Text,
-        created_at -> Timestamp,
-        last_modified -> Timestamp,
+        created_at -> Timestamptz,
+        last_modified_at -> Timestamptz,
     }
 }
 
 diesel::joinable!(default_configs -> functions (function_name));
 diesel::joinable!(dimensions -> functions (function_name));
+diesel::joinable!(webhook_deliveries -> webhooks (webhook_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    api_keys,
     config_versions,
     contexts,
     default_configs,
     dimensions,
     event_log,
-    event_log_y2023m08,
-    event_log_y2023m09,
-    event_log_y2023m10,
-    event_log_y2023m11,
-    event_log_y2023m12,
-    event_log_y2024m01,
-    event_log_y2024m02,
-    event_log_y2024m03,
-    event_log_y2024m04,
-    event_log_y2024m05,
-    event_log_y2024m06,
-    event_log_y2024m07,
-    event_log_y2024m08,
-    event_log_y2024m09,
-    event_log_y2024m10,
-    event_log_y2024m11,
-    event_log_y2024m12,
-    event_log_y2025m01,
-    event_log_y2025m02,
-    event_log_y2025m03,
-    event_log_y2025m04,
-    event_log_y2025m05,
-    event_log_y2025m06,
-    event_log_y2025m07,
-    event_log_y2025m08,
-    event_log_y2025m09,
-    event_log_y2025m10,
-    event_log_y2025m11,
-    event_log_y2025m12,
-    event_log_y2026m01,
-    event_log_y2026m02,
-    event_log_y2026m03,
-    event_log_y2026m04,
-    event_log_y2026m05,
-    event_log_y2026m06,
-    event_log_y2026m07,
-    event_log_y2026m08,
-    event_log_y2026m09,
-    event_log_y2026m10,
-    event_log_y2026m11,
-    event_log_y2026m12,
     experiments,
     functions,
+    role_bindings,
     type_templates,
+    webhook_deliveries,
+    webhook_outbox,
+    webhooks,
 );
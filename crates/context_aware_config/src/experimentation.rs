@@ -0,0 +1,269 @@
+use actix_web::{error::ResponseError, web, web::Data};
+use async_trait::async_trait;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use experimentation_platform::api::experiments::{
+    cac::{BulkResult, CacBackend, CacCaller, CacError},
+    saga::CacPlan,
+    types as experiment_types,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+use service_utils::{
+    db::{
+        audit::AuditActor,
+        pgschema_manager::PgSchemaConnection,
+        utils::{cac_namespace, tenant_namespaces},
+    },
+    helpers::parse_config_tags,
+    rbac::{Action, Resource},
+    service::types::AppState,
+};
+use superposition_macros::{bad_argument, unexpected_error};
+use superposition_types::result as superposition;
+
+use crate::{
+    api::{
+        context::{
+            bulk_operations_api, helpers::authorize_context_actions, ContextAction,
+        },
+        default_config::{upsert_default_config, CreateReq},
+    },
+    db::schema::{contexts, default_configs},
+    helpers::add_config_version,
+};
+
+/// Serves the CAC calls of experiments from this process, when both services
+/// run in the same binary. The writes skip the HTTP layer but go through the
+/// same validations and permission checks as the CAC endpoints.
+pub struct InProcessCacBackend {
+    state: Data<AppState>,
+    /// Makes the CAC writes of an experiment action in the transaction of the
+    /// experiment change, so no saga is needed for them.
+    shared_transaction: bool,
+}
+
+impl InProcessCacBackend {
+    pub fn new(state: Data<AppState>, shared_transaction: bool) -> Self {
+        InProcessCacBackend {
+            state,
+            shared_transaction,
+        }
+    }
+
+    fn namespace(&self, caller: &CacCaller) -> Result<String, CacError> {
+        cac_namespace(self.state.enable_tenant_and_scope, caller.tenant.as_deref())
+            .ok_or_else(|| CacError::Rejected(bad_argument!("x-tenant header not set")))
+    }
+
+    async fn with_conn<T, F>(&self, caller: &CacCaller, f: F) -> Result<T, CacError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Data<AppState>, &mut PgSchemaConnection) -> superposition::Result<T>
+            + Send
+            + 'static,
+    {
+        let namespace = self.namespace(caller)?;
+        let state = self.state.clone();
+        web::block(move || {
+            let mut conn = state
+                .db_pool
+                .get_conn(namespace)
+                .map_err(|e| CacError::Unavailable(e.to_string()))?;
+            f(&state, &mut conn).map_err(cac_error)
+        })
+        .await
+        .map_err(|e| CacError::Unavailable(e.to_string()))?
+    }
+}
+
+/// Errors CAC answers with a client error over HTTP are rejections, the
+/// transaction they happened in was rolled back.
+fn cac_error(error: superposition::AppError) -> CacError {
+    if error.error_response().status().is_client_error() {
+        CacError::Rejected(error)
+    } else {
+        CacError::Unavailable(error.to_string())
+    }
+}
+
+/// Converts between the CAC types of the two crates, which share their JSON
+/// representation.
+fn convert<T: Serialize, U: DeserializeOwned>(value: T) -> superposition::Result<U> {
+    serde_json::to_value(value)
+        .and_then(serde_json::from_value)
+        .map_err(|err| {
+            log::error!("failed to convert CAC request or response: {err}");
+            unexpected_error!("Something went wrong")
+        })
+}
+
+/// Makes the writes of `plan` on `conn`, which is in a transaction with the
+/// actor recorded, and adds a config version for them. The writes are checked
+/// against the caller's permissions like the CAC endpoints check them.
+fn write_plan(
+    state: &Data<AppState>,
+    caller: &CacCaller,
+    default_configs: &Map<String, Value>,
+    operations: &[experiment_types::ContextAction],
+    conn: &mut PgSchemaConnection,
+) -> superposition::Result<(Vec<experiment_types::ContextBulkResponse>, Option<String>)> {
+    if default_configs.is_empty() && operations.is_empty() {
+        return Ok((Vec::new(), None));
+    }
+    let tags = parse_config_tags(caller.config_tags.clone())?;
+    let operations: Vec<ContextAction> = convert(operations)?;
+    if !default_configs.is_empty() {
+        caller.authorizer.require_keys(
+            Resource::DefaultConfig,
+            Action::Write,
+            default_configs.keys(),
+        )?;
+    }
+    authorize_context_actions(&caller.authorizer, &operations, conn)?;
+    for (key, value) in default_configs.iter() {
+        let req = CreateReq {
+            value: Some(value.clone()),
+            schema: None,
+            function_name: None,
+        };
+        upsert_default_config(state, key.clone(), req, &caller.user, conn)?;
+    }
+    let responses = bulk_operations_api(operations, &caller.user, conn)?;
+    let version_id = add_config_version(state, tags, conn)?;
+    Ok((convert(responses)?, Some(version_id.to_string())))
+}
+
+fn actor(caller: &CacCaller) -> AuditActor {
+    AuditActor {
+        user_email: caller.user.email.clone(),
+        request_id: caller.request_id.clone(),
+    }
+}
+
+/// Points unqualified table names of `conn` at `schema` until its
+/// transaction ends.
+fn set_local_search_path(
+    conn: &mut PgSchemaConnection,
+    schema: &str,
+) -> superposition::Result<()> {
+    diesel::sql_query(format!(
+        "SET LOCAL search_path TO \"{schema}\", \"$user\", public"
+    ))
+    .execute(conn)?;
+    Ok(())
+}
+
+#[async_trait]
+impl CacBackend for InProcessCacBackend {
+    async fn bulk_operations(
+        &self,
+        caller: &CacCaller,
+        operations: &[experiment_types::ContextAction],
+    ) -> BulkResult {
+        let (owned_caller, operations) = (caller.clone(), operations.to_vec());
+        self.with_conn(caller, move |state, conn| {
+            actor(&owned_caller).transaction(conn, |transaction_conn| {
+                write_plan(
+                    state,
+                    &owned_caller,
+                    &Map::new(),
+                    &operations,
+                    transaction_conn,
+                )
+            })
+        })
+        .await
+    }
+
+    async fn put_default_config(
+        &self,
+        caller: &CacCaller,
+        key: &str,
+        value: &Value,
+    ) -> Result<(), CacError> {
+        let owned_caller = caller.clone();
+        let default_configs = Map::from_iter([(key.to_string(), value.clone())]);
+        self.with_conn(caller, move |state, conn| {
+            actor(&owned_caller).transaction(conn, |transaction_conn| {
+                write_plan(
+                    state,
+                    &owned_caller,
+                    &default_configs,
+                    &[],
+                    transaction_conn,
+                )
+            })?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn default_config_values(
+        &self,
+        caller: &CacCaller,
+        keys: &[String],
+    ) -> Result<Map<String, Value>, CacError> {
+        let keys = keys.to_vec();
+        self.with_conn(caller, move |_, conn| {
+            let values = default_configs::table
+                .filter(default_configs::key.eq_any(keys))
+                .select((default_configs::key, default_configs::value))
+                .load::<(String, Value)>(conn)?;
+            Ok(values.into_iter().collect())
+        })
+        .await
+    }
+
+    async fn context_exists(
+        &self,
+        caller: &CacCaller,
+        context_id: &str,
+    ) -> Result<bool, CacError> {
+        let context_id = context_id.to_string();
+        self.with_conn(caller, move |_, conn| {
+            let count: i64 = contexts::table
+                .filter(contexts::id.eq(context_id))
+                .count()
+                .get_result(conn)?;
+            Ok(count > 0)
+        })
+        .await
+    }
+
+    fn joins_transactions(&self) -> bool {
+        self.shared_transaction
+    }
+
+    /// Both schemas live in the same database, the CAC writes are made on the
+    /// connection of the experimentation schema with the search path pointed
+    /// at the tenant's CAC schema for their duration.
+    fn apply_in_transaction(
+        &self,
+        caller: &CacCaller,
+        conn: &mut PgSchemaConnection,
+        plan: &CacPlan,
+    ) -> BulkResult {
+        let schemas = match (self.state.enable_tenant_and_scope, &caller.tenant) {
+            (true, Some(tenant)) => Some(tenant_namespaces(tenant)),
+            (true, None) => {
+                return Err(CacError::Rejected(bad_argument!("x-tenant header not set")))
+            }
+            (false, _) => None,
+        };
+        if let Some([cac_schema, _]) = schemas.as_ref() {
+            set_local_search_path(conn, cac_schema).map_err(cac_error)?;
+        }
+        let result = write_plan(
+            &self.state,
+            caller,
+            &plan.default_configs,
+            &plan.operations,
+            conn,
+        )
+        .map_err(cac_error)?;
+        if let Some([_, experimentation_schema]) = schemas.as_ref() {
+            set_local_search_path(conn, experimentation_schema).map_err(cac_error)?;
+        }
+        Ok(result)
+    }
+}
@@ -15,7 +15,6 @@ use diesel::{
     ExpressionMethods, PgConnection, QueryDsl, RunQueryDsl,
 };
 
-use itertools::{self, Itertools};
 use jsonschema::{Draft, JSONSchema, ValidationError};
 use serde_json::{json, Map, Value};
 use service_utils::{
@@ -246,32 +245,6 @@ pub fn validate_jsonschema(
     res
 }
 
-pub fn json_to_sorted_string(v: &Value) -> String {
-    match v {
-        Value::Object(m) => {
-            let mut new_str: String = String::from("");
-            for (i, val) in m.iter().sorted_by_key(|item| item.0) {
-                let p: String = json_to_sorted_string(val);
-                new_str.push_str(i);
-                new_str.push_str(&String::from(":"));
-                new_str.push_str(&p);
-                new_str.push_str(&String::from("$"));
-            }
-            new_str
-        }
-        Value::String(m) => m.to_string(),
-        Value::Number(m) => m.to_string(),
-        Value::Bool(m) => m.to_string(),
-        Value::Null => String::from("null"),
-        Value::Array(m) => {
-            let mut new_vec =
-                m.iter().map(json_to_sorted_string).collect::<Vec<String>>();
-            new_vec.sort();
-            new_vec.join(",")
-        }
-    }
-}
-
 pub fn calculate_context_priority(
     _object_key: &str,
     cond: &Value,
@@ -390,6 +363,7 @@ pub fn add_config_version(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use superposition_types::hashing::json_to_sorted_string;
     #[test]
     fn test_get_meta_schema() {
         let x = get_meta_schema();
@@ -1,5 +1,6 @@
 pub mod api;
 pub mod db;
+pub mod experimentation;
 pub mod helpers;
 pub mod middlewares;
 pub mod validation_functions;
@@ -3,7 +3,7 @@ use std::{
     sync::Arc,
 };
 
-use crate::{Client, CLIENT_FACTORY};
+use crate::{toss_of, Client, CLIENT_FACTORY};
 use once_cell::sync::Lazy;
 use serde_json::Value;
 use std::{
@@ -108,14 +108,38 @@ pub extern "C" fn expt_new_client(
     tenant: *const c_char,
     update_frequency: c_ulong,
     hostname: *const c_char,
+    api_key: *const c_char,
+    key_prefixes: *const c_char,
 ) -> c_int {
     let tenant = unwrap_safe!(cstring_to_rstring(tenant), return 1);
     let hostname = unwrap_safe!(cstring_to_rstring(hostname), return 1);
+    // the api key is optional, NULL means no key
+    let api_key = match api_key.is_null() {
+        true => None,
+        false => Some(unwrap_safe!(cstring_to_rstring(api_key), return 1)),
+    };
+    // comma separated, NULL means every experiment is polled
+    let key_prefixes = match key_prefixes.is_null() {
+        true => None,
+        false => Some(
+            unwrap_safe!(cstring_to_rstring(key_prefixes), return 1)
+                .split(',')
+                .map(|prefix| prefix.trim().to_string())
+                .filter(|prefix| !prefix.is_empty())
+                .collect::<Vec<String>>(),
+        ),
+    };
 
     // println!("Creating cac client thread for tenant {tenant}");
     EXP_RUNTIME.block_on(async move {
         match CLIENT_FACTORY
-            .create_client(tenant.clone(), update_frequency, hostname)
+            .create_client(
+                tenant.clone(),
+                update_frequency,
+                hostname,
+                api_key,
+                key_prefixes,
+            )
             .await
         {
             Ok(_) => 0,
@@ -186,6 +210,57 @@ pub extern "C" fn expt_get_applicable_variant(
         .unwrap_or_else(|err| error_block(err.to_string()))
 }
 
+#[no_mangle]
+pub extern "C" fn expt_get_holdout_status(
+    client: *mut Arc<Client>,
+    c_context: *const c_char,
+    toss: c_short,
+) -> *mut c_char {
+    let context =
+        unwrap_safe!(cstring_to_rstring(c_context), return std::ptr::null_mut());
+    let context = unwrap_safe!(
+        serde_json::from_str::<Value>(context.as_str()),
+        return std::ptr::null_mut()
+    );
+    let status_result = EXP_RUNTIME
+        .block_on(unsafe { (*client).get_holdout_status(&context, toss as i8) });
+    status_result
+        .map(|result| {
+            serde_json::to_string(&result)
+                .map(|json| rstring_to_cstring(json).into_raw())
+                .unwrap_or_else(|err| error_block(err.to_string()))
+        })
+        .unwrap_or_else(|err| error_block(err.to_string()))
+}
+
+#[no_mangle]
+pub extern "C" fn expt_report_exposures(
+    client: *mut Arc<Client>,
+    c_unit_id: *const c_char,
+    c_variant_ids: *const c_char,
+) -> c_int {
+    null_check!(client, "client cannot be a null pointer", return 1);
+    let unit_id = unwrap_safe!(cstring_to_rstring(c_unit_id), return 1);
+    let variant_ids = unwrap_safe!(cstring_to_rstring(c_variant_ids), return 1);
+    let variant_ids = unwrap_safe!(
+        serde_json::from_str::<Vec<String>>(variant_ids.as_str()),
+        return 1
+    );
+    let result = EXP_RUNTIME
+        .block_on(unsafe { (*client).report_exposures(&unit_id, &variant_ids) });
+    unwrap_safe!(result, return 1);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn expt_toss_of(c_identifier: *const c_char, toss: *mut c_short) -> c_int {
+    null_check!(c_identifier, "identifier cannot be a null string", return 1);
+    null_check!(toss, "toss cannot be a null pointer", return 1);
+    let identifier = unwrap_safe!(cstring_to_rstring(c_identifier), return 1);
+    unsafe { *toss = c_short::from(toss_of(&identifier)) };
+    0
+}
+
 #[no_mangle]
 pub extern "C" fn expt_get_satisfied_experiments(
     client: *mut Arc<Client>,
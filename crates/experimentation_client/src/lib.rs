@@ -9,20 +9,47 @@ use std::{
 use chrono::{DateTime, TimeZone, Utc};
 use derive_more::{Deref, DerefMut};
 use serde_json::{Map, Value};
+/// Toss of the unit an identifier names, like a user id, for
+/// `get_applicable_variant` and `get_holdout_status`. The server buckets
+/// identifiers it resolves with experiments the same way.
+pub use superposition_bucketing::toss_of;
+use superposition_bucketing::{
+    choose_variant, globally_held_out, held_out_of, TOTAL_BUCKETS,
+};
 use tokio::{
     sync::RwLock,
     time::{self, Duration},
 };
-pub use types::{Config, Experiment, Experiments, Variants};
-use types::{ExperimentStore, ListExperimentsResponse, Variant, VariantType};
+pub use types::{Config, Experiment, Experiments, HoldoutStatus, Variants};
+use types::{
+    ExperimentStatusType, ExperimentStore, ExposureEvent, ExposureEventsRequest,
+    ListExperimentsResponse, TenantSettings, Variant,
+};
 use utils::MapError;
 
+/// Exposures remembered as reported, past it they are forgotten and units
+/// are reported again, which the server counts once.
+const MAX_REPORTED_EXPOSURES: usize = 100_000;
+
+/// Polls of a client narrowed to key prefixes between two full syncs of its
+/// experiments. An experiment whose overrides moved off the prefixes is not
+/// listed as changed to it, the full sync drops it from the store.
+const FULL_SYNC_POLLS: u32 = 60;
+
+fn first_poll_date() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap()
+}
+
 #[derive(Clone, Debug)]
 pub struct Client {
     pub client_config: Arc<Config>,
     pub(crate) experiments: Arc<RwLock<ExperimentStore>>,
+    /// Percent of traffic held out of every experiment
+    pub(crate) holdout_percentage: Arc<RwLock<u8>>,
     pub(crate) http_client: reqwest::Client,
     last_polled: Arc<RwLock<DateTime<Utc>>>,
+    /// Units and the variants they were reported exposed to
+    reported_exposures: Arc<RwLock<HashSet<(String, String)>>>,
 }
 
 //TODO: replace all unwraps with proper error handling
@@ -33,10 +60,10 @@ impl Client {
         Client {
             client_config: Arc::new(config),
             experiments: Arc::new(RwLock::new(HashMap::new())),
+            holdout_percentage: Arc::new(RwLock::new(0)),
             http_client: reqwest::Client::new(),
-            last_polled: Arc::new(RwLock::new(
-                Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap(),
-            )),
+            last_polled: Arc::new(RwLock::new(first_poll_date())),
+            reported_exposures: Arc::new(RwLock::new(HashSet::new())),
         }
     }
 
@@ -45,16 +72,49 @@ impl Client {
         let hostname = &self.client_config.hostname;
         let mut interval = time::interval(Duration::from_secs(poll_interval));
         let mut start_date = self.last_polled.write().await;
+        let mut polls_since_full_sync = FULL_SYNC_POLLS;
         loop {
+            // the running experiments of a client narrowed to key prefixes
+            // replace its store now and then
+            let full_sync = if self.client_config.key_prefixes.is_some()
+                && polls_since_full_sync >= FULL_SYNC_POLLS
+            {
+                match get_experiments(
+                    hostname.clone(),
+                    self.http_client.clone(),
+                    first_poll_date().to_string(),
+                    self.client_config.tenant.to_string(),
+                    self.client_config.api_key.clone(),
+                    self.client_config.key_prefixes.clone(),
+                    "CREATED,INPROGRESS",
+                )
+                .await
+                {
+                    Ok(experiments) => {
+                        *self.experiments.write().await = experiments;
+                        polls_since_full_sync = 0;
+                        true
+                    }
+                    Err(err) => {
+                        log::error!("failed to sync experiments: {err}");
+                        false
+                    }
+                }
+            } else {
+                false
+            };
             // NOTE: this additional block scopes the write lock
             // at the end of this block, the write lock on exp store is released
             // allowing other threads to read updated data
-            {
+            if !full_sync {
                 let experiments = get_experiments(
                     hostname.clone(),
                     self.http_client.clone(),
                     start_date.to_string(),
                     self.client_config.tenant.to_string(),
+                    self.client_config.api_key.clone(),
+                    self.client_config.key_prefixes.clone(),
+                    "CREATED,INPROGRESS,CONCLUDED",
                 )
                 .await
                 .unwrap_or(HashMap::new());
@@ -68,30 +128,147 @@ impl Client {
                         _ => exp_store.insert(exp_id, experiment),
                     };
                 }
+                polls_since_full_sync += 1;
             } // write lock on exp store releases here
+              // the last known holdout is kept when it can't be fetched
+            if let Ok(settings) = get_settings(
+                hostname.clone(),
+                self.http_client.clone(),
+                self.client_config.tenant.to_string(),
+                self.client_config.api_key.clone(),
+            )
+            .await
+            {
+                *self.holdout_percentage.write().await = settings.holdout_percentage;
+            }
             *start_date = Utc::now();
             interval.tick().await;
         }
     }
 
+    /// Buckets experiments serve, the ones past them are the global holdout.
+    async fn experiment_buckets(&self) -> i32 {
+        TOTAL_BUCKETS - i32::from(*self.holdout_percentage.read().await)
+    }
+
     pub async fn get_applicable_variant(
         &self,
         context: &Value,
         toss: i8,
     ) -> Result<Vec<String>, String> {
+        let buckets = self.experiment_buckets().await;
+        if globally_held_out(toss, buckets) {
+            return Ok(Vec::new());
+        }
         let experiments: Experiments =
             self.get_satisfied_experiments(context, None).await?;
         let mut variants: Vec<String> = Vec::new();
         for exp in experiments {
-            if let Some(v) =
-                self.decide_variant(exp.traffic_percentage, exp.variants, toss)?
-            {
-                variants.push(v.id)
+            if let Some(v) = choose_variant(&exp, &exp.variants, toss, buckets) {
+                variants.push(v.id.clone())
             }
         }
         Ok(variants)
     }
 
+    /// Holdouts the unit of `toss` is in, for the experiments `context`
+    /// satisfies. Reporting the exposures of the returned ids compares the
+    /// held out units with the exposed ones in the experiments' results.
+    pub async fn get_holdout_status(
+        &self,
+        context: &Value,
+        toss: i8,
+    ) -> Result<HoldoutStatus, String> {
+        let buckets = self.experiment_buckets().await;
+        let global = globally_held_out(toss, buckets);
+        let mut variant_ids = self
+            .get_satisfied_experiments(context, None)
+            .await?
+            .into_iter()
+            .filter(|exp| exp.status == ExperimentStatusType::InProgress)
+            .filter(|exp| global || held_out_of(exp, toss, buckets))
+            .map(|exp| format!("{}-holdout", exp.id))
+            .collect::<Vec<String>>();
+        variant_ids.sort();
+        // the global holdout is reported under experiment 0, which no
+        // experiment has as its id
+        let global_variant_id = if global { "0-holdout" } else { "0-exposed" };
+        Ok(HoldoutStatus {
+            global,
+            global_variant_id: global_variant_id.to_string(),
+            variant_ids,
+        })
+    }
+
+    /// Reports that the variants returned by `get_applicable_variant` were
+    /// served to the unit `unit_id`, like a user id, so it is counted as
+    /// exposed in their experiments' results. Variants already reported for
+    /// the unit are skipped.
+    pub async fn report_exposures(
+        &self,
+        unit_id: &str,
+        variant_ids: &[String],
+    ) -> Result<(), String> {
+        let variant_ids = {
+            let reported = self.reported_exposures.read().await;
+            variant_ids
+                .iter()
+                .filter(|variant_id| {
+                    !reported.contains(&(unit_id.to_string(), variant_id.to_string()))
+                })
+                .cloned()
+                .collect::<Vec<String>>()
+        };
+        if variant_ids.is_empty() {
+            return Ok(());
+        }
+        let events = variant_ids
+            .iter()
+            .map(|variant_id| {
+                // variant ids are prefixed with the id of their experiment
+                let experiment_id = variant_id
+                    .split_once('-')
+                    .map(|(experiment_id, _)| experiment_id.to_string())
+                    .ok_or_else(|| format!("invalid variant id {variant_id}"))?;
+                Ok(ExposureEvent {
+                    experiment_id,
+                    variant_id: variant_id.clone(),
+                    unit_id: unit_id.to_string(),
+                    event_type: "exposure",
+                    timestamp: Utc::now(),
+                })
+            })
+            .collect::<Result<Vec<ExposureEvent>, String>>()?;
+        let mut request = self
+            .http_client
+            .post(format!(
+                "{}/experiments/events",
+                self.client_config.hostname
+            ))
+            .header("x-tenant", self.client_config.tenant.to_string())
+            .json(&ExposureEventsRequest { events });
+        if let Some(api_key) = &self.client_config.api_key {
+            request = request.header("x-api-key", api_key);
+        }
+        let response = request.send().await.map_err_to_string()?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "failed to report exposures: {}",
+                response.text().await.map_err_to_string()?
+            ));
+        }
+        let mut reported = self.reported_exposures.write().await;
+        if reported.len() + variant_ids.len() > MAX_REPORTED_EXPOSURES {
+            reported.clear();
+        }
+        reported.extend(
+            variant_ids
+                .into_iter()
+                .map(|variant_id| (unit_id.to_string(), variant_id)),
+        );
+        Ok(())
+    }
+
     pub async fn get_satisfied_experiments(
         &self,
         context: &Value,
@@ -212,36 +389,6 @@ impl Client {
             })
             .collect()
     }
-
-    // decide which variant to return among all applicable experiments
-    fn decide_variant(
-        &self,
-        traffic: u8,
-        applicable_variants: Variants,
-        toss: i8,
-    ) -> Result<Option<Variant>, String> {
-        if toss < 0 {
-            for variant in applicable_variants.iter() {
-                if variant.variant_type == VariantType::Experimental {
-                    return Ok(Some(variant.clone()));
-                }
-            }
-        }
-        let variant_count = applicable_variants.len() as u8;
-        let range = (traffic * variant_count) as i32;
-        if (toss as i32) >= range {
-            return Ok(None);
-        }
-        let buckets = (1..=variant_count)
-            .map(|i| (traffic * i) as i8)
-            .collect::<Vec<i8>>();
-        let index = buckets
-            .into_iter()
-            .position(|x| toss < x)
-            .ok_or_else(|| "Unable to fetch variant's index".to_string())
-            .map_err_to_string()?;
-        Ok(applicable_variants.get(index).cloned())
-    }
 }
 
 async fn get_experiments(
@@ -249,6 +396,9 @@ async fn get_experiments(
     http_client: reqwest::Client,
     start_date: String,
     tenant: String,
+    api_key: Option<String>,
+    key_prefixes: Option<Vec<String>>,
+    statuses: &str,
 ) -> Result<ExperimentStore, String> {
     let mut curr_exp_store: ExperimentStore = HashMap::new();
     let requesting_count = 10;
@@ -258,9 +408,17 @@ async fn get_experiments(
         let endpoint = format!(
             "{hostname}/experiments?from_date={start_date}&to_date={now}&page={page}&count={requesting_count}"
         );
-        let list_experiments_response = http_client
-            .get(format!("{endpoint}&status=CREATED,INPROGRESS,CONCLUDED"))
-            .header("x-tenant", tenant.to_string())
+        let mut request = http_client
+            .get(format!("{endpoint}&status={statuses}"))
+            .query(&[("sort_on", "last_modified")])
+            .header("x-tenant", tenant.to_string());
+        if let Some(key_prefixes) = &key_prefixes {
+            request = request.query(&[("key_prefix", key_prefixes.join(","))]);
+        }
+        if let Some(api_key) = &api_key {
+            request = request.header("x-api-key", api_key);
+        }
+        let list_experiments_response = request
             .send()
             .await
             .map_err_to_string()?
@@ -283,6 +441,29 @@ async fn get_experiments(
     Ok(curr_exp_store)
 }
 
+async fn get_settings(
+    hostname: String,
+    http_client: reqwest::Client,
+    tenant: String,
+    api_key: Option<String>,
+) -> Result<TenantSettings, String> {
+    let mut request = http_client
+        .get(format!("{hostname}/settings"))
+        .header("x-tenant", tenant);
+    if let Some(api_key) = &api_key {
+        request = request.header("x-api-key", api_key);
+    }
+    request
+        .send()
+        .await
+        .map_err_to_string()?
+        .error_for_status()
+        .map_err_to_string()?
+        .json::<TenantSettings>()
+        .await
+        .map_err_to_string()
+}
+
 #[derive(Deref, DerefMut)]
 pub struct ClientFactory(RwLock<HashMap<String, Arc<Client>>>);
 impl ClientFactory {
@@ -291,6 +472,8 @@ impl ClientFactory {
         tenant: String,
         poll_frequency: u64,
         hostname: String,
+        api_key: Option<String>,
+        key_prefixes: Option<Vec<String>>,
     ) -> Result<Arc<Client>, String> {
         let mut factory = self.write().await;
 
@@ -302,6 +485,8 @@ impl ClientFactory {
             tenant: tenant.to_string(),
             hostname,
             poll_frequency,
+            api_key,
+            key_prefixes,
         }));
 
         factory.insert(tenant.to_string(), client.clone());
@@ -1,13 +1,20 @@
 use std::collections::HashMap;
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use superposition_bucketing::{BucketedExperiment, BucketedVariant};
 
 #[derive(Clone, Debug)]
 pub struct Config {
     pub tenant: String,
     pub hostname: String,
     pub poll_frequency: u64,
+    /// Sent as `x-api-key` on every request when set.
+    pub api_key: Option<String>,
+    /// Only experiments overriding keys which start with one of these are
+    /// polled, all of them when unset.
+    pub key_prefixes: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
@@ -31,6 +38,10 @@ pub struct Variant {
     pub id: String,
     pub overrides: Value,
     pub(crate) variant_type: VariantType,
+    /// Added to an experiment in progress, serves no traffic until the
+    /// experiment is ramped again.
+    #[serde(default)]
+    pub(crate) awaiting_ramp: bool,
 }
 
 pub type Variants = Vec<Variant>;
@@ -44,6 +55,43 @@ pub struct Experiment {
     pub(crate) traffic_percentage: u8,
     pub(crate) context: Value,
     pub(crate) status: ExperimentStatusType,
+    #[serde(default)]
+    pub(crate) layer: Option<String>,
+    /// First bucket of the traffic the experiment serves
+    #[serde(default)]
+    pub(crate) slice_start: u8,
+    /// Percent of the buckets left by the global holdout held out of the
+    /// experiment, the last ones
+    #[serde(default)]
+    pub(crate) holdout_percentage: u8,
+}
+
+impl BucketedExperiment for Experiment {
+    fn layer(&self) -> Option<&str> {
+        self.layer.as_deref()
+    }
+
+    fn traffic_percentage(&self) -> i32 {
+        i32::from(self.traffic_percentage)
+    }
+
+    fn holdout_percentage(&self) -> i32 {
+        i32::from(self.holdout_percentage)
+    }
+
+    fn slice_start(&self) -> i32 {
+        i32::from(self.slice_start)
+    }
+}
+
+impl BucketedVariant for Variant {
+    fn is_experimental(&self) -> bool {
+        self.variant_type == VariantType::Experimental
+    }
+
+    fn awaiting_ramp(&self) -> bool {
+        self.awaiting_ramp
+    }
 }
 
 pub type Experiments = Vec<Experiment>;
@@ -56,3 +104,36 @@ pub(crate) struct ListExperimentsResponse {
     pub(crate) total_pages: i64,
     pub(crate) data: Experiments,
 }
+
+#[derive(Deserialize)]
+pub(crate) struct TenantSettings {
+    pub(crate) holdout_percentage: u8,
+}
+
+/// Holdouts a unit is in. Units in the global holdout get no variant of any
+/// experiment, the others none of the experiments they are held out of.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct HoldoutStatus {
+    pub global: bool,
+    /// Id to report the exposures and conversions of the unit under in the
+    /// results of the global holdout, `0-holdout` in it and `0-exposed`
+    /// outside of it
+    pub global_variant_id: String,
+    /// Ids to report the exposures of the unit under, one per experiment it
+    /// is held out of
+    pub variant_ids: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct ExposureEvent {
+    pub(crate) experiment_id: String,
+    pub(crate) variant_id: String,
+    pub(crate) unit_id: String,
+    pub(crate) event_type: &'static str,
+    pub(crate) timestamp: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct ExposureEventsRequest {
+    pub(crate) events: Vec<ExposureEvent>,
+}
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS experiments_webhook ON public.experiments;
+DROP FUNCTION IF EXISTS public.enqueue_experiment_webhook_event();
+DROP TABLE IF EXISTS public.webhook_outbox;
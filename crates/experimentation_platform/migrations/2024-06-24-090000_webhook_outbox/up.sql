@@ -0,0 +1,35 @@
+-- Your SQL goes here
+-- Name: webhook_outbox; Type: TABLE; Schema: public; Owner: -
+--
+-- Events are written by triggers in the transaction making the change, and
+-- delivered to the webhooks registered in the CAC schema of the tenant.
+--
+CREATE TABLE IF NOT EXISTS public.webhook_outbox (
+    id bigserial PRIMARY KEY,
+    event text NOT NULL,
+    payload json NOT NULL,
+    created_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+--
+-- Name: enqueue_experiment_webhook_event(); Type: FUNCTION; Schema: public; Owner: -
+--
+CREATE OR REPLACE FUNCTION public.enqueue_experiment_webhook_event() RETURNS trigger
+    LANGUAGE plpgsql
+    AS $$
+DECLARE
+    event TEXT;
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        event := 'experiment.created';
+    ELSIF NEW.status = 'CONCLUDED' AND OLD.status <> 'CONCLUDED' THEN
+        event := 'experiment.concluded';
+    ELSIF NEW.traffic_percentage <> OLD.traffic_percentage OR NEW.status <> OLD.status THEN
+        event := 'experiment.ramped';
+    ELSE
+        RETURN NULL;
+    END IF;
+    INSERT INTO public.webhook_outbox (event, payload) VALUES (event, row_to_json(NEW));
+    RETURN NULL;
+END;
+$$;
+CREATE TRIGGER experiments_webhook AFTER INSERT OR UPDATE ON public.experiments FOR EACH ROW EXECUTE FUNCTION public.enqueue_experiment_webhook_event();
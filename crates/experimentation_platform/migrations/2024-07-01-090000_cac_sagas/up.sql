@@ -0,0 +1,24 @@
+-- Your SQL goes here
+-- Name: cac_sagas; Type: TABLE; Schema: public; Owner: -
+--
+-- CAC writes of an experiment action, recorded before they are sent so a
+-- failed action can be finished or compensated later.
+--
+CREATE TABLE IF NOT EXISTS public.cac_sagas (
+    id uuid DEFAULT uuid_generate_v4() PRIMARY KEY,
+    experiment_id bigint NOT NULL,
+    kind text NOT NULL CHECK (kind IN ('create', 'conclude', 'update_overrides')),
+    status text NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'applied', 'compensated', 'failed')),
+    plan json NOT NULL,
+    compensation json NOT NULL,
+    payload json,
+    attempts integer NOT NULL DEFAULT 0,
+    next_attempt_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    last_error text,
+    created_by text NOT NULL,
+    request_id text,
+    created_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    last_modified_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+CREATE INDEX IF NOT EXISTS cac_sagas_pending_index ON public.cac_sagas (next_attempt_at) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS cac_sagas_experiment_index ON public.cac_sagas (experiment_id);
@@ -0,0 +1,19 @@
+-- Your SQL goes here
+-- Name: experiment_metrics; Type: TABLE; Schema: public; Owner: -
+--
+-- Events reported for the variants of experiments, aggregated per variant,
+-- event type and metric as they are ingested. Exposures have an empty metric.
+--
+CREATE TABLE IF NOT EXISTS public.experiment_metrics (
+    experiment_id bigint NOT NULL,
+    variant_id text NOT NULL,
+    event_type text NOT NULL CHECK (event_type IN ('exposure', 'conversion')),
+    metric text NOT NULL DEFAULT '',
+    event_count bigint NOT NULL DEFAULT 0,
+    value_count bigint NOT NULL DEFAULT 0,
+    value_sum double precision NOT NULL DEFAULT 0,
+    value_sum_squares double precision NOT NULL DEFAULT 0,
+    first_event_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    last_event_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY (experiment_id, variant_id, event_type, metric)
+);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS public.experiments_layer_index;
+ALTER TABLE public.experiments DROP COLUMN IF EXISTS slice_start;
+ALTER TABLE public.experiments DROP COLUMN IF EXISTS layer;
+DROP TABLE IF EXISTS public.layers;
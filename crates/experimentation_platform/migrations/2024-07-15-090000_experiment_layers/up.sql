@@ -0,0 +1,26 @@
+-- Your SQL goes here
+-- Name: layers; Type: TABLE; Schema: public; Owner: -
+--
+-- Experiments in a layer get disjoint slices of the first traffic_allocation
+-- percent of traffic, so a user is in at most one of them.
+--
+CREATE TABLE IF NOT EXISTS public.layers (
+    name text PRIMARY KEY,
+    description text NOT NULL DEFAULT '',
+    traffic_allocation integer NOT NULL CHECK (traffic_allocation BETWEEN 0 AND 100),
+    created_by text NOT NULL,
+    created_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    last_modified_by text NOT NULL,
+    last_modified_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+--
+-- Name: experiments; Type: TABLE; Schema: public; Owner: -
+--
+-- An experiment serves the traffic from slice_start on, the slice is as wide
+-- as traffic_percentage times the number of variants.
+--
+ALTER TABLE public.experiments ADD COLUMN IF NOT EXISTS layer text REFERENCES public.layers (name) ON DELETE SET NULL;
+ALTER TABLE public.experiments ADD COLUMN IF NOT EXISTS slice_start integer NOT NULL DEFAULT 0;
+CREATE INDEX IF NOT EXISTS experiments_layer_index ON public.experiments (layer) WHERE layer IS NOT NULL;
+DROP TRIGGER IF EXISTS layers_audit ON public.layers;
+CREATE TRIGGER layers_audit AFTER INSERT OR DELETE OR UPDATE ON public.layers FOR EACH ROW EXECUTE FUNCTION public.event_logger();
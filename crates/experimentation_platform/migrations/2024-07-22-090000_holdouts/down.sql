@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE public.experiments DROP COLUMN IF EXISTS holdout_percentage;
+DROP TABLE IF EXISTS public.tenant_settings;
@@ -0,0 +1,22 @@
+-- Your SQL goes here
+-- Name: tenant_settings; Type: TABLE; Schema: public; Owner: -
+--
+-- Settings of the tenant, a single row. Units tossed into the last
+-- holdout_percentage buckets are held out of every experiment.
+--
+CREATE TABLE IF NOT EXISTS public.tenant_settings (
+    id boolean PRIMARY KEY DEFAULT true CHECK (id),
+    holdout_percentage integer NOT NULL DEFAULT 0 CHECK (holdout_percentage BETWEEN 0 AND 50),
+    last_modified_by text NOT NULL DEFAULT 'superposition',
+    last_modified_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+INSERT INTO public.tenant_settings (id) VALUES (true) ON CONFLICT DO NOTHING;
+DROP TRIGGER IF EXISTS tenant_settings_audit ON public.tenant_settings;
+CREATE TRIGGER tenant_settings_audit AFTER INSERT OR DELETE OR UPDATE ON public.tenant_settings FOR EACH ROW EXECUTE FUNCTION public.event_logger();
+--
+-- Name: experiments; Type: TABLE; Schema: public; Owner: -
+--
+-- Units in the last holdout_percentage buckets an experiment can serve are
+-- held out of it.
+--
+ALTER TABLE public.experiments ADD COLUMN IF NOT EXISTS holdout_percentage integer NOT NULL DEFAULT 0 CHECK (holdout_percentage BETWEEN 0 AND 50);
@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS public.experiment_approvals;
+ALTER TABLE public.tenant_settings DROP COLUMN IF EXISTS required_approvers;
+ALTER TABLE public.tenant_settings DROP COLUMN IF EXISTS max_traffic_percentage;
+ALTER TABLE public.tenant_settings DROP COLUMN IF EXISTS max_variants;
+ALTER TABLE public.tenant_settings DROP COLUMN IF EXISTS allow_same_keys_non_overlapping_ctx;
+ALTER TABLE public.tenant_settings DROP COLUMN IF EXISTS allow_diff_keys_overlapping_ctx;
+ALTER TABLE public.tenant_settings DROP COLUMN IF EXISTS allow_same_keys_overlapping_ctx;
@@ -0,0 +1,26 @@
+-- Your SQL goes here
+-- Name: tenant_settings; Type: TABLE; Schema: public; Owner: -
+--
+-- Experimentation policy of the tenant. Overlap flags left NULL follow the
+-- deployment's ALLOW_*_CTX variables, limits left NULL don't apply.
+--
+ALTER TABLE public.tenant_settings ADD COLUMN IF NOT EXISTS allow_same_keys_overlapping_ctx boolean;
+ALTER TABLE public.tenant_settings ADD COLUMN IF NOT EXISTS allow_diff_keys_overlapping_ctx boolean;
+ALTER TABLE public.tenant_settings ADD COLUMN IF NOT EXISTS allow_same_keys_non_overlapping_ctx boolean;
+ALTER TABLE public.tenant_settings ADD COLUMN IF NOT EXISTS max_variants integer CHECK (max_variants >= 2);
+ALTER TABLE public.tenant_settings ADD COLUMN IF NOT EXISTS max_traffic_percentage integer CHECK (max_traffic_percentage BETWEEN 1 AND 100);
+ALTER TABLE public.tenant_settings ADD COLUMN IF NOT EXISTS required_approvers integer NOT NULL DEFAULT 0 CHECK (required_approvers >= 0);
+--
+-- Name: experiment_approvals; Type: TABLE; Schema: public; Owner: -
+--
+-- An experiment serves traffic once required_approvers users other than its
+-- creator approved it.
+--
+CREATE TABLE IF NOT EXISTS public.experiment_approvals (
+    experiment_id bigint NOT NULL REFERENCES public.experiments (id) ON DELETE CASCADE,
+    approved_by text NOT NULL,
+    approved_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY (experiment_id, approved_by)
+);
+DROP TRIGGER IF EXISTS experiment_approvals_audit ON public.experiment_approvals;
+CREATE TRIGGER experiment_approvals_audit AFTER INSERT OR DELETE OR UPDATE ON public.experiment_approvals FOR EACH ROW EXECUTE FUNCTION public.event_logger();
@@ -0,0 +1,40 @@
+-- Your SQL goes here
+-- Name: experiment_events; Type: TABLE; Schema: public; Owner: -
+--
+-- Lifecycle of an experiment, one row per change with who made it and why.
+--
+CREATE TABLE IF NOT EXISTS public.experiment_events (
+    id bigserial PRIMARY KEY,
+    experiment_id bigint NOT NULL REFERENCES public.experiments (id) ON DELETE CASCADE,
+    event_type text NOT NULL,
+    details json NOT NULL DEFAULT '{}',
+    reason text,
+    created_by text NOT NULL,
+    created_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+CREATE INDEX IF NOT EXISTS experiment_events_experiment_id_index ON public.experiment_events (experiment_id, created_at);
+--
+-- Backfill of the experiments created before the history: their creation,
+-- the ramps still in event_log and their conclusion.
+--
+INSERT INTO public.experiment_events (experiment_id, event_type, details, created_by, created_at)
+SELECT id, 'created', json_build_object('variants', (SELECT json_agg(variant ->> 'id') FROM json_array_elements(variants) AS variant)), created_by, created_at
+FROM public.experiments
+WHERE NOT EXISTS (SELECT 1 FROM public.experiment_events);
+INSERT INTO public.experiment_events (experiment_id, event_type, details, created_by, created_at)
+SELECT experiments.id,
+    CASE WHEN (log.new_data ->> 'traffic_percentage')::integer = 0 THEN 'paused' ELSE 'ramped' END,
+    json_build_object('from', (log.original_data ->> 'traffic_percentage')::integer, 'to', (log.new_data ->> 'traffic_percentage')::integer),
+    log.user_name,
+    log."timestamp"::timestamptz
+FROM public.event_log AS log
+JOIN public.experiments ON experiments.id = (log.new_data ->> 'id')::bigint
+WHERE log.table_name = 'experiments'
+    AND log.action = 'UPDATE'
+    AND (log.original_data ->> 'traffic_percentage') IS DISTINCT FROM (log.new_data ->> 'traffic_percentage')
+    AND NOT EXISTS (SELECT 1 FROM public.experiment_events WHERE event_type <> 'created');
+INSERT INTO public.experiment_events (experiment_id, event_type, details, created_by, created_at)
+SELECT id, 'concluded', json_build_object('chosen_variant', chosen_variant), last_modified_by, last_modified
+FROM public.experiments
+WHERE status = 'CONCLUDED'
+    AND NOT EXISTS (SELECT 1 FROM public.experiment_events WHERE event_type = 'concluded');
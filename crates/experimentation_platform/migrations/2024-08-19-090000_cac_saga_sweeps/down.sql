@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS public.cac_sagas_unswept_index;
+ALTER TABLE public.cac_sagas DROP COLUMN IF EXISTS swept_at;
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+--
+-- When the experiment contexts a failed saga may have left in CAC were
+-- removed by the reconciler.
+--
+ALTER TABLE public.cac_sagas ADD COLUMN IF NOT EXISTS swept_at timestamp with time zone;
+CREATE INDEX IF NOT EXISTS cac_sagas_unswept_index ON public.cac_sagas (last_modified_at) WHERE status = 'failed' AND swept_at IS NULL;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS public.experiment_conversions;
+DROP TABLE IF EXISTS public.experiment_exposures;
@@ -0,0 +1,37 @@
+-- Your SQL goes here
+-- Name: experiment_exposures; Type: TABLE; Schema: public; Owner: -
+--
+-- Units, like users, served the variants of experiments. A unit is counted
+-- once however often it is served the variant.
+--
+CREATE TABLE IF NOT EXISTS public.experiment_exposures (
+    experiment_id bigint NOT NULL,
+    variant_id text NOT NULL,
+    unit_id text NOT NULL,
+    first_exposed_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    last_exposed_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY (experiment_id, variant_id, unit_id)
+);
+--
+-- Name: experiment_conversions; Type: TABLE; Schema: public; Owner: -
+--
+-- Conversions of a unit per metric, with the sum of their values. Results
+-- only count the conversions of units exposed to the variant.
+--
+CREATE TABLE IF NOT EXISTS public.experiment_conversions (
+    experiment_id bigint NOT NULL,
+    variant_id text NOT NULL,
+    metric text NOT NULL,
+    unit_id text NOT NULL,
+    event_count bigint NOT NULL DEFAULT 0,
+    value_count bigint NOT NULL DEFAULT 0,
+    value_sum double precision NOT NULL DEFAULT 0,
+    first_event_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    last_event_at timestamp with time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY (experiment_id, variant_id, metric, unit_id)
+);
+--
+-- Per event aggregates can't tell units apart, they are replaced by the
+-- tables above.
+--
+DROP TABLE IF EXISTS public.experiment_metrics;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE public.cac_sagas DROP CONSTRAINT IF EXISTS cac_sagas_kind_check;
+ALTER TABLE public.cac_sagas ADD CONSTRAINT cac_sagas_kind_check CHECK (kind IN ('create', 'conclude', 'update_overrides'));
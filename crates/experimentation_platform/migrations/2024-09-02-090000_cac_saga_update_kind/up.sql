@@ -0,0 +1,6 @@
+-- Your SQL goes here
+--
+-- Sagas of experiment updates which change the variants or their context.
+--
+ALTER TABLE public.cac_sagas DROP CONSTRAINT IF EXISTS cac_sagas_kind_check;
+ALTER TABLE public.cac_sagas ADD CONSTRAINT cac_sagas_kind_check CHECK (kind IN ('create', 'conclude', 'update_overrides', 'update'));
@@ -1 +1,3 @@
 pub mod experiments;
+pub mod layers;
+pub mod settings;
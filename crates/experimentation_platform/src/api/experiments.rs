@@ -1,4 +1,10 @@
+pub mod bucketing;
+pub mod cac;
 pub mod handlers;
 pub mod helpers;
+pub mod history;
+pub mod metrics;
+pub mod saga;
+pub mod statistics;
 pub mod types;
 pub use handlers::endpoints;
@@ -0,0 +1,131 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use chrono::{DateTime, Utc};
+use diesel::{dsl::max, ExpressionMethods, PgConnection, QueryDsl, RunQueryDsl};
+pub use superposition_bucketing::{
+    choose_variant, globally_held_out, held_out_of, layer_bucket, toss_of,
+};
+use superposition_bucketing::{BucketedExperiment, BucketedVariant};
+use superposition_macros::unexpected_error;
+use superposition_types::result as superposition;
+
+use super::types::{Variant, VariantType};
+use crate::{
+    api::settings::helpers::{experiment_buckets, fetch_settings},
+    db::{
+        models::{Experiment, ExperimentStatusType},
+        schema::experiments,
+    },
+};
+
+impl BucketedExperiment for Experiment {
+    fn layer(&self) -> Option<&str> {
+        self.layer.as_deref()
+    }
+
+    fn traffic_percentage(&self) -> i32 {
+        self.traffic_percentage
+    }
+
+    fn holdout_percentage(&self) -> i32 {
+        self.holdout_percentage
+    }
+
+    fn slice_start(&self) -> i32 {
+        self.slice_start
+    }
+}
+
+impl BucketedVariant for Variant {
+    fn is_experimental(&self) -> bool {
+        self.variant_type == VariantType::EXPERIMENTAL
+    }
+
+    fn awaiting_ramp(&self) -> bool {
+        self.awaiting_ramp
+    }
+}
+
+/// Experiments clients bucket users into, with their variants, oldest first.
+pub fn bucketed_experiments(
+    conn: &mut PgConnection,
+) -> superposition::Result<Vec<(Experiment, Vec<Variant>)>> {
+    experiments::table
+        .filter(experiments::status.eq_any([
+            ExperimentStatusType::CREATED,
+            ExperimentStatusType::INPROGRESS,
+        ]))
+        .order(experiments::id.asc())
+        .load::<Experiment>(conn)?
+        .into_iter()
+        .map(|experiment| {
+            let variants =
+                serde_json::from_value::<Vec<Variant>>(experiment.variants.clone())
+                    .map_err(|err| {
+                        log::error!(
+                            "failed to parse variants of {}: {err}",
+                            experiment.id
+                        );
+                        unexpected_error!(
+                            "Something went wrong, failed to bucket experiments"
+                        )
+                    })?;
+            Ok((experiment, variants))
+        })
+        .collect()
+}
+
+/// Bucketed experiments of a tenant and the buckets they serve, as of the
+/// last modification of its experiments and settings.
+pub struct BucketedExperiments {
+    last_modified: (Option<DateTime<Utc>>, DateTime<Utc>),
+    pub buckets: i32,
+    pub experiments: Vec<(Experiment, Vec<Variant>)>,
+}
+
+/// Bucketed experiments of every tenant, loaded again once an experiment or
+/// the settings of the tenant are modified.
+#[derive(Default)]
+pub struct BucketingCache(RwLock<HashMap<String, Arc<BucketedExperiments>>>);
+
+impl BucketingCache {
+    /// Bucketed experiments of the tenant whose experiments live in
+    /// `namespace`.
+    pub fn get(
+        &self,
+        conn: &mut PgConnection,
+        namespace: &str,
+    ) -> superposition::Result<Arc<BucketedExperiments>> {
+        // read before the experiments, a change in between only loads them
+        // again on the next call
+        let settings = fetch_settings(conn, false)?;
+        let last_modified = (
+            experiments::table
+                .select(max(experiments::last_modified))
+                .first::<Option<DateTime<Utc>>>(conn)?,
+            settings.last_modified_at,
+        );
+        let cached = self
+            .0
+            .read()
+            .map_err(|_| log::error!("bucketing cache lock poisoned"))
+            .ok()
+            .and_then(|cache| cache.get(namespace).cloned())
+            .filter(|cached| cached.last_modified == last_modified);
+        if let Some(cached) = cached {
+            return Ok(cached);
+        }
+        let loaded = Arc::new(BucketedExperiments {
+            last_modified,
+            buckets: experiment_buckets(settings.holdout_percentage),
+            experiments: bucketed_experiments(conn)?,
+        });
+        if let Ok(mut cache) = self.0.write() {
+            cache.insert(namespace.to_string(), loaded.clone());
+        }
+        Ok(loaded)
+    }
+}
@@ -0,0 +1,312 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use reqwest::{header::AUTHORIZATION, Method, RequestBuilder, Response, StatusCode};
+use serde_json::{json, Map, Value};
+use service_utils::{
+    db::pgschema_manager::PgSchemaConnection, middlewares::request_id::REQUEST_ID_HEADER,
+    rbac::Authorizer,
+};
+use superposition_macros::{response_error, unexpected_error};
+use superposition_types::{result as superposition, User};
+
+use super::{
+    saga::CacPlan,
+    types::{CacDefaultConfig, ContextAction, ContextBulkResponse},
+};
+
+/// Longest a single call to CAC may take, sagas are leased for longer than
+/// the calls of an action can take together.
+pub const CAC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Why a call to CAC failed. CAC applies bulk operations in one transaction,
+/// so nothing of a rejected call was applied, while the outcome of a call
+/// which got no answer is unknown.
+#[derive(Debug)]
+pub enum CacError {
+    Rejected(superposition::AppError),
+    Unavailable(String),
+}
+
+impl From<CacError> for superposition::AppError {
+    fn from(error: CacError) -> Self {
+        match error {
+            CacError::Rejected(error) => error,
+            CacError::Unavailable(error) => {
+                log::error!("call to CAC failed with error: {error}");
+                unexpected_error!("Something went wrong.")
+            }
+        }
+    }
+}
+
+pub type BulkResult = Result<(Vec<ContextBulkResponse>, Option<String>), CacError>;
+
+/// Who CAC is called for. Calls made for a request carry the user's
+/// authorization, the reconciler uses the one it is configured with.
+#[derive(Clone)]
+pub struct CacCaller {
+    pub tenant: Option<String>,
+    pub config_tags: Option<String>,
+    pub request_id: String,
+    pub user: User,
+    /// Sent to CAC over HTTP
+    pub authorization: Option<String>,
+    /// Permissions in process backends check the writes against, those of the
+    /// request's user
+    pub authorizer: Authorizer,
+}
+
+/// The CAC writes and reads experiments need. `superposition` serves both
+/// services and calls CAC in process, split deployments go over HTTP.
+#[async_trait]
+pub trait CacBackend: Send + Sync {
+    /// Applies `operations` in one CAC transaction, returning the result of
+    /// each operation and the config version it created.
+    async fn bulk_operations(
+        &self,
+        caller: &CacCaller,
+        operations: &[ContextAction],
+    ) -> BulkResult;
+
+    async fn put_default_config(
+        &self,
+        caller: &CacCaller,
+        key: &str,
+        value: &Value,
+    ) -> Result<(), CacError>;
+
+    /// Current values of the default configs in `keys`.
+    async fn default_config_values(
+        &self,
+        caller: &CacCaller,
+        keys: &[String],
+    ) -> Result<Map<String, Value>, CacError>;
+
+    async fn context_exists(
+        &self,
+        caller: &CacCaller,
+        context_id: &str,
+    ) -> Result<bool, CacError>;
+
+    /// Whether the backend can make its writes in a transaction of the
+    /// experimentation schema, see `apply_in_transaction`.
+    fn joins_transactions(&self) -> bool {
+        false
+    }
+
+    /// Applies `plan` in the transaction `conn` is in, so the CAC writes and
+    /// the experiment change commit together. Only called on backends which
+    /// join transactions.
+    fn apply_in_transaction(
+        &self,
+        _caller: &CacCaller,
+        _conn: &mut PgSchemaConnection,
+        _plan: &CacPlan,
+    ) -> BulkResult {
+        Err(CacError::Unavailable(
+            "the CAC backend does not join transactions".to_string(),
+        ))
+    }
+}
+
+async fn check_response(
+    response: Result<Response, reqwest::Error>,
+) -> Result<Response, CacError> {
+    match response {
+        Ok(response) if response.status().is_success() => Ok(response),
+        Ok(response) if response.status().is_client_error() => {
+            let status_code = response.status();
+            let message = response
+                .json::<superposition::ErrorResponse>()
+                .await
+                .map(|error_response| error_response.message)
+                .unwrap_or_else(|_| status_code.to_string());
+            log::error!("http call to CAC failed with {status_code}: {message}");
+            Err(CacError::Rejected(response_error!(status_code, message)))
+        }
+        Ok(response) => Err(CacError::Unavailable(format!(
+            "CAC responded with {}",
+            response.status()
+        ))),
+        Err(error) => Err(CacError::Unavailable(error.to_string())),
+    }
+}
+
+/// Reaches CAC over HTTP, for deployments where it runs separately.
+pub struct HttpCacBackend {
+    client: reqwest::Client,
+    host: String,
+}
+
+impl HttpCacBackend {
+    pub fn new(host: String) -> Self {
+        HttpCacBackend {
+            client: reqwest::Client::new(),
+            host,
+        }
+    }
+
+    fn request(&self, caller: &CacCaller, method: Method, path: &str) -> RequestBuilder {
+        let mut builder = self
+            .client
+            .request(method, format!("{}{path}", self.host))
+            .timeout(CAC_TIMEOUT)
+            // CAC records its writes under the same request in the audit log
+            .header(REQUEST_ID_HEADER, &caller.request_id);
+        if let Some(tenant) = caller.tenant.as_ref() {
+            builder = builder.header("x-tenant", tenant);
+        }
+        if let Some(config_tags) = caller.config_tags.as_ref() {
+            builder = builder.header("x-config-tags", config_tags);
+        }
+        if let Some(authorization) = caller.authorization.as_ref() {
+            builder = builder.header(AUTHORIZATION, authorization);
+        }
+        builder
+    }
+}
+
+#[async_trait]
+impl CacBackend for HttpCacBackend {
+    async fn bulk_operations(
+        &self,
+        caller: &CacCaller,
+        operations: &[ContextAction],
+    ) -> BulkResult {
+        let response = self
+            .request(caller, Method::PUT, "/context/bulk-operations")
+            .json(operations)
+            .send()
+            .await;
+        let response = check_response(response).await?;
+        let config_version = response
+            .headers()
+            .get("x-config-version")
+            .and_then(|val| val.to_str().ok())
+            .map(String::from);
+        let bulk_response =
+            response
+                .json::<Vec<ContextBulkResponse>>()
+                .await
+                .map_err(|err| {
+                    CacError::Unavailable(format!("failed to parse bulk response: {err}"))
+                })?;
+        Ok((bulk_response, config_version))
+    }
+
+    async fn put_default_config(
+        &self,
+        caller: &CacCaller,
+        key: &str,
+        value: &Value,
+    ) -> Result<(), CacError> {
+        let response = self
+            .request(caller, Method::PUT, &format!("/default-config/{key}"))
+            .json(&json!({ "value": value }))
+            .send()
+            .await;
+        check_response(response).await?;
+        Ok(())
+    }
+
+    async fn default_config_values(
+        &self,
+        caller: &CacCaller,
+        keys: &[String],
+    ) -> Result<Map<String, Value>, CacError> {
+        let response = self
+            .request(caller, Method::GET, "/default-config")
+            .send()
+            .await;
+        let default_configs = check_response(response)
+            .await?
+            .json::<Vec<CacDefaultConfig>>()
+            .await
+            .map_err(|err| {
+                CacError::Unavailable(format!("failed to parse default configs: {err}"))
+            })?;
+        Ok(default_configs
+            .into_iter()
+            .filter(|default_config| keys.contains(&default_config.key))
+            .map(|default_config| (default_config.key, default_config.value))
+            .collect())
+    }
+
+    async fn context_exists(
+        &self,
+        caller: &CacCaller,
+        context_id: &str,
+    ) -> Result<bool, CacError> {
+        let response = self
+            .request(caller, Method::GET, &format!("/context/{context_id}"))
+            .send()
+            .await;
+        match response {
+            Ok(response) if response.status() == StatusCode::NOT_FOUND => Ok(false),
+            response => check_response(response).await.map(|_| true),
+        }
+    }
+}
+
+/// A backend bound to the caller of an experiment action.
+#[derive(Clone)]
+pub struct CacClient {
+    backend: Arc<dyn CacBackend>,
+    caller: CacCaller,
+}
+
+impl CacClient {
+    pub fn new(backend: Arc<dyn CacBackend>, caller: CacCaller) -> Self {
+        CacClient { backend, caller }
+    }
+
+    /// The same client attributing its calls to another request and user.
+    pub fn for_request(&self, request_id: String, user: User) -> Self {
+        CacClient {
+            backend: self.backend.clone(),
+            caller: CacCaller {
+                request_id,
+                user,
+                ..self.caller.clone()
+            },
+        }
+    }
+
+    pub async fn bulk_operations(&self, operations: &[ContextAction]) -> BulkResult {
+        self.backend.bulk_operations(&self.caller, operations).await
+    }
+
+    pub async fn put_default_config(
+        &self,
+        key: &str,
+        value: &Value,
+    ) -> Result<(), CacError> {
+        self.backend
+            .put_default_config(&self.caller, key, value)
+            .await
+    }
+
+    pub async fn default_config_values(
+        &self,
+        keys: &[String],
+    ) -> Result<Map<String, Value>, CacError> {
+        self.backend.default_config_values(&self.caller, keys).await
+    }
+
+    pub async fn context_exists(&self, context_id: &str) -> Result<bool, CacError> {
+        self.backend.context_exists(&self.caller, context_id).await
+    }
+
+    pub fn joins_transactions(&self) -> bool {
+        self.backend.joins_transactions()
+    }
+
+    pub fn apply_in_transaction(
+        &self,
+        conn: &mut PgSchemaConnection,
+        plan: &CacPlan,
+    ) -> BulkResult {
+        self.backend.apply_in_transaction(&self.caller, conn, plan)
+    }
+}
@@ -1,78 +1,121 @@
 use std::collections::{HashMap, HashSet};
 
-use actix_http::header::{HeaderMap, HeaderName, HeaderValue};
 use actix_web::{
     get, patch, post, put,
     web::{self, Data, Json, Query},
     HttpRequest, HttpResponse, HttpResponseBuilder, Scope,
 };
-use anyhow::anyhow;
 use chrono::{DateTime, Duration, NaiveDateTime, Utc};
 use diesel::{
+    dsl::sql,
     r2d2::{ConnectionManager, PooledConnection},
-    ExpressionMethods, PgConnection, QueryDsl, RunQueryDsl,
+    sql_types::{Array, Bool, Jsonb, Text},
+    ExpressionMethods, PgArrayExpressionMethods, PgConnection, PgTextExpressionMethods,
+    QueryDsl, RunQueryDsl,
 };
 
-use service_utils::helpers::{construct_request_headers, generate_snowflake_id, request};
+use service_utils::helpers::generate_snowflake_id;
 
-use reqwest::{Method, Response, StatusCode};
-use service_utils::service::types::{
-    AppHeader, AppState, CustomHeaders, DbConnection, Tenant,
+use service_utils::{
+    db::audit::{field_changes, AuditActor},
+    rbac::{Action, Authorizer, Resource},
+    service::types::{AppHeader, AppState, CustomHeaders, DbConnection, Tenant},
 };
-use superposition_macros::{bad_argument, response_error, unexpected_error};
+use superposition_macros::{bad_argument, unexpected_error};
 use superposition_types::{result as superposition, SuperpositionUser, User};
 
 use super::{
+    cac::{CacBackend, CacCaller, CacClient},
     helpers::{
         add_variant_dimension_to_ctx, check_variant_types,
-        check_variants_override_coverage, extract_override_keys, validate_experiment,
+        check_variants_override_coverage, dimension_filter_vars, experiment_approvers,
+        extract_override_keys, merge_variants, prefix_patterns, validate_experiment,
         validate_override_keys,
     },
+    history::{load_history, ramp_event, record_event, steady_traffic, traffic_timeline},
+    metrics::{
+        aggregate_events, check_event_variants, expected_allocation,
+        global_holdout_results, holdout_results, load_metrics, record_metrics,
+        variant_results, GLOBAL_HOLDOUT_EXPERIMENT_ID,
+    },
+    saga::{
+        apply_atomically, apply_experiment_change, apply_plan, assign_variant_contexts,
+        begin_saga, compensate_create, fail_saga, settle_applied, CacPlan,
+        ExperimentChange, SagaKind,
+    },
+    statistics::{compare_with_control, sample_ratio_mismatch, CONFIDENCE_LEVEL},
     types::{
-        AuditQueryFilters, ConcludeExperimentRequest, ContextAction, ContextBulkResponse,
-        ContextMoveReq, ContextPutReq, ExperimentCreateRequest, ExperimentCreateResponse,
-        ExperimentResponse, ExperimentsResponse, ListFilters, OverrideKeysUpdateRequest,
-        RampRequest, Variant,
+        AuditEntity, AuditHistoryEntry, AuditQueryFilters, ConcludeExperimentRequest,
+        ContextAction, ContextMoveReq, ContextPutReq, ExperimentApprovals,
+        ExperimentCreateRequest, ExperimentCreateResponse, ExperimentEventType,
+        ExperimentHistory, ExperimentHistoryEntry, ExperimentResponse, ExperimentResults,
+        ExperimentSortOn, ExperimentUpdateRequest, ExperimentsResponse,
+        GlobalHoldoutResults, ListFilters, MetricEventsRequest, MetricEventsResponse,
+        OverrideKeysUpdateRequest, RampRequest, SortOrder, Variant,
     },
 };
 
 use crate::{
-    api::experiments::helpers::validate_context,
-    db::models::{EventLog, Experiment, ExperimentStatusType},
-    db::schema::{event_log::dsl as event_log, experiments::dsl as experiments},
+    api::{
+        experiments::helpers::validate_context,
+        layers::helpers::{find_layer, place_in_layer, slice_size},
+        settings::helpers::{
+            check_variant_count, experimentation_flags, fetch_settings,
+            max_traffic_percentage, validate_holdout_percentage,
+        },
+    },
+    db::models::{EventLog, Experiment, ExperimentApproval, ExperimentStatusType},
+    db::schema::{
+        event_log::dsl as event_log, experiment_approvals,
+        experiments::dsl as experiments,
+    },
 };
 
 use serde_json::{json, Map, Value};
 
+/// Operand lists of the context's conditions which name the filtered
+/// dimension and hold its value, on their own or in a list.
+const DIMENSION_FILTER_PATH: &str = "$.** ? (exists (@[*] ? (@.var == $dimension)) && exists (@[*] ? (@ == $value || @ == $raw)))";
+
 pub fn endpoints(scope: Scope) -> Scope {
     scope
         .service(get_audit_logs)
+        .service(get_entity_history)
         .service(create)
         .service(conclude_handler)
         .service(list_experiments)
         .service(get_experiment_handler)
         .service(ramp)
+        .service(approve)
         .service(update_overrides)
+        .service(update)
+        .service(ingest_events)
+        .service(get_global_holdout_results)
+        .service(get_results)
+        .service(get_history)
 }
 
-fn construct_header_map(
-    tenant: &str,
+fn cac_client(
+    cac_backend: &Data<dyn CacBackend>,
+    tenant: &Tenant,
     config_tags: Option<String>,
-) -> superposition::Result<HeaderMap> {
-    let mut headers = HeaderMap::new();
-    let tenant_val = HeaderValue::from_str(tenant).map_err(|err| {
-        log::error!("failed to set header: {}", err);
-        unexpected_error!("Something went wrong")
-    })?;
-    headers.insert(HeaderName::from_static("x-tenant"), tenant_val);
-    if let Some(val) = config_tags {
-        let tag_val = HeaderValue::from_str(val.as_str()).map_err(|err| {
-            log::error!("failed to set header: {}", err);
-            unexpected_error!("Something went wrong")
-        })?;
-        headers.insert(HeaderName::from_static("x-config-tags"), tag_val);
-    }
-    Ok(headers)
+    user: &User,
+    actor: &AuditActor,
+    auth: &Authorizer,
+) -> CacClient {
+    let caller = CacCaller {
+        tenant: Some(tenant.as_str().to_string()),
+        config_tags,
+        request_id: actor.request_id.clone(),
+        user: user.clone(),
+        authorization: Some(format!(
+            "{} {}",
+            user.get_auth_type(),
+            user.get_auth_token()
+        )),
+        authorizer: auth.clone(),
+    };
+    CacClient::new(cac_backend.clone().into_inner(), caller)
 }
 
 fn add_config_version_to_header(
@@ -84,66 +127,17 @@ fn add_config_version_to_header(
     }
 }
 
-async fn parse_error_response(
-    response: reqwest::Response,
-) -> superposition::Result<(StatusCode, superposition::ErrorResponse)> {
-    let status_code = response.status();
-    let error_response = response
-        .json::<superposition::ErrorResponse>()
-        .await
-        .map_err(|err: reqwest::Error| {
-            log::error!("failed to parse error response: {}", err);
-            unexpected_error!("Something went wrong")
-        })?;
-    log::error!("http call to CAC failed with err {:?}", error_response);
-
-    Ok((status_code, error_response))
-}
-
-async fn process_cac_http_response(
-    response: Result<Response, reqwest::Error>,
-) -> superposition::Result<(Vec<ContextBulkResponse>, Option<String>)> {
-    let internal_server_error = unexpected_error!("Something went wrong.");
-    match response {
-        Ok(res) if res.status().is_success() => {
-            let config_version = res
-                .headers()
-                .get("x-config-version")
-                .and_then(|val| val.to_str().map_or(None, |v| Some(v.to_string())));
-            let bulk_resp =
-                res.json::<Vec<ContextBulkResponse>>()
-                    .await
-                    .map_err(|err| {
-                        log::error!("failed to parse JSON response with error: {}", err);
-                        internal_server_error
-                    })?;
-            Ok((bulk_resp, config_version))
-        }
-        Ok(res) => {
-            log::error!("http call to CAC failed with status_code {}", res.status());
-
-            if res.status().is_client_error() {
-                let (status_code, error_response) = parse_error_response(res).await?;
-                Err(response_error!(status_code, error_response.message))
-            } else {
-                Err(internal_server_error)
-            }
-        }
-        Err(err) => {
-            log::error!("reqwest failed to send request to CAC with error: {}", err);
-            Err(internal_server_error)
-        }
-    }
-}
-
 #[post("")]
 async fn create(
     state: Data<AppState>,
+    cac_backend: Data<dyn CacBackend>,
     custom_headers: CustomHeaders,
     req: web::Json<ExperimentCreateRequest>,
     db_conn: DbConnection,
     tenant: Tenant,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
 ) -> superposition::Result<HttpResponse> {
     use crate::db::schema::experiments::dsl::experiments;
     let mut variants = req.variants.to_vec();
@@ -155,6 +149,7 @@ async fn create(
     let unique_override_keys: Vec<String> = extract_override_keys(&variants[0].overrides)
         .into_iter()
         .collect();
+    auth.require_keys(Resource::Experiment, Action::Write, &unique_override_keys)?;
 
     let unique_ids_of_variants_from_req: HashSet<&str> =
         HashSet::from_iter(variants.iter().map(|v| v.id.as_str()));
@@ -184,14 +179,41 @@ async fn create(
     // validating context
     validate_context(&req.context)?;
 
-    // validating experiment against other active experiments based on permission flags
-    let flags = &state.experimentation_flags;
-    let (valid, reason) =
-        validate_experiment(&req.context, &unique_override_keys, None, flags, &mut conn)?;
+    // validating experiment against other active experiments based on the
+    // tenant's policy
+    let settings = fetch_settings(&mut conn, false)?;
+    check_variant_count(&settings, variants.len())?;
+    let flags = experimentation_flags(&settings, &state.experimentation_flags);
+    let (valid, reason) = validate_experiment(
+        &req.context,
+        &unique_override_keys,
+        None,
+        req.layer.as_deref(),
+        &flags,
+        &mut conn,
+    )?;
     if !valid {
         return Err(bad_argument!(reason));
     }
 
+    validate_holdout_percentage(req.holdout_percentage)?;
+    if req.layer.is_some() && req.holdout_percentage != 0 {
+        // the traffic of a layer none of its experiments serve is their holdout
+        return Err(bad_argument!(
+            "experiments in a layer can't have a holdout of their own"
+        ));
+    }
+
+    // experiments start without traffic, their slice is reserved when ramped
+    let slice_start = match req.layer.as_deref() {
+        Some(name) => {
+            let layer = find_layer(&mut conn, name, false)?
+                .ok_or_else(|| bad_argument!("layer {} doesn't exist", name))?;
+            place_in_layer(&mut conn, &layer, None, None, 0)?
+        }
+        None => 0,
+    };
+
     // generating snowflake id for experiment
     let experiment_id = generate_snowflake_id(&state)?;
 
@@ -221,46 +243,17 @@ async fn create(
         cac_operations.push(ContextAction::PUT(payload));
     }
 
-    // creating variants' context in CAC
-    let http_client = reqwest::Client::new();
-    let url = state.cac_host.clone() + "/context/bulk-operations";
-    let headers_map = construct_header_map(tenant.as_str(), custom_headers.config_tags)?;
-
-    // Step 1: Perform the HTTP request and handle errors
-    let response = http_client
-        .put(&url)
-        .headers(headers_map.into())
-        .header(
-            "Authorization",
-            format!("{} {}", user.get_auth_type(), user.get_auth_token()),
-        )
-        .json(&cac_operations)
-        .send()
-        .await;
-
-    // directly return an error response if not a 200 response
-    let (resp_contexts, config_version_id) = process_cac_http_response(response).await?;
-    let created_contexts = resp_contexts.into_iter().fold(Vec::new(), |mut acc, item| {
-        if let ContextBulkResponse::PUT(context) = item {
-            acc.push(context);
-        } else {
-            log::error!("Unexpected response item: {:?}", item);
-        }
-        acc
-    });
-    for i in 0..created_contexts.len() {
-        let created_context = &created_contexts[i];
-        variants[i].context_id = Some(created_context.context_id.clone());
-        variants[i].override_id = Some(created_context.override_id.clone());
-    }
-
-    // inserting experiment in db
-    let new_experiment = Experiment {
+    let plan = CacPlan {
+        default_configs: Map::new(),
+        operations: cac_operations,
+    };
+    let build_experiment = |variants: &[Variant]| Experiment {
         id: experiment_id,
         created_by: user.get_email(),
         created_at: Utc::now(),
         last_modified: Utc::now(),
         name: req.name.to_string(),
+        description: req.description.clone(),
         override_keys: unique_override_keys.to_vec(),
         traffic_percentage: 0,
         status: ExperimentStatusType::CREATED,
@@ -268,13 +261,106 @@ async fn create(
         variants: serde_json::to_value(variants).unwrap(),
         last_modified_by: user.get_email(),
         chosen_variant: None,
+        layer: req.layer.clone(),
+        slice_start,
+        holdout_percentage: req.holdout_percentage,
     };
+    let created_details = json!({
+        "context": req.context.clone(),
+        "variants": variants.iter().map(|variant| variant.id.clone()).collect::<Vec<String>>(),
+    });
+    let cac = cac_client(
+        &cac_backend,
+        &tenant,
+        custom_headers.config_tags,
+        &user,
+        &actor,
+        &auth,
+    );
+
+    // creating variants' contexts and the experiment together when CAC joins
+    // the transaction
+    let atomic = apply_atomically(
+        &mut conn,
+        &cac,
+        &plan,
+        &actor,
+        |transaction_conn, responses| {
+            let mut variants = variants.clone();
+            assign_variant_contexts(&mut variants, responses);
+            let inserted_experiment = diesel::insert_into(experiments)
+                .values(&build_experiment(&variants))
+                .get_result::<Experiment>(transaction_conn)?;
+            record_event(
+                transaction_conn,
+                experiment_id,
+                ExperimentEventType::Created,
+                created_details.clone(),
+                req.reason.clone(),
+                &user.get_email(),
+            )?;
+            Ok(inserted_experiment)
+        },
+    );
+    if let Some(result) = atomic {
+        let (inserted_experiment, config_version_id) = result?;
+        let mut http_resp = HttpResponse::Ok();
+        add_config_version_to_header(&config_version_id, &mut http_resp);
+        return Ok(http_resp.json(ExperimentCreateResponse::from(inserted_experiment)));
+    }
 
-    let mut inserted_experiments = diesel::insert_into(experiments)
-        .values(&new_experiment)
-        .get_results(&mut conn)?;
+    // recording the CAC writes before making them, so the contexts of a create
+    // which fails halfway can be found and removed
+    let saga = begin_saga(
+        &mut conn,
+        experiment_id,
+        SagaKind::Create,
+        &plan,
+        &CacPlan::default(),
+        None,
+        &actor,
+    )?;
+
+    // creating variants' context in CAC
+    let (resp_contexts, config_version_id) = match apply_plan(&cac, &plan, false).await {
+        Ok(result) => result,
+        Err(error) => {
+            return Err(
+                fail_saga(&mut conn, &cac, &saga, &CacPlan::default(), error).await,
+            )
+        }
+    };
+    assign_variant_contexts(&mut variants, resp_contexts);
 
-    let inserted_experiment: Experiment = inserted_experiments.remove(0);
+    // inserting experiment in db
+    let new_experiment = build_experiment(&variants);
+
+    let inserted = actor.transaction(&mut conn, |transaction_conn| {
+        let inserted_experiment = diesel::insert_into(experiments)
+            .values(&new_experiment)
+            .get_result::<Experiment>(transaction_conn)?;
+        record_event(
+            transaction_conn,
+            experiment_id,
+            ExperimentEventType::Created,
+            created_details,
+            req.reason.clone(),
+            &user.get_email(),
+        )?;
+        settle_applied(transaction_conn, &saga)?;
+        Ok::<Experiment, superposition::AppError>(inserted_experiment)
+    });
+    let inserted_experiment = match inserted {
+        Ok(experiment) => experiment,
+        Err(err) => {
+            let context_ids = variants
+                .iter()
+                .filter_map(|variant| variant.context_id.clone())
+                .collect();
+            compensate_create(&mut conn, &cac, &saga, context_ids, err.to_string()).await;
+            return Err(err);
+        }
+    };
     let response = ExperimentCreateResponse::from(inserted_experiment);
 
     let mut http_resp = HttpResponse::Ok();
@@ -284,23 +370,40 @@ async fn create(
 
 #[patch("/{experiment_id}/conclude")]
 async fn conclude_handler(
-    state: Data<AppState>,
+    cac_backend: Data<dyn CacBackend>,
     path: web::Path<i64>,
     custom_headers: CustomHeaders,
     req: web::Json<ConcludeExperimentRequest>,
     db_conn: DbConnection,
     tenant: Tenant,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
 ) -> superposition::Result<HttpResponse> {
-    let DbConnection(conn) = db_conn;
+    let DbConnection(mut conn) = db_conn;
+    let experiment_id = path.into_inner();
+    let (override_keys, context) = experiments::experiments
+        .find(experiment_id)
+        .select((experiments::override_keys, experiments::context))
+        .first::<(Vec<String>, Value)>(&mut conn)?;
+    auth.require_keys(Resource::Experiment, Action::Write, &override_keys)?;
+    // the winner is moved to the experiment's context, or into the default
+    // configs of experiments without one
+    let winner_resource = match context.as_object() {
+        Some(context) if !context.is_empty() => Resource::Context,
+        _ => Resource::DefaultConfig,
+    };
+    auth.require_keys(winner_resource, Action::Write, &override_keys)?;
     let (response, config_version_id) = conclude(
-        state,
-        path.into_inner(),
+        cac_backend,
+        experiment_id,
         custom_headers.config_tags,
         req.into_inner(),
         conn,
         tenant,
         user,
+        actor,
+        auth,
     )
     .await?;
     let mut http_resp = HttpResponse::Ok();
@@ -309,13 +412,15 @@ async fn conclude_handler(
 }
 
 pub async fn conclude(
-    state: Data<AppState>,
+    cac_backend: Data<dyn CacBackend>,
     experiment_id: i64,
     config_tags: Option<String>,
     req: ConcludeExperimentRequest,
     mut conn: PooledConnection<ConnectionManager<PgConnection>>,
     tenant: Tenant,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
 ) -> superposition::Result<(Experiment, Option<String>)> {
     use crate::db::schema::experiments::dsl;
 
@@ -338,6 +443,7 @@ pub async fn conclude(
     })?;
 
     let mut operations: Vec<ContextAction> = vec![];
+    let mut default_configs: Map<String, Value> = Map::new();
     let experiment_variants: Vec<Variant> = serde_json::from_value(experiment.variants)
         .map_err(|err| {
         log::error!(
@@ -361,33 +467,7 @@ pub async fn conclude(
                 };
                 operations.push(ContextAction::MOVE((context_id, context_move_req)));
             } else {
-                for (key, val) in variant.overrides {
-                    let create_req = HashMap::from([("value", val)]);
-
-                    let url = format!("{}/default-config/{}", state.cac_host, key);
-
-                    let headers = construct_request_headers(&[
-                        ("x-tenant", tenant.as_str()),
-                        (
-                            "Authorization",
-                            &format!(
-                                "{} {}",
-                                user.get_auth_type(),
-                                user.get_auth_token()
-                            ),
-                        ),
-                    ])
-                    .map_err(|err| {
-                        superposition::AppError::UnexpectedError(anyhow!(err))
-                    })?;
-
-                    let _ =
-                        request::<_, Value>(url, Method::PUT, Some(create_req), headers)
-                            .await
-                            .map_err(|err| {
-                                superposition::AppError::UnexpectedError(anyhow!(err))
-                            })?;
-                }
+                default_configs.extend(variant.overrides);
                 operations.push(ContextAction::DELETE(context_id));
             }
 
@@ -404,34 +484,74 @@ pub async fn conclude(
         ));
     }
 
-    // calling CAC bulk api with operations as payload
-    let http_client = reqwest::Client::new();
-    let url = state.cac_host.clone() + "/context/bulk-operations";
-    let headers_map = construct_header_map(tenant.as_str(), config_tags)?;
-
-    let response = http_client
-        .put(&url)
-        .headers(headers_map.into())
-        .header(
-            "Authorization",
-            format!("{} {}", user.get_auth_type(), user.get_auth_token()),
-        )
-        .json(&operations)
-        .send()
-        .await;
-
-    let (_, config_version_id) = process_cac_http_response(response).await?;
-
-    // updating experiment status in db
-    let updated_experiment = diesel::update(dsl::experiments)
-        .filter(dsl::id.eq(experiment_id))
-        .set((
-            dsl::status.eq(ExperimentStatusType::CONCLUDED),
-            dsl::last_modified.eq(Utc::now()),
-            dsl::last_modified_by.eq(user.get_email()),
-            dsl::chosen_variant.eq(Some(winner_variant_id)),
-        ))
-        .get_result::<Experiment>(&mut conn)?;
+    let cac = cac_client(&cac_backend, &tenant, config_tags, &user, &actor, &auth);
+    let change = ExperimentChange::Conclude {
+        chosen_variant: winner_variant_id,
+        reason: req.reason,
+    };
+    let plan = CacPlan {
+        default_configs,
+        operations,
+    };
+    if let Some(result) =
+        apply_atomically(&mut conn, &cac, &plan, &actor, |transaction_conn, _| {
+            apply_experiment_change(
+                transaction_conn,
+                experiment_id,
+                change.clone(),
+                Vec::new(),
+                &user.get_email(),
+            )
+        })
+    {
+        return result;
+    }
+
+    // current values of the promoted keys, restored if CAC rejects the context
+    // operations after they were written
+    let promoted_keys = plan
+        .default_configs
+        .keys()
+        .cloned()
+        .collect::<Vec<String>>();
+    let compensation = CacPlan {
+        default_configs: match promoted_keys.is_empty() {
+            true => Map::new(),
+            false => cac.default_config_values(&promoted_keys).await?,
+        },
+        operations: Vec::new(),
+    };
+    let saga = begin_saga(
+        &mut conn,
+        experiment_id,
+        SagaKind::Conclude,
+        &plan,
+        &compensation,
+        Some(&change),
+        &actor,
+    )?;
+
+    // writing promoted default configs and calling CAC bulk api with operations
+    let (_, config_version_id) = match apply_plan(&cac, &plan, false).await {
+        Ok(result) => result,
+        Err(error) => {
+            return Err(fail_saga(&mut conn, &cac, &saga, &compensation, error).await)
+        }
+    };
+
+    // updating experiment status in db, the reconciler finishes the saga if
+    // this fails
+    let updated_experiment = actor.transaction(&mut conn, |transaction_conn| {
+        let experiment = apply_experiment_change(
+            transaction_conn,
+            experiment_id,
+            change,
+            Vec::new(),
+            &user.get_email(),
+        )?;
+        settle_applied(transaction_conn, &saga)?;
+        Ok::<Experiment, superposition::AppError>(experiment)
+    })?;
 
     Ok((updated_experiment, config_version_id))
 }
@@ -463,11 +583,52 @@ async fn list_experiments(
         return Ok(HttpResponse::NotModified().finish());
     };
 
+    let filters = filters.into_inner();
+    let dimension_vars = filters
+        .dimension
+        .as_deref()
+        .map(dimension_filter_vars)
+        .transpose()?;
+    let key_patterns = filters
+        .key_prefix
+        .as_ref()
+        .map(|prefixes| prefix_patterns(&prefixes.0));
     let query_builder = |filters: &ListFilters| {
         let mut builder = experiments::experiments.into_boxed();
         if let Some(states) = filters.status.clone() {
             builder = builder.filter(experiments::status.eq_any(states.0.clone()));
         }
+        if let Some(name) = &filters.name {
+            let pattern = prefix_patterns(&[name.clone()]).concat();
+            builder = builder.filter(experiments::name.ilike(format!("%{pattern}")));
+        }
+        if let Some(created_by) = &filters.created_by {
+            builder = builder.filter(experiments::created_by.eq(created_by.clone()));
+        }
+        if let Some(override_key) = &filters.override_key {
+            builder = builder
+                .filter(experiments::override_keys.contains(vec![override_key.clone()]));
+        }
+        if let Some(patterns) = &key_patterns {
+            builder = builder.filter(
+                sql::<Bool>("EXISTS (SELECT 1 FROM unnest(override_keys) AS override_key WHERE override_key LIKE ANY(")
+                    .bind::<Array<Text>, _>(patterns.clone())
+                    .sql("))"),
+            );
+        }
+        if let Some(vars) = &dimension_vars {
+            builder = builder.filter(
+                sql::<Bool>("jsonb_path_exists(context::jsonb, ")
+                    .bind::<Text, _>(DIMENSION_FILTER_PATH)
+                    .sql("::jsonpath, ")
+                    .bind::<Jsonb, _>(vars.clone())
+                    .sql(")"),
+            );
+        }
+        if let Some(chosen_variant) = &filters.chosen_variant {
+            builder = builder
+                .filter(experiments::chosen_variant.eq(Some(chosen_variant.clone())));
+        }
         let now = Utc::now();
         builder
             .filter(
@@ -476,14 +637,34 @@ async fn list_experiments(
             )
             .filter(experiments::last_modified.le(filters.to_date.unwrap_or(now)))
     };
-    let filters = filters.into_inner();
     let base_query = query_builder(&filters);
     let count_query = query_builder(&filters);
 
     let limit = filters.count.unwrap_or(10);
     let offset = (filters.page.unwrap_or(1) - 1) * limit;
+    let base_query = match (filters.sort_on, filters.sort_order) {
+        (ExperimentSortOn::CreatedAt, SortOrder::Asc) => {
+            base_query.order(experiments::created_at.asc())
+        }
+        (ExperimentSortOn::CreatedAt, SortOrder::Desc) => {
+            base_query.order(experiments::created_at.desc())
+        }
+        (ExperimentSortOn::LastModified, SortOrder::Asc) => {
+            base_query.order(experiments::last_modified.asc())
+        }
+        (ExperimentSortOn::LastModified, SortOrder::Desc) => {
+            base_query.order(experiments::last_modified.desc())
+        }
+        (ExperimentSortOn::TrafficPercentage, SortOrder::Asc) => {
+            base_query.order(experiments::traffic_percentage.asc())
+        }
+        (ExperimentSortOn::TrafficPercentage, SortOrder::Desc) => {
+            base_query.order(experiments::traffic_percentage.desc())
+        }
+    };
+    // ties keep the same order from page to page
     let query = base_query
-        .order(experiments::last_modified.desc())
+        .then_order_by(experiments::id.asc())
         .limit(limit)
         .offset(offset);
 
@@ -531,6 +712,8 @@ async fn ramp(
     req: web::Json<RampRequest>,
     db_conn: DbConnection,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
 ) -> superposition::Result<Json<ExperimentResponse>> {
     let DbConnection(mut conn) = db_conn;
     let exp_id = params.into_inner();
@@ -538,6 +721,11 @@ async fn ramp(
     let experiment: Experiment = experiments::experiments
         .find(exp_id)
         .get_result::<Experiment>(&mut conn)?;
+    auth.require_keys(
+        Resource::Experiment,
+        Action::Write,
+        &experiment.override_keys,
+    )?;
 
     let old_traffic_percentage = experiment.traffic_percentage as u8;
     let new_traffic_percentage = req.traffic_percentage as u8;
@@ -550,43 +738,185 @@ async fn ramp(
         unexpected_error!("Something went wrong, failed to ramp traffic percentage")
     })?;
     let variants_count = experiment_variants.len() as u8;
-    let max = 100 / variants_count;
+    let awaiting_ramp = experiment_variants
+        .iter()
+        .any(|variant| variant.awaiting_ramp);
 
     if matches!(experiment.status, ExperimentStatusType::CONCLUDED) {
         return Err(bad_argument!(
             "experiment already concluded, cannot ramp a concluded experiment"
         ));
-    } else if new_traffic_percentage > max {
-        return Err(bad_argument!(
-            "The traffic_percentage cannot exceed {}. Provide a traffic percentage less than {}", max, max
-        ))?;
     } else if new_traffic_percentage != 0
         && new_traffic_percentage == old_traffic_percentage
+        && !awaiting_ramp
     {
         return Err(bad_argument!("The traffic_percentage is same as provided"))?;
     }
-    let updated_experiment: Experiment = diesel::update(experiments::experiments)
-        .filter(experiments::id.eq(exp_id))
-        .set((
-            experiments::traffic_percentage.eq(req.traffic_percentage as i32),
-            experiments::last_modified.eq(Utc::now()),
-            experiments::last_modified_by.eq(user.get_email()),
-            experiments::status.eq(ExperimentStatusType::INPROGRESS),
-        ))
-        .get_result(&mut conn)?;
+    let updated_experiment = actor.transaction(&mut conn, |transaction_conn| {
+        // variants share the buckets left by the global holdout and the
+        // experiment's own
+        let settings = fetch_settings(transaction_conn, true)?;
+        let max = max_traffic_percentage(
+            &settings,
+            experiment.holdout_percentage,
+            usize::from(variants_count),
+        ) as u8;
+        if new_traffic_percentage > max {
+            return Err(bad_argument!(
+                "The traffic_percentage cannot exceed {}. Provide a traffic percentage less than {}", max, max
+            ));
+        }
+        // approvals are cleared when the variants change, a running
+        // experiment needs them again before its traffic goes up
+        if matches!(experiment.status, ExperimentStatusType::CREATED)
+            || new_traffic_percentage > old_traffic_percentage
+            || (awaiting_ramp && new_traffic_percentage != 0)
+        {
+            let approvals = experiment_approvers(transaction_conn, exp_id)?.len();
+            if approvals < settings.required_approvers as usize {
+                return Err(bad_argument!(
+                    "experiment needs {} approvals before it is ramped, it has {}",
+                    settings.required_approvers,
+                    approvals
+                ));
+            }
+        }
+        // the slice of an experiment in a layer grows or moves with its traffic
+        let slice_start = match experiment.layer.as_deref() {
+            Some(name) => {
+                let layer =
+                    find_layer(transaction_conn, name, true)?.ok_or_else(|| {
+                        log::error!("layer {name} of experiment {exp_id} not found");
+                        unexpected_error!(
+                            "Something went wrong, failed to ramp traffic percentage"
+                        )
+                    })?;
+                let size = slice_size(
+                    i32::from(new_traffic_percentage),
+                    experiment_variants.len(),
+                );
+                place_in_layer(
+                    transaction_conn,
+                    &layer,
+                    Some(exp_id),
+                    Some(experiment.slice_start),
+                    size,
+                )?
+            }
+            None => experiment.slice_start,
+        };
+        // variants added while the experiment ran start serving with this ramp
+        let variants = experiment_variants
+            .iter()
+            .cloned()
+            .map(|variant| Variant {
+                awaiting_ramp: variant.awaiting_ramp && new_traffic_percentage == 0,
+                ..variant
+            })
+            .collect::<Vec<Variant>>();
+        let variants = serde_json::to_value(variants).map_err(|e| {
+            log::error!("failed to serialize variants of {exp_id} while ramping: {e}");
+            unexpected_error!("Something went wrong, failed to ramp traffic percentage")
+        })?;
+        let updated = diesel::update(experiments::experiments)
+            .filter(experiments::id.eq(exp_id))
+            .set((
+                experiments::traffic_percentage.eq(req.traffic_percentage as i32),
+                experiments::variants.eq(variants),
+                experiments::slice_start.eq(slice_start),
+                experiments::last_modified.eq(Utc::now()),
+                experiments::last_modified_by.eq(user.get_email()),
+                experiments::status.eq(ExperimentStatusType::INPROGRESS),
+            ))
+            .get_result::<Experiment>(transaction_conn)?;
+        let (event_type, details) = ramp_event(
+            i32::from(old_traffic_percentage),
+            i32::from(new_traffic_percentage),
+        );
+        record_event(
+            transaction_conn,
+            exp_id,
+            event_type,
+            details,
+            req.reason.clone(),
+            &user.get_email(),
+        )?;
+        Ok::<Experiment, superposition::AppError>(updated)
+    })?;
 
     Ok(Json(ExperimentResponse::from(updated_experiment)))
 }
 
+/// Approves an experiment which hasn't been concluded, on behalf of a user
+/// other than its creator.
+#[patch("/{id}/approve")]
+async fn approve(
+    params: web::Path<i64>,
+    db_conn: DbConnection,
+    user: User,
+    actor: AuditActor,
+    auth: Authorizer,
+) -> superposition::Result<Json<ExperimentApprovals>> {
+    let DbConnection(mut conn) = db_conn;
+    let exp_id = params.into_inner();
+
+    let experiment = get_experiment(exp_id, &mut conn)?;
+    auth.require_keys(
+        Resource::Experiment,
+        Action::Write,
+        &experiment.override_keys,
+    )?;
+    if matches!(experiment.status, ExperimentStatusType::CONCLUDED) {
+        return Err(bad_argument!("concluded experiments can't be approved"));
+    }
+    if experiment.created_by == user.get_email() {
+        return Err(bad_argument!(
+            "experiments can't be approved by their creator"
+        ));
+    }
+    let approval = ExperimentApproval {
+        experiment_id: exp_id,
+        approved_by: user.get_email(),
+        approved_at: Utc::now(),
+    };
+    let approved_by = actor.transaction(&mut conn, |transaction_conn| {
+        // approving twice keeps the first approval
+        let inserted = diesel::insert_into(experiment_approvals::table)
+            .values(&approval)
+            .on_conflict_do_nothing()
+            .execute(transaction_conn)?;
+        if inserted > 0 {
+            record_event(
+                transaction_conn,
+                exp_id,
+                ExperimentEventType::Approved,
+                json!({}),
+                None,
+                &approval.approved_by,
+            )?;
+        }
+        experiment_approvers(transaction_conn, exp_id)
+    })?;
+    let settings = fetch_settings(&mut conn, false)?;
+    Ok(Json(ExperimentApprovals {
+        experiment_id: exp_id.to_string(),
+        approved_by,
+        required_approvers: settings.required_approvers,
+    }))
+}
+
 #[put("/{id}/overrides")]
 async fn update_overrides(
     params: web::Path<i64>,
     state: Data<AppState>,
+    cac_backend: Data<dyn CacBackend>,
     custom_headers: CustomHeaders,
     db_conn: DbConnection,
     req: web::Json<OverrideKeysUpdateRequest>,
     tenant: Tenant,
     user: User,
+    actor: AuditActor,
+    auth: Authorizer,
 ) -> superposition::Result<HttpResponse> {
     let DbConnection(mut conn) = db_conn;
     let experiment_id = params.into_inner();
@@ -597,7 +927,7 @@ async fn update_overrides(
     let first_variant = variants.first().ok_or(bad_argument!(
         "Variant not found in request. Provide at least one entry in variant's list",
     ))?;
-    let override_keys = extract_override_keys(&first_variant.overrides)
+    let override_keys: Vec<String> = extract_override_keys(&first_variant.overrides)
         .into_iter()
         .collect();
 
@@ -605,6 +935,11 @@ async fn update_overrides(
     let experiment = experiments::experiments
         .find(experiment_id)
         .first::<Experiment>(&mut conn)?;
+    auth.require_keys(
+        Resource::Experiment,
+        Action::Write,
+        experiment.override_keys.iter().chain(override_keys.iter()),
+    )?;
 
     if experiment.status != ExperimentStatusType::CREATED {
         return Err(bad_argument!(
@@ -644,7 +979,7 @@ async fn update_overrides(
         }
     }
     // Checking if all the variants are overriding the mentioned keys
-    let mut new_variants: Vec<Variant> = variants
+    let new_variants: Vec<Variant> = variants
         .into_iter()
         .map(|variant| {
             let existing_variant: &Variant =
@@ -655,6 +990,7 @@ async fn update_overrides(
                 overrides: variant.overrides,
                 override_id: None,
                 context_id: None,
+                awaiting_ramp: existing_variant.awaiting_ramp,
             }
         })
         .collect();
@@ -674,13 +1010,16 @@ async fn update_overrides(
         )?;
     }
 
-    // validating experiment against other active experiments based on permission flags
-    let flags = &state.experimentation_flags;
+    // validating experiment against other active experiments based on the
+    // tenant's policy
+    let settings = fetch_settings(&mut conn, false)?;
+    let flags = experimentation_flags(&settings, &state.experimentation_flags);
     let (valid, reason) = validate_experiment(
         &experiment.context,
         &override_keys,
         Some(experiment_id),
-        flags,
+        experiment.layer.as_deref(),
+        &flags,
         &mut conn,
     )?;
     if !valid {
@@ -703,7 +1042,7 @@ async fn update_overrides(
     }
 
     // adding operations to create new updated variant contexts
-    for variant in &mut new_variants {
+    for variant in &new_variants {
         let updated_cacccontext =
             add_variant_dimension_to_ctx(&experiment.context, variant.id.to_string())
                 .map_err(|e| {
@@ -724,57 +1063,417 @@ async fn update_overrides(
         cac_operations.push(ContextAction::PUT(payload));
     }
 
-    let http_client = reqwest::Client::new();
-    let url = state.cac_host.clone() + "/context/bulk-operations";
-    let headers_map = construct_header_map(tenant.as_str(), custom_headers.config_tags)?;
+    let plan = CacPlan {
+        default_configs: Map::new(),
+        operations: cac_operations,
+    };
+    let change = ExperimentChange::UpdateOverrides {
+        variants: new_variants,
+        override_keys,
+        reason: payload.reason,
+    };
+    let cac = cac_client(
+        &cac_backend,
+        &tenant,
+        custom_headers.config_tags,
+        &user,
+        &actor,
+        &auth,
+    );
+    let (updated_experiment, config_version_id) = apply_change(
+        &mut conn,
+        &cac,
+        &plan,
+        SagaKind::UpdateOverrides,
+        experiment_id,
+        change,
+        &actor,
+        &user,
+    )
+    .await?;
 
-    let response = http_client
-        .put(&url)
-        .headers(headers_map.into())
-        .header(
-            "Authorization",
-            format!("{} {}", user.get_auth_type(), user.get_auth_token()),
-        )
-        .json(&cac_operations)
-        .send()
-        .await;
-
-    // directly return an error response if not a 200 response
-    let (resp_contexts, config_version_id) = process_cac_http_response(response).await?;
-    let created_contexts = resp_contexts.into_iter().fold(Vec::new(), |mut acc, item| {
-        if let ContextBulkResponse::PUT(context) = item {
-            acc.push(context);
-        } else {
-            log::error!("Unexpected response item: {:?}", item);
+    let mut http_resp = HttpResponse::Ok();
+    add_config_version_to_header(&config_version_id, &mut http_resp);
+    Ok(http_resp.json(ExperimentResponse::from(updated_experiment)))
+}
+
+#[put("/{id}")]
+async fn update(
+    params: web::Path<i64>,
+    state: Data<AppState>,
+    cac_backend: Data<dyn CacBackend>,
+    custom_headers: CustomHeaders,
+    db_conn: DbConnection,
+    req: web::Json<ExperimentUpdateRequest>,
+    tenant: Tenant,
+    user: User,
+    actor: AuditActor,
+    auth: Authorizer,
+) -> superposition::Result<HttpResponse> {
+    let DbConnection(mut conn) = db_conn;
+    let experiment_id = params.into_inner();
+    let req = req.into_inner();
+
+    let experiment = experiments::experiments
+        .find(experiment_id)
+        .first::<Experiment>(&mut conn)?;
+    let running = match experiment.status {
+        ExperimentStatusType::CREATED => false,
+        ExperimentStatusType::INPROGRESS => true,
+        ExperimentStatusType::CONCLUDED => {
+            return Err(bad_argument!(
+                "experiment already concluded, cannot update a concluded experiment"
+            ))
         }
-        acc
-    });
-    for i in 0..created_contexts.len() {
-        let created_context = &created_contexts[i];
+    };
+    if running && req.context.is_some() {
+        return Err(bad_argument!(
+            "context of an experiment in progress can't be changed"
+        ));
+    }
 
-        new_variants[i].context_id = Some(created_context.context_id.clone());
-        new_variants[i].override_id = Some(created_context.override_id.clone());
+    let existing_variants: Vec<Variant> =
+        serde_json::from_value(experiment.variants.clone()).map_err(|err| {
+            log::error!("failed to parse exisiting variants with error {}", err);
+            unexpected_error!("Something went wrong, failed to update experiment")
+        })?;
+    let variants_changed = req.variants.is_some();
+    let mut variants = match req.variants {
+        Some(requested) => {
+            merge_variants(experiment_id, running, existing_variants.clone(), requested)?
+        }
+        None => existing_variants.clone(),
+    };
+    check_variant_types(&variants)?;
+
+    /****************** Validating override_keys and variant overrides *********************/
+
+    let override_keys: Vec<String> = extract_override_keys(&variants[0].overrides)
+        .into_iter()
+        .collect();
+    auth.require_keys(
+        Resource::Experiment,
+        Action::Write,
+        experiment.override_keys.iter().chain(override_keys.iter()),
+    )?;
+    validate_override_keys(&override_keys)?;
+    let variant_overrides = variants
+        .iter()
+        .map(|variant| &variant.overrides)
+        .collect::<Vec<&Map<String, Value>>>();
+    if !check_variants_override_coverage(&variant_overrides, &override_keys) {
+        return Err(bad_argument!(
+            "All variants should contain the keys mentioned in override_keys. Check if any of the following keys [{}] are missing from keys in your variants",
+            override_keys.join(",")
+        ));
     }
 
-    /*************************** Updating experiment in DB **************************/
-    let new_variants_json = serde_json::to_value(new_variants).map_err(|e| {
-        log::error!("failed to serialize new variants to json with error: {e}");
-        bad_argument!("failed to update experiment, bad variant data")
-    })?;
-    let updated_experiment = diesel::update(experiments::experiments.find(experiment_id))
-        .set((
-            experiments::variants.eq(new_variants_json),
-            experiments::override_keys.eq(override_keys),
-            experiments::last_modified.eq(Utc::now()),
-            experiments::last_modified_by.eq(user.get_email()),
-        ))
-        .get_result::<Experiment>(&mut conn)?;
+    let context = req
+        .context
+        .clone()
+        .unwrap_or_else(|| experiment.context.clone());
+    validate_context(&context)?;
+
+    // validating experiment against other active experiments based on the
+    // tenant's policy, renaming it doesn't change who it is served to
+    let targeting_changed = req.context.is_some() || variants_changed;
+    if targeting_changed {
+        let settings = fetch_settings(&mut conn, false)?;
+        check_variant_count(&settings, variants.len())?;
+        let flags = experimentation_flags(&settings, &state.experimentation_flags);
+        let (valid, reason) = validate_experiment(
+            &context,
+            &override_keys,
+            Some(experiment_id),
+            experiment.layer.as_deref(),
+            &flags,
+            &mut conn,
+        )?;
+        if !valid {
+            return Err(bad_argument!(reason));
+        }
+    }
+
+    /******************************* Updating contexts ************************************/
+    // contexts of an experiment which hasn't started are written again when
+    // its context or variants change, a running one only gets contexts for
+    // its new variants
+    let rewrite = !running && targeting_changed;
+    let mut cac_operations: Vec<ContextAction> = vec![];
+    if rewrite {
+        for existing_variant in existing_variants.iter() {
+            let context_id = existing_variant.context_id.clone().ok_or_else(|| {
+                log::error!(
+                    "context id not available for variant {:?}",
+                    existing_variant.id
+                );
+                unexpected_error!("Something went wrong, failed to update experiment")
+            })?;
+            cac_operations.push(ContextAction::DELETE(context_id));
+        }
+        for variant in variants.iter_mut() {
+            variant.context_id = None;
+            variant.override_id = None;
+        }
+    }
+    for variant in variants
+        .iter()
+        .filter(|variant| variant.context_id.is_none())
+    {
+        let variant_context = add_variant_dimension_to_ctx(&context, variant.id.clone())?;
+        let payload = ContextPutReq {
+            context: variant_context
+                .as_object()
+                .ok_or_else(|| {
+                    log::error!("failed to parse updated context with variant dimension");
+                    unexpected_error!("Something went wrong, failed to update experiment")
+                })?
+                .clone(),
+            r#override: json!(variant.overrides),
+        };
+        cac_operations.push(ContextAction::PUT(payload));
+    }
+
+    let fields = [
+        ("name", req.name.is_some()),
+        ("description", req.description.is_some()),
+        ("context", req.context.is_some()),
+        ("variants", variants_changed),
+    ]
+    .into_iter()
+    .filter(|(_, changed)| *changed)
+    .map(|(field, _)| field.to_string())
+    .collect();
+    let change = ExperimentChange::Update {
+        name: req.name,
+        description: req.description,
+        context: req.context,
+        variants,
+        override_keys,
+        fields,
+        reason: req.reason,
+    };
+    if cac_operations.is_empty() {
+        let updated_experiment = actor.transaction(&mut conn, |transaction_conn| {
+            apply_experiment_change(
+                transaction_conn,
+                experiment_id,
+                change,
+                Vec::new(),
+                &user.get_email(),
+            )
+        })?;
+        return Ok(HttpResponse::Ok().json(ExperimentResponse::from(updated_experiment)));
+    }
+
+    let plan = CacPlan {
+        default_configs: Map::new(),
+        operations: cac_operations,
+    };
+    let cac = cac_client(
+        &cac_backend,
+        &tenant,
+        custom_headers.config_tags,
+        &user,
+        &actor,
+        &auth,
+    );
+    let (updated_experiment, config_version_id) = apply_change(
+        &mut conn,
+        &cac,
+        &plan,
+        SagaKind::Update,
+        experiment_id,
+        change,
+        &actor,
+        &user,
+    )
+    .await?;
 
     let mut http_resp = HttpResponse::Ok();
     add_config_version_to_header(&config_version_id, &mut http_resp);
     Ok(http_resp.json(ExperimentResponse::from(updated_experiment)))
 }
 
+/// Makes the CAC writes of `plan` and then `change` to the experiment, in one
+/// transaction when CAC joins it and through a saga otherwise.
+#[allow(clippy::too_many_arguments)]
+async fn apply_change(
+    conn: &mut PooledConnection<ConnectionManager<PgConnection>>,
+    cac: &CacClient,
+    plan: &CacPlan,
+    kind: SagaKind,
+    experiment_id: i64,
+    change: ExperimentChange,
+    actor: &AuditActor,
+    user: &User,
+) -> superposition::Result<(Experiment, Option<String>)> {
+    if let Some(result) =
+        apply_atomically(conn, cac, plan, actor, |transaction_conn, responses| {
+            apply_experiment_change(
+                transaction_conn,
+                experiment_id,
+                change.clone(),
+                responses,
+                &user.get_email(),
+            )
+        })
+    {
+        return result;
+    }
+
+    let saga = begin_saga(
+        conn,
+        experiment_id,
+        kind,
+        plan,
+        &CacPlan::default(),
+        Some(&change),
+        actor,
+    )?;
+
+    let (resp_contexts, config_version_id) = match apply_plan(cac, plan, false).await {
+        Ok(result) => result,
+        Err(error) => {
+            return Err(fail_saga(conn, cac, &saga, &CacPlan::default(), error).await)
+        }
+    };
+
+    /*************************** Updating experiment in DB **************************/
+    let updated_experiment = actor.transaction(conn, |transaction_conn| {
+        let experiment = apply_experiment_change(
+            transaction_conn,
+            experiment_id,
+            change,
+            resp_contexts,
+            &user.get_email(),
+        )?;
+        settle_applied(transaction_conn, &saga)?;
+        Ok::<Experiment, superposition::AppError>(experiment)
+    })?;
+    Ok((updated_experiment, config_version_id))
+}
+
+#[post("/events")]
+async fn ingest_events(
+    req: web::Json<MetricEventsRequest>,
+    db_conn: DbConnection,
+    auth: Authorizer,
+) -> superposition::Result<Json<MetricEventsResponse>> {
+    auth.require(Resource::ExperimentEvent, Action::Write)?;
+    let DbConnection(mut conn) = db_conn;
+    let events = req.into_inner().events;
+    let deltas = aggregate_events(&events, Utc::now())?;
+
+    let experiment_ids = deltas
+        .keys()
+        .map(|(experiment_id, _, _, _, _)| *experiment_id)
+        .collect::<HashSet<i64>>();
+    let experiment_variants = experiments::experiments
+        .filter(experiments::id.eq_any(experiment_ids))
+        .select((experiments::id, experiments::variants))
+        .load::<(i64, Value)>(&mut conn)?
+        .into_iter()
+        .map(|(experiment_id, variants)| {
+            serde_json::from_value::<Vec<Variant>>(variants)
+                .map(|variants| (experiment_id, variants))
+                .map_err(|err| {
+                    log::error!("failed to parse variants of {experiment_id}: {err}");
+                    unexpected_error!("Something went wrong, failed to record events")
+                })
+        })
+        .collect::<superposition::Result<HashMap<i64, Vec<Variant>>>>()?;
+    check_event_variants(&deltas, &experiment_variants)?;
+
+    record_metrics(&mut conn, deltas)?;
+    Ok(Json(MetricEventsResponse {
+        accepted: events.len(),
+    }))
+}
+
+/// Lifecycle of an experiment, oldest first, with the traffic of each variant
+/// over time.
+#[get("/{id}/history")]
+async fn get_history(
+    params: web::Path<i64>,
+    db_conn: DbConnection,
+) -> superposition::Result<Json<ExperimentHistory>> {
+    let DbConnection(mut conn) = db_conn;
+    let experiment = get_experiment(params.into_inner(), &mut conn)?;
+    let events = load_history(&mut conn, experiment.id)?;
+    Ok(Json(ExperimentHistory {
+        experiment_id: experiment.id.to_string(),
+        traffic: traffic_timeline(&events),
+        events,
+    }))
+}
+
+/// The tenant's global holdout against the units outside it.
+#[get("/holdout/results")]
+async fn get_global_holdout_results(
+    db_conn: DbConnection,
+) -> superposition::Result<Json<GlobalHoldoutResults>> {
+    let DbConnection(mut conn) = db_conn;
+    let settings = fetch_settings(&mut conn, false)?;
+    let rows = load_metrics(&mut conn, GLOBAL_HOLDOUT_EXPERIMENT_ID)?;
+    Ok(Json(global_holdout_results(
+        settings.holdout_percentage,
+        &rows,
+    )?))
+}
+
+#[get("/{id}/results")]
+async fn get_results(
+    params: web::Path<i64>,
+    db_conn: DbConnection,
+) -> superposition::Result<Json<ExperimentResults>> {
+    let DbConnection(mut conn) = db_conn;
+    let experiment = get_experiment(params.into_inner(), &mut conn)?;
+    let variants: Vec<Variant> =
+        serde_json::from_value(experiment.variants).map_err(|err| {
+            log::error!("failed to parse variants of {}: {err}", experiment.id);
+            unexpected_error!("Something went wrong, failed to compute results")
+        })?;
+    let rows = load_metrics(&mut conn, experiment.id)?;
+    let mut results = variant_results(&variants, &rows)?;
+    compare_with_control(&mut results);
+    let holdout = holdout_results(experiment.id, &variants, &rows)?;
+
+    // the holdout is checked along with the variants when it is known how
+    // many buckets it had next to theirs: the global holdout, reported for
+    // every running experiment, and the experiment's own
+    let settings = fetch_settings(&mut conn, false)?;
+    let served = load_history(&mut conn, experiment.id)?
+        .into_iter()
+        .filter(|event| event.event_type != ExperimentEventType::Concluded)
+        .collect::<Vec<ExperimentHistoryEntry>>();
+    let traffic = steady_traffic(&traffic_timeline(&served));
+    let held_out = holdout
+        .as_ref()
+        .zip(traffic)
+        .map(|(holdout, _)| {
+            (
+                holdout.holdout.exposures,
+                settings.holdout_percentage + experiment.holdout_percentage,
+            )
+        })
+        .filter(|(_, buckets)| *buckets > 0);
+    let (observed, weights) = expected_allocation(
+        &variants,
+        &results,
+        traffic.unwrap_or(experiment.traffic_percentage),
+        held_out,
+    );
+    let sample_ratio = sample_ratio_mismatch(&observed, &weights);
+    Ok(Json(ExperimentResults {
+        experiment_id: experiment.id.to_string(),
+        confidence_level: CONFIDENCE_LEVEL,
+        holdout,
+        variants: results,
+        sample_ratio,
+    }))
+}
+
 #[get("/audit")]
 async fn get_audit_logs(
     filters: Query<AuditQueryFilters>,
@@ -793,6 +1492,9 @@ async fn get_audit_logs(
         if let Some(username) = filters.username.clone() {
             builder = builder.filter(event_log::user_name.eq(username));
         }
+        if let Some(request_id) = filters.request_id.clone() {
+            builder = builder.filter(event_log::request_id.eq(request_id));
+        }
         let now = Utc::now().naive_utc();
         builder
             .filter(
@@ -824,3 +1526,36 @@ async fn get_audit_logs(
         "data": logs
     })))
 }
+
+/// Every change made to one row, most recent first. Rows are matched on the
+/// primary key in either the old or the new data so deletes are included.
+#[get("/audit/entity/{table}/{id}")]
+async fn get_entity_history(
+    path: web::Path<(AuditEntity, String)>,
+    db_conn: DbConnection,
+) -> superposition::Result<Json<Vec<AuditHistoryEntry>>> {
+    let DbConnection(mut conn) = db_conn;
+    let (entity, id) = path.into_inner();
+    let primary_key = entity.primary_key();
+
+    let logs: Vec<EventLog> = event_log::event_log
+        .filter(event_log::table_name.eq(entity.table_name()))
+        .filter(
+            sql::<Bool>(&format!("(original_data ->> '{primary_key}' = "))
+                .bind::<Text, _>(id.clone())
+                .sql(&format!(" OR new_data ->> '{primary_key}' = "))
+                .bind::<Text, _>(id)
+                .sql(")"),
+        )
+        .order(event_log::timestamp.desc())
+        .load(&mut conn)?;
+
+    let history = logs
+        .into_iter()
+        .map(|log| AuditHistoryEntry {
+            changes: field_changes(log.original_data.as_ref(), log.new_data.as_ref()),
+            log,
+        })
+        .collect();
+    Ok(Json(history))
+}
@@ -1,8 +1,11 @@
 use super::types::{Variant, VariantType};
-use crate::db::models::{Experiment, ExperimentStatusType};
+use crate::db::{
+    models::{Experiment, ExperimentStatusType},
+    schema::experiment_approvals,
+};
 use diesel::pg::PgConnection;
 use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl, RunQueryDsl};
-use serde_json::{Map, Value};
+use serde_json::{json, Map, Value};
 use service_utils::helpers::extract_dimensions;
 use service_utils::service::types::ExperimentationFlags;
 use std::collections::HashSet;
@@ -174,10 +177,13 @@ pub fn is_valid_experiment(
     Ok((valid_experiment, invalid_reason))
 }
 
+/// Experiments of the same `layer` never serve the same user, so they are
+/// not checked against each other.
 pub fn validate_experiment(
     context: &Value,
     override_keys: &[String],
     experiment_id: Option<i64>,
+    layer: Option<&str>,
     flags: &ExperimentationFlags,
     conn: &mut PgConnection,
 ) -> superposition::Result<(bool, String)> {
@@ -193,6 +199,10 @@ pub fn validate_experiment(
                 ),
         )
         .load(conn)?;
+    let active_experiments = active_experiments
+        .into_iter()
+        .filter(|experiment| layer.is_none() || experiment.layer.as_deref() != layer)
+        .collect::<Vec<Experiment>>();
 
     is_valid_experiment(context, override_keys, flags, &active_experiments)
 }
@@ -242,3 +252,168 @@ pub fn add_variant_dimension_to_ctx(
 pub fn extract_override_keys(overrides: &Map<String, Value>) -> HashSet<String> {
     overrides.keys().map(String::from).collect()
 }
+
+/// Variant ids a context condition is restricted to through the `variantIds`
+/// dimension.
+pub fn extract_variant_ids(context: &Value) -> Vec<String> {
+    match context {
+        Value::Object(conditions) => conditions
+            .iter()
+            .flat_map(|(operator, operands)| {
+                let operands_array = operands.as_array();
+                let on_variant_ids = operands_array
+                    .and_then(|operands| operands.get(1))
+                    .and_then(|operand| operand.get("var"))
+                    .and_then(Value::as_str)
+                    == Some("variantIds");
+                match (operator.as_str(), operands_array) {
+                    ("in", Some(operands)) if on_variant_ids => operands
+                        .first()
+                        .and_then(Value::as_str)
+                        .map(String::from)
+                        .into_iter()
+                        .collect(),
+                    _ => extract_variant_ids(operands),
+                }
+            })
+            .collect(),
+        Value::Array(values) => values.iter().flat_map(extract_variant_ids).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Experiment a variant belongs to, variant ids are `<experiment id>-<id>`.
+pub fn experiment_id_of_variant(variant_id: &str) -> Option<i64> {
+    variant_id
+        .split_once('-')
+        .and_then(|(experiment_id, _)| experiment_id.parse().ok())
+}
+
+/// Variants of experiment `experiment_id` once updated to `requested`. Ids
+/// which aren't those of `existing` variants are prefixed with the
+/// experiment's id. A `running` experiment keeps its variants, in their order
+/// and with their contexts, and only takes new experimental ones after them,
+/// which serve no traffic until the experiment is ramped again.
+/// Variants without a context id are the ones to create contexts for.
+pub fn merge_variants(
+    experiment_id: i64,
+    running: bool,
+    existing: Vec<Variant>,
+    requested: Vec<Variant>,
+) -> superposition::Result<Vec<Variant>> {
+    let prefix = format!("{experiment_id}-");
+    let requested = requested
+        .into_iter()
+        .map(|variant| {
+            let is_existing = existing.iter().any(|current| current.id == variant.id);
+            let id = if is_existing || variant.id.starts_with(&prefix) {
+                variant.id
+            } else {
+                format!("{prefix}{}", variant.id)
+            };
+            Variant {
+                id,
+                context_id: None,
+                override_id: None,
+                awaiting_ramp: false,
+                ..variant
+            }
+        })
+        .collect::<Vec<Variant>>();
+    let ids = requested
+        .iter()
+        .map(|variant| variant.id.as_str())
+        .collect::<HashSet<&str>>();
+    if ids.len() != requested.len() {
+        return Err(bad_argument!(
+            "Variant ids are expected to be unique. Provide unqiue variant IDs"
+        ));
+    }
+    if !running {
+        return Ok(requested);
+    }
+
+    let mut merged = Vec::with_capacity(requested.len());
+    for current in existing.iter() {
+        let variant = requested
+            .iter()
+            .find(|variant| variant.id == current.id)
+            .ok_or_else(|| {
+                bad_argument!(
+                    "variant {} can't be removed from an experiment in progress",
+                    current.id
+                )
+            })?;
+        if variant.variant_type != current.variant_type
+            || variant.overrides != current.overrides
+        {
+            return Err(bad_argument!(
+                "variant {} of an experiment in progress can't be changed, only new variants can be added",
+                current.id
+            ));
+        }
+        merged.push(current.clone());
+    }
+    for variant in requested {
+        if existing.iter().any(|current| current.id == variant.id) {
+            continue;
+        }
+        if variant.variant_type != VariantType::EXPERIMENTAL {
+            return Err(bad_argument!(
+                "only experimental variants can be added to an experiment in progress"
+            ));
+        }
+        // the traffic of the experiment was approved without it
+        merged.push(Variant {
+            awaiting_ramp: true,
+            ..variant
+        });
+    }
+    Ok(merged)
+}
+
+/// Variables of the `dimension` list filter, `<dimension>:<value>`. Values
+/// which parse as JSON, like numbers and booleans, match either as such or as
+/// strings.
+pub fn dimension_filter_vars(filter: &str) -> superposition::Result<Value> {
+    let (dimension, raw) = filter
+        .split_once(':')
+        .filter(|(dimension, _)| !dimension.is_empty())
+        .ok_or_else(|| {
+            bad_argument!(
+                "dimension filter should be <dimension>:<value>, got {}",
+                filter
+            )
+        })?;
+    let value = serde_json::from_str::<Value>(raw)
+        .ok()
+        .filter(|value| !value.is_object() && !value.is_array())
+        .unwrap_or_else(|| Value::String(raw.to_string()));
+    Ok(json!({ "dimension": dimension, "value": value, "raw": raw }))
+}
+
+/// `LIKE` patterns matching the strings which start with any of `prefixes`.
+pub fn prefix_patterns(prefixes: &[String]) -> Vec<String> {
+    prefixes
+        .iter()
+        .map(|prefix| {
+            let escaped = prefix
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("{escaped}%")
+        })
+        .collect()
+}
+
+/// Users who approved `experiment_id`, in the order they did.
+pub fn experiment_approvers(
+    conn: &mut PgConnection,
+    experiment_id: i64,
+) -> superposition::Result<Vec<String>> {
+    Ok(experiment_approvals::table
+        .filter(experiment_approvals::experiment_id.eq(experiment_id))
+        .order(experiment_approvals::approved_at.asc())
+        .select(experiment_approvals::approved_by)
+        .load::<String>(conn)?)
+}
@@ -0,0 +1,118 @@
+use chrono::Utc;
+use diesel::{ExpressionMethods, PgConnection, QueryDsl, RunQueryDsl};
+use serde_json::{json, Value};
+use superposition_macros::unexpected_error;
+use superposition_types::result as superposition;
+
+use super::types::{ExperimentEventType, ExperimentHistoryEntry, TrafficPoint};
+use crate::db::{models::ExperimentEvent, schema::experiment_events};
+
+/// Adds an event to the history of `experiment_id`, in the transaction of the
+/// change it records.
+pub fn record_event(
+    conn: &mut PgConnection,
+    experiment_id: i64,
+    event_type: ExperimentEventType,
+    details: Value,
+    reason: Option<String>,
+    user_email: &str,
+) -> superposition::Result<()> {
+    diesel::insert_into(experiment_events::table)
+        .values((
+            experiment_events::experiment_id.eq(experiment_id),
+            experiment_events::event_type.eq(event_type.to_string()),
+            experiment_events::details.eq(details),
+            experiment_events::reason.eq(reason),
+            experiment_events::created_by.eq(user_email),
+            experiment_events::created_at.eq(Utc::now()),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Event of a ramp from `from` to `to` percent of traffic, ramping a running
+/// experiment down to zero pauses it.
+pub fn ramp_event(from: i32, to: i32) -> (ExperimentEventType, Value) {
+    let event_type = if to == 0 && from > 0 {
+        ExperimentEventType::Paused
+    } else {
+        ExperimentEventType::Ramped
+    };
+    (event_type, json!({ "from": from, "to": to }))
+}
+
+pub fn load_history(
+    conn: &mut PgConnection,
+    experiment_id: i64,
+) -> superposition::Result<Vec<ExperimentHistoryEntry>> {
+    experiment_events::table
+        .filter(experiment_events::experiment_id.eq(experiment_id))
+        .order((
+            experiment_events::created_at.asc(),
+            experiment_events::id.asc(),
+        ))
+        .load::<ExperimentEvent>(conn)?
+        .into_iter()
+        .map(|event| {
+            let event_type =
+                event
+                    .event_type
+                    .parse::<ExperimentEventType>()
+                    .map_err(|_| {
+                        log::error!("unknown experiment event type {}", event.event_type);
+                        unexpected_error!("Something went wrong")
+                    })?;
+            Ok(ExperimentHistoryEntry {
+                event_type,
+                details: event.details,
+                reason: event.reason,
+                created_by: event.created_by,
+                created_at: event.created_at,
+            })
+        })
+        .collect()
+}
+
+/// Traffic of each variant over time from the `events` of a history, oldest
+/// first. Experiments serve no traffic until ramped and once concluded.
+pub fn traffic_timeline(events: &[ExperimentHistoryEntry]) -> Vec<TrafficPoint> {
+    let mut points: Vec<TrafficPoint> = Vec::new();
+    for event in events {
+        let traffic_percentage = match event.event_type {
+            ExperimentEventType::Created
+            | ExperimentEventType::Paused
+            | ExperimentEventType::Concluded => Some(0),
+            ExperimentEventType::Ramped => event
+                .details
+                .get("to")
+                .and_then(Value::as_i64)
+                .map(|to| to as i32),
+            ExperimentEventType::Approved
+            | ExperimentEventType::OverridesUpdated
+            | ExperimentEventType::Updated => None,
+        };
+        let Some(traffic_percentage) = traffic_percentage else {
+            continue;
+        };
+        if points.last().map(|point| point.traffic_percentage) != Some(traffic_percentage)
+        {
+            points.push(TrafficPoint {
+                at: event.created_at,
+                traffic_percentage,
+            });
+        }
+    }
+    points
+}
+
+/// The one traffic an experiment served from when it was started, `None`
+/// when it was ramped or paused since, or never started. Units are held out
+/// of a running experiment whatever its traffic, so the share of its units
+/// the holdout is expected to have is only known with a steady traffic.
+pub fn steady_traffic(timeline: &[TrafficPoint]) -> Option<i32> {
+    let mut served = timeline
+        .iter()
+        .skip_while(|point| point.traffic_percentage == 0);
+    let traffic = served.next()?.traffic_percentage;
+    served.next().is_none().then_some(traffic)
+}
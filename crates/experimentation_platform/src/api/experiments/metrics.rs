@@ -0,0 +1,479 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use diesel::{
+    dsl::sql,
+    sql_types::{BigInt, Double, Text, Timestamptz},
+    upsert::excluded,
+    Connection, ExpressionMethods, PgConnection, QueryableByName, RunQueryDsl,
+};
+use superposition_macros::{bad_argument, unexpected_error};
+use superposition_types::result as superposition;
+
+use super::{
+    helpers::experiment_id_of_variant,
+    statistics::{compare_with_control, CONFIDENCE_LEVEL},
+    types::{
+        GlobalHoldoutResults, HoldoutResults, MetricEvent, MetricEventType, MetricResult,
+        PopulationResult, Variant, VariantResult, VariantType,
+    },
+};
+use crate::db::schema::{experiment_conversions, experiment_exposures};
+
+/// Most events taken in one request.
+pub const MAX_EVENTS_PER_REQUEST: usize = 1000;
+const MAX_METRIC_NAME_LENGTH: usize = 128;
+const MAX_UNIT_ID_LENGTH: usize = 256;
+
+/// Row an event is counted in: experiment, variant, event type, metric,
+/// which is empty for exposures, and unit.
+pub type MetricKey = (i64, String, MetricEventType, String, String);
+
+/// What a batch of events adds to the row of one unit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricDelta {
+    pub event_count: i64,
+    pub value_count: i64,
+    pub value_sum: f64,
+    pub first_event_at: DateTime<Utc>,
+    pub last_event_at: DateTime<Utc>,
+}
+
+impl MetricDelta {
+    fn new(at: DateTime<Utc>) -> Self {
+        MetricDelta {
+            event_count: 0,
+            value_count: 0,
+            value_sum: 0.0,
+            first_event_at: at,
+            last_event_at: at,
+        }
+    }
+
+    fn add(&mut self, value: Option<f64>, at: DateTime<Utc>) {
+        self.event_count += 1;
+        if let Some(value) = value {
+            self.value_count += 1;
+            self.value_sum += value;
+        }
+        self.first_event_at = self.first_event_at.min(at);
+        self.last_event_at = self.last_event_at.max(at);
+    }
+}
+
+fn metric_key(event: &MetricEvent) -> superposition::Result<MetricKey> {
+    let experiment_id = event
+        .experiment_id
+        .parse::<i64>()
+        .map_err(|_| bad_argument!("invalid experiment id {}", event.experiment_id))?;
+    if experiment_id_of_variant(&event.variant_id) != Some(experiment_id) {
+        return Err(bad_argument!(
+            "variant {} is not a variant of experiment {}",
+            event.variant_id,
+            experiment_id
+        ));
+    }
+    if event.unit_id.is_empty() || event.unit_id.len() > MAX_UNIT_ID_LENGTH {
+        return Err(bad_argument!(
+            "events need a unit_id of at most {} characters",
+            MAX_UNIT_ID_LENGTH
+        ));
+    }
+    let metric = match (event.event_type, event.metric.as_deref()) {
+        (MetricEventType::Exposure, None) => String::new(),
+        (MetricEventType::Exposure, Some(_)) => {
+            return Err(bad_argument!("exposure events do not take a metric"))
+        }
+        (MetricEventType::Conversion, Some(metric))
+            if !metric.is_empty() && metric.len() <= MAX_METRIC_NAME_LENGTH =>
+        {
+            metric.to_string()
+        }
+        (MetricEventType::Conversion, _) => {
+            return Err(bad_argument!(
+                "conversion events need a metric of at most {} characters",
+                MAX_METRIC_NAME_LENGTH
+            ))
+        }
+    };
+    if event.value.is_some_and(|value| !value.is_finite()) {
+        return Err(bad_argument!("event values have to be finite numbers"));
+    }
+    Ok((
+        experiment_id,
+        event.variant_id.clone(),
+        event.event_type,
+        metric,
+        event.unit_id.clone(),
+    ))
+}
+
+/// Validates `events` and adds them up per unit. The rows are ordered so
+/// concurrent batches lock them in the same order.
+pub fn aggregate_events(
+    events: &[MetricEvent],
+    now: DateTime<Utc>,
+) -> superposition::Result<BTreeMap<MetricKey, MetricDelta>> {
+    if events.len() > MAX_EVENTS_PER_REQUEST {
+        return Err(bad_argument!(
+            "at most {} events can be sent at once",
+            MAX_EVENTS_PER_REQUEST
+        ));
+    }
+    let mut deltas = BTreeMap::new();
+    for event in events {
+        // events from clients with skewed clocks are counted as received
+        let at = event.timestamp.filter(|at| *at <= now).unwrap_or(now);
+        deltas
+            .entry(metric_key(event)?)
+            .or_insert_with(|| MetricDelta::new(at))
+            .add(event.value, at);
+    }
+    Ok(deltas)
+}
+
+/// Records the exposures and conversions of `deltas` in one transaction. A
+/// unit exposed again is only counted once.
+pub fn record_metrics(
+    conn: &mut PgConnection,
+    deltas: BTreeMap<MetricKey, MetricDelta>,
+) -> superposition::Result<()> {
+    use experiment_conversions::dsl as conversions;
+    use experiment_exposures::dsl as exposures;
+    conn.transaction::<_, superposition::AppError, _>(|transaction_conn| {
+        for ((experiment_id, variant_id, event_type, metric, unit_id), delta) in deltas {
+            match event_type {
+                MetricEventType::Exposure => {
+                    diesel::insert_into(exposures::experiment_exposures)
+                        .values((
+                            exposures::experiment_id.eq(experiment_id),
+                            exposures::variant_id.eq(variant_id),
+                            exposures::unit_id.eq(unit_id),
+                            exposures::first_exposed_at.eq(delta.first_event_at),
+                            exposures::last_exposed_at.eq(delta.last_event_at),
+                        ))
+                        .on_conflict((
+                            exposures::experiment_id,
+                            exposures::variant_id,
+                            exposures::unit_id,
+                        ))
+                        .do_update()
+                        .set((
+                            exposures::first_exposed_at.eq(sql::<Timestamptz>(
+                                "LEAST(experiment_exposures.first_exposed_at, excluded.first_exposed_at)",
+                            )),
+                            exposures::last_exposed_at.eq(sql::<Timestamptz>(
+                                "GREATEST(experiment_exposures.last_exposed_at, excluded.last_exposed_at)",
+                            )),
+                        ))
+                        .execute(transaction_conn)?;
+                }
+                MetricEventType::Conversion => {
+                    diesel::insert_into(conversions::experiment_conversions)
+                        .values((
+                            conversions::experiment_id.eq(experiment_id),
+                            conversions::variant_id.eq(variant_id),
+                            conversions::metric.eq(metric),
+                            conversions::unit_id.eq(unit_id),
+                            conversions::event_count.eq(delta.event_count),
+                            conversions::value_count.eq(delta.value_count),
+                            conversions::value_sum.eq(delta.value_sum),
+                            conversions::first_event_at.eq(delta.first_event_at),
+                            conversions::last_event_at.eq(delta.last_event_at),
+                        ))
+                        .on_conflict((
+                            conversions::experiment_id,
+                            conversions::variant_id,
+                            conversions::metric,
+                            conversions::unit_id,
+                        ))
+                        .do_update()
+                        .set((
+                            conversions::event_count.eq(conversions::event_count
+                                + excluded(conversions::event_count)),
+                            conversions::value_count.eq(conversions::value_count
+                                + excluded(conversions::value_count)),
+                            conversions::value_sum.eq(conversions::value_sum
+                                + excluded(conversions::value_sum)),
+                            conversions::first_event_at.eq(sql::<Timestamptz>(
+                                "LEAST(experiment_conversions.first_event_at, excluded.first_event_at)",
+                            )),
+                            conversions::last_event_at.eq(sql::<Timestamptz>(
+                                "GREATEST(experiment_conversions.last_event_at, excluded.last_event_at)",
+                            )),
+                        ))
+                        .execute(transaction_conn)?;
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Experiment the events of the tenant's global holdout and of the units
+/// outside it are reported under, which no experiment has as its id.
+pub const GLOBAL_HOLDOUT_EXPERIMENT_ID: i64 = 0;
+
+/// Id events of the units held out of an experiment are reported under.
+pub fn holdout_variant_id(experiment_id: i64) -> String {
+    format!("{experiment_id}-holdout")
+}
+
+/// Id events of the units outside the global holdout are reported under.
+pub fn global_exposed_variant_id() -> String {
+    format!("{GLOBAL_HOLDOUT_EXPERIMENT_ID}-exposed")
+}
+
+/// Checks that every event names a variant the experiment has, or its
+/// holdout, or one of the populations of the global holdout.
+/// `experiment_variants` are the variants of the experiments of the events.
+pub fn check_event_variants(
+    deltas: &BTreeMap<MetricKey, MetricDelta>,
+    experiment_variants: &HashMap<i64, Vec<Variant>>,
+) -> superposition::Result<()> {
+    for (experiment_id, variant_id, _, _, _) in deltas.keys() {
+        if *experiment_id == GLOBAL_HOLDOUT_EXPERIMENT_ID {
+            if *variant_id != holdout_variant_id(GLOBAL_HOLDOUT_EXPERIMENT_ID)
+                && *variant_id != global_exposed_variant_id()
+            {
+                return Err(bad_argument!(
+                    "variant {} not found in the global holdout",
+                    variant_id
+                ));
+            }
+            continue;
+        }
+        let variants = experiment_variants
+            .get(experiment_id)
+            .ok_or_else(|| bad_argument!("experiment {} not found", experiment_id))?;
+        if *variant_id != holdout_variant_id(*experiment_id)
+            && !variants.iter().any(|variant| &variant.id == variant_id)
+        {
+            return Err(bad_argument!(
+                "variant {} not found in experiment {}",
+                variant_id,
+                experiment_id
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Units of a variant, counted in the results: the units exposed to it, with
+/// an empty metric, or the exposed units which converted on a metric. Values
+/// are the totals of each unit, with units which reported none left out.
+#[derive(QueryableByName, Clone, Debug)]
+pub struct UnitMetric {
+    #[diesel(sql_type = Text)]
+    pub variant_id: String,
+    #[diesel(sql_type = Text)]
+    pub event_type: String,
+    #[diesel(sql_type = Text)]
+    pub metric: String,
+    #[diesel(sql_type = BigInt)]
+    pub units: i64,
+    #[diesel(sql_type = BigInt)]
+    pub value_count: i64,
+    #[diesel(sql_type = Double)]
+    pub value_sum: f64,
+    #[diesel(sql_type = Double)]
+    pub value_sum_squares: f64,
+    #[diesel(sql_type = Timestamptz)]
+    pub last_event_at: DateTime<Utc>,
+}
+
+/// Distinct units of each variant of the experiment, conversions of units
+/// never exposed to the variant are not counted.
+pub fn load_metrics(
+    conn: &mut PgConnection,
+    experiment_id: i64,
+) -> superposition::Result<Vec<UnitMetric>> {
+    Ok(diesel::sql_query(
+        "SELECT variant_id, 'exposure' AS event_type, '' AS metric, count(*) AS units, 0::bigint AS value_count, 0::float8 AS value_sum, 0::float8 AS value_sum_squares, max(last_exposed_at) AS last_event_at FROM experiment_exposures WHERE experiment_id = $1 GROUP BY variant_id UNION ALL SELECT c.variant_id, 'conversion' AS event_type, c.metric, count(*) AS units, count(*) FILTER (WHERE c.value_count > 0) AS value_count, coalesce(sum(c.value_sum) FILTER (WHERE c.value_count > 0), 0) AS value_sum, coalesce(sum(c.value_sum * c.value_sum) FILTER (WHERE c.value_count > 0), 0) AS value_sum_squares, max(c.last_event_at) AS last_event_at FROM experiment_conversions c JOIN experiment_exposures e USING (experiment_id, variant_id, unit_id) WHERE c.experiment_id = $1 GROUP BY c.variant_id, c.metric",
+    )
+    .bind::<BigInt, _>(experiment_id)
+    .load::<UnitMetric>(conn)?)
+}
+
+/// Sample standard deviation of the values of `units` units, at least two,
+/// from their sum and sum of squares. Units missing from the sums count as 0.
+fn standard_deviation(value_sum: f64, value_sum_squares: f64, units: i64) -> f64 {
+    let count = units as f64;
+    let variance = (value_sum_squares - value_sum * value_sum / count) / (count - 1.0);
+    // rounding can take the variance of equal values below zero
+    variance.max(0.0).sqrt()
+}
+
+/// Results of each variant from its aggregates, in the order of `variants`.
+/// Variants without events are reported with zero counts. Means of metrics
+/// with values are per exposed unit, units which didn't convert count as 0.
+pub fn variant_results(
+    variants: &[Variant],
+    rows: &[UnitMetric],
+) -> superposition::Result<Vec<VariantResult>> {
+    let metrics = rows
+        .iter()
+        .filter(|row| row.event_type == MetricEventType::Conversion.to_string())
+        .map(|row| row.metric.as_str())
+        .collect::<HashSet<&str>>();
+    let mut metrics = metrics.into_iter().collect::<Vec<&str>>();
+    metrics.sort();
+    let valued_metrics = rows
+        .iter()
+        .filter(|row| row.value_count > 0)
+        .map(|row| row.metric.as_str())
+        .collect::<HashSet<&str>>();
+
+    let mut results = Vec::with_capacity(variants.len());
+    for variant in variants {
+        let variant_rows = rows
+            .iter()
+            .filter(|row| row.variant_id == variant.id)
+            .collect::<Vec<&UnitMetric>>();
+        let mut exposures = 0;
+        let mut conversions = HashMap::new();
+        for row in variant_rows.iter() {
+            match row.event_type.parse::<MetricEventType>() {
+                Ok(MetricEventType::Exposure) => exposures += row.units,
+                Ok(MetricEventType::Conversion) => {
+                    conversions.insert(row.metric.as_str(), *row);
+                }
+                Err(_) => {
+                    log::error!("unknown metric event type {}", row.event_type);
+                    return Err(unexpected_error!("Something went wrong"));
+                }
+            }
+        }
+        let metric_results = metrics
+            .iter()
+            .map(|metric| {
+                let row = conversions.get(metric);
+                let count = row.map_or(0, |row| row.units);
+                let value_count = row.map_or(0, |row| row.value_count);
+                let value_sum = row.map_or(0.0, |row| row.value_sum);
+                let value_sum_squares = row.map_or(0.0, |row| row.value_sum_squares);
+                let valued = valued_metrics.contains(metric);
+                MetricResult {
+                    metric: metric.to_string(),
+                    conversions: count,
+                    conversion_rate: (exposures > 0)
+                        .then(|| count as f64 / exposures as f64),
+                    conversion_rate_interval: None,
+                    value_count,
+                    mean: (valued && exposures > 0).then(|| value_sum / exposures as f64),
+                    standard_deviation: (valued && exposures > 1).then(|| {
+                        standard_deviation(value_sum, value_sum_squares, exposures)
+                    }),
+                    mean_interval: None,
+                    conversion_test: None,
+                    mean_test: None,
+                }
+            })
+            .collect();
+        results.push(VariantResult {
+            variant_id: variant.id.clone(),
+            variant_type: variant.variant_type.clone(),
+            exposures,
+            metrics: metric_results,
+            last_event_at: variant_rows.iter().map(|row| row.last_event_at).max(),
+        });
+    }
+    Ok(results)
+}
+
+/// Results of the holdout `holdout_id` and of each of `populations`, tested
+/// against the holdout.
+fn against_holdout(
+    holdout_id: &str,
+    populations: &[Variant],
+    rows: &[UnitMetric],
+) -> superposition::Result<(PopulationResult, Vec<VariantResult>)> {
+    // the holdout is the baseline the populations are tested against
+    let mut compared = vec![Variant {
+        id: holdout_id.to_string(),
+        variant_type: VariantType::CONTROL,
+        context_id: None,
+        override_id: None,
+        overrides: Default::default(),
+        awaiting_ramp: false,
+    }];
+    compared.extend(populations.iter().map(|population| Variant {
+        variant_type: VariantType::EXPERIMENTAL,
+        ..population.clone()
+    }));
+    let mut results = variant_results(&compared, rows)?;
+    compare_with_control(&mut results);
+    let mut results = results.into_iter();
+    let holdout = results
+        .next()
+        .ok_or_else(|| unexpected_error!("Something went wrong"))?;
+    let populations = results
+        .zip(populations)
+        .map(|(result, population)| VariantResult {
+            variant_type: population.variant_type.clone(),
+            ..result
+        })
+        .collect();
+    Ok((holdout.into(), populations))
+}
+
+/// Compares the holdout of experiment `experiment_id` with the units exposed
+/// to each of its `variants`.
+pub fn holdout_results(
+    experiment_id: i64,
+    variants: &[Variant],
+    rows: &[UnitMetric],
+) -> superposition::Result<Option<HoldoutResults>> {
+    let holdout_id = holdout_variant_id(experiment_id);
+    if !rows.iter().any(|row| row.variant_id == holdout_id) {
+        return Ok(None);
+    }
+    let (holdout, variants) = against_holdout(&holdout_id, variants, rows)?;
+    Ok(Some(HoldoutResults { holdout, variants }))
+}
+
+/// Compares the tenant's global holdout with the units outside it, from the
+/// rows of `GLOBAL_HOLDOUT_EXPERIMENT_ID`.
+pub fn global_holdout_results(
+    holdout_percentage: i32,
+    rows: &[UnitMetric],
+) -> superposition::Result<GlobalHoldoutResults> {
+    let exposed = Variant {
+        id: global_exposed_variant_id(),
+        variant_type: VariantType::EXPERIMENTAL,
+        context_id: None,
+        override_id: None,
+        overrides: Default::default(),
+        awaiting_ramp: false,
+    };
+    let holdout_id = holdout_variant_id(GLOBAL_HOLDOUT_EXPERIMENT_ID);
+    let (holdout, mut exposed) = against_holdout(&holdout_id, &[exposed], rows)?;
+    let exposed = exposed
+        .pop()
+        .ok_or_else(|| unexpected_error!("Something went wrong"))?;
+    Ok(GlobalHoldoutResults {
+        confidence_level: CONFIDENCE_LEVEL,
+        holdout_percentage,
+        holdout,
+        exposed: exposed.into(),
+    })
+}
+
+/// Exposed units of the `variants` serving traffic, and of the holdout when
+/// `holdout` has its units and the buckets held out, with the share of traffic
+/// each population is expected to get: the traffic of the experiment for
+/// every variant. `results` are in the order of `variants`.
+pub fn expected_allocation(
+    variants: &[Variant],
+    results: &[VariantResult],
+    traffic_percentage: i32,
+    holdout: Option<(i64, i32)>,
+) -> (Vec<i64>, Vec<f64>) {
+    variants
+        .iter()
+        .zip(results)
+        .filter(|(variant, _)| !variant.awaiting_ramp)
+        .map(|(_, result)| (result.exposures, f64::from(traffic_percentage)))
+        .chain(holdout.map(|(units, buckets)| (units, f64::from(buckets))))
+        .unzip()
+}
@@ -0,0 +1,855 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use actix_web::{rt, web};
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use diesel::{
+    AsChangeset, Connection, ExpressionMethods, PgConnection, QueryDsl, RunQueryDsl,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use service_utils::{
+    db::{
+        audit::AuditActor,
+        pgschema_manager::{PgSchemaConnection, PgSchemaManager},
+        utils::tenant_namespaces,
+    },
+    helpers::{get_from_env_or_default, get_from_env_unsafe},
+    rbac::Authorizer,
+};
+use superposition_macros::{bad_argument, response_error, unexpected_error};
+use superposition_types::{hashing::hash_json, result as superposition, User};
+
+use super::{
+    cac::{CacBackend, CacCaller, CacClient, CacError},
+    helpers::extract_variant_ids,
+    history::record_event,
+    types::{ContextAction, ContextBulkResponse, ExperimentEventType, Variant},
+};
+use crate::db::{
+    models::{CacSaga, Experiment, ExperimentStatusType},
+    schema::{cac_sagas, experiment_approvals, experiments},
+};
+
+/// How long the request which started a saga has to finish it before the
+/// reconciler may pick it up.
+const SAGA_LEASE_SECS: i64 = 300;
+const SAGA_BATCH_SIZE: i64 = 20;
+/// User the reconciler's own CAC writes are attributed to. Resumed sagas are
+/// attributed to the user who started them.
+const RECONCILER_USER: &str = "experiment-reconciler";
+
+#[derive(
+    Copy, Clone, Debug, PartialEq, strum_macros::Display, strum_macros::EnumString,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum SagaKind {
+    Create,
+    Conclude,
+    UpdateOverrides,
+    Update,
+}
+
+#[derive(
+    Copy, Clone, Debug, PartialEq, strum_macros::Display, strum_macros::EnumString,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum SagaStatus {
+    Pending,
+    Applied,
+    Compensated,
+    Failed,
+}
+
+/// CAC writes of an experiment action. Default configs are written one by one
+/// before the context operations, which CAC applies in a single transaction.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct CacPlan {
+    #[serde(default)]
+    pub default_configs: Map<String, Value>,
+    #[serde(default)]
+    pub operations: Vec<ContextAction>,
+}
+
+/// Change made to the experiment row once the CAC writes of a saga went
+/// through.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExperimentChange {
+    Conclude {
+        chosen_variant: String,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    /// Variants get the ids of the contexts created for them, in order.
+    UpdateOverrides {
+        variants: Vec<Variant>,
+        override_keys: Vec<String>,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    /// Variants without a context id get the ids of the contexts created for
+    /// them, in order. `fields` are the ones the update changed.
+    Update {
+        name: Option<String>,
+        description: Option<String>,
+        context: Option<Value>,
+        variants: Vec<Variant>,
+        override_keys: Vec<String>,
+        fields: Vec<String>,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = experiments)]
+struct ExperimentChangeset {
+    name: Option<String>,
+    description: Option<String>,
+    context: Option<Value>,
+    variants: Value,
+    override_keys: Vec<String>,
+    last_modified: DateTime<Utc>,
+    last_modified_by: String,
+}
+
+fn to_json<T: Serialize>(value: &T) -> superposition::Result<Value> {
+    serde_json::to_value(value).map_err(|err| {
+        log::error!("failed to serialize saga: {err}");
+        unexpected_error!("Something went wrong")
+    })
+}
+
+/// Records the CAC writes an action is about to make. The saga is leased to
+/// the caller, which settles it once the writes and the experiment row are
+/// done. An experiment has at most one saga in progress.
+pub fn begin_saga(
+    conn: &mut PgConnection,
+    experiment_id: i64,
+    kind: SagaKind,
+    plan: &CacPlan,
+    compensation: &CacPlan,
+    change: Option<&ExperimentChange>,
+    actor: &AuditActor,
+) -> superposition::Result<CacSaga> {
+    let in_progress: i64 = cac_sagas::table
+        .filter(cac_sagas::experiment_id.eq(experiment_id))
+        .filter(cac_sagas::status.eq(SagaStatus::Pending.to_string()))
+        .count()
+        .get_result(conn)?;
+    if in_progress > 0 {
+        return Err(bad_argument!(
+            "experiment {} has a change in progress, try again once it is done",
+            experiment_id
+        ));
+    }
+    let saga = diesel::insert_into(cac_sagas::table)
+        .values((
+            cac_sagas::experiment_id.eq(experiment_id),
+            cac_sagas::kind.eq(kind.to_string()),
+            cac_sagas::plan.eq(to_json(plan)?),
+            cac_sagas::compensation.eq(to_json(compensation)?),
+            cac_sagas::payload.eq(change.map(to_json).transpose()?),
+            cac_sagas::next_attempt_at
+                .eq(Utc::now() + chrono::Duration::seconds(SAGA_LEASE_SECS)),
+            cac_sagas::created_by.eq(&actor.user_email),
+            cac_sagas::request_id.eq(&actor.request_id),
+        ))
+        .get_result::<CacSaga>(conn)?;
+    Ok(saga)
+}
+
+/// Settles a pending saga. Fails with `NotFound` once the saga was settled by
+/// someone else, like a create the reconciler gave up on after its lease, so
+/// the caller's transaction is rolled back.
+pub fn settle_saga(
+    conn: &mut PgConnection,
+    saga_id: uuid::Uuid,
+    status: SagaStatus,
+    error: Option<String>,
+) -> diesel::QueryResult<()> {
+    let settled = diesel::update(cac_sagas::table.find(saga_id))
+        .filter(cac_sagas::status.eq(SagaStatus::Pending.to_string()))
+        .set((
+            cac_sagas::status.eq(status.to_string()),
+            cac_sagas::last_error.eq(error),
+            cac_sagas::last_modified_at.eq(Utc::now()),
+        ))
+        .execute(conn)?;
+    if settled == 0 {
+        return Err(diesel::result::Error::NotFound);
+    }
+    Ok(())
+}
+
+/// Settles the saga of a request once its experiment change is made, in the
+/// same transaction.
+pub fn settle_applied(
+    conn: &mut PgConnection,
+    saga: &CacSaga,
+) -> superposition::Result<()> {
+    settle_saga(conn, saga.id, SagaStatus::Applied, None).map_err(|err| match err {
+        diesel::result::Error::NotFound => response_error!(
+            reqwest::StatusCode::CONFLICT,
+            format!(
+                "the change to experiment {} outlived its lease and was taken over by the reconciler",
+                saga.experiment_id
+            )
+        ),
+        err => err.into(),
+    })
+}
+
+/// Sends the writes of `plan`. A retried plan leaves out the context deletes
+/// and moves CAC already applied, the other writes are idempotent.
+pub async fn apply_plan(
+    cac: &CacClient,
+    plan: &CacPlan,
+    retry: bool,
+) -> Result<(Vec<ContextBulkResponse>, Option<String>), CacError> {
+    for (key, value) in plan.default_configs.iter() {
+        cac.put_default_config(key, value).await?;
+    }
+    let mut operations = Vec::with_capacity(plan.operations.len());
+    for operation in plan.operations.iter() {
+        let applied = match operation {
+            ContextAction::DELETE(context_id) | ContextAction::MOVE((context_id, _))
+                if retry =>
+            {
+                !cac.context_exists(context_id).await?
+            }
+            _ => false,
+        };
+        if !applied {
+            operations.push(operation.clone());
+        }
+    }
+    if operations.is_empty() {
+        return Ok((Vec::new(), None));
+    }
+    cac.bulk_operations(&operations).await
+}
+
+/// Makes the CAC writes of `plan` and the experiment change `f` in one
+/// transaction when the CAC backend joins it, no saga is needed then. `None`
+/// when the backend does not.
+pub fn apply_atomically<T, F>(
+    conn: &mut PgSchemaConnection,
+    cac: &CacClient,
+    plan: &CacPlan,
+    actor: &AuditActor,
+    f: F,
+) -> Option<superposition::Result<(T, Option<String>)>>
+where
+    F: FnOnce(
+        &mut PgSchemaConnection,
+        Vec<ContextBulkResponse>,
+    ) -> superposition::Result<T>,
+{
+    if !cac.joins_transactions() {
+        return None;
+    }
+    Some(actor.transaction(conn, |transaction_conn| {
+        let (responses, config_version) =
+            cac.apply_in_transaction(transaction_conn, plan)?;
+        let value = f(transaction_conn, responses)?;
+        Ok::<(T, Option<String>), superposition::AppError>((value, config_version))
+    }))
+}
+
+/// Gives variants the ids of the contexts created for them, in order.
+pub fn assign_variant_contexts<'a>(
+    variants: impl IntoIterator<Item = &'a mut Variant>,
+    responses: Vec<ContextBulkResponse>,
+) {
+    let created_contexts = responses.into_iter().filter_map(|item| match item {
+        ContextBulkResponse::PUT(context) => Some(context),
+        _ => None,
+    });
+    for (variant, context) in variants.into_iter().zip(created_contexts) {
+        variant.context_id = Some(context.context_id);
+        variant.override_id = Some(context.override_id);
+    }
+}
+
+/// Makes the experiment row change of a saga, `responses` are those of the
+/// bulk call which applied it.
+pub fn apply_experiment_change(
+    conn: &mut PgConnection,
+    experiment_id: i64,
+    change: ExperimentChange,
+    responses: Vec<ContextBulkResponse>,
+    user_email: &str,
+) -> superposition::Result<Experiment> {
+    let now = Utc::now();
+    let (experiment, event_type, details, reason) = match change {
+        ExperimentChange::Conclude {
+            chosen_variant,
+            reason,
+        } => {
+            let experiment = diesel::update(experiments::table.find(experiment_id))
+                .set((
+                    experiments::status.eq(ExperimentStatusType::CONCLUDED),
+                    experiments::last_modified.eq(now),
+                    experiments::last_modified_by.eq(user_email),
+                    experiments::chosen_variant.eq(Some(chosen_variant.clone())),
+                ))
+                .get_result::<Experiment>(conn)?;
+            let details = json!({ "chosen_variant": chosen_variant });
+            (experiment, ExperimentEventType::Concluded, details, reason)
+        }
+        ExperimentChange::UpdateOverrides {
+            mut variants,
+            override_keys,
+            reason,
+        } => {
+            assign_variant_contexts(&mut variants, responses);
+            clear_approvals(conn, experiment_id)?;
+            let experiment = diesel::update(experiments::table.find(experiment_id))
+                .set((
+                    experiments::variants.eq(to_json(&variants)?),
+                    experiments::override_keys.eq(override_keys.clone()),
+                    experiments::last_modified.eq(now),
+                    experiments::last_modified_by.eq(user_email),
+                ))
+                .get_result::<Experiment>(conn)?;
+            let details = json!({ "override_keys": override_keys });
+            (
+                experiment,
+                ExperimentEventType::OverridesUpdated,
+                details,
+                reason,
+            )
+        }
+        ExperimentChange::Update {
+            name,
+            description,
+            context,
+            mut variants,
+            override_keys,
+            fields,
+            reason,
+        } => {
+            assign_variant_contexts(
+                variants
+                    .iter_mut()
+                    .filter(|variant| variant.context_id.is_none()),
+                responses,
+            );
+            if fields
+                .iter()
+                .any(|field| field == "context" || field == "variants")
+            {
+                clear_approvals(conn, experiment_id)?;
+            }
+            let changeset = ExperimentChangeset {
+                name,
+                description,
+                context,
+                variants: to_json(&variants)?,
+                override_keys,
+                last_modified: now,
+                last_modified_by: user_email.to_string(),
+            };
+            let experiment = diesel::update(experiments::table.find(experiment_id))
+                .set(changeset)
+                .get_result::<Experiment>(conn)?;
+            let variant_ids = variants
+                .iter()
+                .map(|variant| variant.id.clone())
+                .collect::<Vec<String>>();
+            let details = json!({ "fields": fields, "variants": variant_ids });
+            (experiment, ExperimentEventType::Updated, details, reason)
+        }
+    };
+    record_event(conn, experiment_id, event_type, details, reason, user_email)?;
+    Ok(experiment)
+}
+
+/// Approvals are given again once the variants or the context that were
+/// approved changed.
+fn clear_approvals(
+    conn: &mut PgConnection,
+    experiment_id: i64,
+) -> superposition::Result<()> {
+    diesel::delete(
+        experiment_approvals::table
+            .filter(experiment_approvals::experiment_id.eq(experiment_id)),
+    )
+    .execute(conn)?;
+    Ok(())
+}
+
+/// Default config writes of a saga are undone when its context operations are
+/// rejected, CAC applied none of those.
+async fn compensate_rejection(
+    conn: &mut PgConnection,
+    cac: &CacClient,
+    saga: &CacSaga,
+    compensation: &CacPlan,
+    error: String,
+) -> superposition::Result<()> {
+    let restore = CacPlan {
+        default_configs: compensation.default_configs.clone(),
+        operations: Vec::new(),
+    };
+    let status = match apply_plan(cac, &restore, false).await {
+        Ok(_) => SagaStatus::Compensated,
+        Err(restore_error) => {
+            log::error!(
+                "failed to restore default configs of saga {}: {restore_error:?}",
+                saga.id
+            );
+            SagaStatus::Failed
+        }
+    };
+    settle_saga(conn, saga.id, status, Some(error))?;
+    Ok(())
+}
+
+/// Settles a saga whose CAC writes failed while serving a request, returning
+/// the error for the caller. Creates are given up and their contexts, if any
+/// were written, are removed by the reconciler. Other actions are retried by
+/// the reconciler when CAC did not answer.
+pub async fn fail_saga(
+    conn: &mut PgConnection,
+    cac: &CacClient,
+    saga: &CacSaga,
+    compensation: &CacPlan,
+    error: CacError,
+) -> superposition::AppError {
+    let settled = match &error {
+        CacError::Rejected(rejection) => {
+            compensate_rejection(conn, cac, saga, compensation, rejection.to_string())
+                .await
+        }
+        CacError::Unavailable(reason) if saga.kind == SagaKind::Create.to_string() => {
+            settle_saga(conn, saga.id, SagaStatus::Failed, Some(reason.clone()))
+                .map_err(superposition::AppError::from)
+        }
+        CacError::Unavailable(reason) => diesel::update(cac_sagas::table.find(saga.id))
+            .set((
+                cac_sagas::attempts.eq(saga.attempts + 1),
+                cac_sagas::next_attempt_at.eq(Utc::now()),
+                cac_sagas::last_error.eq(reason),
+                cac_sagas::last_modified_at.eq(Utc::now()),
+            ))
+            .execute(conn)
+            .map(|_| ())
+            .map_err(superposition::AppError::from),
+    };
+    if let Err(err) = settled {
+        log::error!("failed to settle saga {}: {err}", saga.id);
+    }
+    match error {
+        CacError::Unavailable(_) if saga.kind != SagaKind::Create.to_string() => {
+            response_error!(
+                reqwest::StatusCode::SERVICE_UNAVAILABLE,
+                format!(
+                    "CAC could not be reached, the change to experiment {} will be retried",
+                    saga.experiment_id
+                )
+            )
+        }
+        error => error.into(),
+    }
+}
+
+/// Removes the contexts written by a create whose experiment row could not be
+/// inserted.
+pub async fn compensate_create(
+    conn: &mut PgConnection,
+    cac: &CacClient,
+    saga: &CacSaga,
+    context_ids: Vec<String>,
+    error: String,
+) {
+    let compensation = CacPlan {
+        default_configs: Map::new(),
+        operations: context_ids.into_iter().map(ContextAction::DELETE).collect(),
+    };
+    // left for the reconciler to remove when this fails
+    let status = match apply_plan(cac, &compensation, true).await {
+        Ok(_) => SagaStatus::Compensated,
+        Err(err) => {
+            log::error!("failed to compensate saga {}: {err:?}", saga.id);
+            SagaStatus::Failed
+        }
+    };
+    if let Err(err) = settle_saga(conn, saga.id, status, Some(error)) {
+        log::error!("failed to settle saga {}: {err}", saga.id);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ReconcilePolicy {
+    pub interval: Duration,
+    /// Attempts after which a saga is marked failed
+    pub max_attempts: i32,
+    /// Wait before the first retry, doubled on every retry after it
+    pub retry_base: Duration,
+    pub max_retry_wait: Duration,
+    /// Authorization header value the reconciler calls CAC with
+    pub authorization: Option<String>,
+}
+
+impl ReconcilePolicy {
+    pub fn from_env() -> Self {
+        ReconcilePolicy {
+            interval: Duration::from_secs(get_from_env_or_default(
+                "EXPERIMENT_RECONCILE_INTERVAL_SECS",
+                60,
+            )),
+            max_attempts: get_from_env_or_default("EXPERIMENT_SAGA_MAX_ATTEMPTS", 10),
+            retry_base: Duration::from_secs(get_from_env_or_default(
+                "EXPERIMENT_SAGA_RETRY_BASE_SECS",
+                30,
+            )),
+            max_retry_wait: Duration::from_secs(60 * 60),
+            authorization: get_from_env_unsafe::<String>("CAC_SERVICE_AUTHORIZATION")
+                .ok()
+                .filter(|authorization| !authorization.is_empty()),
+        }
+    }
+
+    /// Wait before the next attempt of a saga which failed `attempts` times.
+    pub fn retry_wait(&self, attempts: i32) -> Duration {
+        let exponent = attempts.clamp(1, 16) as u32 - 1;
+        self.retry_base
+            .saturating_mul(2u32.pow(exponent))
+            .min(self.max_retry_wait)
+    }
+}
+
+/// Picks the pending sagas which are due and leases them, so other replicas
+/// leave them alone while they are retried.
+fn claim_due_sagas(conn: &mut PgConnection) -> anyhow::Result<Vec<CacSaga>> {
+    conn.transaction::<_, anyhow::Error, _>(|transaction_conn| {
+        let due = cac_sagas::table
+            .filter(cac_sagas::status.eq(SagaStatus::Pending.to_string()))
+            .filter(cac_sagas::next_attempt_at.le(Utc::now()))
+            .order(cac_sagas::next_attempt_at.asc())
+            .limit(SAGA_BATCH_SIZE)
+            .for_update()
+            .skip_locked()
+            .load::<CacSaga>(transaction_conn)?;
+        let ids = due.iter().map(|saga| saga.id).collect::<Vec<uuid::Uuid>>();
+        diesel::update(cac_sagas::table)
+            .filter(cac_sagas::id.eq_any(ids))
+            .set(
+                cac_sagas::next_attempt_at
+                    .eq(Utc::now() + chrono::Duration::seconds(SAGA_LEASE_SECS)),
+            )
+            .execute(transaction_conn)?;
+        Ok(due)
+    })
+}
+
+fn record_retry(
+    conn: &mut PgConnection,
+    saga: &CacSaga,
+    error: String,
+    policy: &ReconcilePolicy,
+) -> anyhow::Result<()> {
+    let attempts = saga.attempts + 1;
+    let now = Utc::now();
+    let (status, next_attempt_at) = if attempts >= policy.max_attempts {
+        log::error!(
+            "giving up on saga {} of experiment {} after {attempts} attempts: {error}",
+            saga.id,
+            saga.experiment_id
+        );
+        (SagaStatus::Failed, now)
+    } else {
+        (
+            SagaStatus::Pending,
+            now + chrono::Duration::from_std(policy.retry_wait(attempts))?,
+        )
+    };
+    diesel::update(cac_sagas::table.find(saga.id))
+        .set((
+            cac_sagas::status.eq(status.to_string()),
+            cac_sagas::attempts.eq(attempts),
+            cac_sagas::next_attempt_at.eq(next_attempt_at),
+            cac_sagas::last_error.eq(error),
+            cac_sagas::last_modified_at.eq(now),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+fn system_user(email: &str) -> User {
+    User {
+        email: email.to_string(),
+        username: email.to_string(),
+        auth_token: String::new(),
+        auth_type: String::new(),
+    }
+}
+
+async fn with_conn<T, F>(
+    schema_manager: &PgSchemaManager,
+    namespace: &str,
+    f: F,
+) -> anyhow::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut PgConnection) -> anyhow::Result<T> + Send + 'static,
+{
+    let (manager, namespace) = (schema_manager.clone(), namespace.to_string());
+    web::block(move || {
+        let mut conn = manager.get_conn(namespace)?;
+        f(&mut conn)
+    })
+    .await
+    .map_err(|e| anyhow!("{e}"))?
+}
+
+/// Finishes a saga left pending by its request. Creates are given up, the
+/// contexts they may have written are removed by the sweep of failed sagas.
+async fn resume_saga(
+    schema_manager: &PgSchemaManager,
+    namespace: &str,
+    cac: &CacClient,
+    saga: CacSaga,
+    policy: &ReconcilePolicy,
+) -> anyhow::Result<()> {
+    let saga_id = saga.id;
+    if saga.kind == SagaKind::Create.to_string() {
+        log::warn!(
+            "abandoning create saga {saga_id} of experiment {}",
+            saga.experiment_id
+        );
+        return with_conn(schema_manager, namespace, move |conn| {
+            settle_saga(conn, saga_id, SagaStatus::Failed, saga.last_error.clone())?;
+            Ok(())
+        })
+        .await;
+    }
+    let plan: CacPlan = serde_json::from_value(saga.plan.clone())?;
+    let compensation: CacPlan = serde_json::from_value(saga.compensation.clone())?;
+    let change: ExperimentChange = serde_json::from_value(
+        saga.payload
+            .clone()
+            .ok_or_else(|| anyhow!("saga {saga_id} has no experiment change"))?,
+    )?;
+    let cac = cac.for_request(
+        saga.request_id.clone().unwrap_or_default(),
+        system_user(&saga.created_by),
+    );
+
+    match apply_plan(&cac, &plan, true).await {
+        Ok((responses, _)) => {
+            with_conn(schema_manager, namespace, move |conn| {
+                let actor = AuditActor {
+                    user_email: saga.created_by.clone(),
+                    request_id: saga.request_id.clone().unwrap_or_default(),
+                };
+                actor
+                    .transaction(conn, |transaction_conn| {
+                        apply_experiment_change(
+                            transaction_conn,
+                            saga.experiment_id,
+                            change,
+                            responses,
+                            &saga.created_by,
+                        )?;
+                        settle_saga(
+                            transaction_conn,
+                            saga_id,
+                            SagaStatus::Applied,
+                            None,
+                        )?;
+                        Ok::<(), superposition::AppError>(())
+                    })
+                    .map_err(|e| anyhow!("{e}"))?;
+                log::info!(
+                    "resumed saga {saga_id} of experiment {}",
+                    saga.experiment_id
+                );
+                Ok(())
+            })
+            .await
+        }
+        Err(CacError::Rejected(rejection)) => {
+            let restore = CacPlan {
+                default_configs: compensation.default_configs,
+                operations: Vec::new(),
+            };
+            let status = match apply_plan(&cac, &restore, false).await {
+                Ok(_) => SagaStatus::Compensated,
+                Err(_) => SagaStatus::Failed,
+            };
+            log::error!("CAC rejected saga {saga_id}: {rejection}");
+            with_conn(schema_manager, namespace, move |conn| {
+                settle_saga(conn, saga_id, status, Some(rejection.to_string()))?;
+                Ok(())
+            })
+            .await
+        }
+        Err(CacError::Unavailable(reason)) => {
+            let policy = policy.clone();
+            with_conn(schema_manager, namespace, move |conn| {
+                record_retry(conn, &saga, reason, &policy)
+            })
+            .await
+        }
+    }
+}
+
+/// Ids of the `variantIds` contexts the writes of `plan` create.
+fn experiment_contexts_of(plan: &CacPlan) -> HashSet<String> {
+    plan.operations
+        .iter()
+        .filter_map(|operation| match operation {
+            ContextAction::PUT(put) => Some(&put.context),
+            ContextAction::MOVE((_, move_req)) => Some(&move_req.context),
+            ContextAction::DELETE(_) => None,
+        })
+        .map(|context| Value::Object(context.clone()))
+        .filter(|condition| !extract_variant_ids(condition).is_empty())
+        .map(|condition| hash_json(&condition))
+        .collect()
+}
+
+/// Deletes the experiment contexts the CAC writes of failed sagas may have
+/// left behind, once their request has had a lease to give up on them. Only
+/// the contexts a saga's plan creates are looked at, and those the variants
+/// of a running experiment point to are kept.
+async fn sweep_failed_sagas(
+    schema_manager: &PgSchemaManager,
+    namespace: &str,
+    cac: &CacClient,
+) -> anyhow::Result<usize> {
+    let settled_before = Utc::now() - chrono::Duration::seconds(SAGA_LEASE_SECS);
+    let (failed, in_use) = with_conn(schema_manager, namespace, move |conn| {
+        let failed = cac_sagas::table
+            .filter(cac_sagas::status.eq(SagaStatus::Failed.to_string()))
+            .filter(cac_sagas::swept_at.is_null())
+            .filter(cac_sagas::last_modified_at.le(settled_before))
+            .order(cac_sagas::last_modified_at.asc())
+            .limit(SAGA_BATCH_SIZE)
+            .load::<CacSaga>(conn)?;
+        let experiment_ids = failed
+            .iter()
+            .map(|saga| saga.experiment_id)
+            .collect::<Vec<i64>>();
+        let in_use = experiments::table
+            .filter(experiments::id.eq_any(experiment_ids))
+            .filter(experiments::status.ne(ExperimentStatusType::CONCLUDED))
+            .select(experiments::variants)
+            .load::<Value>(conn)?
+            .into_iter()
+            .filter_map(|variants| serde_json::from_value::<Vec<Variant>>(variants).ok())
+            .flatten()
+            .filter_map(|variant| variant.context_id)
+            .collect::<HashSet<String>>();
+        Ok((failed, in_use))
+    })
+    .await?;
+
+    let mut removed = 0;
+    for saga in failed {
+        let saga_id = saga.id;
+        let plan: CacPlan = serde_json::from_value(saga.plan)?;
+        let orphans = experiment_contexts_of(&plan)
+            .into_iter()
+            .filter(|context_id| !in_use.contains(context_id))
+            .map(ContextAction::DELETE)
+            .collect::<Vec<ContextAction>>();
+        let sweep = CacPlan {
+            default_configs: Map::new(),
+            operations: orphans,
+        };
+        let (responses, _) = apply_plan(cac, &sweep, true).await.map_err(|e| {
+            anyhow!("failed to delete the contexts of saga {saga_id}: {e:?}")
+        })?;
+        removed += responses.len();
+        with_conn(schema_manager, namespace, move |conn| {
+            diesel::update(cac_sagas::table.find(saga_id))
+                .set(cac_sagas::swept_at.eq(Utc::now()))
+                .execute(conn)?;
+            Ok(())
+        })
+        .await?;
+    }
+    Ok(removed)
+}
+
+async fn reconcile(
+    schema_manager: &PgSchemaManager,
+    namespace: &str,
+    cac: &CacClient,
+    policy: &ReconcilePolicy,
+) -> anyhow::Result<()> {
+    let due = with_conn(schema_manager, namespace, claim_due_sagas).await?;
+    for saga in due {
+        let saga_id = saga.id;
+        if let Err(e) = resume_saga(schema_manager, namespace, cac, saga, policy).await {
+            log::error!("failed to resume saga {saga_id}: {e}");
+        }
+    }
+    let removed = sweep_failed_sagas(schema_manager, namespace, cac).await?;
+    if removed > 0 {
+        log::info!(
+            "removed {removed} experiment contexts of failed sagas for {namespace}"
+        );
+    }
+    Ok(())
+}
+
+/// Background task finishing the experiment actions whose CAC writes did not
+/// go through and removing the experiment contexts failed ones left in CAC.
+pub async fn run_experiment_reconciler(
+    schema_manager: PgSchemaManager,
+    tenants: Arc<RwLock<HashSet<String>>>,
+    enable_tenant_and_scope: bool,
+    cac_backend: Arc<dyn CacBackend>,
+    policy: ReconcilePolicy,
+) {
+    let mut interval = rt::time::interval(policy.interval);
+    loop {
+        interval.tick().await;
+        let targets = if enable_tenant_and_scope {
+            match tenants.read() {
+                Ok(tenants) => tenants
+                    .iter()
+                    .map(|tenant| {
+                        let [_, experimentation_schema] = tenant_namespaces(tenant);
+                        (Some(tenant.clone()), experimentation_schema)
+                    })
+                    .collect::<Vec<(Option<String>, String)>>(),
+                Err(e) => {
+                    log::error!("failed to read the active tenants: {e}");
+                    continue;
+                }
+            }
+        } else {
+            vec![(None, "cac_v1".to_string())]
+        };
+        for (tenant, namespace) in targets {
+            let caller = CacCaller {
+                tenant,
+                config_tags: None,
+                request_id: String::new(),
+                user: system_user(RECONCILER_USER),
+                authorization: policy.authorization.clone(),
+                // resumed changes were authorized when they were requested
+                authorizer: Authorizer::new(
+                    RECONCILER_USER.to_string(),
+                    Vec::new(),
+                    false,
+                ),
+            };
+            let cac = CacClient::new(cac_backend.clone(), caller);
+            if let Err(e) = reconcile(&schema_manager, &namespace, &cac, &policy).await {
+                log::error!("experiment reconciliation failed for {namespace}: {e}");
+            }
+        }
+    }
+}
@@ -0,0 +1,417 @@
+use std::f64::consts::{PI, SQRT_2};
+
+use super::types::{
+    Interval, MetricResult, SampleRatioCheck, SignificanceTest, VariantResult,
+    VariantType,
+};
+
+/// Confidence level of the intervals, tests below `1 - CONFIDENCE_LEVEL` are
+/// significant.
+pub const CONFIDENCE_LEVEL: f64 = 0.95;
+/// p-value under which the exposures are off the traffic split. Kept low as
+/// the check is made every time results are read.
+pub const SAMPLE_RATIO_THRESHOLD: f64 = 0.001;
+
+const MAX_ITERATIONS: usize = 300;
+const EPSILON: f64 = 1e-14;
+const TINY: f64 = 1e-300;
+const LANCZOS: [f64; 9] = [
+    0.9999999999998099,
+    676.5203681218851,
+    -1259.139216722403,
+    771.3234287776531,
+    -176.6150291621406,
+    12.50734327868691,
+    -0.1385710952657201,
+    9.984369578019572e-6,
+    1.505632735149312e-7,
+];
+
+/// Complementary error function, with a relative error below 1.2e-7.
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let polynomial = -1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98
+                                + t * (1.488_515_87
+                                    + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+    let result = t * (-z * z + polynomial).exp();
+    if x >= 0.0 {
+        result
+    } else {
+        2.0 - result
+    }
+}
+
+fn ln_gamma(x: f64) -> f64 {
+    if x < 0.5 {
+        // reflection formula
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = LANCZOS
+        .iter()
+        .enumerate()
+        .skip(1)
+        .fold(LANCZOS[0], |sum, (i, c)| sum + c / (x + i as f64));
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+/// Continued fraction of the incomplete beta function, by Lentz's method.
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    let clamp = |value: f64| if value.abs() < TINY { TINY } else { value };
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut h = d;
+    for m in 1..=MAX_ITERATIONS {
+        let m = m as f64;
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / clamp(1.0 + even * d);
+        c = clamp(1.0 + even / c);
+        h *= d * c;
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / clamp(1.0 + odd * d);
+        c = clamp(1.0 + odd / c);
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    h
+}
+
+fn regularized_incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln())
+            .exp();
+    // the continued fraction converges fast on this side of the mean
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// Regularized upper incomplete gamma function Q(a, x).
+fn regularized_upper_gamma(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let front = (-x + a * x.ln() - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut denominator = a;
+        for _ in 0..MAX_ITERATIONS {
+            denominator += 1.0;
+            term *= x / denominator;
+            sum += term;
+            if term.abs() < sum.abs() * EPSILON {
+                break;
+            }
+        }
+        1.0 - front * sum
+    } else {
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / TINY;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..=MAX_ITERATIONS {
+            let i = i as f64;
+            let an = -i * (i - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = b + an / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < EPSILON {
+                break;
+            }
+        }
+        front * h
+    }
+}
+
+pub fn normal_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / SQRT_2)
+}
+
+pub fn student_t_cdf(t: f64, degrees_of_freedom: f64) -> f64 {
+    let x = degrees_of_freedom / (degrees_of_freedom + t * t);
+    let tail = 0.5 * regularized_incomplete_beta(degrees_of_freedom / 2.0, 0.5, x);
+    if t > 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// Probability of a chi-square statistic of at least `x`.
+pub fn chi_square_survival(x: f64, degrees_of_freedom: f64) -> f64 {
+    regularized_upper_gamma(degrees_of_freedom / 2.0, x / 2.0)
+}
+
+/// Point above the median where a symmetric `cdf` reaches `p`, by bisection.
+fn upper_quantile(cdf: impl Fn(f64) -> f64, p: f64) -> f64 {
+    let (mut low, mut high) = (0.0, 1e4);
+    for _ in 0..MAX_ITERATIONS {
+        let mid = (low + high) / 2.0;
+        if cdf(mid) < p {
+            low = mid;
+        } else {
+            high = mid;
+        }
+        if high - low < 1e-10 {
+            break;
+        }
+    }
+    (low + high) / 2.0
+}
+
+fn critical_value(degrees_of_freedom: Option<f64>) -> f64 {
+    let p = 1.0 - (1.0 - CONFIDENCE_LEVEL) / 2.0;
+    match degrees_of_freedom {
+        Some(df) => upper_quantile(|t| student_t_cdf(t, df), p),
+        None => upper_quantile(normal_cdf, p),
+    }
+}
+
+fn interval(center: f64, half_width: f64) -> Interval {
+    Interval {
+        lower: center - half_width,
+        upper: center + half_width,
+    }
+}
+
+/// Successes out of trials, like converted units out of exposed units.
+#[derive(Clone, Copy, Debug)]
+pub struct Proportion {
+    pub successes: i64,
+    pub trials: i64,
+}
+
+impl Proportion {
+    /// `None` without trials or with more successes than trials, where the
+    /// count isn't a proportion anymore.
+    fn rate(&self) -> Option<f64> {
+        (self.trials > 0 && (0..=self.trials).contains(&self.successes))
+            .then(|| self.successes as f64 / self.trials as f64)
+    }
+}
+
+/// Summary of the values of a sample.
+#[derive(Clone, Copy, Debug)]
+pub struct Sample {
+    pub count: i64,
+    pub mean: f64,
+    pub standard_deviation: f64,
+}
+
+impl Sample {
+    fn squared_standard_error(&self) -> f64 {
+        self.standard_deviation.powi(2) / self.count as f64
+    }
+}
+
+/// Wilson score interval of a proportion, which stays within [0, 1] and holds
+/// up for rates close to either end.
+pub fn wilson_interval(proportion: Proportion) -> Option<Interval> {
+    let rate = proportion.rate()?;
+    let trials = proportion.trials as f64;
+    let z = critical_value(None);
+    let z2 = z * z;
+    let denominator = 1.0 + z2 / trials;
+    let center = (rate + z2 / (2.0 * trials)) / denominator;
+    let half_width = z
+        * (rate * (1.0 - rate) / trials + z2 / (4.0 * trials * trials)).sqrt()
+        / denominator;
+    Some(interval(center, half_width))
+}
+
+/// Student t interval of the mean of a sample of at least two values.
+pub fn mean_interval(sample: Sample) -> Option<Interval> {
+    (sample.count > 1).then(|| {
+        let t = critical_value(Some((sample.count - 1) as f64));
+        interval(sample.mean, t * sample.squared_standard_error().sqrt())
+    })
+}
+
+fn relative_lift(control: f64, treatment: f64) -> Option<f64> {
+    (control != 0.0).then(|| (treatment - control) / control)
+}
+
+fn significance_test(
+    control: f64,
+    treatment: f64,
+    difference_interval: Interval,
+    p_value: f64,
+) -> SignificanceTest {
+    SignificanceTest {
+        lift: relative_lift(control, treatment),
+        difference_interval,
+        p_value,
+        significant: p_value < 1.0 - CONFIDENCE_LEVEL,
+    }
+}
+
+/// Two-proportion z-test of `treatment` against `control`, two sided with a
+/// pooled variance. The interval of the difference uses unpooled variances.
+pub fn two_proportion_z_test(
+    control: Proportion,
+    treatment: Proportion,
+) -> Option<SignificanceTest> {
+    let (control_rate, treatment_rate) = (control.rate()?, treatment.rate()?);
+    let (control_trials, treatment_trials) =
+        (control.trials as f64, treatment.trials as f64);
+    let pooled = (control.successes + treatment.successes) as f64
+        / (control_trials + treatment_trials);
+    let pooled_error =
+        (pooled * (1.0 - pooled) * (1.0 / control_trials + 1.0 / treatment_trials))
+            .sqrt();
+    if pooled_error == 0.0 {
+        return None;
+    }
+    let difference = treatment_rate - control_rate;
+    let z = difference / pooled_error;
+    let p_value = erfc(z.abs() / SQRT_2).min(1.0);
+    let standard_error = (control_rate * (1.0 - control_rate) / control_trials
+        + treatment_rate * (1.0 - treatment_rate) / treatment_trials)
+        .sqrt();
+    Some(significance_test(
+        control_rate,
+        treatment_rate,
+        interval(difference, critical_value(None) * standard_error),
+        p_value,
+    ))
+}
+
+/// Welch's t-test of the mean of `treatment` against `control`, two sided,
+/// which doesn't assume the variants have the same variance.
+pub fn welch_t_test(control: Sample, treatment: Sample) -> Option<SignificanceTest> {
+    if control.count < 2 || treatment.count < 2 {
+        return None;
+    }
+    let (control_error, treatment_error) = (
+        control.squared_standard_error(),
+        treatment.squared_standard_error(),
+    );
+    let squared_error = control_error + treatment_error;
+    if squared_error == 0.0 {
+        return None;
+    }
+    // Welch–Satterthwaite approximation
+    let degrees_of_freedom = squared_error.powi(2)
+        / (control_error.powi(2) / (control.count - 1) as f64
+            + treatment_error.powi(2) / (treatment.count - 1) as f64);
+    let difference = treatment.mean - control.mean;
+    let t = difference / squared_error.sqrt();
+    let p_value = (2.0 * student_t_cdf(-t.abs(), degrees_of_freedom)).min(1.0);
+    Some(significance_test(
+        control.mean,
+        treatment.mean,
+        interval(
+            difference,
+            critical_value(Some(degrees_of_freedom)) * squared_error.sqrt(),
+        ),
+        p_value,
+    ))
+}
+
+/// Chi-square goodness of fit test of `observed` counts against the share
+/// `weights` give each of them. `None` without counts to test.
+pub fn sample_ratio_mismatch(
+    observed: &[i64],
+    weights: &[f64],
+) -> Option<SampleRatioCheck> {
+    let total = observed.iter().sum::<i64>() as f64;
+    let total_weight = weights.iter().sum::<f64>();
+    if observed.len() < 2
+        || observed.len() != weights.len()
+        || total <= 0.0
+        || total_weight <= 0.0
+    {
+        return None;
+    }
+    let mut chi_square = 0.0;
+    for (count, weight) in observed.iter().zip(weights) {
+        let expected = total * weight / total_weight;
+        if expected <= 0.0 {
+            return None;
+        }
+        chi_square += (*count as f64 - expected).powi(2) / expected;
+    }
+    let p_value = chi_square_survival(chi_square, (observed.len() - 1) as f64);
+    Some(SampleRatioCheck {
+        chi_square,
+        p_value,
+        mismatch: p_value < SAMPLE_RATIO_THRESHOLD,
+    })
+}
+
+/// Values of `metric` per exposed unit.
+fn sample(metric: &MetricResult, exposures: i64) -> Option<Sample> {
+    Some(Sample {
+        count: exposures,
+        mean: metric.mean?,
+        standard_deviation: metric.standard_deviation?,
+    })
+}
+
+/// Fills in the intervals of every metric of `results` and tests the
+/// variants against the control variant, when the experiment has one.
+pub fn compare_with_control(results: &mut [VariantResult]) {
+    let control = results
+        .iter()
+        .find(|result| result.variant_type == VariantType::CONTROL)
+        .cloned();
+    for result in results.iter_mut() {
+        let exposures = result.exposures;
+        let is_control = result.variant_type == VariantType::CONTROL;
+        for metric in result.metrics.iter_mut() {
+            let conversions = Proportion {
+                successes: metric.conversions,
+                trials: exposures,
+            };
+            metric.conversion_rate_interval = wilson_interval(conversions);
+            metric.mean_interval = sample(metric, exposures).and_then(mean_interval);
+
+            let Some(control) = control.as_ref().filter(|_| !is_control) else {
+                continue;
+            };
+            let Some(control_metric) =
+                control.metrics.iter().find(|m| m.metric == metric.metric)
+            else {
+                continue;
+            };
+            let control_conversions = Proportion {
+                successes: control_metric.conversions,
+                trials: control.exposures,
+            };
+            metric.conversion_test =
+                two_proportion_z_test(control_conversions, conversions);
+            metric.mean_test = sample(control_metric, control.exposures)
+                .zip(sample(metric, exposures))
+                .and_then(|(control, treatment)| welch_t_test(control, treatment));
+        }
+    }
+}
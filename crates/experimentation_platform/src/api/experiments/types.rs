@@ -1,9 +1,9 @@
 use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
-use service_utils::helpers::deserialize_stringified_list;
+use service_utils::{db::audit::FieldChange, helpers::deserialize_stringified_list};
 
-use crate::db::models::{self, ExperimentStatusType};
+use crate::db::models::{self, EventLog, ExperimentStatusType};
 
 #[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
 pub enum VariantType {
@@ -18,6 +18,10 @@ pub struct Variant {
     pub context_id: Option<String>,
     pub override_id: Option<String>,
     pub overrides: Map<String, Value>,
+    /// Added to an experiment in progress, serves no traffic until the
+    /// experiment is ramped again.
+    #[serde(default)]
+    pub awaiting_ramp: bool,
 }
 
 /********** Experiment Create Req Types ************/
@@ -25,8 +29,18 @@ pub struct Variant {
 #[derive(Deserialize)]
 pub struct ExperimentCreateRequest {
     pub name: String,
+    #[serde(default)]
+    pub description: String,
     pub context: Value,
     pub variants: Vec<Variant>,
+    /// Layer whose other experiments get traffic disjoint from this one
+    pub layer: Option<String>,
+    /// Percent of traffic held out of the experiment, which gets the default
+    /// config and is compared with the traffic exposed to it
+    #[serde(default)]
+    pub holdout_percentage: i32,
+    /// Why the experiment is created, kept in its history
+    pub reason: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -61,6 +75,11 @@ pub struct ExperimentResponse {
     pub context: Value,
     pub variants: Value,
     pub chosen_variant: Option<String>,
+    pub layer: Option<String>,
+    /// First bucket of the traffic the experiment serves
+    pub slice_start: i32,
+    pub holdout_percentage: i32,
+    pub description: String,
 }
 
 impl From<models::Experiment> for ExperimentResponse {
@@ -79,6 +98,10 @@ impl From<models::Experiment> for ExperimentResponse {
             context: experiment.context,
             variants: experiment.variants,
             chosen_variant: experiment.chosen_variant,
+            layer: experiment.layer,
+            slice_start: experiment.slice_start,
+            holdout_percentage: experiment.holdout_percentage,
+            description: experiment.description,
         }
     }
 }
@@ -90,11 +113,27 @@ pub struct ExperimentsResponse {
     pub data: Vec<ExperimentResponse>,
 }
 
+/********** Experiment Update Req Types ************/
+
+/// Changes to an experiment, fields left out are kept.
+#[derive(Deserialize)]
+pub struct ExperimentUpdateRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub context: Option<Value>,
+    /// Every variant the experiment is left with. Ids of existing variants
+    /// are kept, new variants get theirs prefixed with the experiment's id
+    /// like on create.
+    pub variants: Option<Vec<Variant>>,
+    pub reason: Option<String>,
+}
+
 /********** Experiment Conclude Req Types **********/
 
 #[derive(Deserialize, Debug)]
 pub struct ConcludeExperimentRequest {
     pub chosen_variant: String,
+    pub reason: Option<String>,
 }
 
 /********** Context Bulk API Type *************/
@@ -126,6 +165,12 @@ pub enum ContextBulkResponse {
     MOVE(ContextPutResp),
 }
 
+#[derive(Deserialize, Debug)]
+pub struct CacDefaultConfig {
+    pub key: String,
+    pub value: Value,
+}
+
 /********** List API Filter Type *************/
 
 #[derive(Deserialize, Debug, Clone)]
@@ -134,6 +179,23 @@ pub struct StatusTypes(
     pub  Vec<ExperimentStatusType>,
 );
 
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ExperimentSortOn {
+    CreatedAt,
+    #[default]
+    LastModified,
+    TrafficPercentage,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct ListFilters {
     pub status: Option<StatusTypes>,
@@ -141,12 +203,37 @@ pub struct ListFilters {
     pub to_date: Option<DateTime<Utc>>,
     pub page: Option<i64>,
     pub count: Option<i64>,
+    /// Part of the name, case insensitive
+    pub name: Option<String>,
+    pub created_by: Option<String>,
+    pub override_key: Option<String>,
+    /// Experiments overriding a key which starts with any of these
+    pub key_prefix: Option<StringArgs>,
+    /// `<dimension>:<value>`, experiments whose context targets the value
+    /// of the dimension, on its own or in a list
+    pub dimension: Option<String>,
+    pub chosen_variant: Option<String>,
+    #[serde(default)]
+    pub sort_on: ExperimentSortOn,
+    #[serde(default)]
+    pub sort_order: SortOrder,
 }
 
 /********** Ramp API type **********/
 #[derive(Deserialize, Debug)]
 pub struct RampRequest {
     pub traffic_percentage: u64,
+    pub reason: Option<String>,
+}
+
+/********** Approve API type **********/
+
+#[derive(Serialize, Debug)]
+pub struct ExperimentApprovals {
+    pub experiment_id: String,
+    pub approved_by: Vec<String>,
+    /// Approvals the experiment needs before it serves traffic
+    pub required_approvers: i32,
 }
 
 /********** Update API type ********/
@@ -160,6 +247,7 @@ pub struct VariantUpdateRequest {
 #[derive(Deserialize, Debug)]
 pub struct OverrideKeysUpdateRequest {
     pub variants: Vec<VariantUpdateRequest>,
+    pub reason: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -167,6 +255,219 @@ pub struct ContextMoveReq {
     pub context: serde_json::Map<String, Value>,
 }
 
+/********** Metrics API Types **********/
+
+#[derive(
+    Deserialize,
+    Serialize,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum MetricEventType {
+    /// A user was served the variant
+    Exposure,
+    /// A user served the variant did what `metric` names
+    Conversion,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct MetricEvent {
+    pub experiment_id: String,
+    pub variant_id: String,
+    /// Unit, like a user, the event is about. Units are counted once per
+    /// variant however many events they have.
+    pub unit_id: String,
+    pub event_type: MetricEventType,
+    /// Name of the conversion, required for conversions only
+    pub metric: Option<String>,
+    /// Numeric value of a conversion, like the amount of an order
+    pub value: Option<f64>,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct MetricEventsRequest {
+    pub events: Vec<MetricEvent>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MetricEventsResponse {
+    pub accepted: usize,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct Interval {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// Test of a variant against the control variant.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct SignificanceTest {
+    /// Change relative to the control variant, `None` when the control's
+    /// value is zero
+    pub lift: Option<f64>,
+    /// Interval of the difference with the control variant
+    pub difference_interval: Interval,
+    pub p_value: f64,
+    pub significant: bool,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct MetricResult {
+    pub metric: String,
+    /// Exposed units which converted
+    pub conversions: i64,
+    /// Share of the exposed units which converted, `None` before the first
+    /// exposure
+    pub conversion_rate: Option<f64>,
+    pub conversion_rate_interval: Option<Interval>,
+    /// Converted units which reported a value
+    pub value_count: i64,
+    /// Total value per exposed unit, `None` for metrics without values
+    pub mean: Option<f64>,
+    pub standard_deviation: Option<f64>,
+    pub mean_interval: Option<Interval>,
+    /// Two-proportion z-test of the conversion rate, not set on the control
+    pub conversion_test: Option<SignificanceTest>,
+    /// Welch's t-test of the mean, not set on the control
+    pub mean_test: Option<SignificanceTest>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct VariantResult {
+    pub variant_id: String,
+    pub variant_type: VariantType,
+    /// Units exposed to the variant
+    pub exposures: i64,
+    pub metrics: Vec<MetricResult>,
+    pub last_event_at: Option<DateTime<Utc>>,
+}
+
+/// Chi-square test of the exposed units of the variants, and of the holdout
+/// when its share is known, against the traffic split of the experiment.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct SampleRatioCheck {
+    pub chi_square: f64,
+    pub p_value: f64,
+    /// Exposures are off the split, results can't be trusted
+    pub mismatch: bool,
+}
+
+/// Results of a population of an experiment, tests are against the holdout.
+#[derive(Serialize, Clone, Debug)]
+pub struct PopulationResult {
+    pub exposures: i64,
+    pub metrics: Vec<MetricResult>,
+    pub last_event_at: Option<DateTime<Utc>>,
+}
+
+impl From<VariantResult> for PopulationResult {
+    fn from(result: VariantResult) -> Self {
+        PopulationResult {
+            exposures: result.exposures,
+            metrics: result.metrics,
+            last_event_at: result.last_event_at,
+        }
+    }
+}
+
+/// Units held out of the experiment, by the global holdout or its own,
+/// against the units exposed to each of its variants.
+#[derive(Serialize, Debug)]
+pub struct HoldoutResults {
+    pub holdout: PopulationResult,
+    /// Variants of the experiment, tests are against the holdout
+    pub variants: Vec<VariantResult>,
+}
+
+/// Units in the tenant's global holdout against the units outside it, the
+/// impact of everything served to them.
+#[derive(Serialize, Debug)]
+pub struct GlobalHoldoutResults {
+    pub confidence_level: f64,
+    /// Percent of traffic in the global holdout
+    pub holdout_percentage: i32,
+    pub holdout: PopulationResult,
+    /// Tests are against the holdout
+    pub exposed: PopulationResult,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ExperimentResults {
+    pub experiment_id: String,
+    pub confidence_level: f64,
+    pub variants: Vec<VariantResult>,
+    /// `None` before the first exposure
+    pub sample_ratio: Option<SampleRatioCheck>,
+    /// `None` until units held out of the experiment are reported
+    pub holdout: Option<HoldoutResults>,
+}
+
+/*********** Experiment History API Types **************/
+
+#[derive(
+    Deserialize,
+    Serialize,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ExperimentEventType {
+    Created,
+    Approved,
+    /// Variants' overrides changed, `details` has the override keys
+    OverridesUpdated,
+    /// Traffic changed, `details` has it `from` and `to`
+    Ramped,
+    /// Traffic of a running experiment ramped down to zero
+    Paused,
+    /// `details` has the `chosen_variant`
+    Concluded,
+    /// Name, description, context or variants changed, `details` has the
+    /// changed `fields` and the `variants` after the change
+    Updated,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ExperimentHistoryEntry {
+    pub event_type: ExperimentEventType,
+    pub details: Value,
+    pub reason: Option<String>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Traffic percentage of each variant from `at` on.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct TrafficPoint {
+    pub at: DateTime<Utc>,
+    pub traffic_percentage: i32,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ExperimentHistory {
+    pub experiment_id: String,
+    /// Oldest first
+    pub events: Vec<ExperimentHistoryEntry>,
+    pub traffic: Vec<TrafficPoint>,
+}
+
 /*********** List Audit API Filter Type **************/
 
 #[derive(Deserialize, Debug, Clone)]
@@ -180,7 +481,40 @@ pub struct AuditQueryFilters {
     pub to_date: Option<NaiveDateTime>,
     pub table: Option<StringArgs>,
     pub action: Option<StringArgs>,
+    /// Email of the user who made the change
     pub username: Option<String>,
+    pub request_id: Option<String>,
     pub count: Option<i64>,
     pub page: Option<i64>,
 }
+
+/// Tables whose rows have a change history, named as in `event_log`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEntity {
+    Experiments,
+    Layers,
+}
+
+impl AuditEntity {
+    pub fn table_name(&self) -> &'static str {
+        match self {
+            AuditEntity::Experiments => "experiments",
+            AuditEntity::Layers => "layers",
+        }
+    }
+
+    pub fn primary_key(&self) -> &'static str {
+        match self {
+            AuditEntity::Experiments => "id",
+            AuditEntity::Layers => "name",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditHistoryEntry {
+    #[serde(flatten)]
+    pub log: EventLog,
+    pub changes: Vec<FieldChange>,
+}
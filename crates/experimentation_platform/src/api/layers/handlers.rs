@@ -0,0 +1,191 @@
+use actix_web::{
+    delete, get, post, put,
+    web::{Json, Path},
+    HttpResponse, Scope,
+};
+use chrono::{DateTime, Utc};
+use diesel::{AsChangeset, QueryDsl, RunQueryDsl};
+use service_utils::{
+    db::audit::AuditActor,
+    rbac::{Action, Authorizer, Resource},
+    service::types::DbConnection,
+};
+use superposition_macros::{bad_argument, not_found};
+use superposition_types::{result as superposition, SuperpositionUser, User};
+
+use super::{
+    helpers::{
+        active_slices, find_layer, validate_layer_name, validate_traffic_allocation,
+    },
+    types::{CreateLayerRequest, LayerResponse, UpdateLayerRequest},
+};
+use crate::{
+    api::settings::helpers::{experiment_buckets, fetch_settings},
+    db::{models::Layer, schema::layers},
+};
+
+pub fn endpoints() -> Scope {
+    Scope::new("")
+        .service(list_layers)
+        .service(create_layer)
+        .service(get_layer)
+        .service(update_layer)
+        .service(delete_layer)
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = layers)]
+struct LayerChangeset {
+    description: Option<String>,
+    traffic_allocation: Option<i32>,
+    last_modified_by: String,
+    last_modified_at: DateTime<Utc>,
+}
+
+#[get("")]
+async fn list_layers(
+    auth: Authorizer,
+    db_conn: DbConnection,
+) -> superposition::Result<Json<Vec<Layer>>> {
+    auth.require(Resource::Experiment, Action::Read)?;
+    let DbConnection(mut conn) = db_conn;
+
+    let result = layers::table
+        .order(layers::name.asc())
+        .load::<Layer>(&mut conn)?;
+    Ok(Json(result))
+}
+
+#[post("")]
+async fn create_layer(
+    req: Json<CreateLayerRequest>,
+    auth: Authorizer,
+    db_conn: DbConnection,
+    user: User,
+    actor: AuditActor,
+) -> superposition::Result<HttpResponse> {
+    auth.require(Resource::Experiment, Action::Write)?;
+    let DbConnection(mut conn) = db_conn;
+    let req = req.into_inner();
+
+    validate_layer_name(&req.name)?;
+    let layer = Layer {
+        name: req.name,
+        description: req.description,
+        traffic_allocation: req.traffic_allocation,
+        created_by: user.get_email(),
+        created_at: Utc::now(),
+        last_modified_by: user.get_email(),
+        last_modified_at: Utc::now(),
+    };
+    let layer = actor.transaction(&mut conn, |transaction_conn| {
+        let settings = fetch_settings(transaction_conn, true)?;
+        validate_traffic_allocation(
+            layer.traffic_allocation,
+            experiment_buckets(settings.holdout_percentage),
+        )?;
+        Ok::<Layer, superposition::AppError>(
+            diesel::insert_into(layers::table)
+                .values(&layer)
+                .get_result::<Layer>(transaction_conn)?,
+        )
+    })?;
+    Ok(HttpResponse::Created().json(layer))
+}
+
+#[get("/{name}")]
+async fn get_layer(
+    path: Path<String>,
+    auth: Authorizer,
+    db_conn: DbConnection,
+) -> superposition::Result<Json<LayerResponse>> {
+    auth.require(Resource::Experiment, Action::Read)?;
+    let DbConnection(mut conn) = db_conn;
+    let name = path.into_inner();
+
+    let layer = find_layer(&mut conn, &name, false)?
+        .ok_or_else(|| not_found!("layer {} doesn't exist", name))?;
+    let slices = active_slices(&mut conn, &layer.name, None)?;
+    Ok(Json(LayerResponse::new(layer, slices)))
+}
+
+#[put("/{name}")]
+async fn update_layer(
+    path: Path<String>,
+    req: Json<UpdateLayerRequest>,
+    auth: Authorizer,
+    db_conn: DbConnection,
+    user: User,
+    actor: AuditActor,
+) -> superposition::Result<Json<Layer>> {
+    auth.require(Resource::Experiment, Action::Write)?;
+    let DbConnection(mut conn) = db_conn;
+    let name = path.into_inner();
+    let req = req.into_inner();
+
+    let layer = actor.transaction(&mut conn, |transaction_conn| {
+        find_layer(transaction_conn, &name, true)?
+            .ok_or_else(|| not_found!("layer {} doesn't exist", name))?;
+        if let Some(traffic_allocation) = req.traffic_allocation {
+            let settings = fetch_settings(transaction_conn, true)?;
+            validate_traffic_allocation(
+                traffic_allocation,
+                experiment_buckets(settings.holdout_percentage),
+            )?;
+        }
+        // the slices of running experiments stay where they are
+        let used = active_slices(transaction_conn, &name, None)?
+            .iter()
+            .map(|(_, slice)| slice.end())
+            .max()
+            .unwrap_or_default();
+        if req.traffic_allocation.is_some_and(|allocation| allocation < used) {
+            return Err(bad_argument!(
+                "experiments of layer {} serve the first {}% of traffic, its allocation can't be lower",
+                name,
+                used
+            ));
+        }
+        let changeset = LayerChangeset {
+            description: req.description,
+            traffic_allocation: req.traffic_allocation,
+            last_modified_by: user.get_email(),
+            last_modified_at: Utc::now(),
+        };
+        Ok(diesel::update(layers::table.find(&name))
+            .set(changeset)
+            .get_result::<Layer>(transaction_conn)?)
+    })?;
+    Ok(Json(layer))
+}
+
+#[delete("/{name}")]
+async fn delete_layer(
+    path: Path<String>,
+    auth: Authorizer,
+    db_conn: DbConnection,
+    user: User,
+    actor: AuditActor,
+) -> superposition::Result<HttpResponse> {
+    auth.require(Resource::Experiment, Action::Write)?;
+    let DbConnection(mut conn) = db_conn;
+    let name = path.into_inner();
+
+    actor.transaction(&mut conn, |transaction_conn| {
+        find_layer(transaction_conn, &name, true)?
+            .ok_or_else(|| not_found!("layer {} doesn't exist", name))?;
+        let active = active_slices(transaction_conn, &name, None)?;
+        if !active.is_empty() {
+            return Err(bad_argument!(
+                "layer {} has {} experiments which aren't concluded, conclude them before deleting it",
+                name,
+                active.len()
+            ));
+        }
+        // concluded experiments of the layer are kept, without it
+        diesel::delete(layers::table.find(&name)).execute(transaction_conn)?;
+        Ok::<(), superposition::AppError>(())
+    })?;
+    log::info!("layer {name} deleted by {}", user.get_email());
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,168 @@
+use diesel::{
+    BoolExpressionMethods, ExpressionMethods, OptionalExtension, PgConnection, QueryDsl,
+    RunQueryDsl,
+};
+use superposition_macros::bad_argument;
+use superposition_types::result as superposition;
+
+use crate::db::{
+    models::{Experiment, ExperimentStatusType, Layer},
+    schema::{experiments, layers},
+};
+
+/// Buckets users are tossed into, a bucket is one percent of traffic.
+pub const TOTAL_TRAFFIC: i32 = 100;
+const MAX_LAYER_NAME_LENGTH: usize = 64;
+
+/// Buckets `[start, start + size)` an experiment serves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Slice {
+    pub start: i32,
+    pub size: i32,
+}
+
+impl Slice {
+    pub fn end(&self) -> i32 {
+        self.start + self.size
+    }
+
+    fn contains(&self, bucket: i32) -> bool {
+        self.start <= bucket && bucket < self.end()
+    }
+
+    fn overlaps(&self, other: &Slice) -> bool {
+        self.start < other.end() && other.start < self.end()
+    }
+}
+
+pub fn validate_layer_name(name: &str) -> superposition::Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_LAYER_NAME_LENGTH
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(bad_argument!(
+            "layer names have at most {} letters, digits, '-' or '_'",
+            MAX_LAYER_NAME_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+/// `buckets` are the ones experiments serve, past the global holdout.
+pub fn validate_traffic_allocation(
+    traffic_allocation: i32,
+    buckets: i32,
+) -> superposition::Result<()> {
+    if !(0..=buckets).contains(&traffic_allocation) {
+        return Err(bad_argument!(
+            "traffic_allocation has to be between 0 and {}, the {}% of traffic not held out",
+            buckets,
+            buckets
+        ));
+    }
+    Ok(())
+}
+
+/// Traffic an experiment serves, `traffic_percentage` for each variant.
+pub fn slice_size(traffic_percentage: i32, variant_count: usize) -> i32 {
+    traffic_percentage * variant_count as i32
+}
+
+pub fn experiment_slice(experiment: &Experiment) -> Slice {
+    let variant_count = experiment.variants.as_array().map_or(0, Vec::len);
+    Slice {
+        start: experiment.slice_start,
+        size: slice_size(experiment.traffic_percentage, variant_count),
+    }
+}
+
+/// Start of a slice of `size` in the first `allocation` buckets, clear of the
+/// `taken` slices. An experiment keeps its `current` start when its slice
+/// still fits there, so users already in it stay in it, and otherwise moves
+/// to the first free start. Slices never start inside a taken one, which
+/// places experiments created without traffic where they can grow.
+pub fn assign_slice(
+    current: Option<i32>,
+    size: i32,
+    allocation: i32,
+    taken: &[Slice],
+) -> Option<i32> {
+    let fits = |start: i32| {
+        let slice = Slice { start, size };
+        start >= 0
+            && slice.end() <= allocation
+            && !taken
+                .iter()
+                .any(|other| other.contains(start) || slice.overlaps(other))
+    };
+    let mut starts = taken.iter().map(Slice::end).collect::<Vec<i32>>();
+    starts.push(0);
+    starts.sort();
+    current.into_iter().chain(starts).find(|start| fits(*start))
+}
+
+/// `lock` holds the layer until the transaction `conn` is in ends, so slices
+/// in it are assigned one at a time.
+pub fn find_layer(
+    conn: &mut PgConnection,
+    name: &str,
+    lock: bool,
+) -> superposition::Result<Option<Layer>> {
+    let query = layers::table.find(name);
+    let layer = if lock {
+        query.for_update().first::<Layer>(conn)
+    } else {
+        query.first::<Layer>(conn)
+    };
+    Ok(layer.optional()?)
+}
+
+/// Experiments of `layer` which can serve traffic, other than `except`, with
+/// their slices.
+pub fn active_slices(
+    conn: &mut PgConnection,
+    layer: &str,
+    except: Option<i64>,
+) -> superposition::Result<Vec<(Experiment, Slice)>> {
+    let active = experiments::table
+        .filter(experiments::layer.eq(layer))
+        .filter(experiments::id.ne(except.unwrap_or_default()))
+        .filter(
+            experiments::status
+                .eq(ExperimentStatusType::CREATED)
+                .or(experiments::status.eq(ExperimentStatusType::INPROGRESS)),
+        )
+        .order(experiments::slice_start)
+        .load::<Experiment>(conn)?;
+    Ok(active
+        .into_iter()
+        .map(|experiment| {
+            let slice = experiment_slice(&experiment);
+            (experiment, slice)
+        })
+        .collect())
+}
+
+/// Slice start for experiment `experiment_id` of `layer` serving `size`,
+/// see `assign_slice`.
+pub fn place_in_layer(
+    conn: &mut PgConnection,
+    layer: &Layer,
+    experiment_id: Option<i64>,
+    current: Option<i32>,
+    size: i32,
+) -> superposition::Result<i32> {
+    let taken = active_slices(conn, &layer.name, experiment_id)?
+        .into_iter()
+        .map(|(_, slice)| slice)
+        .collect::<Vec<Slice>>();
+    assign_slice(current, size, layer.traffic_allocation, &taken).ok_or_else(|| {
+        bad_argument!(
+            "layer {} has no free slice of {}% of traffic, conclude one of its experiments or raise its traffic allocation",
+            layer.name,
+            size
+        )
+    })
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+use super::helpers::Slice;
+use crate::db::models::{Experiment, ExperimentStatusType, Layer};
+
+#[derive(Debug, Deserialize)]
+pub struct CreateLayerRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Percent of traffic the experiments of the layer share
+    pub traffic_allocation: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateLayerRequest {
+    pub description: Option<String>,
+    pub traffic_allocation: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExperimentSlice {
+    pub experiment_id: String,
+    pub name: String,
+    pub status: ExperimentStatusType,
+    pub start: i32,
+    pub size: i32,
+}
+
+impl From<(Experiment, Slice)> for ExperimentSlice {
+    fn from((experiment, slice): (Experiment, Slice)) -> Self {
+        ExperimentSlice {
+            experiment_id: experiment.id.to_string(),
+            name: experiment.name,
+            status: experiment.status,
+            start: slice.start,
+            size: slice.size,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LayerResponse {
+    #[serde(flatten)]
+    pub layer: Layer,
+    /// Traffic of the layer none of its experiments serve
+    pub free_traffic: i32,
+    /// Slices of the experiments which can serve traffic, in traffic order
+    pub slices: Vec<ExperimentSlice>,
+}
+
+impl LayerResponse {
+    pub fn new(layer: Layer, slices: Vec<(Experiment, Slice)>) -> Self {
+        let used = slices.iter().map(|(_, slice)| slice.size).sum::<i32>();
+        LayerResponse {
+            free_traffic: layer.traffic_allocation - used,
+            layer,
+            slices: slices.into_iter().map(ExperimentSlice::from).collect(),
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use actix_web::{
+    get, put,
+    web::{self, Json},
+    Scope,
+};
+use chrono::{DateTime, Utc};
+use diesel::{AsChangeset, QueryDsl, RunQueryDsl};
+use service_utils::{
+    db::audit::AuditActor,
+    rbac::{Action, Authorizer, Resource},
+    service::types::DbConnection,
+};
+use superposition_types::{result as superposition, SuperpositionUser, User};
+
+use super::{
+    helpers::{check_holdout_change, fetch_settings, validate_settings},
+    types::UpdateSettingsRequest,
+};
+use crate::db::{models::TenantSettings, schema::tenant_settings};
+
+pub fn endpoints() -> Scope {
+    Scope::new("")
+        .service(get_settings)
+        .service(update_settings)
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = tenant_settings)]
+struct SettingsChangeset {
+    holdout_percentage: Option<i32>,
+    allow_same_keys_overlapping_ctx: Option<Option<bool>>,
+    allow_diff_keys_overlapping_ctx: Option<Option<bool>>,
+    allow_same_keys_non_overlapping_ctx: Option<Option<bool>>,
+    max_variants: Option<Option<i32>>,
+    max_traffic_percentage: Option<Option<i32>>,
+    required_approvers: Option<i32>,
+    last_modified_by: String,
+    last_modified_at: DateTime<Utc>,
+}
+
+#[get("")]
+async fn get_settings(
+    auth: Authorizer,
+    db_conn: DbConnection,
+) -> superposition::Result<Json<TenantSettings>> {
+    // clients read the holdout to bucket users
+    auth.require(Resource::Experiment, Action::Read)?;
+    let DbConnection(mut conn) = db_conn;
+    Ok(Json(fetch_settings(&mut conn, false)?))
+}
+
+#[put("")]
+async fn update_settings(
+    req: web::Json<UpdateSettingsRequest>,
+    auth: Authorizer,
+    db_conn: DbConnection,
+    user: User,
+    actor: AuditActor,
+) -> superposition::Result<Json<TenantSettings>> {
+    // the policy decides what every experiment of the tenant may do
+    auth.require(Resource::TenantSettings, Action::Write)?;
+    let DbConnection(mut conn) = db_conn;
+    let req = req.into_inner();
+
+    validate_settings(&req)?;
+    let settings = actor.transaction(&mut conn, |transaction_conn| {
+        let current = tenant_settings::table
+            .find(true)
+            .for_update()
+            .first::<TenantSettings>(transaction_conn)?;
+        if let Some(holdout_percentage) = req
+            .holdout_percentage
+            .filter(|holdout| *holdout != current.holdout_percentage)
+        {
+            check_holdout_change(transaction_conn, holdout_percentage)?;
+        }
+        // limits apply to experiments created or ramped from now on
+        let changeset = SettingsChangeset {
+            holdout_percentage: req.holdout_percentage,
+            allow_same_keys_overlapping_ctx: req.allow_same_keys_overlapping_ctx,
+            allow_diff_keys_overlapping_ctx: req.allow_diff_keys_overlapping_ctx,
+            allow_same_keys_non_overlapping_ctx: req.allow_same_keys_non_overlapping_ctx,
+            max_variants: req.max_variants,
+            max_traffic_percentage: req.max_traffic_percentage,
+            required_approvers: req.required_approvers,
+            last_modified_by: user.get_email(),
+            last_modified_at: Utc::now(),
+        };
+        Ok::<TenantSettings, superposition::AppError>(
+            diesel::update(tenant_settings::table.find(true))
+                .set(changeset)
+                .get_result::<TenantSettings>(transaction_conn)?,
+        )
+    })?;
+    Ok(Json(settings))
+}
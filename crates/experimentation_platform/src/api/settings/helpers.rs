@@ -0,0 +1,155 @@
+use diesel::{ExpressionMethods, PgConnection, QueryDsl, RunQueryDsl};
+use service_utils::service::types::ExperimentationFlags;
+use superposition_macros::bad_argument;
+use superposition_types::result as superposition;
+
+use super::types::UpdateSettingsRequest;
+use crate::{
+    api::layers::helpers::TOTAL_TRAFFIC,
+    db::{
+        models::{ExperimentStatusType, TenantSettings},
+        schema::{experiments, layers, tenant_settings},
+    },
+};
+
+/// Most traffic a holdout can take, so experiments keep at least half.
+pub const MAX_HOLDOUT_PERCENTAGE: i32 = 50;
+/// Experiments have a control and at least one experimental variant.
+const MIN_VARIANTS: i32 = 2;
+
+pub fn validate_holdout_percentage(holdout_percentage: i32) -> superposition::Result<()> {
+    if !(0..=MAX_HOLDOUT_PERCENTAGE).contains(&holdout_percentage) {
+        return Err(bad_argument!(
+            "holdout_percentage has to be between 0 and {}",
+            MAX_HOLDOUT_PERCENTAGE
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_settings(req: &UpdateSettingsRequest) -> superposition::Result<()> {
+    if let Some(holdout_percentage) = req.holdout_percentage {
+        validate_holdout_percentage(holdout_percentage)?;
+    }
+    if let Some(Some(max_variants)) = req.max_variants {
+        if max_variants < MIN_VARIANTS {
+            return Err(bad_argument!(
+                "max_variants has to be at least {}",
+                MIN_VARIANTS
+            ));
+        }
+    }
+    if let Some(Some(max_traffic_percentage)) = req.max_traffic_percentage {
+        if !(1..=TOTAL_TRAFFIC).contains(&max_traffic_percentage) {
+            return Err(bad_argument!(
+                "max_traffic_percentage has to be between 1 and {}",
+                TOTAL_TRAFFIC
+            ));
+        }
+    }
+    if req
+        .required_approvers
+        .is_some_and(|approvers| approvers < 0)
+    {
+        return Err(bad_argument!("required_approvers can't be negative"));
+    }
+    Ok(())
+}
+
+/// `lock` holds the settings until the transaction `conn` is in ends, without
+/// blocking other readers, so they can't change under a ramp.
+pub fn fetch_settings(
+    conn: &mut PgConnection,
+    lock: bool,
+) -> superposition::Result<TenantSettings> {
+    let query = tenant_settings::table.find(true);
+    let settings = if lock {
+        query.for_share().first::<TenantSettings>(conn)
+    } else {
+        query.first::<TenantSettings>(conn)
+    };
+    Ok(settings?)
+}
+
+/// Buckets experiments serve, the ones past them are the global holdout.
+pub fn experiment_buckets(holdout_percentage: i32) -> i32 {
+    TOTAL_TRAFFIC - holdout_percentage
+}
+
+/// The global holdout decides which buckets experiments and layers serve, so
+/// it only changes while no experiment is in progress and when every layer
+/// fits in the buckets left.
+pub fn check_holdout_change(
+    conn: &mut PgConnection,
+    holdout_percentage: i32,
+) -> superposition::Result<()> {
+    let running = experiments::table
+        .filter(experiments::status.eq(ExperimentStatusType::INPROGRESS))
+        .count()
+        .get_result::<i64>(conn)?;
+    if running > 0 {
+        return Err(bad_argument!(
+            "{} experiments are in progress, the holdout can't change until they are concluded",
+            running
+        ));
+    }
+    let buckets = experiment_buckets(holdout_percentage);
+    let too_wide = layers::table
+        .filter(layers::traffic_allocation.gt(buckets))
+        .select(layers::name)
+        .order(layers::name.asc())
+        .load::<String>(conn)?;
+    if !too_wide.is_empty() {
+        return Err(bad_argument!(
+            "layers {} take more than the {}% of traffic left to experiments, lower their traffic allocation first",
+            too_wide.join(", "),
+            buckets
+        ));
+    }
+    Ok(())
+}
+
+/// Overlap restrictions of the tenant, its own flags or else the deployment's
+/// `defaults`.
+pub fn experimentation_flags(
+    settings: &TenantSettings,
+    defaults: &ExperimentationFlags,
+) -> ExperimentationFlags {
+    ExperimentationFlags {
+        allow_same_keys_overlapping_ctx: settings
+            .allow_same_keys_overlapping_ctx
+            .unwrap_or(defaults.allow_same_keys_overlapping_ctx),
+        allow_diff_keys_overlapping_ctx: settings
+            .allow_diff_keys_overlapping_ctx
+            .unwrap_or(defaults.allow_diff_keys_overlapping_ctx),
+        allow_same_keys_non_overlapping_ctx: settings
+            .allow_same_keys_non_overlapping_ctx
+            .unwrap_or(defaults.allow_same_keys_non_overlapping_ctx),
+    }
+}
+
+pub fn check_variant_count(
+    settings: &TenantSettings,
+    variant_count: usize,
+) -> superposition::Result<()> {
+    match settings.max_variants {
+        Some(max_variants) if variant_count > max_variants as usize => Err(
+            bad_argument!("experiments can have at most {} variants", max_variants),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Most traffic each of `variant_count` variants can be ramped to, sharing
+/// the buckets not held out under the tenant's cap.
+pub fn max_traffic_percentage(
+    settings: &TenantSettings,
+    experiment_holdout: i32,
+    variant_count: usize,
+) -> i32 {
+    let buckets = experiment_buckets(settings.holdout_percentage) - experiment_holdout;
+    let max = buckets / variant_count.max(1) as i32;
+    settings
+        .max_traffic_percentage
+        .map_or(max, |cap| max.min(cap))
+}
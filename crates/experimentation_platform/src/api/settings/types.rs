@@ -0,0 +1,32 @@
+use serde::{Deserialize, Deserializer};
+
+/// Tells a field set to `null` apart from a missing one, which stays `None`.
+fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Settings left out are kept, nullable ones set to `null` are cleared.
+#[derive(Debug, Deserialize)]
+pub struct UpdateSettingsRequest {
+    /// Percent of traffic held out of every experiment
+    pub holdout_percentage: Option<i32>,
+    /// Overlap flags, `null` follows the deployment's `ALLOW_*_CTX` variables
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub allow_same_keys_overlapping_ctx: Option<Option<bool>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub allow_diff_keys_overlapping_ctx: Option<Option<bool>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub allow_same_keys_non_overlapping_ctx: Option<Option<bool>>,
+    /// Most variants an experiment can have, `null` for no limit
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub max_variants: Option<Option<i32>>,
+    /// Most traffic a variant can be ramped to, `null` for no limit
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub max_traffic_percentage: Option<Option<i32>>,
+    /// Approvals an experiment needs before it serves traffic
+    pub required_approvers: Option<i32>,
+}
@@ -0,0 +1,93 @@
+use service_utils::db::migrations::SchemaMigration;
+
+/// Migrations applied to every tenant schema of this service, in order. The
+/// diesel initial setup migration is left out, it only matters for `public`.
+/// Migrations added under `migrations/` have to be listed here too.
+pub const MIGRATIONS: &[SchemaMigration] = &[
+    SchemaMigration {
+        version: "2023-10-16-134612_experimentation-init",
+        up_sql: include_str!(
+            "../../migrations/2023-10-16-134612_experimentation-init/up.sql"
+        ),
+        marker: Some("experiments"),
+    },
+    SchemaMigration {
+        version: "2024-01-18-063937_audit_log_partitions",
+        up_sql: include_str!(
+            "../../migrations/2024-01-18-063937_audit_log_partitions/up.sql"
+        ),
+        marker: None,
+    },
+    SchemaMigration {
+        version: "2024-06-17-090000_audit_actor",
+        up_sql: include_str!("../../migrations/2024-06-17-090000_audit_actor/up.sql"),
+        marker: Some("event_log.request_id"),
+    },
+    SchemaMigration {
+        version: "2024-06-24-090000_webhook_outbox",
+        up_sql: include_str!("../../migrations/2024-06-24-090000_webhook_outbox/up.sql"),
+        marker: Some("webhook_outbox"),
+    },
+    SchemaMigration {
+        version: "2024-07-01-090000_cac_sagas",
+        up_sql: include_str!("../../migrations/2024-07-01-090000_cac_sagas/up.sql"),
+        marker: Some("cac_sagas"),
+    },
+    SchemaMigration {
+        version: "2024-07-08-090000_experiment_metrics",
+        up_sql: include_str!(
+            "../../migrations/2024-07-08-090000_experiment_metrics/up.sql"
+        ),
+        marker: Some("experiment_metrics"),
+    },
+    SchemaMigration {
+        version: "2024-07-15-090000_experiment_layers",
+        up_sql: include_str!(
+            "../../migrations/2024-07-15-090000_experiment_layers/up.sql"
+        ),
+        marker: Some("layers"),
+    },
+    SchemaMigration {
+        version: "2024-07-22-090000_holdouts",
+        up_sql: include_str!("../../migrations/2024-07-22-090000_holdouts/up.sql"),
+        marker: Some("tenant_settings"),
+    },
+    SchemaMigration {
+        version: "2024-07-29-090000_tenant_policy",
+        up_sql: include_str!("../../migrations/2024-07-29-090000_tenant_policy/up.sql"),
+        marker: Some("experiment_approvals"),
+    },
+    SchemaMigration {
+        version: "2024-08-05-090000_experiment_events",
+        up_sql: include_str!(
+            "../../migrations/2024-08-05-090000_experiment_events/up.sql"
+        ),
+        marker: Some("experiment_events"),
+    },
+    SchemaMigration {
+        version: "2024-08-12-090000_experiment_description",
+        up_sql: include_str!(
+            "../../migrations/2024-08-12-090000_experiment_description/up.sql"
+        ),
+        marker: Some("experiments.description"),
+    },
+    SchemaMigration {
+        version: "2024-08-19-090000_cac_saga_sweeps",
+        up_sql: include_str!("../../migrations/2024-08-19-090000_cac_saga_sweeps/up.sql"),
+        marker: Some("cac_sagas.swept_at"),
+    },
+    SchemaMigration {
+        version: "2024-08-26-090000_experiment_units",
+        up_sql: include_str!(
+            "../../migrations/2024-08-26-090000_experiment_units/up.sql"
+        ),
+        marker: Some("experiment_exposures"),
+    },
+    SchemaMigration {
+        version: "2024-09-02-090000_cac_saga_update_kind",
+        up_sql: include_str!(
+            "../../migrations/2024-09-02-090000_cac_saga_update_kind/up.sql"
+        ),
+        marker: None,
+    },
+];
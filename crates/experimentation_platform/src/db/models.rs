@@ -43,6 +43,10 @@ pub struct Experiment {
     pub variants: Value,
     pub last_modified_by: String,
     pub chosen_variant: Option<String>,
+    pub layer: Option<String>,
+    pub slice_start: i32,
+    pub holdout_percentage: i32,
+    pub description: String,
 }
 
 pub type Experiments = Vec<Experiment>;
@@ -60,4 +64,83 @@ pub struct EventLog {
     pub original_data: Option<Value>,
     pub new_data: Option<Value>,
     pub query: String,
+    pub request_id: Option<String>,
+}
+
+#[derive(Queryable, Selectable, Serialize, Clone, Debug)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = cac_sagas)]
+#[diesel(primary_key(id))]
+pub struct CacSaga {
+    pub id: uuid::Uuid,
+    pub experiment_id: i64,
+    pub kind: String,
+    pub status: String,
+    pub plan: Value,
+    pub compensation: Value,
+    pub payload: Option<Value>,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub created_by: String,
+    pub request_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_modified_at: DateTime<Utc>,
+    pub swept_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Queryable, Selectable, Insertable, Serialize, Clone, Debug)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = layers)]
+#[diesel(primary_key(name))]
+pub struct Layer {
+    pub name: String,
+    pub description: String,
+    pub traffic_allocation: i32,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub last_modified_by: String,
+    pub last_modified_at: DateTime<Utc>,
+}
+
+#[derive(Queryable, Selectable, Serialize, Clone, Debug)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = tenant_settings)]
+#[diesel(primary_key(id))]
+pub struct TenantSettings {
+    #[serde(skip)]
+    pub id: bool,
+    pub holdout_percentage: i32,
+    pub last_modified_by: String,
+    pub last_modified_at: DateTime<Utc>,
+    pub allow_same_keys_overlapping_ctx: Option<bool>,
+    pub allow_diff_keys_overlapping_ctx: Option<bool>,
+    pub allow_same_keys_non_overlapping_ctx: Option<bool>,
+    pub max_variants: Option<i32>,
+    pub max_traffic_percentage: Option<i32>,
+    pub required_approvers: i32,
+}
+
+#[derive(Queryable, Selectable, Insertable, Serialize, Clone, Debug)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = experiment_approvals)]
+#[diesel(primary_key(experiment_id, approved_by))]
+pub struct ExperimentApproval {
+    pub experiment_id: i64,
+    pub approved_by: String,
+    pub approved_at: DateTime<Utc>,
+}
+
+#[derive(Queryable, Selectable, Serialize, Clone, Debug)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = experiment_events)]
+#[diesel(primary_key(id))]
+pub struct ExperimentEvent {
+    pub id: i64,
+    pub experiment_id: i64,
+    pub event_type: String,
+    pub details: Value,
+    pub reason: Option<String>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
 }
@@ -7,488 +7,27 @@ pub mod sql_types {
 }
 
 diesel::table! {
-    event_log (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2023m08 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2023m09 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2023m10 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2023m11 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2023m12 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m01 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m02 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m03 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m04 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m05 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m06 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m07 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m08 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m09 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m10 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m11 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2024m12 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m01 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m02 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m03 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m04 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m05 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m06 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m07 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m08 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m09 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m10 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m11 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2025m12 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m01 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m02 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m03 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m04 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m05 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m06 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
-    }
-}
-
-diesel::table! {
-    event_log_y2026m07 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
+    cac_sagas (id) {
+        id -> Uuid,
+        experiment_id -> Int8,
+        kind -> Text,
+        status -> Text,
+        plan -> Json,
+        compensation -> Json,
+        payload -> Nullable<Json>,
+        attempts -> Int4,
+        next_attempt_at -> Timestamptz,
+        last_error -> Nullable<Text>,
+        created_by -> Text,
+        request_id -> Nullable<Text>,
+        created_at -> Timestamptz,
+        last_modified_at -> Timestamptz,
+        swept_at -> Nullable<Timestamptz>,
     }
 }
 
 diesel::table! {
-    event_log_y2026m08 (id, timestamp) {
+    event_log (id, timestamp) {
         id -> Uuid,
         table_name -> Text,
         user_name -> Text,
@@ -497,58 +36,51 @@ diesel::table! {
         original_data -> Nullable<Json>,
         new_data -> Nullable<Json>,
         query -> Text,
+        request_id -> Nullable<Text>,
     }
 }
 
 diesel::table! {
-    event_log_y2026m09 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
+    experiment_approvals (experiment_id, approved_by) {
+        experiment_id -> Int8,
+        approved_by -> Text,
+        approved_at -> Timestamptz,
     }
 }
 
 diesel::table! {
-    event_log_y2026m10 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
+    experiment_conversions (experiment_id, variant_id, metric, unit_id) {
+        experiment_id -> Int8,
+        variant_id -> Text,
+        metric -> Text,
+        unit_id -> Text,
+        event_count -> Int8,
+        value_count -> Int8,
+        value_sum -> Float8,
+        first_event_at -> Timestamptz,
+        last_event_at -> Timestamptz,
     }
 }
 
 diesel::table! {
-    event_log_y2026m11 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
+    experiment_events (id) {
+        id -> Int8,
+        experiment_id -> Int8,
+        event_type -> Text,
+        details -> Json,
+        reason -> Nullable<Text>,
+        created_by -> Text,
+        created_at -> Timestamptz,
     }
 }
 
 diesel::table! {
-    event_log_y2026m12 (id, timestamp) {
-        id -> Uuid,
-        table_name -> Text,
-        user_name -> Text,
-        timestamp -> Timestamp,
-        action -> Text,
-        original_data -> Nullable<Json>,
-        new_data -> Nullable<Json>,
-        query -> Text,
+    experiment_exposures (experiment_id, variant_id, unit_id) {
+        experiment_id -> Int8,
+        variant_id -> Text,
+        unit_id -> Text,
+        first_exposed_at -> Timestamptz,
+        last_exposed_at -> Timestamptz,
     }
 }
 
@@ -569,51 +101,62 @@ diesel::table! {
         variants -> Json,
         last_modified_by -> Text,
         chosen_variant -> Nullable<Text>,
+        layer -> Nullable<Text>,
+        slice_start -> Int4,
+        holdout_percentage -> Int4,
+        description -> Text,
     }
 }
 
+diesel::table! {
+    layers (name) {
+        name -> Text,
+        description -> Text,
+        traffic_allocation -> Int4,
+        created_by -> Text,
+        created_at -> Timestamptz,
+        last_modified_by -> Text,
+        last_modified_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    tenant_settings (id) {
+        id -> Bool,
+        holdout_percentage -> Int4,
+        last_modified_by -> Text,
+        last_modified_at -> Timestamptz,
+        allow_same_keys_overlapping_ctx -> Nullable<Bool>,
+        allow_diff_keys_overlapping_ctx -> Nullable<Bool>,
+        allow_same_keys_non_overlapping_ctx -> Nullable<Bool>,
+        max_variants -> Nullable<Int4>,
+        max_traffic_percentage -> Nullable<Int4>,
+        required_approvers -> Int4,
+    }
+}
+
+diesel::table! {
+    webhook_outbox (id) {
+        id -> Int8,
+        event -> Text,
+        payload -> Json,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::joinable!(experiment_approvals -> experiments (experiment_id));
+diesel::joinable!(experiment_events -> experiments (experiment_id));
+diesel::joinable!(experiments -> layers (layer));
+
 diesel::allow_tables_to_appear_in_same_query!(
+    cac_sagas,
     event_log,
-    event_log_y2023m08,
-    event_log_y2023m09,
-    event_log_y2023m10,
-    event_log_y2023m11,
-    event_log_y2023m12,
-    event_log_y2024m01,
-    event_log_y2024m02,
-    event_log_y2024m03,
-    event_log_y2024m04,
-    event_log_y2024m05,
-    event_log_y2024m06,
-    event_log_y2024m07,
-    event_log_y2024m08,
-    event_log_y2024m09,
-    event_log_y2024m10,
-    event_log_y2024m11,
-    event_log_y2024m12,
-    event_log_y2025m01,
-    event_log_y2025m02,
-    event_log_y2025m03,
-    event_log_y2025m04,
-    event_log_y2025m05,
-    event_log_y2025m06,
-    event_log_y2025m07,
-    event_log_y2025m08,
-    event_log_y2025m09,
-    event_log_y2025m10,
-    event_log_y2025m11,
-    event_log_y2025m12,
-    event_log_y2026m01,
-    event_log_y2026m02,
-    event_log_y2026m03,
-    event_log_y2026m04,
-    event_log_y2026m05,
-    event_log_y2026m06,
-    event_log_y2026m07,
-    event_log_y2026m08,
-    event_log_y2026m09,
-    event_log_y2026m10,
-    event_log_y2026m11,
-    event_log_y2026m12,
+    experiment_approvals,
+    experiment_conversions,
+    experiment_events,
+    experiment_exposures,
     experiments,
+    layers,
+    tenant_settings,
+    webhook_outbox,
 );
@@ -1,6 +1,19 @@
 use chrono::Utc;
-use experimentation_platform::api::experiments::helpers;
-use experimentation_platform::db::models::{Experiment, ExperimentStatusType};
+use experimentation_platform::api::experiments::{
+    bucketing, helpers, history, metrics,
+    statistics::{self, Proportion, Sample},
+    types::{
+        ExperimentEventType, ExperimentHistoryEntry, MetricEvent, MetricEventType,
+        Variant, VariantResult, VariantType,
+    },
+};
+use experimentation_platform::api::layers::helpers::{assign_slice, Slice};
+use experimentation_platform::api::settings::{
+    helpers as settings, types::UpdateSettingsRequest,
+};
+use experimentation_platform::db::models::{
+    Experiment, ExperimentStatusType, TenantSettings,
+};
 use serde_json::{json, Map, Value};
 use service_utils::helpers::extract_dimensions;
 use service_utils::service::types::ExperimentationFlags;
@@ -64,6 +77,10 @@ fn experiment_gen(
         context: context.clone(),
         variants: variants.clone(),
         chosen_variant: None,
+        layer: None,
+        slice_start: 0,
+        holdout_percentage: 0,
+        description: String::new(),
     }
 }
 
@@ -593,3 +610,781 @@ fn test_pass_context_without_variant_ids_dimensions() {
 
     assert!(helpers::validate_context(&experiment_context).is_ok());
 }
+
+#[test]
+fn test_extract_variant_ids() {
+    let variant_context = multiple_dimension_ctx_gen(vec![
+        Dimensions::Os("os1".to_string()),
+        Dimensions::VariantIds("123456789-variant1".to_string()),
+    ]);
+    let variant_ids = helpers::extract_variant_ids(&variant_context);
+    assert_eq!(variant_ids, vec!["123456789-variant1".to_string()]);
+    assert_eq!(
+        helpers::experiment_id_of_variant(&variant_ids[0]),
+        Some(123456789)
+    );
+
+    let context = multiple_dimension_ctx_gen(vec![
+        Dimensions::Os("os1".to_string()),
+        Dimensions::Client("testclient1".to_string()),
+    ]);
+    assert!(helpers::extract_variant_ids(&context).is_empty());
+    assert_eq!(helpers::experiment_id_of_variant("variant1"), None);
+}
+
+fn metric_event(
+    variant_id: &str,
+    event_type: MetricEventType,
+    metric: Option<&str>,
+    value: Option<f64>,
+) -> MetricEvent {
+    MetricEvent {
+        experiment_id: "123456789".to_string(),
+        variant_id: variant_id.to_string(),
+        unit_id: "user-1".to_string(),
+        event_type,
+        metric: metric.map(String::from),
+        value,
+        timestamp: None,
+    }
+}
+
+#[test]
+fn test_aggregate_metric_events() {
+    let mut events = vec![
+        metric_event("123456789-control", MetricEventType::Exposure, None, None),
+        metric_event("123456789-control", MetricEventType::Exposure, None, None),
+        metric_event(
+            "123456789-control",
+            MetricEventType::Conversion,
+            Some("checkout"),
+            Some(10.0),
+        ),
+        metric_event(
+            "123456789-control",
+            MetricEventType::Conversion,
+            Some("checkout"),
+            Some(20.0),
+        ),
+    ];
+    let mut other_unit =
+        metric_event("123456789-control", MetricEventType::Exposure, None, None);
+    other_unit.unit_id = "user-2".to_string();
+    events.push(other_unit);
+    let deltas = metrics::aggregate_events(&events, Utc::now()).unwrap();
+    // events are added up per unit
+    assert_eq!(deltas.len(), 3);
+    let exposures = &deltas[&(
+        123456789,
+        "123456789-control".to_string(),
+        MetricEventType::Exposure,
+        String::new(),
+        "user-1".to_string(),
+    )];
+    assert_eq!(exposures.event_count, 2);
+    let checkout = &deltas[&(
+        123456789,
+        "123456789-control".to_string(),
+        MetricEventType::Conversion,
+        "checkout".to_string(),
+        "user-1".to_string(),
+    )];
+    assert_eq!(checkout.event_count, 2);
+    assert_eq!(checkout.value_count, 2);
+    assert_eq!(checkout.value_sum, 30.0);
+}
+
+#[test]
+fn test_reject_invalid_metric_events() {
+    let invalid_events = [
+        // variant of another experiment
+        metric_event("987654321-control", MetricEventType::Exposure, None, None),
+        metric_event("123456789-control", MetricEventType::Conversion, None, None),
+        metric_event(
+            "123456789-control",
+            MetricEventType::Exposure,
+            Some("checkout"),
+            None,
+        ),
+        metric_event(
+            "123456789-control",
+            MetricEventType::Conversion,
+            Some("checkout"),
+            Some(f64::NAN),
+        ),
+        MetricEvent {
+            unit_id: String::new(),
+            ..metric_event("123456789-control", MetricEventType::Exposure, None, None)
+        },
+    ];
+    for event in invalid_events {
+        assert!(matches!(
+            metrics::aggregate_events(&[event], Utc::now()),
+            Err(AppError::BadArgument(_))
+        ));
+    }
+}
+
+fn metric_row(
+    variant_id: &str,
+    event_type: MetricEventType,
+    metric: &str,
+    units: i64,
+    value_sum: f64,
+) -> metrics::UnitMetric {
+    metrics::UnitMetric {
+        variant_id: variant_id.to_string(),
+        event_type: event_type.to_string(),
+        metric: metric.to_string(),
+        units,
+        value_count: if value_sum > 0.0 { units } else { 0 },
+        value_sum,
+        value_sum_squares: 0.0,
+        last_event_at: Utc::now(),
+    }
+}
+
+#[test]
+fn test_variant_results() {
+    let variants = ["control", "experimental"]
+        .into_iter()
+        .map(|id| Variant {
+            id: format!("123456789-{id}"),
+            variant_type: match id {
+                "control" => VariantType::CONTROL,
+                _ => VariantType::EXPERIMENTAL,
+            },
+            context_id: None,
+            override_id: None,
+            overrides: Map::new(),
+            awaiting_ramp: false,
+        })
+        .collect::<Vec<Variant>>();
+    let rows = vec![
+        metric_row("123456789-control", MetricEventType::Exposure, "", 100, 0.0),
+        metric_row(
+            "123456789-control",
+            MetricEventType::Conversion,
+            "checkout",
+            10,
+            250.0,
+        ),
+    ];
+    let results = metrics::variant_results(&variants, &rows).unwrap();
+    assert_eq!(results.len(), 2);
+
+    let control = &results[0];
+    assert_eq!(control.exposures, 100);
+    assert_eq!(control.metrics[0].conversions, 10);
+    assert_eq!(control.metrics[0].conversion_rate, Some(0.1));
+    // values are averaged over every exposed unit
+    assert_eq!(control.metrics[0].mean, Some(2.5));
+
+    // variants without events are reported with zero counts
+    let experimental = &results[1];
+    assert_eq!(experimental.exposures, 0);
+    assert_eq!(experimental.metrics[0].conversions, 0);
+    assert_eq!(experimental.metrics[0].conversion_rate, None);
+    assert_eq!(experimental.last_event_at, None);
+}
+
+#[test]
+fn test_holdout_results() {
+    let variants = ["control", "experimental"]
+        .into_iter()
+        .map(|id| Variant {
+            id: format!("123456789-{id}"),
+            variant_type: match id {
+                "control" => VariantType::CONTROL,
+                _ => VariantType::EXPERIMENTAL,
+            },
+            context_id: None,
+            override_id: None,
+            overrides: Map::new(),
+            awaiting_ramp: false,
+        })
+        .collect::<Vec<Variant>>();
+    let mut rows = vec![
+        metric_row("123456789-control", MetricEventType::Exposure, "", 500, 0.0),
+        metric_row(
+            "123456789-experimental",
+            MetricEventType::Exposure,
+            "",
+            500,
+            0.0,
+        ),
+        metric_row(
+            "123456789-control",
+            MetricEventType::Conversion,
+            "checkout",
+            50,
+            0.0,
+        ),
+        metric_row(
+            "123456789-experimental",
+            MetricEventType::Conversion,
+            "checkout",
+            70,
+            0.0,
+        ),
+    ];
+    // no holdout reported yet
+    assert!(metrics::holdout_results(123456789, &variants, &rows)
+        .unwrap()
+        .is_none());
+
+    let holdout_id = metrics::holdout_variant_id(123456789);
+    assert_eq!(holdout_id, "123456789-holdout");
+    rows.push(metric_row(
+        &holdout_id,
+        MetricEventType::Exposure,
+        "",
+        1000,
+        0.0,
+    ));
+    rows.push(metric_row(
+        &holdout_id,
+        MetricEventType::Conversion,
+        "checkout",
+        80,
+        0.0,
+    ));
+    let results = metrics::holdout_results(123456789, &variants, &rows)
+        .unwrap()
+        .unwrap();
+    assert_eq!(results.holdout.exposures, 1000);
+    assert_eq!(results.holdout.metrics[0].conversion_test, None);
+    // every variant is tested against the holdout on its own
+    assert_eq!(results.variants.len(), 2);
+    let control = &results.variants[0];
+    assert_eq!(control.variant_type, VariantType::CONTROL);
+    assert_eq!(control.exposures, 500);
+    let test = control.metrics[0].conversion_test.clone().unwrap();
+    assert_close(test.lift.unwrap(), 0.25, 1e-9);
+    assert!(!test.significant);
+    let experimental = &results.variants[1];
+    assert_eq!(experimental.variant_type, VariantType::EXPERIMENTAL);
+    assert_eq!(experimental.metrics[0].conversions, 70);
+    let test = experimental.metrics[0].conversion_test.clone().unwrap();
+    assert_close(test.lift.unwrap(), 0.75, 1e-9);
+    assert!(test.significant);
+}
+
+#[test]
+fn test_global_holdout_results() {
+    let holdout_id = metrics::holdout_variant_id(metrics::GLOBAL_HOLDOUT_EXPERIMENT_ID);
+    let exposed_id = metrics::global_exposed_variant_id();
+    assert_eq!(
+        (holdout_id.as_str(), exposed_id.as_str()),
+        ("0-holdout", "0-exposed")
+    );
+    let row = |variant_id: &str, event_type: MetricEventType, units: i64| {
+        let metric = match event_type {
+            MetricEventType::Exposure => "",
+            MetricEventType::Conversion => "checkout",
+        };
+        metric_row(variant_id, event_type, metric, units, 0.0)
+    };
+    let rows = vec![
+        row(&holdout_id, MetricEventType::Exposure, 500),
+        row(&holdout_id, MetricEventType::Conversion, 40),
+        row(&exposed_id, MetricEventType::Exposure, 9500),
+        row(&exposed_id, MetricEventType::Conversion, 950),
+    ];
+    let results = metrics::global_holdout_results(5, &rows).unwrap();
+    assert_eq!(results.holdout_percentage, 5);
+    assert_eq!(results.holdout.exposures, 500);
+    assert_eq!(results.exposed.exposures, 9500);
+    let test = results.exposed.metrics[0].conversion_test.clone().unwrap();
+    assert_close(test.lift.unwrap(), 0.25, 1e-9);
+
+    // events of the global holdout are only taken for its two populations
+    let deltas = metrics::aggregate_events(
+        &[MetricEvent {
+            experiment_id: "0".to_string(),
+            ..metric_event("0-control", MetricEventType::Exposure, None, None)
+        }],
+        Utc::now(),
+    )
+    .unwrap();
+    assert!(matches!(
+        metrics::check_event_variants(&deltas, &Default::default()),
+        Err(AppError::BadArgument(_))
+    ));
+    let deltas = metrics::aggregate_events(
+        &[MetricEvent {
+            experiment_id: "0".to_string(),
+            ..metric_event(&exposed_id, MetricEventType::Exposure, None, None)
+        }],
+        Utc::now(),
+    )
+    .unwrap();
+    assert!(metrics::check_event_variants(&deltas, &Default::default()).is_ok());
+}
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() < tolerance,
+        "expected {expected}, got {actual}"
+    );
+}
+
+#[test]
+fn test_distribution_functions() {
+    assert_close(statistics::normal_cdf(0.0), 0.5, 1e-7);
+    assert_close(statistics::normal_cdf(1.959964), 0.975, 1e-6);
+    assert_close(statistics::normal_cdf(-1.959964), 0.025, 1e-6);
+    assert_close(statistics::student_t_cdf(2.228139, 10.0), 0.975, 1e-6);
+    assert_close(statistics::student_t_cdf(-12.7062, 1.0), 0.025, 1e-5);
+    assert_close(statistics::chi_square_survival(3.841459, 1.0), 0.05, 1e-6);
+    assert_close(statistics::chi_square_survival(9.487729, 4.0), 0.05, 1e-6);
+}
+
+#[test]
+fn test_two_proportion_z_test() {
+    let control = Proportion {
+        successes: 200,
+        trials: 1000,
+    };
+    let treatment = Proportion {
+        successes: 250,
+        trials: 1000,
+    };
+    let test = statistics::two_proportion_z_test(control, treatment).unwrap();
+    assert_close(test.lift.unwrap(), 0.25, 1e-9);
+    assert_close(test.p_value, 0.00742, 1e-4);
+    assert!(test.significant);
+    assert!(test.difference_interval.lower > 0.0);
+
+    // more conversions than exposures aren't a proportion
+    let repeated = Proportion {
+        successes: 1200,
+        trials: 1000,
+    };
+    assert!(statistics::two_proportion_z_test(control, repeated).is_none());
+    let interval = statistics::wilson_interval(control).unwrap();
+    assert!(interval.lower < 0.2 && 0.2 < interval.upper);
+}
+
+#[test]
+fn test_welch_t_test() {
+    let control = Sample {
+        count: 50,
+        mean: 10.0,
+        standard_deviation: 2.0,
+    };
+    let treatment = Sample {
+        count: 50,
+        mean: 11.0,
+        standard_deviation: 2.0,
+    };
+    let test = statistics::welch_t_test(control, treatment).unwrap();
+    assert_close(test.lift.unwrap(), 0.1, 1e-9);
+    assert_close(test.p_value, 0.0141, 1e-3);
+    assert!(test.significant);
+
+    let same = statistics::welch_t_test(control, control).unwrap();
+    assert_close(same.p_value, 1.0, 1e-9);
+    assert!(!same.significant);
+}
+
+#[test]
+fn test_sample_ratio_mismatch() {
+    let even = statistics::sample_ratio_mismatch(&[5000, 5010], &[1.0, 1.0]).unwrap();
+    assert!(!even.mismatch);
+    let skewed = statistics::sample_ratio_mismatch(&[5000, 4500], &[1.0, 1.0]).unwrap();
+    assert_close(skewed.chi_square, 26.315789, 1e-5);
+    assert!(skewed.mismatch);
+    assert!(statistics::sample_ratio_mismatch(&[0, 0], &[1.0, 1.0]).is_none());
+}
+
+#[test]
+fn test_expected_allocation() {
+    let result = |id: &str, exposures: i64| VariantResult {
+        variant_id: id.to_string(),
+        variant_type: VariantType::CONTROL,
+        exposures,
+        metrics: Vec::new(),
+        last_event_at: None,
+    };
+    let variant = |id: &str, awaiting_ramp: bool| Variant {
+        id: id.to_string(),
+        variant_type: VariantType::EXPERIMENTAL,
+        context_id: None,
+        override_id: None,
+        overrides: Map::new(),
+        awaiting_ramp,
+    };
+    let variants = [
+        variant("123456789-control", false),
+        variant("123456789-test", false),
+        variant("123456789-late", true),
+    ];
+    let results = [
+        result("123456789-control", 2000),
+        result("123456789-test", 2010),
+        result("123456789-late", 0),
+    ];
+    // 20% of traffic per variant next to a 5% global and 5% own holdout, the
+    // variant added while the experiment ran has none yet
+    let (observed, weights) =
+        metrics::expected_allocation(&variants, &results, 20, Some((1000, 10)));
+    assert_eq!(observed, vec![2000, 2010, 1000]);
+    assert_eq!(weights, vec![20.0, 20.0, 10.0]);
+    let check = statistics::sample_ratio_mismatch(&observed, &weights).unwrap();
+    assert!(!check.mismatch);
+
+    // equal weights would take the smaller holdout for a mismatch
+    let check = statistics::sample_ratio_mismatch(&observed, &[1.0; 3]).unwrap();
+    assert!(check.mismatch);
+
+    let (observed, weights) = metrics::expected_allocation(&variants, &results, 20, None);
+    assert_eq!(observed, vec![2000, 2010]);
+    assert_eq!(weights, vec![20.0, 20.0]);
+}
+
+#[test]
+fn test_assign_slice() {
+    let taken = [
+        Slice { start: 0, size: 20 },
+        Slice {
+            start: 30,
+            size: 10,
+        },
+    ];
+    // a new experiment is placed at the first free bucket
+    assert_eq!(assign_slice(None, 0, 60, &taken), Some(20));
+    // ramping in place while there is room keeps the slice where it is
+    assert_eq!(assign_slice(Some(20), 10, 60, &taken), Some(20));
+    // and moves it to the first gap which fits when there is not
+    assert_eq!(assign_slice(Some(20), 16, 60, &taken), Some(40));
+    // slices stay within the allocation of the layer
+    assert_eq!(assign_slice(Some(20), 30, 60, &taken), None);
+    assert_eq!(assign_slice(None, 20, 60, &[]), Some(0));
+}
+
+#[test]
+fn test_tenant_policy() {
+    let mut tenant_settings = TenantSettings {
+        id: true,
+        holdout_percentage: 10,
+        last_modified_by: "test".to_string(),
+        last_modified_at: Utc::now(),
+        allow_same_keys_overlapping_ctx: Some(false),
+        allow_diff_keys_overlapping_ctx: None,
+        allow_same_keys_non_overlapping_ctx: None,
+        max_variants: Some(3),
+        max_traffic_percentage: None,
+        required_approvers: 0,
+    };
+    let defaults = ExperimentationFlags {
+        allow_same_keys_overlapping_ctx: true,
+        allow_diff_keys_overlapping_ctx: false,
+        allow_same_keys_non_overlapping_ctx: true,
+    };
+    // flags the tenant didn't set follow the deployment's
+    let flags = settings::experimentation_flags(&tenant_settings, &defaults);
+    assert!(!flags.allow_same_keys_overlapping_ctx);
+    assert!(!flags.allow_diff_keys_overlapping_ctx);
+    assert!(flags.allow_same_keys_non_overlapping_ctx);
+
+    assert!(settings::check_variant_count(&tenant_settings, 3).is_ok());
+    assert!(matches!(
+        settings::check_variant_count(&tenant_settings, 4),
+        Err(AppError::BadArgument(_))
+    ));
+
+    // variants share the traffic left by the global and experiment holdouts
+    assert_eq!(settings::max_traffic_percentage(&tenant_settings, 6, 3), 28);
+    tenant_settings.max_traffic_percentage = Some(20);
+    assert_eq!(settings::max_traffic_percentage(&tenant_settings, 6, 3), 20);
+    assert_eq!(settings::max_traffic_percentage(&tenant_settings, 0, 8), 11);
+}
+
+#[test]
+fn test_update_settings_request() {
+    // fields left out are kept, fields set to null are cleared
+    let req = serde_json::from_value::<UpdateSettingsRequest>(json!({
+        "max_variants": null,
+        "max_traffic_percentage": 40,
+    }))
+    .unwrap();
+    assert_eq!(req.max_variants, Some(None));
+    assert_eq!(req.max_traffic_percentage, Some(Some(40)));
+    assert_eq!(req.allow_same_keys_overlapping_ctx, None);
+    assert!(settings::validate_settings(&req).is_ok());
+
+    let invalid = [
+        json!({"holdout_percentage": 60}),
+        json!({"max_variants": 1}),
+        json!({"max_traffic_percentage": 0}),
+        json!({"required_approvers": -1}),
+    ];
+    for req in invalid {
+        let req = serde_json::from_value::<UpdateSettingsRequest>(req).unwrap();
+        assert!(matches!(
+            settings::validate_settings(&req),
+            Err(AppError::BadArgument(_))
+        ));
+    }
+}
+
+#[test]
+fn test_traffic_timeline() {
+    let start = Utc::now();
+    let entry = |minutes: i64, event_type: ExperimentEventType, details: Value| {
+        ExperimentHistoryEntry {
+            event_type,
+            details,
+            reason: None,
+            created_by: "test".to_string(),
+            created_at: start + chrono::Duration::minutes(minutes),
+        }
+    };
+    let (paused, pause_details) = history::ramp_event(20, 0);
+    assert_eq!(paused, ExperimentEventType::Paused);
+    assert_eq!(pause_details, json!({"from": 20, "to": 0}));
+    // ramping a created experiment to zero starts it without traffic
+    assert_eq!(history::ramp_event(0, 0).0, ExperimentEventType::Ramped);
+
+    let events = vec![
+        entry(0, ExperimentEventType::Created, json!({})),
+        entry(1, ExperimentEventType::Approved, json!({})),
+        entry(2, ExperimentEventType::Ramped, json!({"from": 0, "to": 10})),
+        entry(3, ExperimentEventType::OverridesUpdated, json!({})),
+        entry(
+            4,
+            ExperimentEventType::Ramped,
+            json!({"from": 10, "to": 20}),
+        ),
+        entry(5, paused, pause_details),
+        entry(
+            6,
+            ExperimentEventType::Concluded,
+            json!({"chosen_variant": "a"}),
+        ),
+    ];
+    let traffic = history::traffic_timeline(&events)
+        .into_iter()
+        .map(|point| ((point.at - start).num_minutes(), point.traffic_percentage))
+        .collect::<Vec<(i64, i32)>>();
+    // only changes of traffic are points of the timeline
+    assert_eq!(traffic, vec![(0, 0), (2, 10), (4, 20), (5, 0)]);
+
+    // the holdout's share is only known while the traffic didn't change
+    assert_eq!(
+        history::steady_traffic(&history::traffic_timeline(&events[..4])),
+        Some(10)
+    );
+    assert_eq!(
+        history::steady_traffic(&history::traffic_timeline(&events[..5])),
+        None
+    );
+    assert_eq!(
+        history::steady_traffic(&history::traffic_timeline(&events[..2])),
+        None
+    );
+}
+
+#[test]
+fn test_merge_variants() {
+    let variant = |id: &str, variant_type: VariantType, value: i64| Variant {
+        id: id.to_string(),
+        variant_type,
+        context_id: Some(format!("context-{id}")),
+        override_id: Some(format!("override-{id}")),
+        overrides: Map::from_iter([("key1".to_string(), json!(value))]),
+        awaiting_ramp: false,
+    };
+    let existing = vec![
+        variant("123456789-control", VariantType::CONTROL, 1),
+        variant("123456789-test", VariantType::EXPERIMENTAL, 2),
+    ];
+    let ids = |variants: &[Variant]| {
+        variants
+            .iter()
+            .map(|variant| variant.id.clone())
+            .collect::<Vec<String>>()
+    };
+
+    // created experiments take any variants, all of which get new contexts
+    let merged = helpers::merge_variants(
+        123456789,
+        false,
+        existing.clone(),
+        vec![
+            variant("123456789-control", VariantType::CONTROL, 3),
+            variant("next", VariantType::EXPERIMENTAL, 4),
+        ],
+    )
+    .unwrap();
+    assert_eq!(ids(&merged), vec!["123456789-control", "123456789-next"]);
+    assert!(merged.iter().all(|variant| variant.context_id.is_none()));
+    assert!(matches!(
+        helpers::merge_variants(
+            123456789,
+            false,
+            existing.clone(),
+            vec![
+                variant("next", VariantType::CONTROL, 3),
+                variant("123456789-next", VariantType::EXPERIMENTAL, 4),
+            ],
+        ),
+        Err(AppError::BadArgument(_))
+    ));
+
+    // running experiments keep their variants and contexts, in order
+    let merged = helpers::merge_variants(
+        123456789,
+        true,
+        existing.clone(),
+        vec![
+            variant("next", VariantType::EXPERIMENTAL, 5),
+            variant("123456789-test", VariantType::EXPERIMENTAL, 2),
+            variant("123456789-control", VariantType::CONTROL, 1),
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        ids(&merged),
+        vec!["123456789-control", "123456789-test", "123456789-next"]
+    );
+    assert_eq!(
+        merged
+            .iter()
+            .map(|variant| variant.context_id.is_some())
+            .collect::<Vec<bool>>(),
+        vec![true, true, false]
+    );
+    // only the new variant waits for the next ramp to serve traffic
+    assert_eq!(
+        merged
+            .iter()
+            .map(|variant| variant.awaiting_ramp)
+            .collect::<Vec<bool>>(),
+        vec![false, false, true]
+    );
+    for requested in [
+        // removed
+        vec![variant("123456789-control", VariantType::CONTROL, 1)],
+        // changed
+        vec![
+            variant("123456789-control", VariantType::CONTROL, 1),
+            variant("123456789-test", VariantType::EXPERIMENTAL, 6),
+        ],
+        // a second control
+        vec![
+            variant("123456789-control", VariantType::CONTROL, 1),
+            variant("123456789-test", VariantType::EXPERIMENTAL, 2),
+            variant("next", VariantType::CONTROL, 1),
+        ],
+    ] {
+        assert!(matches!(
+            helpers::merge_variants(123456789, true, existing.clone(), requested),
+            Err(AppError::BadArgument(_))
+        ));
+    }
+}
+
+#[test]
+fn test_list_filters() {
+    assert_eq!(
+        helpers::dimension_filter_vars("os:android").unwrap(),
+        json!({"dimension": "os", "value": "android", "raw": "android"})
+    );
+    assert_eq!(
+        helpers::dimension_filter_vars("version:3").unwrap(),
+        json!({"dimension": "version", "value": 3, "raw": "3"})
+    );
+    // only the first ':' separates the dimension from its value
+    assert_eq!(
+        helpers::dimension_filter_vars("url:https://example.com").unwrap(),
+        json!({"dimension": "url", "value": "https://example.com", "raw": "https://example.com"})
+    );
+    for filter in ["android", ":android"] {
+        assert!(matches!(
+            helpers::dimension_filter_vars(filter),
+            Err(AppError::BadArgument(_))
+        ));
+    }
+
+    assert_eq!(
+        helpers::prefix_patterns(&[
+            "checkout.".to_string(),
+            "promo_50%".to_string(),
+            "a\\b".to_string()
+        ]),
+        vec!["checkout.%", "promo\\_50\\%%", "a\\\\b%"]
+    );
+}
+
+#[test]
+fn test_bucketing() {
+    // the algorithm is tested in superposition_bucketing, shared with the
+    // experimentation client, this checks how experiments are read for it
+    let variant = |id: &str, variant_type: VariantType| Variant {
+        id: id.to_string(),
+        variant_type,
+        context_id: None,
+        override_id: None,
+        overrides: Map::new(),
+        awaiting_ramp: false,
+    };
+    let variants = vec![
+        variant("123456789-control", VariantType::CONTROL),
+        variant("123456789-test", VariantType::EXPERIMENTAL),
+    ];
+    let mut experiment = experiment_gen(
+        &["key1".to_string()],
+        &json!({}),
+        ExperimentStatusType::INPROGRESS,
+        &json!(variants),
+    );
+    experiment.traffic_percentage = 10;
+    experiment.holdout_percentage = 10;
+    let chosen = |experiment: &Experiment, toss: i8, buckets: i32| {
+        bucketing::choose_variant(experiment, &variants, toss, buckets)
+            .map(|variant| variant.id.clone())
+    };
+    assert_eq!(
+        chosen(&experiment, 5, 100),
+        Some("123456789-control".to_string())
+    );
+    assert_eq!(
+        chosen(&experiment, 15, 100),
+        Some("123456789-test".to_string())
+    );
+    assert_eq!(chosen(&experiment, 25, 100), None);
+    // the experiment's holdout, then the global one
+    assert_eq!(chosen(&experiment, 95, 100), None);
+    assert_eq!(chosen(&experiment, 95, 90), None);
+    assert_eq!(
+        chosen(&experiment, -1, 100),
+        Some("123456789-test".to_string())
+    );
+
+    experiment.slice_start = 20;
+    assert_eq!(chosen(&experiment, 5, 100), None);
+    assert_eq!(
+        chosen(&experiment, 25, 100),
+        Some("123456789-control".to_string())
+    );
+
+    // a variant added while the experiment ran serves nothing before a ramp
+    let variants = vec![
+        variant("123456789-control", VariantType::CONTROL),
+        Variant {
+            awaiting_ramp: true,
+            ..variant("123456789-late", VariantType::EXPERIMENTAL)
+        },
+        variant("123456789-test", VariantType::EXPERIMENTAL),
+    ];
+    experiment.slice_start = 0;
+    let chosen = |toss: i8| {
+        bucketing::choose_variant(&experiment, &variants, toss, 100)
+            .map(|variant| variant.id.clone())
+    };
+    assert_eq!(chosen(15), Some("123456789-test".to_string()));
+    assert_eq!(chosen(25), None);
+    assert_eq!(chosen(-1), Some("123456789-test".to_string()));
+}
@@ -2,8 +2,9 @@ use leptos::ServerFnError;
 
 use crate::{
     types::{
-        Config, DefaultConfig, Dimension, Experiment, ExperimentsResponse,
-        FetchTypeTemplateResponse, FunctionResponse, ListFilters,
+        AuditHistoryEntry, Config, DefaultConfig, Dimension, Experiment,
+        ExperimentHistory, ExperimentResults, ExperimentsResponse,
+        FetchTypeTemplateResponse, FunctionResponse, ListFilters, Tenant,
     },
     utils::{
         construct_request_headers, get_host, parse_json_response, request,
@@ -30,6 +31,23 @@ pub async fn fetch_dimensions(tenant: String) -> Result<Vec<Dimension>, ServerFn
     Ok(response)
 }
 
+pub async fn fetch_tenants() -> Result<Vec<Tenant>, ServerFnError> {
+    let client = reqwest::Client::new();
+    let host = use_host_server();
+
+    let url = format!("{}/tenants", host);
+    let response: Vec<Tenant> = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+
+    Ok(response)
+}
+
 // #[server(GetDefaultConfig, "/fxn", "GetJson")]
 pub async fn fetch_default_config(
     tenant: String,
@@ -103,6 +121,30 @@ pub async fn fetch_experiments(
     if let Some(count) = filters.count {
         query_params.push(format!("count={}", count));
     }
+    let text_filters = [
+        ("name", filters.name),
+        ("created_by", filters.created_by),
+        ("override_key", filters.override_key),
+        (
+            "key_prefix",
+            filters.key_prefix.map(|prefixes| prefixes.join(",")),
+        ),
+        ("dimension", filters.dimension),
+        ("chosen_variant", filters.chosen_variant),
+    ];
+    for (field, value) in text_filters {
+        if let Some(value) = value {
+            let value: String =
+                url::form_urlencoded::byte_serialize(value.as_bytes()).collect();
+            query_params.push(format!("{}={}", field, value));
+        }
+    }
+    if let Some(sort_on) = filters.sort_on {
+        query_params.push(format!("sort_on={}", sort_on));
+    }
+    if let Some(sort_order) = filters.sort_order {
+        query_params.push(format!("sort_order={}", sort_order));
+    }
 
     let url = format!("{}/experiments?{}", host, query_params.join("&"));
     let response: ExperimentsResponse = client
@@ -233,3 +275,67 @@ pub async fn fetch_types(
         .await
         .map_err(err_handler)
 }
+
+/// `service` is the path the audit api of the entity is served under,
+/// `audit` for CAC and `experiments/audit` for experiments.
+pub async fn fetch_entity_history(
+    service: &'static str,
+    table: String,
+    id: String,
+    tenant: String,
+) -> Result<Vec<AuditHistoryEntry>, ServerFnError> {
+    let host = use_host_server();
+    let url = format!("{host}/{service}/entity/{table}/{id}");
+    let err_handler = |e: String| ServerFnError::ServerError(e.to_string());
+    let response = request::<()>(
+        url,
+        reqwest::Method::GET,
+        None,
+        construct_request_headers(&[("x-tenant", &tenant)]).map_err(err_handler)?,
+    )
+    .await
+    .map_err(err_handler)?;
+    parse_json_response::<Vec<AuditHistoryEntry>>(response)
+        .await
+        .map_err(err_handler)
+}
+
+pub async fn fetch_experiment_results(
+    id: String,
+    tenant: String,
+) -> Result<ExperimentResults, ServerFnError> {
+    let host = use_host_server();
+    let url = format!("{host}/experiments/{id}/results");
+    let err_handler = |e: String| ServerFnError::ServerError(e.to_string());
+    let response = request::<()>(
+        url,
+        reqwest::Method::GET,
+        None,
+        construct_request_headers(&[("x-tenant", &tenant)]).map_err(err_handler)?,
+    )
+    .await
+    .map_err(err_handler)?;
+    parse_json_response::<ExperimentResults>(response)
+        .await
+        .map_err(err_handler)
+}
+
+pub async fn fetch_experiment_history(
+    id: String,
+    tenant: String,
+) -> Result<ExperimentHistory, ServerFnError> {
+    let host = use_host_server();
+    let url = format!("{host}/experiments/{id}/history");
+    let err_handler = |e: String| ServerFnError::ServerError(e.to_string());
+    let response = request::<()>(
+        url,
+        reqwest::Method::GET,
+        None,
+        construct_request_headers(&[("x-tenant", &tenant)]).map_err(err_handler)?,
+    )
+    .await
+    .map_err(err_handler)?;
+    parse_json_response::<ExperimentHistory>(response)
+        .await
+        .map_err(err_handler)
+}
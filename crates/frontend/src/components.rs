@@ -1,4 +1,5 @@
 pub mod alert;
+pub mod audit_history;
 pub mod button;
 pub mod condition_pills;
 pub mod context_card;
@@ -11,7 +12,9 @@ pub mod dropdown;
 pub mod experiment;
 pub mod experiment_conclude_form;
 pub mod experiment_form;
+pub mod experiment_history;
 pub mod experiment_ramp_form;
+pub mod experiment_results;
 pub mod function_form;
 pub mod input_components;
 pub mod modal;
@@ -0,0 +1,133 @@
+use leptos::*;
+use serde_json::Value;
+
+use crate::{
+    api::fetch_entity_history,
+    components::skeleton::{Skeleton, SkeletonVariant},
+    types::{AuditHistoryEntry, FieldChange},
+};
+
+fn display_value(value: &Option<Value>) -> String {
+    match value {
+        Some(Value::String(value)) => value.clone(),
+        Some(value) => value.to_string(),
+        None => String::from("-"),
+    }
+}
+
+fn action_badge(action: &str) -> &'static str {
+    match action {
+        "INSERT" => "badge badge-success text-white",
+        "DELETE" => "badge badge-error text-white",
+        _ => "badge badge-info text-white",
+    }
+}
+
+#[component]
+fn field_change_row(change: FieldChange) -> impl IntoView {
+    view! {
+        <tr>
+            <td class="font-mono">{change.field.clone()}</td>
+            <td class="font-mono text-red-700 break-all">{display_value(&change.old_value)}</td>
+            <td class="font-mono text-green-700 break-all">
+                {display_value(&change.new_value)}
+            </td>
+        </tr>
+    }
+}
+
+#[component]
+fn history_entry(entry: AuditHistoryEntry) -> impl IntoView {
+    let badge_class = action_badge(&entry.action);
+    view! {
+        <li>
+            <hr/>
+            <div class="timeline-middle">
+                <i class="ri-checkbox-blank-circle-fill text-purple-700"></i>
+            </div>
+            <div class="timeline-end timeline-box w-full mb-4">
+                <div class="flex flex-row gap-3 items-center">
+                    <span class=badge_class>{entry.action.clone()}</span>
+                    <span class="font-semibold">{entry.user_name.clone()}</span>
+                    <span class="text-gray-500">
+                        {format!("{}", entry.timestamp.format("%v %T"))}
+                    </span>
+                </div>
+                {entry
+                    .request_id
+                    .clone()
+                    .map(|request_id| {
+                        view! {
+                            <div class="text-xs text-gray-400">request {request_id}</div>
+                        }
+                    })}
+                <table class="table table-xs mt-2">
+                    <thead>
+                        <tr>
+                            <th>Field</th>
+                            <th>Before</th>
+                            <th>After</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {entry
+                            .changes
+                            .into_iter()
+                            .map(|change| view! { <FieldChangeRow change=change/> })
+                            .collect_view()}
+                    </tbody>
+                </table>
+            </div>
+            <hr/>
+        </li>
+    }
+}
+
+/// Change history of one row as a timeline, `service` is `audit` for CAC
+/// tables and `experiments/audit` for experiments.
+#[component]
+pub fn audit_history(
+    #[prop(default = "audit")] service: &'static str,
+    table: &'static str,
+    id: String,
+) -> impl IntoView {
+    let tenant_rs = use_context::<ReadSignal<String>>().unwrap();
+    let history_resource = create_blocking_resource(
+        move || (tenant_rs.get(), id.clone()),
+        move |(tenant, id)| async move {
+            fetch_entity_history(service, table.to_string(), id, tenant).await
+        },
+    );
+
+    view! {
+        <div class="flex flex-col gap-2 mt-6">
+            <h3 class="card-title">History</h3>
+            <Suspense fallback=move || {
+                view! { <Skeleton variant=SkeletonVariant::Content/> }
+            }>
+                {move || match history_resource.get() {
+                    Some(Ok(history)) if history.is_empty() => {
+                        view! { <p class="text-gray-500">No changes recorded</p> }.into_view()
+                    }
+                    Some(Ok(history)) => {
+                        view! {
+                            <ul class="timeline timeline-vertical timeline-compact">
+                                {history
+                                    .into_iter()
+                                    .map(|entry| view! { <HistoryEntry entry=entry/> })
+                                    .collect_view()}
+                            </ul>
+                        }
+                            .into_view()
+                    }
+                    Some(Err(e)) => {
+                        logging::log!("failed to fetch history: {:?}", e);
+                        view! { <p class="text-gray-500">Failed to load history</p> }.into_view()
+                    }
+                    None => view! {}.into_view(),
+                }}
+
+            </Suspense>
+        </div>
+    }
+}
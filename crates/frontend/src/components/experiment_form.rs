@@ -97,7 +97,15 @@ where
         spawn_local({
             async move {
                 let result = if edit {
-                    update_experiment(experiment_id, f_variants, tenant).await
+                    update_experiment(
+                        experiment_id,
+                        f_context,
+                        f_variants,
+                        f_experiment_name,
+                        tenant,
+                        dimensions.get_value(),
+                    )
+                    .await
                 } else {
                     create_experiment(
                         f_context,
@@ -129,7 +137,6 @@ where
                     <span class="label-text">Experiment Name</span>
                 </label>
                 <input
-                    disabled=edit
                     value=move || experiment_name.get()
                     on:input=move |ev| set_experiment_name.set(event_target_value(&ev))
                     type="text"
@@ -151,7 +158,6 @@ where
                             context=context
                             handle_change=handle_context_form_change
                             is_standalone=false
-                            disabled=edit
                             heading_sub_text=String::from(
                                 "Define rules under which this experiment would run",
                             )
@@ -1,4 +1,4 @@
-use crate::types::{Variant, VariantFormT};
+use crate::types::{Variant, VariantFormT, VariantType};
 use serde::Serialize;
 use serde_json::{Map, Value};
 
@@ -13,6 +13,7 @@ pub struct ExperimentCreateRequest {
 #[derive(Serialize, Debug)]
 pub struct VariantUpdateRequest {
     pub id: String,
+    pub variant_type: VariantType,
     pub overrides: Map<String, Value>,
 }
 
@@ -20,6 +21,7 @@ impl From<VariantFormT> for VariantUpdateRequest {
     fn from(value: VariantFormT) -> Self {
         VariantUpdateRequest {
             id: value.id,
+            variant_type: value.variant_type,
             overrides: Map::from_iter(value.overrides),
         }
     }
@@ -33,5 +35,7 @@ impl FromIterator<VariantFormT> for Vec<VariantUpdateRequest> {
 
 #[derive(Serialize, Debug)]
 pub struct ExperimentUpdateRequest {
+    pub name: String,
+    pub context: Value,
     pub variants: Vec<VariantUpdateRequest>,
 }
@@ -41,15 +41,23 @@ pub async fn create_experiment(
 
 pub async fn update_experiment(
     experiment_id: String,
+    conditions: Vec<(String, String, String)>,
     variants: Vec<VariantFormT>,
+    name: String,
     tenant: String,
+    dimensions: Vec<Dimension>,
 ) -> Result<Value, String> {
+    if name.is_empty() {
+        return Err(String::from("experiment name should not be empty"));
+    }
     let payload = ExperimentUpdateRequest {
+        name,
+        context: construct_context(conditions, dimensions),
         variants: FromIterator::from_iter(variants),
     };
 
     let host = get_host();
-    let url = format!("{}/experiments/{}/overrides", host, experiment_id);
+    let url = format!("{}/experiments/{}", host, experiment_id);
 
     let response = request(
         url,
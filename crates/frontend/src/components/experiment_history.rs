@@ -0,0 +1,191 @@
+use chrono::Utc;
+use leptos::*;
+use serde_json::Value;
+
+use crate::{
+    api::fetch_experiment_history,
+    components::skeleton::{Skeleton, SkeletonVariant},
+    types::{ExperimentEventType, ExperimentHistoryEntry, TrafficPoint},
+};
+
+const CHART_WIDTH: f64 = 600.0;
+const CHART_HEIGHT: f64 = 120.0;
+
+fn event_badge(event_type: ExperimentEventType) -> &'static str {
+    match event_type {
+        ExperimentEventType::Created => "badge badge-neutral",
+        ExperimentEventType::Approved => "badge badge-info text-white",
+        ExperimentEventType::OverridesUpdated => "badge badge-warning",
+        ExperimentEventType::Ramped => "badge badge-primary",
+        ExperimentEventType::Paused => "badge badge-error text-white",
+        ExperimentEventType::Concluded => "badge badge-success text-white",
+        ExperimentEventType::Updated => "badge badge-warning",
+    }
+}
+
+fn joined(details: &Value, field: &str) -> Option<String> {
+    details.get(field).and_then(Value::as_array).map(|values| {
+        values
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<&str>>()
+            .join(", ")
+    })
+}
+
+fn event_summary(entry: &ExperimentHistoryEntry) -> Option<String> {
+    let details = &entry.details;
+    match entry.event_type {
+        ExperimentEventType::Ramped | ExperimentEventType::Paused => Some(format!(
+            "{}% → {}%",
+            details.get("from").cloned().unwrap_or(Value::Null),
+            details.get("to").cloned().unwrap_or(Value::Null),
+        )),
+        ExperimentEventType::Concluded => details
+            .get("chosen_variant")
+            .and_then(Value::as_str)
+            .map(|variant| format!("chose {variant}")),
+        ExperimentEventType::OverridesUpdated => joined(details, "override_keys"),
+        ExperimentEventType::Updated => joined(details, "fields"),
+        ExperimentEventType::Created | ExperimentEventType::Approved => None,
+    }
+}
+
+/// Step line of the traffic of each variant from the first point until now,
+/// scaled to the chart.
+fn traffic_path(traffic: &[TrafficPoint]) -> String {
+    let (Some(first), Some(last)) = (traffic.first(), traffic.last()) else {
+        return String::new();
+    };
+    let start = first.at.timestamp() as f64;
+    let span = (Utc::now().timestamp() as f64 - start).max(1.0);
+    let x = |at: f64| (at - start) / span * CHART_WIDTH;
+    let y = |traffic: i32| CHART_HEIGHT - traffic as f64 / 100.0 * CHART_HEIGHT;
+
+    let mut path = format!("M 0 {:.1}", y(first.traffic_percentage));
+    let mut current = first.traffic_percentage;
+    for point in traffic.iter().skip(1) {
+        let at = x(point.at.timestamp() as f64);
+        path.push_str(&format!(
+            " L {at:.1} {:.1} L {at:.1} {:.1}",
+            y(current),
+            y(point.traffic_percentage)
+        ));
+        current = point.traffic_percentage;
+    }
+    path.push_str(&format!(
+        " L {CHART_WIDTH:.1} {:.1}",
+        y(last.traffic_percentage)
+    ));
+    path
+}
+
+#[component]
+fn traffic_chart(traffic: Vec<TrafficPoint>) -> impl IntoView {
+    let peak = traffic
+        .iter()
+        .map(|point| point.traffic_percentage)
+        .max()
+        .unwrap_or_default();
+    let since = traffic
+        .first()
+        .map(|point| format!("{}", point.at.format("%v %T")))
+        .unwrap_or_default();
+    view! {
+        <div class="flex flex-col gap-1">
+            <div class="flex flex-row justify-between text-xs text-gray-500">
+                <span>{format!("Traffic per variant, peak {peak}%")}</span>
+                <span>{format!("since {since}")}</span>
+            </div>
+            <svg
+                class="w-full h-32 border rounded-lg bg-gray-50"
+                viewBox=format!("0 0 {CHART_WIDTH} {CHART_HEIGHT}")
+                preserveAspectRatio="none"
+            >
+                <path
+                    d=traffic_path(&traffic)
+                    fill="none"
+                    stroke="rgb(126 34 206)"
+                    stroke-width="2"
+                    vector-effect="non-scaling-stroke"
+                ></path>
+            </svg>
+        </div>
+    }
+}
+
+#[component]
+fn event_entry(entry: ExperimentHistoryEntry) -> impl IntoView {
+    let summary = event_summary(&entry);
+    view! {
+        <li>
+            <hr/>
+            <div class="timeline-middle">
+                <i class="ri-checkbox-blank-circle-fill text-purple-700"></i>
+            </div>
+            <div class="timeline-end timeline-box w-full mb-4">
+                <div class="flex flex-row gap-3 items-center">
+                    <span class=event_badge(entry.event_type)>
+                        {entry.event_type.to_string()}
+                    </span>
+                    {summary.map(|summary| view! { <span class="font-mono">{summary}</span> })}
+                    <span class="font-semibold">{entry.created_by.clone()}</span>
+                    <span class="text-gray-500">
+                        {format!("{}", entry.created_at.format("%v %T"))}
+                    </span>
+                </div>
+                {entry
+                    .reason
+                    .clone()
+                    .map(|reason| view! { <p class="text-sm text-gray-600 mt-1">{reason}</p> })}
+            </div>
+            <hr/>
+        </li>
+    }
+}
+
+/// Lifecycle of an experiment, its traffic over time and every event with
+/// the reason given for it.
+#[component]
+pub fn experiment_history(id: String) -> impl IntoView {
+    let tenant_rs = use_context::<ReadSignal<String>>().unwrap();
+    let history_resource = create_blocking_resource(
+        move || (tenant_rs.get(), id.clone()),
+        |(tenant, id)| async move { fetch_experiment_history(id, tenant).await },
+    );
+
+    view! {
+        <div class="flex flex-col gap-2 mt-6">
+            <h3 class="card-title">Timeline</h3>
+            <Suspense fallback=move || {
+                view! { <Skeleton variant=SkeletonVariant::Content/> }
+            }>
+                {move || match history_resource.get() {
+                    Some(Ok(history)) if history.events.is_empty() => {
+                        view! { <p class="text-gray-500">No events recorded</p> }.into_view()
+                    }
+                    Some(Ok(history)) => {
+                        view! {
+                            <TrafficChart traffic=history.traffic/>
+                            <ul class="timeline timeline-vertical timeline-compact">
+                                {history
+                                    .events
+                                    .into_iter()
+                                    .map(|entry| view! { <EventEntry entry=entry/> })
+                                    .collect_view()}
+                            </ul>
+                        }
+                            .into_view()
+                    }
+                    Some(Err(e)) => {
+                        logging::log!("failed to fetch experiment history: {:?}", e);
+                        view! { <p class="text-gray-500">Failed to load timeline</p> }
+                            .into_view()
+                    }
+                    None => view! {}.into_view(),
+                }}
+
+            </Suspense>
+        </div>
+    }
+}
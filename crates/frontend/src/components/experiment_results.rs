@@ -0,0 +1,310 @@
+use leptos::*;
+
+use crate::{
+    api::fetch_experiment_results,
+    components::skeleton::{Skeleton, SkeletonVariant},
+    types::{
+        ExperimentResults, HoldoutResults, Interval, MetricResult, PopulationResult,
+        SampleRatioCheck, SignificanceTest, VariantResult, VariantType,
+    },
+};
+
+fn percent(value: f64) -> String {
+    format!("{:.2}%", value * 100.0)
+}
+
+fn with_interval(
+    value: Option<f64>,
+    interval: Option<Interval>,
+    format: fn(f64) -> String,
+) -> String {
+    match (value, interval) {
+        (Some(value), Some(interval)) => format!(
+            "{} [{}, {}]",
+            format(value),
+            format(interval.lower),
+            format(interval.upper)
+        ),
+        (Some(value), None) => format(value),
+        (None, _) => String::from("-"),
+    }
+}
+
+fn number(value: f64) -> String {
+    format!("{value:.3}")
+}
+
+#[component]
+fn test_cells(test: Option<SignificanceTest>) -> impl IntoView {
+    match test {
+        Some(test) => {
+            let lift = test
+                .lift
+                .map(|lift| format!("{:+.2}%", lift * 100.0))
+                .unwrap_or_else(|| String::from("-"));
+            let lift_class = match (test.significant, test.lift) {
+                (true, Some(lift)) if lift > 0.0 => "font-semibold text-green-700",
+                (true, Some(_)) => "font-semibold text-red-700",
+                _ => "text-gray-500",
+            };
+            view! {
+                <td class=lift_class>{lift}</td>
+                <td>{format!("{:.4}", test.p_value)}</td>
+            }
+            .into_view()
+        }
+        None => view! {
+            <td>-</td>
+            <td>-</td>
+        }
+        .into_view(),
+    }
+}
+
+#[component]
+fn metric_row(variant: VariantResult, metric: MetricResult) -> impl IntoView {
+    let badge_class = match variant.variant_type {
+        VariantType::CONTROL => "badge badge-neutral",
+        VariantType::EXPERIMENTAL => "badge badge-primary",
+    };
+    view! {
+        <tr>
+            <td>
+                <span class=badge_class>{variant.variant_type.to_string()}</span>
+                <span class="ml-2 font-mono text-xs">{variant.variant_id.clone()}</span>
+            </td>
+            <td>{metric.conversions}</td>
+            <td>
+                {with_interval(
+                    metric.conversion_rate,
+                    metric.conversion_rate_interval,
+                    percent,
+                )}
+            </td>
+            <TestCells test=metric.conversion_test.clone()/>
+            <td>{with_interval(metric.mean, metric.mean_interval, number)}</td>
+            <TestCells test=metric.mean_test.clone()/>
+        </tr>
+    }
+}
+
+#[component]
+fn metric_table(metric: String, variants: Vec<VariantResult>) -> impl IntoView {
+    let rows = variants
+        .into_iter()
+        .filter_map(|variant| {
+            let result = variant.metrics.iter().find(|m| m.metric == metric).cloned();
+            result.map(|result| view! { <MetricRow variant=variant metric=result/> })
+        })
+        .collect_view();
+    view! {
+        <div class="overflow-x-auto">
+            <h4 class="font-semibold mt-4">{metric.clone()}</h4>
+            <table class="table table-xs">
+                <thead>
+                    <tr>
+                        <th>Variant</th>
+                        <th>Conversions</th>
+                        <th>Conversion Rate</th>
+                        <th>Lift</th>
+                        <th>p-value</th>
+                        <th>Mean</th>
+                        <th>Mean Lift</th>
+                        <th>p-value</th>
+                    </tr>
+                </thead>
+                <tbody>{rows}</tbody>
+            </table>
+        </div>
+    }
+}
+
+#[component]
+fn population_row(
+    name: String,
+    population: PopulationResult,
+    metric: String,
+) -> impl IntoView {
+    population
+        .metrics
+        .into_iter()
+        .find(|m| m.metric == metric)
+        .map(|metric| {
+            view! {
+                <tr>
+                    <td>{name}</td>
+                    <td>{population.exposures}</td>
+                    <td>{metric.conversions}</td>
+                    <td>
+                        {with_interval(
+                            metric.conversion_rate,
+                            metric.conversion_rate_interval,
+                            percent,
+                        )}
+                    </td>
+                    <TestCells test=metric.conversion_test.clone()/>
+                    <td>{with_interval(metric.mean, metric.mean_interval, number)}</td>
+                    <TestCells test=metric.mean_test.clone()/>
+                </tr>
+            }
+        })
+}
+
+/// Units held out of the experiment against the ones exposed to each of its
+/// variants, lifts are over the holdout.
+#[component]
+fn holdout_view(holdout: HoldoutResults) -> impl IntoView {
+    let metrics = holdout
+        .holdout
+        .metrics
+        .iter()
+        .map(|metric| metric.metric.clone())
+        .collect::<Vec<String>>();
+    view! {
+        <h4 class="font-semibold mt-4">Holdout</h4>
+        <p class="text-xs text-gray-500">
+            Users held out of the experiment against the users exposed to each of its variants
+        </p>
+        {metrics
+            .into_iter()
+            .map(|metric| {
+                view! {
+                    <div class="overflow-x-auto">
+                        <h5 class="text-sm mt-2">{metric.clone()}</h5>
+                        <table class="table table-xs">
+                            <thead>
+                                <tr>
+                                    <th>Population</th>
+                                    <th>Exposures</th>
+                                    <th>Conversions</th>
+                                    <th>Conversion Rate</th>
+                                    <th>Lift</th>
+                                    <th>p-value</th>
+                                    <th>Mean</th>
+                                    <th>Mean Lift</th>
+                                    <th>p-value</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                <PopulationRow
+                                    name=String::from("Holdout")
+                                    population=holdout.holdout.clone()
+                                    metric=metric.clone()
+                                />
+                                {holdout
+                                    .variants
+                                    .iter()
+                                    .map(|variant| {
+                                        view! {
+                                            <PopulationRow
+                                                name=variant.variant_id.clone()
+                                                population=PopulationResult::from(variant.clone())
+                                                metric=metric.clone()
+                                            />
+                                        }
+                                    })
+                                    .collect_view()}
+                            </tbody>
+                        </table>
+                    </div>
+                }
+            })
+            .collect_view()}
+    }
+}
+
+#[component]
+fn sample_ratio_alert(check: SampleRatioCheck) -> impl IntoView {
+    check.mismatch.then(|| {
+        view! {
+            <div role="alert" class="alert alert-warning">
+                <i class="ri-error-warning-line"></i>
+                <span>
+                    {format!(
+                        "Sample ratio mismatch: exposures are off the traffic split (p = {:.6}), the results can't be trusted",
+                        check.p_value,
+                    )}
+                </span>
+            </div>
+        }
+    })
+}
+
+#[component]
+fn results_view(results: ExperimentResults) -> impl IntoView {
+    let metrics = results
+        .variants
+        .first()
+        .map(|variant| {
+            variant
+                .metrics
+                .iter()
+                .map(|metric| metric.metric.clone())
+                .collect::<Vec<String>>()
+        })
+        .unwrap_or_default();
+    let variants = results.variants.clone();
+    view! {
+        {results.sample_ratio.map(|check| view! { <SampleRatioAlert check=check/> })}
+        <div class="stats shadow">
+            {results
+                .variants
+                .iter()
+                .map(|variant| {
+                    view! {
+                        <div class="stat">
+                            <div class="stat-title">{variant.variant_type.to_string()}</div>
+                            <div class="stat-value text-2xl">{variant.exposures}</div>
+                            <div class="stat-desc">exposures</div>
+                        </div>
+                    }
+                })
+                .collect_view()}
+        </div>
+        <p class="text-xs text-gray-500">
+            {format!(
+                "Intervals at {:.0}% confidence, lifts and p-values are against the control variant",
+                results.confidence_level * 100.0,
+            )}
+        </p>
+        {metrics
+            .into_iter()
+            .map(|metric| view! { <MetricTable metric=metric variants=variants.clone()/> })
+            .collect_view()}
+        {results.holdout.map(|holdout| view! { <HoldoutView holdout=holdout/> })}
+    }
+}
+
+/// Per-variant results of an experiment with their significance against the
+/// control variant.
+#[component]
+pub fn experiment_results(id: String) -> impl IntoView {
+    let tenant_rs = use_context::<ReadSignal<String>>().unwrap();
+    let results_resource = create_blocking_resource(
+        move || (tenant_rs.get(), id.clone()),
+        |(tenant, id)| async move { fetch_experiment_results(id, tenant).await },
+    );
+
+    view! {
+        <div class="flex flex-col gap-2 mt-6">
+            <h3 class="card-title">Results</h3>
+            <Suspense fallback=move || {
+                view! { <Skeleton variant=SkeletonVariant::Content/> }
+            }>
+                {move || match results_resource.get() {
+                    Some(Ok(results)) if results.variants.iter().all(|v| v.exposures == 0) => {
+                        view! { <p class="text-gray-500">No exposures reported yet</p> }
+                            .into_view()
+                    }
+                    Some(Ok(results)) => view! { <ResultsView results=results/> }.into_view(),
+                    Some(Err(e)) => {
+                        logging::log!("failed to fetch results: {:?}", e);
+                        view! { <p class="text-gray-500">Failed to load results</p> }.into_view()
+                    }
+                    None => view! {}.into_view(),
+                }}
+
+            </Suspense>
+        </div>
+    }
+}
@@ -1,3 +1,4 @@
+use crate::api::fetch_tenants;
 use crate::components::nav_item::NavItem;
 use crate::components::skeleton::{Skeleton, SkeletonVariant};
 use crate::types::AppRoute;
@@ -67,6 +68,25 @@ pub fn side_nav(
     let (app_routes, set_app_routes) =
         create_signal(create_routes(tenant_rs.get().as_str()));
 
+    // tenants can be created at runtime, the list from the env is only used
+    // when the tenant registry can't be reached
+    let tenants_resource = create_blocking_resource(
+        || (),
+        |_| async move {
+            match fetch_tenants().await {
+                Ok(tenants) => tenants
+                    .into_iter()
+                    .filter(|tenant| tenant.is_active)
+                    .map(|tenant| tenant.name)
+                    .collect::<Vec<String>>(),
+                Err(e) => {
+                    logging::log!("Unable to fetch tenants: {:?}", e);
+                    get_tenants()
+                }
+            }
+        },
+    );
+
     let resolved_path = create_rw_signal(resolved_path);
     let original_path = create_rw_signal(original_path);
 
@@ -127,7 +147,7 @@ pub fn side_nav(
                 >
 
                     {move || {
-                        let tenants = get_tenants();
+                        let tenants = tenants_resource.get().unwrap_or_default();
                         match tenants.is_empty() {
                             false => {
                                 tenants
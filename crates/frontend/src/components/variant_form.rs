@@ -265,7 +265,6 @@ where
             <div>
                 <button
                     class="btn btn-purple-outline btn-sm text-xs m-1"
-                    disabled=edit
                     on:click:undelegated=move |_| {
                         leptos::logging::log!("add new variant");
                         set_variants
@@ -1,6 +1,7 @@
 use crate::api::fetch_config;
 use crate::api::{delete_context, fetch_default_config, fetch_dimensions};
 use crate::components::alert::AlertType;
+use crate::components::audit_history::AuditHistory;
 use crate::components::button::Button;
 use crate::components::context_card::ContextCard;
 use crate::components::context_form::utils::{create_context, update_context};
@@ -21,6 +22,8 @@ use serde_json::{json, Map, Value};
 pub struct Data {
     pub context: Vec<(String, String, String)>,
     pub overrides: Vec<(String, Value)>,
+    /// Set when an existing context is edited
+    pub context_id: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
@@ -170,6 +173,7 @@ pub fn context_override() -> impl IntoView {
             set_selected_data.set(Some(Data {
                 context: conditions,
                 overrides: overrides.into_iter().collect::<Vec<(String, Value)>>(),
+                context_id: Some(context.id.clone()),
             }));
             set_form_mode.set(Some(FormMode::Edit));
 
@@ -184,6 +188,7 @@ pub fn context_override() -> impl IntoView {
             set_selected_data.set(Some(Data {
                 context: conditions,
                 overrides: overrides.into_iter().collect::<Vec<(String, Value)>>(),
+                context_id: None,
             }));
             set_form_mode.set(Some(FormMode::Create));
 
@@ -265,11 +270,16 @@ pub fn context_override() -> impl IntoView {
                                                 handle_submit=handle_submit
                                                 edit=true
                                             />
+                                            {data
+                                                .context_id
+                                                .map(|id| {
+                                                    view! { <AuditHistory table="contexts" id=id/> }
+                                                })}
                                         }
                                             .into_view()
                                     }
                                     (Some(FormMode::Create), data) => {
-                                        let Data { context, overrides } = data.unwrap_or_default();
+                                        let Data { context, overrides, .. } = data.unwrap_or_default();
                                         view! {
                                             <Form
                                                 context=context
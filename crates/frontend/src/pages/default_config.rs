@@ -1,5 +1,6 @@
 use crate::api::{delete_default_config, fetch_default_config};
 
+use crate::components::audit_history::AuditHistory;
 use crate::components::default_config_form::DefaultConfigForm;
 use crate::components::drawer::{close_drawer, open_drawer, Drawer, DrawerBtn};
 use crate::components::skeleton::Skeleton;
@@ -180,6 +181,7 @@ pub fn default_config() -> impl IntoView {
                 {move || {
                     let prefix = key_prefix.get();
                     if let Some(selected_config_data) = selected_config.get() {
+                        let history_key = selected_config_data.key.clone();
                         view! {
                             <Drawer
                                 id="default_config_drawer".to_string()
@@ -199,6 +201,7 @@ pub fn default_config() -> impl IntoView {
                                         close_drawer("default_config_drawer");
                                     }
                                 />
+                                <AuditHistory table="default_configs" id=history_key/>
 
                             </Drawer>
                         }
@@ -1,3 +1,4 @@
+use crate::components::audit_history::AuditHistory;
 use crate::components::dimension_form::DimensionForm;
 use crate::components::drawer::{close_drawer, open_drawer, Drawer, DrawerBtn};
 use crate::components::skeleton::Skeleton;
@@ -90,6 +91,7 @@ pub fn Dimensions() -> impl IntoView {
                     selected_dimension.set(None);
                 };
                 if let Some(selected_dimension_data) = selected_dimension.get() {
+                    let history_dimension = selected_dimension_data.dimension.clone();
                     view! {
                         <Drawer
                             id="dimension_drawer".to_string()
@@ -108,6 +110,7 @@ pub fn Dimensions() -> impl IntoView {
                                     close_drawer("dimension_drawer");
                                 }
                             />
+                            <AuditHistory table="dimensions" id=history_dimension/>
 
                         </Drawer>
                     }
@@ -6,10 +6,13 @@ use serde::{Deserialize, Serialize};
 use crate::{
     api::{fetch_default_config, fetch_dimensions, fetch_experiment},
     components::{
+        audit_history::AuditHistory,
         experiment::Experiment,
         experiment_conclude_form::ExperimentConcludeForm,
         experiment_form::ExperimentForm,
+        experiment_history::ExperimentHistory,
         experiment_ramp_form::utils::ramp_experiment,
+        experiment_results::ExperimentResults,
         modal::Modal,
         skeleton::{Skeleton, SkeletonVariant},
     },
@@ -101,6 +104,19 @@ pub fn experiment_page() -> impl IntoView {
                                 handle_conclude=handle_conclude
                                 handle_edit=handle_edit
                             />
+                            <div class="m-5">
+                                <ExperimentResults id=experiment.id.clone()/>
+                            </div>
+                            <div class="m-5">
+                                <ExperimentHistory id=experiment.id.clone()/>
+                            </div>
+                            <div class="m-5">
+                                <AuditHistory
+                                    service="experiments/audit"
+                                    table="experiments"
+                                    id=experiment.id.clone()
+                                />
+                            </div>
                             <Modal
                                 id="ramp_form_modal".to_string()
                                 handle_close=move || { close_modal("ramp_form_modal") }
@@ -11,7 +11,7 @@ use crate::components::skeleton::Skeleton;
 use crate::components::table::types::TablePaginationProps;
 use crate::components::{experiment_form::ExperimentForm, stat::Stat, table::Table};
 
-use crate::types::{ExperimentsResponse, ListFilters};
+use crate::types::{ExperimentSortOn, ExperimentsResponse, ListFilters, SortOrder};
 
 use self::utils::experiment_table_columns;
 use crate::{
@@ -27,6 +27,101 @@ struct CombinedResource {
     default_config: Vec<DefaultConfig>,
 }
 
+const SORTS: [(ExperimentSortOn, SortOrder, &str); 4] = [
+    (
+        ExperimentSortOn::LastModified,
+        SortOrder::Desc,
+        "Recently modified",
+    ),
+    (ExperimentSortOn::CreatedAt, SortOrder::Desc, "Newest"),
+    (ExperimentSortOn::CreatedAt, SortOrder::Asc, "Oldest"),
+    (
+        ExperimentSortOn::TrafficPercentage,
+        SortOrder::Desc,
+        "Most traffic",
+    ),
+];
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+#[component]
+fn experiment_filters(set_filters: WriteSignal<ListFilters>) -> impl IntoView {
+    // every change of the filters starts again from the first page
+    let update = move |change: Box<dyn FnOnce(&mut ListFilters)>| {
+        set_filters.update(|filters| {
+            change(filters);
+            filters.page = Some(1);
+        })
+    };
+    view! {
+        <div class="flex flex-row flex-wrap gap-3 mb-4">
+            <input
+                type="text"
+                placeholder="Search by name"
+                class="input input-bordered input-sm w-full max-w-xs"
+                on:change=move |ev| {
+                    let value = non_empty(event_target_value(&ev));
+                    update(Box::new(move |filters| filters.name = value))
+                }
+            />
+            <input
+                type="text"
+                placeholder="Created by"
+                class="input input-bordered input-sm w-full max-w-xs"
+                on:change=move |ev| {
+                    let value = non_empty(event_target_value(&ev));
+                    update(Box::new(move |filters| filters.created_by = value))
+                }
+            />
+            <input
+                type="text"
+                placeholder="Override key"
+                class="input input-bordered input-sm w-full max-w-xs"
+                on:change=move |ev| {
+                    let value = non_empty(event_target_value(&ev));
+                    update(Box::new(move |filters| filters.override_key = value))
+                }
+            />
+            <input
+                type="text"
+                placeholder="Dimension, e.g. os:android"
+                class="input input-bordered input-sm w-full max-w-xs"
+                on:change=move |ev| {
+                    let value = non_empty(event_target_value(&ev));
+                    update(Box::new(move |filters| filters.dimension = value))
+                }
+            />
+            <select
+                class="select select-bordered select-sm w-full max-w-xs"
+                on:change=move |ev| {
+                    let sort = event_target_value(&ev)
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|idx| SORTS.get(idx).copied());
+                    update(
+                        Box::new(move |filters| {
+                            filters.sort_on = sort.map(|(sort_on, _, _)| sort_on);
+                            filters.sort_order = sort.map(|(_, sort_order, _)| sort_order);
+                        }),
+                    )
+                }
+            >
+
+                {SORTS
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, (_, _, label))| {
+                        view! { <option value=idx.to_string()>{*label}</option> }
+                    })
+                    .collect_view()}
+            </select>
+        </div>
+    }
+}
+
 #[component]
 pub fn experiment_list() -> impl IntoView {
     // acquire tenant
@@ -37,6 +132,7 @@ pub fn experiment_list() -> impl IntoView {
         to_date: Utc.timestamp_opt(4130561031, 0).single(),
         page: Some(1),
         count: Some(10),
+        ..ListFilters::default()
     });
 
     let (reset_exp_form, set_exp_form) = create_signal(0);
@@ -100,7 +196,6 @@ pub fn experiment_list() -> impl IntoView {
         });
     });
 
-    // TODO: Add filters
     view! {
         <div class="p-8">
             <Suspense fallback=move || view! { <Skeleton/> }>
@@ -133,6 +228,7 @@ pub fn experiment_list() -> impl IntoView {
                                 </DrawerBtn>
                             </div>
                         </div>
+                        <ExperimentFilters set_filters=set_filters/>
                         <div>
                             {move || {
                                 let value = combined_resource.get();
@@ -6,7 +6,10 @@ use leptos::*;
 use leptos_router::use_params_map;
 
 use crate::{
-    components::skeleton::{Skeleton, SkeletonVariant},
+    components::{
+        audit_history::AuditHistory,
+        skeleton::{Skeleton, SkeletonVariant},
+    },
     types::FunctionResponse,
 };
 
@@ -82,6 +85,7 @@ pub fn function_page() -> impl IntoView {
                 match function {
                     Some(function) => {
                         let function_ef = function.clone();
+                        let history_function_name = function.function_name.clone();
                         let function_data = function.clone();
                         publish_error_ws.set("".to_string());
                         match function.published_at {
@@ -503,6 +507,9 @@ pub fn function_page() -> impl IntoView {
                                         })
                                 }}
 
+                                <div class="m-5">
+                                    <AuditHistory table="functions" id=history_function_name/>
+                                </div>
                             </div>
                         }
                             .into_view()
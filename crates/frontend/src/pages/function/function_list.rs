@@ -28,6 +28,7 @@ pub fn function_list() -> impl IntoView {
         to_date: Utc.timestamp_opt(4130561031, 0).single(),
         page: Some(1),
         count: Some(10),
+        ..ListFilters::default()
     });
     let table_columns = create_memo(move |_| function_table_columns());
 
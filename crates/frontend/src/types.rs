@@ -49,6 +49,15 @@ pub struct Envs {
 
 /*************************Function Type ***************************/
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Tenant {
+    pub name: String,
+    pub is_active: bool,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub last_modified_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FunctionResponse {
     pub function_name: String,
@@ -108,13 +117,44 @@ pub struct ExperimentsResponse {
 #[derive(Serialize, Deserialize, Debug, Clone, Deref, DerefMut, PartialEq)]
 pub struct StatusTypes(pub Vec<ExperimentStatusType>);
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, strum_macros::Display,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ExperimentSortOn {
+    CreatedAt,
+    #[default]
+    LastModified,
+    TrafficPercentage,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, strum_macros::Display,
+)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub struct ListFilters {
     pub status: Option<StatusTypes>,
     pub from_date: Option<DateTime<Utc>>,
     pub to_date: Option<DateTime<Utc>>,
     pub page: Option<i64>,
     pub count: Option<i64>,
+    pub name: Option<String>,
+    pub created_by: Option<String>,
+    pub override_key: Option<String>,
+    pub key_prefix: Option<Vec<String>>,
+    pub dimension: Option<String>,
+    pub chosen_variant: Option<String>,
+    pub sort_on: Option<ExperimentSortOn>,
+    pub sort_order: Option<SortOrder>,
 }
 
 #[derive(Deserialize, Serialize, Clone, PartialEq, Debug, strum_macros::Display)]
@@ -291,3 +331,137 @@ pub struct FetchTypeTemplateResponse {
     pub total_pages: i64,
     pub data: Vec<TypeTemplate>,
 }
+
+/*************************** Audit Log types ********************************/
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old_value: Option<Value>,
+    pub new_value: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AuditHistoryEntry {
+    pub id: String,
+    pub user_name: String,
+    pub timestamp: NaiveDateTime,
+    pub action: String,
+    pub request_id: Option<String>,
+    pub changes: Vec<FieldChange>,
+}
+
+/*************************** Experiment Results types ***************************/
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct Interval {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SignificanceTest {
+    pub lift: Option<f64>,
+    pub difference_interval: Interval,
+    pub p_value: f64,
+    pub significant: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MetricResult {
+    pub metric: String,
+    pub conversions: i64,
+    pub conversion_rate: Option<f64>,
+    pub conversion_rate_interval: Option<Interval>,
+    pub value_count: i64,
+    pub mean: Option<f64>,
+    pub standard_deviation: Option<f64>,
+    pub mean_interval: Option<Interval>,
+    pub conversion_test: Option<SignificanceTest>,
+    pub mean_test: Option<SignificanceTest>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct VariantResult {
+    pub variant_id: String,
+    pub variant_type: VariantType,
+    pub exposures: i64,
+    pub metrics: Vec<MetricResult>,
+    pub last_event_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SampleRatioCheck {
+    pub chi_square: f64,
+    pub p_value: f64,
+    pub mismatch: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PopulationResult {
+    pub exposures: i64,
+    pub metrics: Vec<MetricResult>,
+    pub last_event_at: Option<DateTime<Utc>>,
+}
+
+impl From<VariantResult> for PopulationResult {
+    fn from(result: VariantResult) -> Self {
+        PopulationResult {
+            exposures: result.exposures,
+            metrics: result.metrics,
+            last_event_at: result.last_event_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HoldoutResults {
+    pub holdout: PopulationResult,
+    pub variants: Vec<VariantResult>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ExperimentResults {
+    pub experiment_id: String,
+    pub confidence_level: f64,
+    pub variants: Vec<VariantResult>,
+    pub sample_ratio: Option<SampleRatioCheck>,
+    pub holdout: Option<HoldoutResults>,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Deserialize, Serialize, strum_macros::Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "title_case")]
+pub enum ExperimentEventType {
+    Created,
+    Approved,
+    OverridesUpdated,
+    Ramped,
+    Paused,
+    Concluded,
+    Updated,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ExperimentHistoryEntry {
+    pub event_type: ExperimentEventType,
+    pub details: Value,
+    pub reason: Option<String>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TrafficPoint {
+    pub at: DateTime<Utc>,
+    pub traffic_percentage: i32,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ExperimentHistory {
+    pub experiment_id: String,
+    pub events: Vec<ExperimentHistoryEntry>,
+    pub traffic: Vec<TrafficPoint>,
+}
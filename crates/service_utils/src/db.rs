@@ -1,2 +1,5 @@
+pub mod audit;
+pub mod migrations;
+pub mod partitions;
 pub mod pgschema_manager;
 pub mod utils;
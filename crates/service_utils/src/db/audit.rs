@@ -0,0 +1,131 @@
+use std::{
+    collections::BTreeSet,
+    future::{ready, Ready},
+};
+
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
+use diesel::{pg::Pg, sql_types::Text, Connection, QueryResult, RunQueryDsl};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use superposition_types::{SuperpositionUser, User};
+
+use crate::middlewares::request_id::RequestId;
+
+/// Who is making the writes of a request. The `event_logger()` trigger reads
+/// it from transaction local settings, so writes have to run in a transaction
+/// which called `record`, or through `transaction`.
+#[derive(Debug, Clone, Default)]
+pub struct AuditActor {
+    pub user_email: String,
+    pub request_id: String,
+}
+
+impl AuditActor {
+    /// Sets the actor for the rest of the current transaction, outside of one
+    /// it has no effect.
+    pub fn record<C>(&self, conn: &mut C) -> QueryResult<()>
+    where
+        C: Connection<Backend = Pg>,
+    {
+        diesel::sql_query(
+            "SELECT set_config('superposition.user_email', $1, true), set_config('superposition.request_id', $2, true)",
+        )
+        .bind::<Text, _>(&self.user_email)
+        .bind::<Text, _>(&self.request_id)
+        .execute(conn)?;
+        Ok(())
+    }
+
+    /// Runs `f` in a transaction attributed to this actor.
+    pub fn transaction<T, E, F, C>(&self, conn: &mut C, f: F) -> Result<T, E>
+    where
+        C: Connection<Backend = Pg>,
+        F: FnOnce(&mut C) -> Result<T, E>,
+        E: From<diesel::result::Error>,
+    {
+        conn.transaction(|transaction_conn| {
+            self.record(transaction_conn)?;
+            f(transaction_conn)
+        })
+    }
+}
+
+impl FromRequest for AuditActor {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let extensions = req.extensions();
+        let actor = AuditActor {
+            user_email: extensions
+                .get::<User>()
+                .map(|user| user.get_email())
+                .unwrap_or_default(),
+            request_id: extensions
+                .get::<RequestId>()
+                .map(|request_id| request_id.0.clone())
+                .unwrap_or_default(),
+        };
+        ready(Ok(actor))
+    }
+}
+
+/// A column whose value differs between the `original_data` and `new_data`
+/// of an `event_log` entry, `None` when the row did not exist on that side.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old_value: Option<Value>,
+    pub new_value: Option<Value>,
+}
+
+/// Field level diff of an `event_log` entry, sorted by field. Inserts list
+/// every column as added and deletes every column as removed.
+pub fn field_changes(original: Option<&Value>, new: Option<&Value>) -> Vec<FieldChange> {
+    let empty = Map::new();
+    let original = original.and_then(Value::as_object).unwrap_or(&empty);
+    let new = new.and_then(Value::as_object).unwrap_or(&empty);
+    original
+        .keys()
+        .chain(new.keys())
+        .collect::<BTreeSet<&String>>()
+        .into_iter()
+        .filter(|field| original.get(*field) != new.get(*field))
+        .map(|field| FieldChange {
+            field: field.to_string(),
+            old_value: original.get(field).cloned(),
+            new_value: new.get(field).cloned(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_field_changes() {
+        let original = json!({"key": "a", "value": 1, "created_by": "x"});
+        let new = json!({"key": "a", "value": 2, "schema": {}, "created_by": "x"});
+        assert_eq!(
+            field_changes(Some(&original), Some(&new)),
+            vec![
+                FieldChange {
+                    field: "schema".to_string(),
+                    old_value: None,
+                    new_value: Some(json!({})),
+                },
+                FieldChange {
+                    field: "value".to_string(),
+                    old_value: Some(json!(1)),
+                    new_value: Some(json!(2)),
+                },
+            ]
+        );
+        let deleted = field_changes(Some(&original), None);
+        assert_eq!(deleted.len(), 3);
+        assert!(deleted.iter().all(|change| change.new_value.is_none()));
+    }
+}
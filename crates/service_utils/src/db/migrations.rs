@@ -0,0 +1,235 @@
+use std::fmt;
+
+use diesel::{
+    connection::SimpleConnection,
+    sql_types::{BigInt, Text},
+    Connection, PgConnection, QueryableByName, RunQueryDsl,
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// A migration embedded in the binary. Migrations are written against the
+/// `public` schema and rewritten for the schema they are applied to.
+pub struct SchemaMigration {
+    pub version: &'static str,
+    pub up_sql: &'static str,
+    /// A `table` or `table.column` the migration creates. Schemas migrated
+    /// before they had a migrations table are baselined with it, migrations
+    /// which are safe to run again leave it empty.
+    pub marker: Option<&'static str>,
+}
+
+/// Table in every tenant schema recording the migrations applied to it.
+pub const MIGRATIONS_TABLE: &str = "__superposition_migrations";
+
+#[derive(Debug, Clone, Default)]
+pub struct MigrationReport {
+    pub schema: String,
+    pub baselined: Vec<String>,
+    pub applied: Vec<String>,
+}
+
+impl fmt::Display for MigrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.baselined.is_empty() && self.applied.is_empty() {
+            return write!(f, "{}: up-to-date", self.schema);
+        }
+        write!(f, "{}:", self.schema)?;
+        for version in self.baselined.iter() {
+            write!(f, "\n  = {version} (already applied, recorded)")?;
+        }
+        for version in self.applied.iter() {
+            write!(f, "\n  + {version}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(QueryableByName)]
+struct Count {
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+#[derive(QueryableByName)]
+struct Version {
+    #[diesel(sql_type = Text)]
+    version: String,
+}
+
+static PUBLIC_QUALIFIER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\bpublic\.").expect("invalid schema qualifier regex"));
+
+/// Rewrites a migration for `schema`, only `public.` qualifiers are replaced
+/// so names and strings containing `public` are kept as they are.
+pub fn migration_sql_for_schema(migration: &SchemaMigration, schema: &str) -> String {
+    PUBLIC_QUALIFIER
+        .replace_all(migration.up_sql, format!("{schema}."))
+        .into_owned()
+}
+
+fn relation_exists(
+    conn: &mut PgConnection,
+    schema: &str,
+    marker: &str,
+) -> anyhow::Result<bool> {
+    let query = match marker.split_once('.') {
+        Some((table, column)) => diesel::sql_query(
+            "SELECT count(*) AS count FROM information_schema.columns WHERE table_schema = $1 AND table_name = $2 AND column_name = $3",
+        )
+        .bind::<Text, _>(schema)
+        .bind::<Text, _>(table)
+        .bind::<Text, _>(column)
+        .get_result::<Count>(conn),
+        None => diesel::sql_query(
+            "SELECT count(*) AS count FROM information_schema.tables WHERE table_schema = $1 AND table_name = $2",
+        )
+        .bind::<Text, _>(schema)
+        .bind::<Text, _>(marker)
+        .get_result::<Count>(conn),
+    };
+    Ok(query?.count > 0)
+}
+
+/// Creates the migrations table of `schema`, returning whether it was missing.
+fn ensure_migrations_table(
+    conn: &mut PgConnection,
+    schema: &str,
+) -> anyhow::Result<bool> {
+    let exists = relation_exists(conn, schema, MIGRATIONS_TABLE)?;
+    if !exists {
+        conn.batch_execute(&format!(
+            "CREATE TABLE {schema}.{MIGRATIONS_TABLE} (
+                version TEXT PRIMARY KEY,
+                run_on TIMESTAMPTZ NOT NULL DEFAULT now()
+            );"
+        ))?;
+    }
+    Ok(!exists)
+}
+
+fn record_version(
+    conn: &mut PgConnection,
+    schema: &str,
+    version: &str,
+) -> anyhow::Result<()> {
+    diesel::sql_query(format!(
+        "INSERT INTO {schema}.{MIGRATIONS_TABLE} (version) VALUES ($1)"
+    ))
+    .bind::<Text, _>(version)
+    .execute(conn)?;
+    Ok(())
+}
+
+/// Schemas created by `scripts/create-tenant.sh` have no migrations table,
+/// the migrations whose marker exists are recorded without running them.
+fn baseline(
+    conn: &mut PgConnection,
+    schema: &str,
+    migrations: &[SchemaMigration],
+) -> anyhow::Result<Vec<String>> {
+    let mut baselined = Vec::new();
+    for migration in migrations.iter() {
+        let Some(marker) = migration.marker else {
+            continue;
+        };
+        if relation_exists(conn, schema, marker)? {
+            record_version(conn, schema, migration.version)?;
+            baselined.push(migration.version.to_string());
+        }
+    }
+    Ok(baselined)
+}
+
+/// Applies the migrations `schema` has not seen yet, in order, each in its
+/// own savepoint so a failure leaves the earlier ones recorded. Replicas
+/// migrating the same schema wait for each other on an advisory lock held
+/// until the migrations are committed.
+pub fn run_pending_migrations(
+    conn: &mut PgConnection,
+    schema: &str,
+    migrations: &[SchemaMigration],
+) -> anyhow::Result<MigrationReport> {
+    let (report, failure) = conn.transaction::<_, anyhow::Error, _>(|conn| {
+        diesel::sql_query(format!(
+            "SELECT pg_advisory_xact_lock(hashtext('{MIGRATIONS_TABLE}'), hashtext($1))"
+        ))
+        .bind::<Text, _>(schema)
+        .execute(conn)?;
+        apply_pending_migrations(conn, schema, migrations)
+    })?;
+    match failure {
+        Some(e) => Err(e),
+        None => Ok(report),
+    }
+}
+
+/// The failure of a migration is returned next to the report instead of as
+/// an error, so that the migrations applied before it are committed.
+fn apply_pending_migrations(
+    conn: &mut PgConnection,
+    schema: &str,
+    migrations: &[SchemaMigration],
+) -> anyhow::Result<(MigrationReport, Option<anyhow::Error>)> {
+    let mut report = MigrationReport {
+        schema: schema.to_string(),
+        ..Default::default()
+    };
+    conn.batch_execute(&format!("CREATE SCHEMA IF NOT EXISTS {schema};"))?;
+    if ensure_migrations_table(conn, schema)? {
+        report.baselined = baseline(conn, schema, migrations)?;
+    }
+
+    let done =
+        diesel::sql_query(format!("SELECT version FROM {schema}.{MIGRATIONS_TABLE}"))
+            .load::<Version>(conn)?
+            .into_iter()
+            .map(|row| row.version)
+            .collect::<Vec<String>>();
+
+    for migration in migrations.iter() {
+        if done.iter().any(|version| version == migration.version) {
+            continue;
+        }
+        log::info!("running migration {} on {schema}", migration.version);
+        let result = conn.transaction::<_, anyhow::Error, _>(|transaction_conn| {
+            // some migrations use unqualified table names
+            transaction_conn
+                .batch_execute(&format!("SET LOCAL search_path TO {schema}, public;"))?;
+            transaction_conn
+                .batch_execute(&migration_sql_for_schema(migration, schema))?;
+            record_version(transaction_conn, schema, migration.version)
+        });
+        if let Err(e) = result {
+            let failure = anyhow::anyhow!(
+                "migration {} failed on {schema}: {e}",
+                migration.version
+            );
+            return Ok((report, Some(failure)));
+        }
+        report.applied.push(migration.version.to_string());
+    }
+    Ok((report, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migration_sql_for_schema() {
+        let migration = SchemaMigration {
+            version: "2024-01-01-000000_test",
+            up_sql: "CREATE TABLE public.publications (id TEXT);\n\
+                     INSERT INTO public.publications VALUES ('public');\n\
+                     CREATE INDEX publications_index ON public.publications (id);",
+            marker: None,
+        };
+        assert_eq!(
+            migration_sql_for_schema(&migration, "dev_cac"),
+            "CREATE TABLE dev_cac.publications (id TEXT);\n\
+             INSERT INTO dev_cac.publications VALUES ('public');\n\
+             CREATE INDEX publications_index ON dev_cac.publications (id);"
+        );
+    }
+}
@@ -0,0 +1,363 @@
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{Datelike, NaiveDate};
+use diesel::{
+    connection::SimpleConnection,
+    sql_types::{BigInt, Bool, Text},
+    PgConnection, QueryableByName, RunQueryDsl,
+};
+
+use crate::helpers::{get_from_env_or_default, get_from_env_unsafe};
+
+/// `event_log` is range partitioned by month, one `event_log_yYYYYmMM` table
+/// per month.
+const EVENT_LOG_TABLE: &str = "event_log";
+const ARCHIVE_BATCH_SIZE: i64 = 5000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Month {
+    year: i32,
+    /// 1 to 12
+    month: u32,
+}
+
+impl Month {
+    pub fn of(date: NaiveDate) -> Self {
+        Month {
+            year: date.year(),
+            month: date.month(),
+        }
+    }
+
+    pub fn add(self, months: i32) -> Self {
+        let index = self.year * 12 + self.month as i32 - 1 + months;
+        Month {
+            year: index.div_euclid(12),
+            month: index.rem_euclid(12) as u32 + 1,
+        }
+    }
+
+    pub fn partition_name(&self) -> String {
+        format!("{EVENT_LOG_TABLE}_y{:04}m{:02}", self.year, self.month)
+    }
+
+    pub fn from_partition_name(name: &str) -> Option<Self> {
+        let (year, month) = name
+            .strip_prefix(EVENT_LOG_TABLE)?
+            .strip_prefix("_y")?
+            .split_once('m')?;
+        let month = Month {
+            year: year.parse().ok()?,
+            month: month.parse().ok()?,
+        };
+        (1..=12).contains(&month.month).then_some(month)
+    }
+
+    fn first_day(&self) -> String {
+        format!("{:04}-{:02}-01", self.year, self.month)
+    }
+}
+
+#[derive(
+    Copy, Clone, Debug, PartialEq, strum_macros::Display, strum_macros::EnumString,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum RetentionAction {
+    /// Detached partitions stay in the schema as plain tables.
+    Detach,
+    Drop,
+}
+
+#[derive(Clone, Debug)]
+pub struct RetentionPolicy {
+    /// Months of history kept besides the current month.
+    pub months: u32,
+    pub action: RetentionAction,
+    /// Expired partitions are written here as json lines before they are
+    /// detached or dropped.
+    pub archive_dir: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug)]
+pub struct EventLogPolicy {
+    /// Partitions created after the current month.
+    pub months_ahead: u32,
+    /// Partitions are kept forever without one.
+    pub retention: Option<RetentionPolicy>,
+}
+
+impl EventLogPolicy {
+    pub fn from_env() -> Self {
+        let retention = get_from_env_unsafe::<u32>("EVENT_LOG_RETENTION_MONTHS")
+            .ok()
+            .filter(|months| *months > 0)
+            .map(|months| RetentionPolicy {
+                months,
+                action: get_from_env_or_default(
+                    "EVENT_LOG_RETENTION_ACTION",
+                    RetentionAction::Detach,
+                ),
+                archive_dir: get_from_env_unsafe::<String>("EVENT_LOG_ARCHIVE_DIR")
+                    .ok()
+                    .filter(|dir| !dir.is_empty())
+                    .map(PathBuf::from),
+            });
+        EventLogPolicy {
+            months_ahead: get_from_env_or_default("EVENT_LOG_PARTITIONS_AHEAD", 3),
+            retention,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PartitionReport {
+    pub schema: String,
+    pub created: Vec<String>,
+    pub archived: Vec<PathBuf>,
+    pub detached: Vec<String>,
+    pub dropped: Vec<String>,
+}
+
+impl PartitionReport {
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.detached.is_empty() && self.dropped.is_empty()
+    }
+}
+
+impl fmt::Display for PartitionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "{}.{EVENT_LOG_TABLE}: up-to-date", self.schema);
+        }
+        write!(f, "{}.{EVENT_LOG_TABLE}:", self.schema)?;
+        for partition in self.created.iter() {
+            write!(f, "\n  + {partition}")?;
+        }
+        for path in self.archived.iter() {
+            write!(f, "\n  > {}", path.display())?;
+        }
+        for partition in self.detached.iter() {
+            write!(f, "\n  ~ {partition} (detached)")?;
+        }
+        for partition in self.dropped.iter() {
+            write!(f, "\n  - {partition}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(QueryableByName)]
+struct Exists {
+    #[diesel(sql_type = Bool)]
+    exists: bool,
+}
+
+#[derive(QueryableByName)]
+struct Partition {
+    #[diesel(sql_type = Text)]
+    name: String,
+}
+
+#[derive(QueryableByName)]
+struct Row {
+    #[diesel(sql_type = Text)]
+    row: String,
+}
+
+fn event_log_exists(conn: &mut PgConnection, schema: &str) -> anyhow::Result<bool> {
+    let result = diesel::sql_query("SELECT to_regclass($1) IS NOT NULL AS exists")
+        .bind::<Text, _>(format!("{schema}.{EVENT_LOG_TABLE}"))
+        .get_result::<Exists>(conn)?;
+    Ok(result.exists)
+}
+
+/// Names of the tables currently attached to the `event_log` of `schema`.
+pub fn list_event_log_partitions(
+    conn: &mut PgConnection,
+    schema: &str,
+) -> anyhow::Result<Vec<String>> {
+    let partitions = diesel::sql_query(
+        "SELECT child.relname::TEXT AS name FROM pg_inherits
+            JOIN pg_class parent ON parent.oid = pg_inherits.inhparent
+            JOIN pg_class child ON child.oid = pg_inherits.inhrelid
+            JOIN pg_namespace ON pg_namespace.oid = parent.relnamespace
+            WHERE pg_namespace.nspname = $1 AND parent.relname = $2",
+    )
+    .bind::<Text, _>(schema)
+    .bind::<Text, _>(EVENT_LOG_TABLE)
+    .load::<Partition>(conn)?;
+    Ok(partitions
+        .into_iter()
+        .map(|partition| partition.name)
+        .collect())
+}
+
+/// Creates the partitions from `current` to `months_ahead` months after it
+/// which are missing, returning their names.
+pub fn ensure_event_log_partitions(
+    conn: &mut PgConnection,
+    schema: &str,
+    current: Month,
+    months_ahead: u32,
+) -> anyhow::Result<Vec<String>> {
+    let existing = list_event_log_partitions(conn, schema)?;
+    let mut created = Vec::new();
+    for offset in 0..=months_ahead as i32 {
+        let month = current.add(offset);
+        let partition = month.partition_name();
+        if existing.contains(&partition) {
+            continue;
+        }
+        conn.batch_execute(&format!(
+            "CREATE TABLE IF NOT EXISTS {schema}.{partition} PARTITION OF {schema}.{EVENT_LOG_TABLE} FOR VALUES FROM ('{}') TO ('{}');",
+            month.first_day(),
+            month.add(1).first_day()
+        ))?;
+        log::info!("created partition {schema}.{partition}");
+        created.push(partition);
+    }
+    Ok(created)
+}
+
+/// Writes every row of a partition as a json line to
+/// `<dir>/<schema>.<partition>.jsonl`.
+fn archive_partition(
+    conn: &mut PgConnection,
+    schema: &str,
+    partition: &str,
+    dir: &Path,
+) -> anyhow::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{schema}.{partition}.jsonl"));
+    let mut writer = BufWriter::new(File::create(&path)?);
+    let mut offset = 0;
+    loop {
+        let rows = diesel::sql_query(format!(
+            "SELECT row_to_json(log)::TEXT AS row FROM {schema}.{partition} log
+                ORDER BY log.\"timestamp\", log.id LIMIT $1 OFFSET $2"
+        ))
+        .bind::<BigInt, _>(ARCHIVE_BATCH_SIZE)
+        .bind::<BigInt, _>(offset)
+        .load::<Row>(conn)?;
+        for row in rows.iter() {
+            writeln!(writer, "{}", row.row)?;
+        }
+        if (rows.len() as i64) < ARCHIVE_BATCH_SIZE {
+            break;
+        }
+        offset += ARCHIVE_BATCH_SIZE;
+    }
+    writer.flush()?;
+    Ok(path)
+}
+
+/// Partitions which are entirely older than what `retention` keeps.
+fn expired_partitions(
+    partitions: &[String],
+    current: Month,
+    retention: &RetentionPolicy,
+) -> Vec<String> {
+    let oldest_kept = current.add(-(retention.months as i32));
+    let mut expired = partitions
+        .iter()
+        .filter(|partition| {
+            Month::from_partition_name(partition).is_some_and(|month| month < oldest_kept)
+        })
+        .cloned()
+        .collect::<Vec<String>>();
+    expired.sort();
+    expired
+}
+
+/// Creates upcoming partitions of the `event_log` of `schema` and applies the
+/// retention policy to old ones. A partition is only detached or dropped once
+/// it has been archived, when archiving is configured.
+pub fn maintain_event_log(
+    conn: &mut PgConnection,
+    schema: &str,
+    policy: &EventLogPolicy,
+    today: NaiveDate,
+) -> anyhow::Result<PartitionReport> {
+    let mut report = PartitionReport {
+        schema: schema.to_string(),
+        ..Default::default()
+    };
+    if !event_log_exists(conn, schema)? {
+        log::warn!("{schema} has no {EVENT_LOG_TABLE}, skipping partition maintenance");
+        return Ok(report);
+    }
+    let current = Month::of(today);
+    report.created =
+        ensure_event_log_partitions(conn, schema, current, policy.months_ahead)?;
+
+    let Some(retention) = policy.retention.as_ref() else {
+        return Ok(report);
+    };
+    let partitions = list_event_log_partitions(conn, schema)?;
+    for partition in expired_partitions(&partitions, current, retention) {
+        if let Some(dir) = retention.archive_dir.as_ref() {
+            let path = archive_partition(conn, schema, &partition, dir)?;
+            report.archived.push(path);
+        }
+        match retention.action {
+            RetentionAction::Detach => {
+                conn.batch_execute(&format!(
+                    "ALTER TABLE {schema}.{EVENT_LOG_TABLE} DETACH PARTITION {schema}.{partition};"
+                ))?;
+                report.detached.push(partition);
+            }
+            RetentionAction::Drop => {
+                conn.batch_execute(&format!("DROP TABLE {schema}.{partition};"))?;
+                report.dropped.push(partition);
+            }
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_month_arithmetic() {
+        let month = Month::of(NaiveDate::from_ymd_opt(2026, 11, 17).unwrap());
+        assert_eq!(month.add(1).partition_name(), "event_log_y2026m12");
+        assert_eq!(month.add(2).partition_name(), "event_log_y2027m01");
+        assert_eq!(month.add(-11).partition_name(), "event_log_y2025m12");
+        assert_eq!(month.add(2).first_day(), "2027-01-01");
+        assert_eq!(
+            Month::from_partition_name("event_log_y2027m01"),
+            Some(month.add(2))
+        );
+        assert_eq!(Month::from_partition_name("event_log_y2027m13"), None);
+        assert_eq!(Month::from_partition_name("event_log_default"), None);
+    }
+
+    #[test]
+    fn test_expired_partitions() {
+        let current = Month::of(NaiveDate::from_ymd_opt(2026, 3, 1).unwrap());
+        let retention = RetentionPolicy {
+            months: 3,
+            action: RetentionAction::Detach,
+            archive_dir: None,
+        };
+        let partitions = [
+            "event_log_y2026m03",
+            "event_log_y2025m11",
+            "event_log_y2025m12",
+            "event_log_y2025m10",
+            "event_log_default",
+        ]
+        .map(String::from);
+        assert_eq!(
+            expired_partitions(&partitions, current, &retention),
+            vec!["event_log_y2025m10", "event_log_y2025m11"]
+        );
+    }
+}
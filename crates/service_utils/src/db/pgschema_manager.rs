@@ -1,6 +1,9 @@
 extern crate derive_more;
-use derive_more::{Deref, DerefMut, Display};
-use std::collections::HashMap;
+use derive_more::Display;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
 
 use anyhow::anyhow;
 use diesel::{
@@ -45,34 +48,77 @@ impl ConnectionConfig {
     }
 }
 
-#[derive(Deref, DerefMut, Clone)]
-pub struct PgSchemaManager(HashMap<String, PgSchemaConnectionPool>);
+/// Connection pools keyed by namespace, shared across workers so that pools
+/// for tenants provisioned at runtime are visible everywhere.
+#[derive(Clone)]
+pub struct PgSchemaManager {
+    pools: Arc<RwLock<HashMap<String, PgSchemaConnectionPool>>>,
+    database_url: String,
+    max_pool_size: u32,
+}
 
-impl From<Vec<ConnectionConfig>> for PgSchemaManager {
-    fn from(value: Vec<ConnectionConfig>) -> Self {
-        let mut schema_manager: PgSchemaManager = PgSchemaManager(HashMap::new());
-        for config in value.into_iter() {
-            let manager = ConnectionManager::<PgConnection>::new(config.conn_url());
-            schema_manager.insert(
-                config.name.clone(),
-                Pool::builder()
-                    .max_size(config.count)
-                    .build(manager)
-                    .unwrap_or_else(|_| {
-                        panic!("Invalid config provided, {}", config.name)
-                    }),
-            );
+impl PgSchemaManager {
+    pub fn new(database_url: String, max_pool_size: u32) -> Self {
+        PgSchemaManager {
+            pools: Arc::new(RwLock::new(HashMap::new())),
+            database_url,
+            max_pool_size,
         }
-        schema_manager
     }
-}
 
-impl PgSchemaManager {
+    pub fn database_url(&self) -> &str {
+        &self.database_url
+    }
+
+    fn build_pool(config: &ConnectionConfig) -> anyhow::Result<PgSchemaConnectionPool> {
+        let manager = ConnectionManager::<PgConnection>::new(config.conn_url());
+        Pool::builder()
+            .max_size(config.count)
+            .build(manager)
+            .map_err(|e| anyhow!("Invalid config provided, {}: {e}", config.name))
+    }
+
+    /// Creates a pool for `schema` and registers it under `name`, replacing any
+    /// existing pool with the same name.
+    pub fn add_schema(&self, name: String, schema: String) -> anyhow::Result<()> {
+        let config = ConnectionConfig::new(
+            name.clone(),
+            self.database_url.clone(),
+            schema,
+            self.max_pool_size,
+        );
+        let pool = Self::build_pool(&config)?;
+        self.pools
+            .write()
+            .map_err(|_| anyhow!("schema manager lock poisoned"))?
+            .insert(name, pool);
+        Ok(())
+    }
+
+    pub fn remove_schema(&self, name: &str) -> anyhow::Result<()> {
+        self.pools
+            .write()
+            .map_err(|_| anyhow!("schema manager lock poisoned"))?
+            .remove(name);
+        Ok(())
+    }
+
+    pub fn schemas(&self) -> anyhow::Result<Vec<String>> {
+        let pools = self
+            .pools
+            .read()
+            .map_err(|_| anyhow!("schema manager lock poisoned"))?;
+        Ok(pools.keys().cloned().collect())
+    }
+
     pub fn get_conn(&self, name: String) -> anyhow::Result<PgSchemaConnection> {
-        let conn = self
+        let pool = self
+            .pools
+            .read()
+            .map_err(|_| anyhow!("schema manager lock poisoned"))?
             .get(&name) // gets the pool for the given namespace
-            .ok_or_else(|| anyhow!("Invalid connection name provided: {}", name))?
-            .get()?; // fetches the connection from the pool
-        Ok(conn)
+            .cloned()
+            .ok_or_else(|| anyhow!("Invalid connection name provided: {}", name))?;
+        Ok(pool.get()?) // fetches the connection from the pool
     }
 }
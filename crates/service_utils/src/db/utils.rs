@@ -1,12 +1,10 @@
 use crate::aws::kms;
-use crate::db::pgschema_manager::{ConnectionConfig, PgSchemaManager};
+use crate::db::pgschema_manager::PgSchemaManager;
 use crate::helpers::{get_from_env_or_default, get_from_env_unsafe};
-use crate::service::types::AppEnv;
 use diesel::{
     r2d2::{ConnectionManager, Pool},
     PgConnection,
 };
-use std::collections::HashSet;
 use urlencoding::encode;
 
 pub async fn get_database_url() -> String {
@@ -35,37 +33,79 @@ pub async fn get_pool() -> Pool<ConnectionManager<PgConnection>> {
         .expect("Error building a connection pool")
 }
 
+/// Namespace of the pool used for tenant agnostic data like the tenant registry.
+pub const ADMIN_NAMESPACE: &str = "superposition";
+
+pub fn tenant_namespaces(tenant: &str) -> [String; 2] {
+    [format!("{tenant}_cac"), format!("{tenant}_experimentation")]
+}
+
+/// Namespace of the CAC pool serving a request, `None` when tenancy is on
+/// and the request has no tenant.
+pub fn cac_namespace(
+    enable_tenant_and_scope: bool,
+    tenant: Option<&str>,
+) -> Option<String> {
+    match (enable_tenant_and_scope, tenant) {
+        (false, _) => Some("cac_v1".to_string()),
+        (true, Some(tenant)) => {
+            let [cac_namespace, _] = tenant_namespaces(tenant);
+            Some(cac_namespace)
+        }
+        (true, None) => None,
+    }
+}
+
+/// Namespace of the experimentation pool serving a request, `None` when
+/// tenancy is on and the request has no tenant.
+pub fn experimentation_namespace(
+    enable_tenant_and_scope: bool,
+    tenant: Option<&str>,
+) -> Option<String> {
+    match (enable_tenant_and_scope, tenant) {
+        (false, _) => Some("cac_v1".to_string()),
+        (true, Some(tenant)) => {
+            let [_, experimentation_namespace] = tenant_namespaces(tenant);
+            Some(experimentation_namespace)
+        }
+        (true, None) => None,
+    }
+}
+
+pub fn register_tenant_pools(
+    schema_manager: &PgSchemaManager,
+    tenant: &str,
+) -> anyhow::Result<()> {
+    for namespace in tenant_namespaces(tenant) {
+        schema_manager.add_schema(namespace.clone(), namespace)?;
+    }
+    Ok(())
+}
+
+pub fn deregister_tenant_pools(
+    schema_manager: &PgSchemaManager,
+    tenant: &str,
+) -> anyhow::Result<()> {
+    for namespace in tenant_namespaces(tenant) {
+        schema_manager.remove_schema(&namespace)?;
+    }
+    Ok(())
+}
+
+/// Builds the schema manager, tenant pools are registered separately once the
+/// active tenants are known.
 pub async fn init_pool_manager(
-    tenants: HashSet<String>,
     enable_tenant_and_scope: bool,
-    app_env: AppEnv,
     max_pool_size: u32,
 ) -> PgSchemaManager {
     let database_url = get_database_url().await;
-    let namespaces = match (enable_tenant_and_scope, app_env) {
-        (true, _) => tenants
-            .iter()
-            .flat_map(|tenant| {
-                [
-                    format!("{}_cac", tenant),
-                    format!("{}_experimentation", tenant),
-                ]
-            })
-            .collect::<Vec<String>>(),
-        (false, _) => vec!["cac_v1".to_string()],
+    let namespace = match enable_tenant_and_scope {
+        true => ADMIN_NAMESPACE,
+        false => "cac_v1",
     };
-
-    let connection_configs = namespaces
-        .iter()
-        .map(|namespace| {
-            ConnectionConfig::new(
-                namespace.to_string(),
-                database_url.clone(),
-                namespace.to_string(),
-                max_pool_size,
-            )
-        })
-        .collect::<Vec<ConnectionConfig>>();
-
-    PgSchemaManager::from(connection_configs)
+    let schema_manager = PgSchemaManager::new(database_url, max_pool_size);
+    schema_manager
+        .add_schema(namespace.to_string(), namespace.to_string())
+        .unwrap_or_else(|e| panic!("{e}"));
+    schema_manager
 }
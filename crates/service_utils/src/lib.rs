@@ -2,6 +2,7 @@ pub mod aws;
 pub mod db;
 pub mod helpers;
 pub mod middlewares;
+pub mod rbac;
 pub mod service;
 
 /// General purpose base64 engine
@@ -1,2 +1,4 @@
 pub mod app_scope;
+pub mod auth;
+pub mod request_id;
 pub mod tenant;
@@ -0,0 +1,193 @@
+mod api_key;
+mod jwt;
+mod no_auth;
+
+use std::{
+    future::{ready, Ready},
+    rc::Rc,
+    sync::Arc,
+};
+
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    error,
+    http::Method,
+    Error, HttpMessage,
+};
+use futures_util::future::LocalBoxFuture;
+use serde_json::json;
+use superposition_types::User;
+
+use crate::helpers::{get_from_env_or_default, get_from_env_unsafe};
+
+pub use api_key::{hash_api_key, ApiKeyAuthProvider, StaticApiKey};
+pub use jwt::JwtAuthProvider;
+pub use no_auth::NoAuthProvider;
+
+pub enum AuthOutcome {
+    Authenticated(User),
+    /// The request carried credentials for this provider which could not be
+    /// verified.
+    Rejected(String),
+    /// The request carried no credentials this provider understands.
+    NotApplicable,
+}
+
+/// A way of verifying who made a request. Providers are asked in order and
+/// the first one which doesn't return `NotApplicable` decides.
+pub trait AuthProvider: Send + Sync {
+    fn authenticate<'a>(
+        &'a self,
+        req: &'a ServiceRequest,
+    ) -> LocalBoxFuture<'a, AuthOutcome>;
+}
+
+/// Whether `AUTH_PROVIDERS` verifies requests, anything but `NONE` does.
+pub fn authentication_enabled() -> bool {
+    !get_from_env_or_default::<String>("AUTH_PROVIDERS", "NONE".into())
+        .trim()
+        .eq_ignore_ascii_case("NONE")
+}
+
+/// Builds the providers listed in `AUTH_PROVIDERS`, a comma separated list of
+/// `JWT` and `API_KEY`, or `NONE` to attribute every request to the default
+/// user in development.
+pub async fn init_auth_providers() -> anyhow::Result<Vec<Arc<dyn AuthProvider>>> {
+    let kinds = get_from_env_or_default::<String>("AUTH_PROVIDERS", "NONE".into())
+        .split(',')
+        .map(|kind| kind.trim().to_uppercase())
+        .filter(|kind| !kind.is_empty())
+        .collect::<Vec<String>>();
+
+    let mut providers: Vec<Arc<dyn AuthProvider>> = Vec::new();
+    for kind in kinds.iter() {
+        match kind.as_str() {
+            "NONE" if kinds.len() == 1 => {
+                log::warn!("authentication is disabled, requests are not verified");
+                providers.push(Arc::new(NoAuthProvider));
+            }
+            "NONE" => {
+                return Err(anyhow::anyhow!(
+                    "NONE cannot be combined with other auth providers"
+                ))
+            }
+            "JWT" => {
+                let jwks_source: String = get_from_env_unsafe("AUTH_JWKS")
+                    .map_err(|e| anyhow::anyhow!("AUTH_JWKS is not set: {e}"))?;
+                let algorithms =
+                    jwt::parse_algorithms(&get_from_env_or_default::<String>(
+                        "AUTH_JWT_ALGORITHMS",
+                        "RS256".into(),
+                    ))?;
+                let provider = JwtAuthProvider::from_source(
+                    &jwks_source,
+                    algorithms,
+                    get_from_env_unsafe("AUTH_JWT_ISSUER").ok(),
+                    get_from_env_unsafe("AUTH_JWT_AUDIENCE").ok(),
+                    get_from_env_or_default("AUTH_JWT_EMAIL_CLAIM", "email".into()),
+                )
+                .await?;
+                providers.push(Arc::new(provider));
+            }
+            "API_KEY" => {
+                // keys issued through the API need no file
+                let provider = match get_from_env_unsafe::<String>("AUTH_API_KEYS_FILE") {
+                    Ok(keys_file) => ApiKeyAuthProvider::from_file(&keys_file)?,
+                    Err(_) => ApiKeyAuthProvider::default(),
+                };
+                providers.push(Arc::new(provider));
+            }
+            other => return Err(anyhow::anyhow!("unknown auth provider {other}")),
+        }
+    }
+    Ok(providers)
+}
+
+fn is_read(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+pub struct AuthMiddlewareFactory {
+    providers: Arc<Vec<Arc<dyn AuthProvider>>>,
+}
+
+impl AuthMiddlewareFactory {
+    pub fn new(providers: Vec<Arc<dyn AuthProvider>>) -> Self {
+        AuthMiddlewareFactory {
+            providers: Arc::new(providers),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for AuthMiddlewareFactory
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = AuthMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuthMiddleware {
+            service: Rc::new(service),
+            providers: self.providers.clone(),
+        }))
+    }
+}
+
+pub struct AuthMiddleware<S> {
+    service: Rc<S>,
+    providers: Arc<Vec<Arc<dyn AuthProvider>>>,
+}
+
+impl<S, B> Service<ServiceRequest> for AuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let srv = self.service.clone();
+        let providers = self.providers.clone();
+
+        Box::pin(async move {
+            let mut outcome = AuthOutcome::NotApplicable;
+            for provider in providers.iter() {
+                outcome = provider.authenticate(&req).await;
+                if !matches!(outcome, AuthOutcome::NotApplicable) {
+                    break;
+                }
+            }
+
+            match outcome {
+                AuthOutcome::Authenticated(user) => {
+                    req.extensions_mut().insert::<User>(user);
+                }
+                AuthOutcome::Rejected(message) => {
+                    log::info!("rejected credentials for {}: {message}", req.path());
+                    return Err(error::ErrorUnauthorized(json!({ "message": message })));
+                }
+                // anonymous reads are allowed, handlers which need a user
+                // still fail to extract one
+                AuthOutcome::NotApplicable if is_read(req.method()) => (),
+                AuthOutcome::NotApplicable => {
+                    return Err(error::ErrorUnauthorized(json!({
+                        "message": "authentication is required for this request"
+                    })));
+                }
+            }
+
+            srv.call(req).await
+        })
+    }
+}
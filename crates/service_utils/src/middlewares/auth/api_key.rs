@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use actix_web::{dev::ServiceRequest, web::Data, HttpMessage};
+use diesel::{
+    sql_types::{Array, Text},
+    QueryableByName, RunQueryDsl,
+};
+use futures_util::future::LocalBoxFuture;
+use serde::Deserialize;
+use superposition_types::User;
+
+use super::{AuthOutcome, AuthProvider};
+use crate::{
+    db::utils::cac_namespace,
+    rbac::{ApiKeyGrants, ApiKeyScope},
+    service::types::{AppState, Tenant},
+};
+
+/// Keys listed under this tenant are accepted for every tenant.
+const ANY_TENANT: &str = "*";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StaticApiKey {
+    pub name: String,
+    /// blake3 hash of the key in hex, the key itself is never stored
+    pub key_hash: String,
+    pub email: String,
+}
+
+pub fn hash_api_key(key: &str) -> String {
+    blake3::hash(key.as_bytes()).to_hex().to_string()
+}
+
+#[derive(QueryableByName)]
+struct StoredApiKey {
+    #[diesel(sql_type = Text)]
+    name: String,
+    #[diesel(sql_type = Array<Text>)]
+    scopes: Vec<String>,
+}
+
+/// API keys sent in the `x-api-key` header or as `Authorization: ApiKey <key>`.
+/// Keys are looked up in the optional JSON file mapping tenants to the keys
+/// allowed for them, then in the `api_keys` table of the tenant.
+#[derive(Default)]
+pub struct ApiKeyAuthProvider {
+    keys: HashMap<String, Vec<(StaticApiKey, blake3::Hash)>>,
+}
+
+impl ApiKeyAuthProvider {
+    pub fn new(keys: HashMap<String, Vec<StaticApiKey>>) -> anyhow::Result<Self> {
+        let mut parsed = HashMap::new();
+        for (tenant, tenant_keys) in keys.into_iter() {
+            let tenant_keys = tenant_keys
+                .into_iter()
+                .map(|key| {
+                    let hash = blake3::Hash::from_hex(&key.key_hash).map_err(|e| {
+                        anyhow::anyhow!("invalid hash for api key {}: {e}", key.name)
+                    })?;
+                    Ok((key, hash))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            parsed.insert(tenant, tenant_keys);
+        }
+        Ok(ApiKeyAuthProvider { keys: parsed })
+    }
+
+    pub fn from_file(path: &str) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("could not read api keys from {path}: {e}"))?;
+        Self::new(serde_json::from_str(&contents)?)
+    }
+
+    fn find(&self, tenant: &str, key: &str) -> Option<&StaticApiKey> {
+        // blake3::Hash comparisons are constant time
+        let hash = blake3::hash(key.as_bytes());
+        [tenant, ANY_TENANT]
+            .iter()
+            .filter_map(|tenant| self.keys.get(*tenant))
+            .flatten()
+            .find(|(_, key_hash)| *key_hash == hash)
+            .map(|(key, _)| key)
+    }
+
+    /// Looks up a key issued through the API, marking it used at most once a
+    /// minute so busy keys don't write on every request.
+    fn find_stored(
+        &self,
+        req: &ServiceRequest,
+        key: &str,
+    ) -> Result<Option<StoredApiKey>, String> {
+        let app_state = req
+            .app_data::<Data<AppState>>()
+            .ok_or("app state not set")?;
+        let tenant = req.extensions().get::<Tenant>().cloned();
+        let Some(namespace) = cac_namespace(
+            app_state.enable_tenant_and_scope,
+            tenant.as_ref().map(|tenant| tenant.as_str()),
+        ) else {
+            return Ok(None);
+        };
+        let mut conn = app_state
+            .db_pool
+            .get_conn(namespace)
+            .map_err(|e| format!("unable to get db connection from pool: {e}"))?;
+        let mut rows = diesel::sql_query(
+"WITH found AS (SELECT key_hash, name, scopes, last_used_at FROM api_keys WHERE key_hash = $1 AND revoked_at IS NULL AND (expires_at IS NULL OR expires_at > now())), touched AS (UPDATE api_keys SET last_used_at = now() WHERE key_hash IN (SELECT key_hash FROM found WHERE last_used_at IS NULL OR last_used_at < now() - interval '1 minute')) SELECT name, scopes FROM found",
+        )
+        .bind::<Text, _>(hash_api_key(key))
+        .load::<StoredApiKey>(&mut conn)
+        .map_err(|e| format!("failed to look up api key: {e}"))?;
+        Ok(rows.pop())
+    }
+}
+
+fn api_key_from_headers(req: &ServiceRequest) -> Option<Result<&str, ()>> {
+    let headers = req.headers();
+    if let Some(key) = headers.get("x-api-key") {
+        return Some(key.to_str().map_err(|_| ()));
+    }
+    headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("ApiKey "))
+        .map(|key| Ok(key.trim()))
+}
+
+impl AuthProvider for ApiKeyAuthProvider {
+    fn authenticate<'a>(
+        &'a self,
+        req: &'a ServiceRequest,
+    ) -> LocalBoxFuture<'a, AuthOutcome> {
+        Box::pin(async move {
+            let key = match api_key_from_headers(req) {
+                None => return AuthOutcome::NotApplicable,
+                Some(Err(())) => return AuthOutcome::Rejected("invalid api key".into()),
+                Some(Ok(key)) => key,
+            };
+            let tenant = req
+                .extensions()
+                .get::<Tenant>()
+                .map(|tenant| tenant.0.clone())
+                .unwrap_or_else(|| ANY_TENANT.to_string());
+
+            if let Some(api_key) = self.find(&tenant, key) {
+                return AuthOutcome::Authenticated(User {
+                    email: api_key.email.clone(),
+                    username: api_key.name.clone(),
+                    auth_token: key.to_string(),
+                    auth_type: "ApiKey".into(),
+                });
+            }
+            match self.find_stored(req, key) {
+                Ok(Some(api_key)) => {
+                    let grants = api_key
+                        .scopes
+                        .iter()
+                        .filter_map(|scope| match scope.parse::<ApiKeyScope>() {
+                            Ok(scope) => Some(scope),
+                            Err(_) => {
+                                log::warn!(
+                                    "api key {} has unknown scope {scope}",
+                                    api_key.name
+                                );
+                                None
+                            }
+                        })
+                        .map(|scope| scope.grant())
+                        .collect();
+                    req.extensions_mut().insert(ApiKeyGrants(grants));
+                    AuthOutcome::Authenticated(User {
+                        email: format!("api-key:{}", api_key.name),
+                        username: api_key.name,
+                        auth_token: key.to_string(),
+                        auth_type: "ApiKey".into(),
+                    })
+                }
+                Ok(None) => AuthOutcome::Rejected("invalid api key".into()),
+                Err(message) => {
+                    log::error!("{message}");
+                    AuthOutcome::Rejected("could not verify api key".into())
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_api_key() {
+        let key = |name: &str, secret: &str| StaticApiKey {
+            name: name.into(),
+            key_hash: hash_api_key(secret),
+            email: format!("{name}@example.com"),
+        };
+        let provider = ApiKeyAuthProvider::new(HashMap::from([
+            ("dev".to_string(), vec![key("deploy", "dev-secret")]),
+            (ANY_TENANT.to_string(), vec![key("admin", "admin-secret")]),
+        ]))
+        .unwrap();
+
+        assert_eq!(provider.find("dev", "dev-secret").unwrap().name, "deploy");
+        assert!(provider.find("test", "dev-secret").is_none());
+        assert_eq!(provider.find("test", "admin-secret").unwrap().name, "admin");
+        assert!(provider.find("dev", "wrong").is_none());
+    }
+}
@@ -0,0 +1,316 @@
+use std::{
+    str::FromStr,
+    sync::{Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use actix_web::dev::ServiceRequest;
+use futures_util::future::LocalBoxFuture;
+use jsonwebtoken::{
+    decode, decode_header,
+    jwk::{Jwk, JwkSet},
+    Algorithm, DecodingKey, Validation,
+};
+use serde_json::{Map, Value};
+use superposition_types::User;
+
+use super::{AuthOutcome, AuthProvider};
+
+/// Unknown key ids refetch the JWKS at most this often, so tokens with made
+/// up key ids can't be used to hammer the provider.
+const JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+enum VerifyError {
+    UnknownKey(String),
+    Invalid(String),
+}
+
+impl From<String> for VerifyError {
+    fn from(message: String) -> Self {
+        VerifyError::Invalid(message)
+    }
+}
+
+impl From<&str> for VerifyError {
+    fn from(message: &str) -> Self {
+        VerifyError::Invalid(message.to_string())
+    }
+}
+
+/// Verifies `Authorization: Bearer <jwt>` tokens against a JWKS, as issued by
+/// an OIDC provider.
+pub struct JwtAuthProvider {
+    jwks: RwLock<JwkSet>,
+    /// where the JWKS is refetched from when a token uses a key it doesn't
+    /// have, keys loaded from a file are never refetched
+    jwks_url: Option<String>,
+    last_fetched: Mutex<Instant>,
+    /// algorithms tokens can be signed with, the one in the token header is
+    /// only trusted when it is listed here and matches the key
+    algorithms: Vec<Algorithm>,
+    issuer: Option<String>,
+    audience: Option<String>,
+    email_claim: String,
+}
+
+async fn fetch_jwks(url: &str) -> anyhow::Result<JwkSet> {
+    Ok(reqwest::get(url).await?.error_for_status()?.json().await?)
+}
+
+/// Parses a comma separated list of algorithms like `RS256,ES256`.
+pub fn parse_algorithms(algorithms: &str) -> anyhow::Result<Vec<Algorithm>> {
+    algorithms
+        .split(',')
+        .map(str::trim)
+        .filter(|alg| !alg.is_empty())
+        .map(|alg| {
+            Algorithm::from_str(alg)
+                .map_err(|_| anyhow::anyhow!("unknown JWT algorithm {alg}"))
+        })
+        .collect()
+}
+
+/// Algorithm a key is meant for, keys without one can be used with any of
+/// the allowed algorithms.
+fn key_algorithm(jwk: &Jwk) -> Result<Option<Algorithm>, String> {
+    let Some(key_algorithm) = &jwk.common.key_algorithm else {
+        return Ok(None);
+    };
+    serde_json::to_value(key_algorithm)
+        .ok()
+        .and_then(|alg| alg.as_str().and_then(|alg| Algorithm::from_str(alg).ok()))
+        .map(Some)
+        .ok_or(format!("key uses unsupported algorithm {key_algorithm:?}"))
+}
+
+impl JwtAuthProvider {
+    pub fn new(
+        jwks: JwkSet,
+        jwks_url: Option<String>,
+        algorithms: Vec<Algorithm>,
+        issuer: Option<String>,
+        audience: Option<String>,
+        email_claim: String,
+    ) -> Self {
+        JwtAuthProvider {
+            jwks: RwLock::new(jwks),
+            jwks_url,
+            last_fetched: Mutex::new(Instant::now()),
+            algorithms,
+            issuer,
+            audience,
+            email_claim,
+        }
+    }
+
+    /// Loads the JWKS from an http(s) url or a local file. Keys from a url
+    /// are refetched when a token is signed with a key id they don't have.
+    pub async fn from_source(
+        source: &str,
+        algorithms: Vec<Algorithm>,
+        issuer: Option<String>,
+        audience: Option<String>,
+        email_claim: String,
+    ) -> anyhow::Result<Self> {
+        let (jwks, jwks_url) =
+            if source.starts_with("http://") || source.starts_with("https://") {
+                (fetch_jwks(source).await?, Some(source.to_string()))
+            } else {
+                let jwks =
+                    serde_json::from_str(&std::fs::read_to_string(source).map_err(
+                        |e| anyhow::anyhow!("could not read JWKS from {source}: {e}"),
+                    )?)?;
+                (jwks, None)
+            };
+        Ok(Self::new(
+            jwks,
+            jwks_url,
+            algorithms,
+            issuer,
+            audience,
+            email_claim,
+        ))
+    }
+
+    /// Refetches the JWKS unless it was fetched recently, returning whether
+    /// it was.
+    async fn refresh(&self) -> bool {
+        let Some(url) = &self.jwks_url else {
+            return false;
+        };
+        {
+            let Ok(mut last_fetched) = self.last_fetched.lock() else {
+                return false;
+            };
+            if last_fetched.elapsed() < JWKS_REFRESH_INTERVAL {
+                return false;
+            }
+            *last_fetched = Instant::now();
+        }
+        match fetch_jwks(url).await {
+            Ok(jwks) => match self.jwks.write() {
+                Ok(mut current) => {
+                    *current = jwks;
+                    true
+                }
+                Err(_) => false,
+            },
+            Err(e) => {
+                log::error!("failed to refetch JWKS from {url}: {e}");
+                false
+            }
+        }
+    }
+
+    fn verify(&self, token: &str) -> Result<User, VerifyError> {
+        let header = decode_header(token).map_err(|e| format!("invalid token: {e}"))?;
+        let kid = header.kid.ok_or("token has no key id")?;
+        let (key, algorithm) = {
+            let jwks = self.jwks.read().map_err(|_| "JWKS lock poisoned")?;
+            let jwk = jwks.find(&kid).ok_or(VerifyError::UnknownKey(format!(
+                "token signed with unknown key {kid}"
+            )))?;
+            let key =
+                DecodingKey::from_jwk(jwk).map_err(|e| format!("invalid key: {e}"))?;
+            (key, key_algorithm(jwk)?.unwrap_or(header.alg))
+        };
+        if algorithm != header.alg || !self.algorithms.contains(&algorithm) {
+            return Err(format!(
+                "token signed with disallowed algorithm {:?}",
+                header.alg
+            )
+            .into());
+        }
+
+        let mut validation = Validation::new(algorithm);
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &self.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        let claims = decode::<Map<String, Value>>(token, &key, &validation)
+            .map_err(|e| format!("invalid token: {e}"))?
+            .claims;
+
+        let claim = |name: &str| claims.get(name).and_then(Value::as_str);
+        let email = claim(&self.email_claim)
+            .ok_or(format!("token has no {} claim", self.email_claim))?;
+        let username = claim("preferred_username")
+            .or_else(|| claim("sub"))
+            .unwrap_or(email);
+        Ok(User {
+            email: email.to_string(),
+            username: username.to_string(),
+            auth_token: token.to_string(),
+            auth_type: "Bearer".into(),
+        })
+    }
+}
+
+impl AuthProvider for JwtAuthProvider {
+    fn authenticate<'a>(
+        &'a self,
+        req: &'a ServiceRequest,
+    ) -> LocalBoxFuture<'a, AuthOutcome> {
+        Box::pin(async move {
+            let token = req
+                .headers()
+                .get("authorization")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "));
+            let Some(token) = token.map(str::trim) else {
+                return AuthOutcome::NotApplicable;
+            };
+            let mut result = self.verify(token);
+            // the provider may have rotated its keys since they were fetched
+            if matches!(result, Err(VerifyError::UnknownKey(_))) && self.refresh().await {
+                result = self.verify(token);
+            }
+            match result {
+                Ok(user) => AuthOutcome::Authenticated(user),
+                Err(VerifyError::UnknownKey(message) | VerifyError::Invalid(message)) => {
+                    AuthOutcome::Rejected(message)
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    const SECRET: &[u8] = b"superposition-test-secret";
+
+    fn provider() -> JwtAuthProvider {
+        let jwks = serde_json::from_value(json!({
+            "keys": [{ "kty": "oct", "kid": "test", "alg": "HS256", "k": "c3VwZXJwb3NpdGlvbi10ZXN0LXNlY3JldA" }]
+        }))
+        .unwrap();
+        JwtAuthProvider::new(
+            jwks,
+            None,
+            vec![Algorithm::HS256],
+            Some("https://issuer.example.com".into()),
+            None,
+            "email".into(),
+        )
+    }
+
+    fn token(kid: &str, claims: Value) -> String {
+        signed(Header::default(), kid, claims)
+    }
+
+    fn signed(mut header: Header, kid: &str, claims: Value) -> String {
+        header.kid = Some(kid.into());
+        encode(&header, &claims, &EncodingKey::from_secret(SECRET)).unwrap()
+    }
+
+    #[test]
+    fn test_verify_jwt() {
+        let exp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 600;
+        let provider = provider();
+
+        let user = provider
+            .verify(&token(
+                "test",
+                json!({
+                    "iss": "https://issuer.example.com",
+                    "email": "jane@example.com",
+                    "sub": "jane",
+                    "exp": exp,
+                }),
+            ))
+            .unwrap();
+        assert_eq!(user.email, "jane@example.com");
+        assert_eq!(user.username, "jane");
+
+        let other_issuer = json!({
+            "iss": "https://other.example.com",
+            "email": "jane@example.com",
+            "exp": exp,
+        });
+        assert!(provider.verify(&token("test", other_issuer)).is_err());
+        let unknown_key = json!({
+            "iss": "https://issuer.example.com",
+            "email": "jane@example.com",
+            "exp": exp,
+        });
+        assert!(matches!(
+            provider.verify(&token("rotated", unknown_key.clone())),
+            Err(VerifyError::UnknownKey(_))
+        ));
+        // the key is for HS256, other algorithms in the header are refused
+        let other_alg = signed(Header::new(Algorithm::HS384), "test", unknown_key);
+        assert!(provider.verify(&other_alg).is_err());
+    }
+}
@@ -63,6 +63,20 @@ fn extract_tenant_from_query_params(query_str: &str) -> Option<&str> {
         .and_then(|tenant_query_param| tenant_query_param.split('=').nth(1))
 }
 
+/// Entries ending with `/*` exclude the path and everything under it, other
+/// entries have to match exactly.
+fn is_excluded_path(excluded: &str, path: &str) -> bool {
+    match excluded.strip_suffix("/*") {
+        Some(prefix) => {
+            path == prefix
+                || path
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        }
+        None => excluded == path,
+    }
+}
+
 impl<S, B> Service<ServiceRequest> for TenantMiddleware<S>
 where
     S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
@@ -95,7 +109,8 @@ where
             let request_path = req.uri().path().replace(&base, "");
             let is_excluded: bool = app_state
                 .tenant_middleware_exclusion_list
-                .contains(&request_path);
+                .iter()
+                .any(|excluded| is_excluded_path(excluded, &request_path));
 
             if !is_excluded && app_state.enable_tenant_and_scope {
                 debug!(
@@ -116,7 +131,7 @@ where
                     .or_else(|| extract_tenant_from_query_params(req.query_string()));
 
                 let validated_tenant: Tenant = match tenant {
-                    Some(val) if app_state.tenants.contains(val) => {
+                    Some(val) if app_state.is_active_tenant(val) => {
                         Tenant(String::from(val))
                     }
                     Some(_) => {
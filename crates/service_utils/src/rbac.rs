@@ -0,0 +1,445 @@
+use std::future::{ready, Ready};
+
+use actix_web::{
+    error, http::StatusCode, web::Data, Error, FromRequest, HttpMessage, HttpRequest,
+};
+use diesel::{
+    sql_types::{Nullable, Text},
+    QueryableByName, RunQueryDsl,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use superposition_types::{
+    result::{self as superposition, AppError, ResponseError},
+    SuperpositionUser, User,
+};
+
+use crate::{
+    db::utils::cac_namespace,
+    service::types::{AppState, Tenant},
+};
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum Role {
+    Viewer,
+    Editor,
+    /// Can only write experiments, the contexts of their variants included.
+    /// Concluding needs write access to what the winner is applied to.
+    ExperimentOwner,
+    Admin,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum Resource {
+    Dimension,
+    DefaultConfig,
+    Context,
+    Function,
+    Type,
+    Experiment,
+    /// Exposures and conversions reported for the variants of experiments
+    ExperimentEvent,
+    /// Experimentation policy of the tenant, changed by admins only
+    TenantSettings,
+    RoleBinding,
+    ApiKey,
+    Webhook,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, strum_macros::Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Action {
+    Read,
+    Write,
+}
+
+impl Resource {
+    /// Resources which hand out access or data, only admins can see or change
+    /// them.
+    pub fn is_admin_only(&self) -> bool {
+        matches!(
+            self,
+            Resource::RoleBinding | Resource::ApiKey | Resource::Webhook
+        )
+    }
+}
+
+impl Role {
+    pub fn allows(&self, resource: Resource, action: Action) -> bool {
+        match (self, action) {
+            (Role::Admin, _) => true,
+            (_, Action::Read) => !resource.is_admin_only(),
+            (Role::Viewer, Action::Write) => false,
+            (_, Action::Write) if resource == Resource::TenantSettings => false,
+            (Role::Editor, Action::Write) => {
+                !matches!(resource, Resource::Experiment | Resource::ExperimentEvent)
+                    && !resource.is_admin_only()
+            }
+            (Role::ExperimentOwner, Action::Write) => {
+                matches!(resource, Resource::Experiment | Resource::ExperimentEvent)
+            }
+        }
+    }
+}
+
+/// A role binding of the caller, optionally narrowed to one resource type and
+/// to keys starting with a prefix.
+#[derive(Debug, Clone)]
+pub struct Grant {
+    pub role: Role,
+    pub resource: Option<Resource>,
+    pub key_prefix: Option<String>,
+}
+
+impl Grant {
+    /// `key` is the config key being changed, writes which don't name keys
+    /// are only allowed by grants without a prefix.
+    pub fn permits(&self, resource: Resource, action: Action, key: Option<&str>) -> bool {
+        self.role.allows(resource, action)
+            && self.resource.map_or(true, |r| r == resource)
+            && match (&self.key_prefix, key) {
+                (None, _) => true,
+                (Some(prefix), Some(key)) => key.starts_with(prefix.as_str()),
+                (Some(_), None) => action == Action::Read,
+            }
+    }
+}
+
+/// What an API key may do, each scope stands for the grants of a role binding.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum ApiKeyScope {
+    ReadConfig,
+    WriteDimension,
+    WriteDefaultConfig,
+    WriteContext,
+    WriteFunction,
+    WriteType,
+    ManageExperiments,
+    /// Only report exposures and conversions, for the services serving
+    /// variants
+    ReportEvents,
+    Admin,
+}
+
+impl ApiKeyScope {
+    pub fn grant(&self) -> Grant {
+        let (role, resource) = match self {
+            ApiKeyScope::ReadConfig => (Role::Viewer, None),
+            ApiKeyScope::WriteDimension => (Role::Editor, Some(Resource::Dimension)),
+            ApiKeyScope::WriteDefaultConfig => {
+                (Role::Editor, Some(Resource::DefaultConfig))
+            }
+            ApiKeyScope::WriteContext => (Role::Editor, Some(Resource::Context)),
+            ApiKeyScope::WriteFunction => (Role::Editor, Some(Resource::Function)),
+            ApiKeyScope::WriteType => (Role::Editor, Some(Resource::Type)),
+            ApiKeyScope::ManageExperiments => (Role::ExperimentOwner, None),
+            ApiKeyScope::ReportEvents => {
+                (Role::ExperimentOwner, Some(Resource::ExperimentEvent))
+            }
+            ApiKeyScope::Admin => (Role::Admin, None),
+        };
+        Grant {
+            role,
+            resource,
+            key_prefix: None,
+        }
+    }
+}
+
+/// Grants of the API key a request was authenticated with, set by the auth
+/// middleware. Keys are limited to their scopes even when RBAC is disabled.
+#[derive(Debug, Clone)]
+pub struct ApiKeyGrants(pub Vec<Grant>);
+
+#[derive(QueryableByName)]
+struct BindingRow {
+    #[diesel(sql_type = Text)]
+    role: String,
+    #[diesel(sql_type = Nullable<Text>)]
+    resource: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    key_prefix: Option<String>,
+}
+
+fn forbidden(message: String) -> AppError {
+    AppError::ResponseError(ResponseError {
+        message,
+        status_code: StatusCode::FORBIDDEN,
+    })
+}
+
+/// The permissions of the caller in the current tenant. Handlers call
+/// `require` or `require_keys` before writing, when RBAC is disabled every
+/// check passes.
+#[derive(Clone)]
+pub struct Authorizer {
+    email: String,
+    grants: Vec<Grant>,
+    enforced: bool,
+}
+
+impl Authorizer {
+    pub fn new(email: String, grants: Vec<Grant>, enforced: bool) -> Self {
+        Authorizer {
+            email,
+            grants,
+            enforced,
+        }
+    }
+
+    pub fn grants(&self) -> &[Grant] {
+        &self.grants
+    }
+
+    pub fn require(
+        &self,
+        resource: Resource,
+        action: Action,
+    ) -> superposition::Result<()> {
+        if !self.enforced
+            || self
+                .grants
+                .iter()
+                .any(|grant| grant.permits(resource, action, None))
+        {
+            return Ok(());
+        }
+        Err(forbidden(format!(
+            "{} is not allowed to {action} {resource}",
+            self.email
+        )))
+    }
+
+    pub fn require_keys<I, K>(
+        &self,
+        resource: Resource,
+        action: Action,
+        keys: I,
+    ) -> superposition::Result<()>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<str>,
+    {
+        if !self.enforced {
+            return Ok(());
+        }
+        // writes touching no keys still need some grant on the resource
+        if !self.grants.iter().any(|grant| {
+            grant.role.allows(resource, action)
+                && grant.resource.map_or(true, |r| r == resource)
+        }) {
+            return Err(forbidden(format!(
+                "{} is not allowed to {action} {resource}",
+                self.email
+            )));
+        }
+        for key in keys {
+            let key = key.as_ref();
+            if !self
+                .grants
+                .iter()
+                .any(|grant| grant.permits(resource, action, Some(key)))
+            {
+                return Err(forbidden(format!(
+                    "{} is not allowed to {action} {resource} {key}",
+                    self.email
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn load(req: &HttpRequest) -> Result<Self, Error> {
+        let app_state = req.app_data::<Data<AppState>>().ok_or_else(|| {
+            log::error!("Authorizer: AppState not set");
+            error::ErrorInternalServerError("")
+        })?;
+        let user = req.extensions().get::<User>().cloned();
+        if let Some(ApiKeyGrants(grants)) = req.extensions().get::<ApiKeyGrants>() {
+            let email = user.map(|user| user.get_email()).unwrap_or_default();
+            return Ok(Authorizer::new(email, grants.clone(), true));
+        }
+        if !app_state.enable_rbac {
+            let email = user.map(|user| user.get_email()).unwrap_or_default();
+            return Ok(Authorizer::new(email, Vec::new(), false));
+        }
+        let user = user.ok_or_else(|| {
+            error::ErrorUnauthorized(json!({ "message": "authentication required" }))
+        })?;
+        let email = user.get_email();
+        if app_state.rbac_admins.contains(&email) {
+            let admin = Grant {
+                role: Role::Admin,
+                resource: None,
+                key_prefix: None,
+            };
+            return Ok(Authorizer::new(email, vec![admin], true));
+        }
+
+        // role bindings live in the CAC schema of the tenant
+        let tenant = req.extensions().get::<Tenant>().cloned();
+        let namespace = cac_namespace(
+            app_state.enable_tenant_and_scope,
+            tenant.as_ref().map(|tenant| tenant.as_str()),
+        )
+        .ok_or_else(|| {
+            log::error!("Authorizer: Tenant not set in request extensions");
+            error::ErrorInternalServerError("")
+        })?;
+        let mut conn = app_state.db_pool.get_conn(namespace).map_err(|e| {
+            log::info!("Unable to get db connection from pool, error: {e}");
+            error::ErrorInternalServerError("")
+        })?;
+        let rows = diesel::sql_query(
+            "SELECT role, resource, key_prefix FROM role_bindings WHERE subject = $1",
+        )
+        .bind::<Text, _>(&email)
+        .load::<BindingRow>(&mut conn)
+        .map_err(|e| {
+            log::error!("failed to load role bindings of {email}: {e}");
+            error::ErrorInternalServerError("")
+        })?;
+
+        let grants = rows
+            .into_iter()
+            .filter_map(|row| {
+                let role = row.role.parse::<Role>().ok()?;
+                let resource = match row.resource {
+                    Some(resource) => Some(resource.parse::<Resource>().ok()?),
+                    None => None,
+                };
+                Some(Grant {
+                    role,
+                    resource,
+                    key_prefix: row.key_prefix,
+                })
+            })
+            .collect();
+        Ok(Authorizer::new(email, grants, true))
+    }
+}
+
+impl FromRequest for Authorizer {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        ready(Authorizer::load(req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_prefix_grants() {
+        let payments_editor = Grant {
+            role: Role::Editor,
+            resource: None,
+            key_prefix: Some("payments.".into()),
+        };
+        let authorizer =
+            Authorizer::new("dev@example.com".into(), vec![payments_editor], true);
+
+        assert!(authorizer
+            .require_keys(Resource::DefaultConfig, Action::Write, ["payments.timeout"])
+            .is_ok());
+        assert!(authorizer
+            .require_keys(
+                Resource::Context,
+                Action::Write,
+                ["payments.timeout", "checkout.theme"]
+            )
+            .is_err());
+        assert!(authorizer
+            .require(Resource::DefaultConfig, Action::Write)
+            .is_err());
+        assert!(authorizer
+            .require_keys(Resource::Experiment, Action::Write, ["payments.timeout"])
+            .is_err());
+        assert!(authorizer
+            .require(Resource::Dimension, Action::Read)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_role_permissions() {
+        assert!(Role::ExperimentOwner.allows(Resource::Experiment, Action::Write));
+        assert!(!Role::ExperimentOwner.allows(Resource::Dimension, Action::Write));
+        assert!(!Role::ExperimentOwner.allows(Resource::Context, Action::Write));
+        assert!(!Role::Viewer.allows(Resource::Context, Action::Write));
+        assert!(!Role::Editor.allows(Resource::RoleBinding, Action::Read));
+        assert!(Role::Admin.allows(Resource::RoleBinding, Action::Write));
+        assert!(!Role::Editor.allows(Resource::ApiKey, Action::Write));
+        assert!(Role::ExperimentOwner.allows(Resource::ExperimentEvent, Action::Write));
+        assert!(!Role::Viewer.allows(Resource::ExperimentEvent, Action::Write));
+        assert!(!Role::ExperimentOwner.allows(Resource::TenantSettings, Action::Write));
+        assert!(!Role::Editor.allows(Resource::TenantSettings, Action::Write));
+        assert!(Role::Editor.allows(Resource::TenantSettings, Action::Read));
+        assert!(Role::Admin.allows(Resource::TenantSettings, Action::Write));
+    }
+
+    #[test]
+    fn test_api_key_scopes() {
+        let grants = [ApiKeyScope::ReadConfig, ApiKeyScope::WriteContext]
+            .iter()
+            .map(ApiKeyScope::grant)
+            .collect();
+        let authorizer = Authorizer::new("ci".into(), grants, true);
+
+        assert!(authorizer
+            .require(Resource::Dimension, Action::Read)
+            .is_ok());
+        assert!(authorizer
+            .require_keys(Resource::Context, Action::Write, ["payments.timeout"])
+            .is_ok());
+        assert!(authorizer
+            .require(Resource::DefaultConfig, Action::Write)
+            .is_err());
+        assert!(authorizer.require(Resource::ApiKey, Action::Read).is_err());
+
+        let reporter =
+            Authorizer::new("sdk".into(), vec![ApiKeyScope::ReportEvents.grant()], true);
+        assert!(reporter
+            .require(Resource::ExperimentEvent, Action::Write)
+            .is_ok());
+        assert!(reporter
+            .require(Resource::Experiment, Action::Write)
+            .is_err());
+        assert!(reporter.require(Resource::Context, Action::Read).is_err());
+    }
+}
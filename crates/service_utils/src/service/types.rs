@@ -7,7 +7,7 @@ use std::{
     collections::HashSet,
     future::{ready, Ready},
     str::FromStr,
-    sync::Arc,
+    sync::{Arc, RwLock},
 };
 
 use actix_web::{error, web::Data, Error, FromRequest, HttpMessage};
@@ -40,16 +40,37 @@ pub enum AppHeader {
 pub struct AppState {
     pub cac_host: String,
     pub app_env: AppEnv,
-    pub tenants: HashSet<String>,
+    /// active tenants, shared across workers as tenants can be provisioned at runtime
+    pub tenants: Arc<RwLock<HashSet<String>>>,
     pub cac_version: String,
     pub db_pool: PgSchemaManager,
     pub default_config_validation_schema: JSONSchema,
     pub meta_schema: JSONSchema,
+    /// overlap flags of tenants which didn't set their own
     pub experimentation_flags: ExperimentationFlags,
     pub snowflake_generator: Arc<Mutex<SnowflakeIdGenerator>>,
     pub enable_tenant_and_scope: bool,
     pub tenant_middleware_exclusion_list: HashSet<String>,
     pub service_prefix: String,
+    pub enable_rbac: bool,
+    /// users with the admin role in every tenant, used to create the first
+    /// role bindings
+    pub rbac_admins: HashSet<String>,
+    /// users who can create, list and deactivate tenants, checked whether or
+    /// not RBAC is enabled
+    pub tenant_admins: HashSet<String>,
+}
+
+impl AppState {
+    pub fn is_active_tenant(&self, tenant: &str) -> bool {
+        self.tenants
+            .read()
+            .map(|tenants| tenants.contains(tenant))
+            .unwrap_or_else(|_| {
+                log::error!("tenants lock poisoned");
+                false
+            })
+    }
 }
 
 impl FromStr for AppEnv {
@@ -1,14 +1,24 @@
-use actix_web::dev::Service;
-use actix_web::HttpMessage;
+mod tenant;
+
 use actix_web::{web, web::get, web::scope, web::Data, App, HttpResponse, HttpServer};
-use context_aware_config::api::*;
+use context_aware_config::api::{
+    webhooks::{delivery::DeliveryPolicy, secrets::WebhookSecretKey},
+    *,
+};
+use context_aware_config::experimentation::InProcessCacBackend;
 use context_aware_config::helpers::{
     get_default_config_validation_schema, get_meta_schema,
 };
-use experimentation_platform::api::*;
-use std::sync::Arc;
+use experimentation_platform::api::{
+    experiments::{
+        bucketing::BucketingCache,
+        cac::{CacBackend, HttpCacBackend},
+        saga::ReconcilePolicy,
+    },
+    *,
+};
+use std::sync::{Arc, RwLock};
 use std::{collections::HashSet, io::Result};
-use superposition_types::User;
 
 use snowflake::SnowflakeIdGenerator;
 use std::{sync::Mutex, time::Duration};
@@ -19,11 +29,15 @@ use frontend::types::Envs as UIEnvs;
 use leptos::*;
 use leptos_actix::{generate_route_list, LeptosRoutes};
 use service_utils::{
+    db::partitions::EventLogPolicy,
     db::pgschema_manager::PgSchemaManager,
-    db::utils::init_pool_manager,
+    db::utils::{init_pool_manager, register_tenant_pools},
     helpers::{get_from_env_or_default, get_from_env_unsafe},
     middlewares::{
-        app_scope::AppExecutionScopeMiddlewareFactory, tenant::TenantMiddlewareFactory,
+        app_scope::AppExecutionScopeMiddlewareFactory,
+        auth::{authentication_enabled, init_auth_providers, AuthMiddlewareFactory},
+        request_id::RequestIdMiddlewareFactory,
+        tenant::TenantMiddlewareFactory,
     },
     service::types::{AppEnv, AppScope, AppState, ExperimentationFlags},
 };
@@ -74,10 +88,22 @@ async fn main() -> Result<()> {
     let app_env: AppEnv = get_from_env_unsafe("APP_ENV").expect("APP_ENV is not set");
     let enable_tenant_and_scope: bool = get_from_env_unsafe("ENABLE_TENANT_AND_SCOPE")
         .expect("ENABLE_TENANT_AND_SCOPE is not set");
-    let tenants: HashSet<String> = get_from_env_unsafe::<String>("TENANTS")
-        .expect("TENANTS is not set")
+    let env_tenants: HashSet<String> =
+        get_from_env_or_default::<String>("TENANTS", String::new())
+            .split(',')
+            .filter(|tenant| !tenant.is_empty())
+            .map(|tenant| tenant.to_string())
+            .collect::<HashSet<String>>();
+    let enable_rbac: bool = get_from_env_or_default("ENABLE_RBAC", false);
+    let rbac_admins = get_from_env_or_default::<String>("RBAC_ADMINS", String::new())
+        .split(',')
+        .filter(|admin| !admin.is_empty())
+        .map(String::from)
+        .collect::<HashSet<String>>();
+    let tenant_admins = get_from_env_or_default::<String>("TENANT_ADMINS", String::new())
         .split(',')
-        .map(|tenant| tenant.to_string())
+        .filter(|admin| !admin.is_empty())
+        .map(String::from)
         .collect::<HashSet<String>>();
     let tenant_middleware_exclusion_list =
         get_from_env_unsafe::<String>("TENANT_MIDDLEWARE_EXCLUSION_LIST")
@@ -86,13 +112,44 @@ async fn main() -> Result<()> {
             .map(String::from)
             .collect::<HashSet<String>>();
 
-    let schema_manager: PgSchemaManager = init_pool_manager(
-        tenants.clone(),
-        enable_tenant_and_scope,
-        app_env,
-        max_pool_size,
-    )
-    .await;
+    let schema_manager: PgSchemaManager =
+        init_pool_manager(enable_tenant_and_scope, max_pool_size).await;
+
+    let tenants: HashSet<String> = if enable_tenant_and_scope {
+        let tenants =
+            tenant::helpers::init_tenant_registry(&schema_manager, &env_tenants)
+                .expect("Failed to load tenants");
+        for tenant in tenants.iter() {
+            register_tenant_pools(&schema_manager, tenant)
+                .unwrap_or_else(|e| panic!("Failed to connect to {tenant}: {e}"));
+        }
+        tenants
+    } else {
+        env_tenants
+    };
+
+    // `superposition migrate` applies pending migrations to every tenant and exits
+    let migrate_only = std::env::args().nth(1).is_some_and(|arg| arg == "migrate");
+    if migrate_only && !enable_tenant_and_scope {
+        println!("Tenancy is disabled, run the diesel migrations instead");
+        return Ok(());
+    }
+    if migrate_only
+        || (enable_tenant_and_scope
+            && get_from_env_or_default("MIGRATE_ON_STARTUP", true))
+    {
+        let reports = tenant::helpers::migrate_tenants(&schema_manager, &tenants)
+            .unwrap_or_else(|e| panic!("Failed to migrate tenant schemas: {e}"));
+        for report in reports.iter() {
+            match migrate_only {
+                true => println!("{report}"),
+                false => log::info!("{report}"),
+            }
+        }
+    }
+    if migrate_only {
+        return Ok(());
+    }
 
     /****** EXPERIMENTATION PLATFORM ENVs *********/
 
@@ -129,43 +186,96 @@ async fn main() -> Result<()> {
     });
 
     let snowflake_generator = Arc::new(Mutex::new(SnowflakeIdGenerator::new(1, 1)));
+    let active_tenants = Arc::new(RwLock::new(tenants));
+    let cac_host = cac_host.to_owned() + base.as_str();
+    let app_state = Data::new(AppState {
+        db_pool: schema_manager.clone(),
+        default_config_validation_schema: get_default_config_validation_schema(),
+        cac_host: cac_host.to_owned(),
+        cac_version: cac_version.to_owned(),
+
+        experimentation_flags: ExperimentationFlags {
+            allow_same_keys_overlapping_ctx: allow_same_keys_overlapping_ctx.to_owned(),
+            allow_diff_keys_overlapping_ctx: allow_diff_keys_overlapping_ctx.to_owned(),
+            allow_same_keys_non_overlapping_ctx: allow_same_keys_non_overlapping_ctx
+                .to_owned(),
+        },
+
+        snowflake_generator: snowflake_generator.clone(),
+        meta_schema: get_meta_schema(),
+        app_env: app_env.to_owned(),
+        enable_tenant_and_scope: enable_tenant_and_scope.to_owned(),
+        tenants: active_tenants.clone(),
+        tenant_middleware_exclusion_list: tenant_middleware_exclusion_list.to_owned(),
+        service_prefix: service_prefix_str.to_owned(),
+        enable_rbac,
+        rbac_admins: rbac_admins.to_owned(),
+        tenant_admins: tenant_admins.to_owned(),
+    });
+    // experiments reach CAC in process unless it is deployed separately
+    let cac_backend_kind: String =
+        get_from_env_or_default("CAC_BACKEND", "in_process".into());
+    let cac_backend: Arc<dyn CacBackend> = match cac_backend_kind.as_str() {
+        "http" => Arc::new(HttpCacBackend::new(cac_host.to_owned())),
+        "in_process" => Arc::new(InProcessCacBackend::new(
+            app_state.clone(),
+            get_from_env_or_default("CAC_SHARED_TRANSACTION", false),
+        )),
+        other => panic!("Unknown CAC_BACKEND {other}, expected in_process or http"),
+    };
+    let reconcile_policy = ReconcilePolicy::from_env();
+    // over HTTP the reconciler goes through the permission checks of CAC, which
+    // it can't pass without credentials of its own
+    if cac_backend_kind == "http"
+        && authentication_enabled()
+        && reconcile_policy.authorization.is_none()
+    {
+        panic!("CAC_SERVICE_AUTHORIZATION has to be set when CAC_BACKEND is http and authentication is on");
+    }
+    actix_web::rt::spawn(tenant::helpers::run_event_log_maintenance(
+        schema_manager.clone(),
+        active_tenants.clone(),
+        enable_tenant_and_scope,
+        EventLogPolicy::from_env(),
+        Duration::from_secs(get_from_env_or_default(
+            "EVENT_LOG_MAINTENANCE_INTERVAL_SECS",
+            3600,
+        )),
+    ));
+    let webhook_secret_key = WebhookSecretKey::from_env()
+        .await
+        .unwrap_or_else(|e| panic!("Failed to load the webhook secret key: {e}"));
+    actix_web::rt::spawn(webhooks::delivery::run_webhook_dispatcher(
+        schema_manager.clone(),
+        active_tenants.clone(),
+        enable_tenant_and_scope,
+        webhook_secret_key.clone(),
+        DeliveryPolicy::from_env(),
+    ));
+    actix_web::rt::spawn(experiments::saga::run_experiment_reconciler(
+        schema_manager.clone(),
+        active_tenants.clone(),
+        enable_tenant_and_scope,
+        cac_backend.clone(),
+        reconcile_policy,
+    ));
+    let bucketing_cache = Data::new(BucketingCache::default());
+    let auth_providers = init_auth_providers()
+        .await
+        .unwrap_or_else(|e| panic!("Failed to set up authentication: {e}"));
 
     HttpServer::new(move || {
         let leptos_options = &conf.leptos_options;
         let site_root = &leptos_options.site_root;
         let leptos_envs = ui_envs.clone();
-        let cac_host = cac_host.to_owned() + base.as_str();
         App::new()
-            .wrap_fn(|req, srv| {
-                let user = User::default();
-                req.extensions_mut().insert::<User>(user);
-                srv.call(req)
-            })
+            .wrap(AuthMiddlewareFactory::new(auth_providers.clone()))
             .wrap(TenantMiddlewareFactory)
-            .app_data(Data::new(AppState {
-                db_pool: schema_manager.clone(),
-                default_config_validation_schema: get_default_config_validation_schema(),
-                cac_host: cac_host.to_owned(),
-                cac_version: cac_version.to_owned(),
-
-                experimentation_flags: ExperimentationFlags {
-                    allow_same_keys_overlapping_ctx: allow_same_keys_overlapping_ctx
-                        .to_owned(),
-                    allow_diff_keys_overlapping_ctx: allow_diff_keys_overlapping_ctx
-                        .to_owned(),
-                    allow_same_keys_non_overlapping_ctx:
-                        allow_same_keys_non_overlapping_ctx.to_owned(),
-                },
-
-                snowflake_generator: snowflake_generator.clone(),
-                meta_schema: get_meta_schema(),
-                app_env: app_env.to_owned(),
-                enable_tenant_and_scope: enable_tenant_and_scope.to_owned(),
-                tenants: tenants.to_owned(),
-                tenant_middleware_exclusion_list: tenant_middleware_exclusion_list
-                    .to_owned(),
-                service_prefix: service_prefix_str.to_owned(),
-            }))
+            .wrap(RequestIdMiddlewareFactory)
+            .app_data(app_state.clone())
+            .app_data(Data::from(cac_backend.clone()))
+            .app_data(Data::new(webhook_secret_key.clone()))
+            .app_data(bucketing_cache.clone())
             .wrap(
                 actix_web::middleware::DefaultHeaders::new()
                     .add(("X-SERVER-VERSION", cac_version.to_string()))
@@ -221,6 +331,36 @@ async fn main() -> Result<()> {
                             .wrap(AppExecutionScopeMiddlewareFactory::new(AppScope::CAC))
                             .service(type_templates::endpoints()),
                     )
+                    .service(
+                        scope("/rbac")
+                            .wrap(AppExecutionScopeMiddlewareFactory::new(AppScope::CAC))
+                            .service(rbac::endpoints()),
+                    )
+                    .service(
+                        scope("/api-keys")
+                            .wrap(AppExecutionScopeMiddlewareFactory::new(AppScope::CAC))
+                            .service(api_keys::endpoints()),
+                    )
+                    .service(
+                        scope("/webhooks")
+                            .wrap(AppExecutionScopeMiddlewareFactory::new(AppScope::CAC))
+                            .service(webhooks::endpoints()),
+                    )
+                    .service(scope("/tenants").service(tenant::endpoints()))
+                    .service(
+                        scope("/settings")
+                            .wrap(AppExecutionScopeMiddlewareFactory::new(
+                                AppScope::EXPERIMENTATION,
+                            ))
+                            .service(settings::endpoints()),
+                    )
+                    .service(
+                        scope("/layers")
+                            .wrap(AppExecutionScopeMiddlewareFactory::new(
+                                AppScope::EXPERIMENTATION,
+                            ))
+                            .service(layers::endpoints()),
+                    )
                     .service(
                         experiments::endpoints(scope("/experiments")).wrap(
                             AppExecutionScopeMiddlewareFactory::new(AppScope::EXPERIMENTATION),
@@ -0,0 +1,177 @@
+use actix_web::{
+    delete, get,
+    http::StatusCode,
+    post,
+    web::{Data, Json, Path},
+    HttpResponse, Scope,
+};
+use chrono::Utc;
+use diesel::{Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use regex::Regex;
+use service_utils::{
+    db::utils::{deregister_tenant_pools, register_tenant_pools, ADMIN_NAMESPACE},
+    service::types::AppState,
+};
+use superposition_macros::{bad_argument, not_found, response_error, unexpected_error};
+use superposition_types::{result as superposition, SuperpositionUser, User};
+
+use super::{
+    helpers::{migrate_tenant_schemas, provision_tenant_schemas, TENANT_NAME_REGEX},
+    types::{tenants, CreateTenantRequest, Tenant},
+};
+
+pub fn endpoints() -> Scope {
+    Scope::new("")
+        .service(create_tenant)
+        .service(list_tenants)
+        .service(deactivate_tenant)
+}
+
+fn update_active_tenants(
+    state: &AppState,
+    tenant: &str,
+    is_active: bool,
+) -> superposition::Result<()> {
+    let mut active_tenants = state
+        .tenants
+        .write()
+        .map_err(|_| unexpected_error!("tenants lock poisoned"))?;
+    if is_active {
+        active_tenants.insert(tenant.to_string());
+    } else {
+        active_tenants.remove(tenant);
+    }
+    Ok(())
+}
+
+/// Tenants are managed by the `TENANT_ADMINS`, even with RBAC disabled, role
+/// bindings are per tenant and can't grant this.
+fn require_platform_admin(state: &AppState, user: &User) -> superposition::Result<()> {
+    if !state.tenant_admins.contains(&user.get_email()) {
+        return Err(response_error!(
+            StatusCode::FORBIDDEN,
+            format!("{} is not allowed to manage tenants", user.get_email())
+        ));
+    }
+    Ok(())
+}
+
+#[post("")]
+async fn create_tenant(
+    state: Data<AppState>,
+    req: Json<CreateTenantRequest>,
+    user: User,
+) -> superposition::Result<HttpResponse> {
+    require_platform_admin(&state, &user)?;
+    let tenant_name = req.into_inner().name;
+    let name_regex = Regex::new(TENANT_NAME_REGEX).map_err(|err| {
+        unexpected_error!("could not parse regex due to: {}", err.to_string())
+    })?;
+    if !name_regex.is_match(&tenant_name) {
+        return Err(bad_argument!(
+            "The tenant name {} is invalid, it should obey the regex {}",
+            tenant_name,
+            TENANT_NAME_REGEX
+        ));
+    }
+
+    let mut conn = state
+        .db_pool
+        .get_conn(ADMIN_NAMESPACE.to_string())
+        .map_err(|e| unexpected_error!(e))?;
+
+    let tenant =
+        conn.transaction::<_, superposition::AppError, _>(|transaction_conn| {
+            let existing = tenants::table
+                .find(&tenant_name)
+                .first::<Tenant>(transaction_conn)
+                .optional()?;
+            match existing {
+                Some(tenant) if tenant.is_active => {
+                    Err(bad_argument!("tenant {} already exists", tenant_name))
+                }
+                Some(_) => {
+                    // migrations added while the tenant was inactive never ran on it
+                    migrate_tenant_schemas(transaction_conn, &tenant_name)?;
+                    Ok(diesel::update(tenants::table.find(&tenant_name))
+                        .set((
+                            tenants::is_active.eq(true),
+                            tenants::last_modified_at.eq(Utc::now()),
+                        ))
+                        .get_result::<Tenant>(transaction_conn)?)
+                }
+                None => {
+                    provision_tenant_schemas(
+                        transaction_conn,
+                        &tenant_name,
+                        &user.get_email(),
+                    )?;
+                    Ok(diesel::insert_into(tenants::table)
+                        .values((
+                            tenants::name.eq(&tenant_name),
+                            tenants::created_by.eq(user.get_email()),
+                        ))
+                        .get_result::<Tenant>(transaction_conn)?)
+                }
+            }
+        })?;
+
+    register_tenant_pools(&state.db_pool, &tenant.name).map_err(|e| {
+        log::error!("failed to create pools for {}: {e}", tenant.name);
+        unexpected_error!(
+            "tenant {} was created but could not be connected to",
+            tenant.name
+        )
+    })?;
+    update_active_tenants(&state, &tenant.name, true)?;
+    log::info!("tenant {} created by {}", tenant.name, user.get_email());
+
+    Ok(HttpResponse::Created().json(tenant))
+}
+
+#[get("")]
+async fn list_tenants(
+    state: Data<AppState>,
+    user: User,
+) -> superposition::Result<Json<Vec<Tenant>>> {
+    require_platform_admin(&state, &user)?;
+    let mut conn = state
+        .db_pool
+        .get_conn(ADMIN_NAMESPACE.to_string())
+        .map_err(|e| unexpected_error!(e))?;
+    let result = tenants::table
+        .order(tenants::name.asc())
+        .load::<Tenant>(&mut conn)?;
+    Ok(Json(result))
+}
+
+/// Stops serving a tenant, its schemas and data are kept so it can be
+/// created again later.
+#[delete("/{tenant}")]
+async fn deactivate_tenant(
+    state: Data<AppState>,
+    path: Path<String>,
+    user: User,
+) -> superposition::Result<Json<Tenant>> {
+    require_platform_admin(&state, &user)?;
+    let tenant_name = path.into_inner();
+    let mut conn = state
+        .db_pool
+        .get_conn(ADMIN_NAMESPACE.to_string())
+        .map_err(|e| unexpected_error!(e))?;
+    let tenant = diesel::update(tenants::table.find(&tenant_name))
+        .set((
+            tenants::is_active.eq(false),
+            tenants::last_modified_at.eq(Utc::now()),
+        ))
+        .get_result::<Tenant>(&mut conn)
+        .optional()?
+        .ok_or(not_found!("tenant {} doesn't exist", tenant_name))?;
+
+    update_active_tenants(&state, &tenant.name, false)?;
+    deregister_tenant_pools(&state.db_pool, &tenant.name)
+        .map_err(|e| unexpected_error!(e))?;
+    log::info!("tenant {} deactivated by {}", tenant.name, user.get_email());
+
+    Ok(Json(tenant))
+}
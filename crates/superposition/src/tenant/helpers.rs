@@ -0,0 +1,240 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use actix_web::{rt, web};
+use diesel::{
+    connection::SimpleConnection, sql_types::Text, ExpressionMethods, PgConnection,
+    QueryDsl, RunQueryDsl,
+};
+use service_utils::db::{
+    migrations::{run_pending_migrations, MigrationReport, SchemaMigration},
+    partitions::{
+        ensure_event_log_partitions, maintain_event_log, EventLogPolicy, Month,
+        PartitionReport,
+    },
+    pgschema_manager::PgSchemaManager,
+    utils::tenant_namespaces,
+    utils::ADMIN_NAMESPACE,
+};
+use superposition_macros::{bad_argument, unexpected_error};
+use superposition_types::result as superposition;
+
+use super::types::tenants;
+
+/// Tenant names end up in schema names, postgres limits identifiers to 63
+/// bytes and `_experimentation` takes 16 of them.
+pub const TENANT_NAME_REGEX: &str = "^[a-z][a-z0-9_]{0,46}$";
+
+const TENANT_REGISTRY_SQL: &str = r#"
+CREATE SCHEMA IF NOT EXISTS superposition;
+CREATE TABLE IF NOT EXISTS superposition.tenants (
+    name TEXT PRIMARY KEY,
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    created_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_modified_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+"#;
+
+/// Creates the tenant registry if needed, records the tenants from the
+/// `TENANTS` env which were created before the registry existed, and returns
+/// the active tenants.
+pub fn init_tenant_registry(
+    schema_manager: &PgSchemaManager,
+    seed_tenants: &HashSet<String>,
+) -> anyhow::Result<HashSet<String>> {
+    let mut conn = schema_manager.get_conn(ADMIN_NAMESPACE.to_string())?;
+    conn.batch_execute(TENANT_REGISTRY_SQL)?;
+    for tenant in seed_tenants.iter() {
+        diesel::insert_into(tenants::table)
+            .values((
+                tenants::name.eq(tenant),
+                tenants::created_by.eq("superposition"),
+            ))
+            .on_conflict_do_nothing()
+            .execute(&mut conn)?;
+    }
+    let active = tenants::table
+        .filter(tenants::is_active.eq(true))
+        .select(tenants::name)
+        .load::<String>(&mut conn)?;
+    Ok(active.into_iter().collect())
+}
+
+fn schema_exists(conn: &mut PgConnection, schema: &str) -> superposition::Result<bool> {
+    #[derive(diesel::QueryableByName)]
+    struct SchemaCount {
+        #[diesel(sql_type = diesel::sql_types::BigInt)]
+        count: i64,
+    }
+    let result = diesel::sql_query(
+        "SELECT count(*) AS count FROM information_schema.schemata WHERE schema_name = $1",
+    )
+    .bind::<Text, _>(schema)
+    .get_result::<SchemaCount>(conn)?;
+    Ok(result.count > 0)
+}
+
+const CAC_MIGRATIONS: &[SchemaMigration] =
+    context_aware_config::db::migrations::MIGRATIONS;
+const EXPERIMENTATION_MIGRATIONS: &[SchemaMigration] =
+    experimentation_platform::db::migrations::MIGRATIONS;
+
+/// Applies pending migrations to the schemas of every tenant, stopping at the
+/// first schema that fails.
+pub fn migrate_tenants(
+    schema_manager: &PgSchemaManager,
+    tenants: &HashSet<String>,
+) -> anyhow::Result<Vec<MigrationReport>> {
+    let mut tenants = tenants.iter().collect::<Vec<&String>>();
+    tenants.sort();
+    let mut reports = Vec::new();
+    for tenant in tenants {
+        let [cac_schema, experimentation_schema] = tenant_namespaces(tenant);
+        for (schema, migrations) in [
+            (cac_schema, CAC_MIGRATIONS),
+            (experimentation_schema, EXPERIMENTATION_MIGRATIONS),
+        ] {
+            let mut conn = schema_manager.get_conn(schema.clone())?;
+            reports.push(run_pending_migrations(&mut conn, &schema, migrations)?);
+        }
+    }
+    Ok(reports)
+}
+
+/// Pools and schemas holding an `event_log`, without tenancy the migrations of
+/// both services run on `public`.
+fn event_log_schemas(
+    enable_tenant_and_scope: bool,
+    tenants: &HashSet<String>,
+) -> Vec<(String, String)> {
+    if !enable_tenant_and_scope {
+        return vec![("cac_v1".to_string(), "public".to_string())];
+    }
+    let mut tenants = tenants.iter().collect::<Vec<&String>>();
+    tenants.sort();
+    tenants
+        .into_iter()
+        .flat_map(|tenant| tenant_namespaces(tenant))
+        .map(|namespace| (namespace.clone(), namespace))
+        .collect()
+}
+
+/// Runs partition maintenance on the `event_log` of every schema, a schema
+/// that fails is logged and does not stop the others.
+pub fn maintain_event_logs(
+    schema_manager: &PgSchemaManager,
+    schemas: &[(String, String)],
+    policy: &EventLogPolicy,
+) -> Vec<PartitionReport> {
+    let today = chrono::Utc::now().date_naive();
+    let mut reports = Vec::new();
+    for (namespace, schema) in schemas.iter() {
+        let result = schema_manager
+            .get_conn(namespace.clone())
+            .and_then(|mut conn| maintain_event_log(&mut conn, schema, policy, today));
+        match result {
+            Ok(report) => reports.push(report),
+            Err(e) => log::error!("event_log maintenance failed on {schema}: {e}"),
+        }
+    }
+    reports
+}
+
+/// Background task creating `event_log` partitions ahead of time and
+/// retiring old ones, every `every` starting right away.
+pub async fn run_event_log_maintenance(
+    schema_manager: PgSchemaManager,
+    tenants: Arc<RwLock<HashSet<String>>>,
+    enable_tenant_and_scope: bool,
+    policy: EventLogPolicy,
+    every: Duration,
+) {
+    let mut interval = rt::time::interval(every);
+    loop {
+        interval.tick().await;
+        let schemas = match tenants.read() {
+            Ok(tenants) => event_log_schemas(enable_tenant_and_scope, &tenants),
+            Err(e) => {
+                log::error!("failed to read the active tenants: {e}");
+                continue;
+            }
+        };
+        let schema_manager = schema_manager.clone();
+        let policy = policy.clone();
+        let result =
+            web::block(move || maintain_event_logs(&schema_manager, &schemas, &policy))
+                .await;
+        match result {
+            Ok(reports) => reports
+                .iter()
+                .filter(|report| !report.is_empty())
+                .for_each(|report| log::info!("{report}")),
+            Err(e) => log::error!("event_log maintenance did not run: {e}"),
+        }
+    }
+}
+
+/// Runs the pending migrations of both services on the schemas of a tenant.
+pub fn migrate_tenant_schemas(
+    conn: &mut PgConnection,
+    tenant: &str,
+) -> superposition::Result<()> {
+    let [cac_schema, experimentation_schema] = tenant_namespaces(tenant);
+    run_pending_migrations(conn, &cac_schema, CAC_MIGRATIONS)
+        .and_then(|_| {
+            run_pending_migrations(
+                conn,
+                &experimentation_schema,
+                EXPERIMENTATION_MIGRATIONS,
+            )
+        })
+        .map_err(|e| {
+            log::error!("failed to migrate schemas of {tenant}: {e}");
+            unexpected_error!("failed to migrate schemas of {}", tenant)
+        })?;
+    Ok(())
+}
+
+/// Creates the CAC and experimentation schemas of a tenant and runs the
+/// migrations of both services on them. Must be called inside a transaction.
+pub fn provision_tenant_schemas(
+    conn: &mut PgConnection,
+    tenant: &str,
+    created_by: &str,
+) -> superposition::Result<()> {
+    let [cac_schema, experimentation_schema] = tenant_namespaces(tenant);
+    for schema in [&cac_schema, &experimentation_schema] {
+        if schema_exists(conn, schema)? {
+            return Err(bad_argument!(
+                "schema {} already exists, it has to be dropped before {} can be created",
+                schema,
+                tenant
+            ));
+        }
+    }
+
+    migrate_tenant_schemas(conn, tenant)?;
+
+    // the migrations only create partitions up to the time they were written
+    let months_ahead = EventLogPolicy::from_env().months_ahead;
+    let current = Month::of(chrono::Utc::now().date_naive());
+    for schema in [&cac_schema, &experimentation_schema] {
+        ensure_event_log_partitions(conn, schema, current, months_ahead).map_err(
+            |e| {
+                log::error!("failed to create event_log partitions in {schema}: {e}");
+                unexpected_error!("failed to create schemas for {}", tenant)
+            },
+        )?;
+    }
+
+    diesel::sql_query(format!(
+        "INSERT INTO {cac_schema}.dimensions (dimension, priority, created_at, created_by, schema, function_name) VALUES ('variantIds', 1, CURRENT_TIMESTAMP, $1, '{{\"type\": \"string\",\"pattern\": \".*\"}}'::json, null)"
+    ))
+    .bind::<Text, _>(created_by)
+    .execute(conn)?;
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use diesel::{Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+
+diesel::table! {
+    superposition.tenants (name) {
+        name -> Text,
+        is_active -> Bool,
+        created_by -> Text,
+        created_at -> Timestamptz,
+        last_modified_at -> Timestamptz,
+    }
+}
+
+#[derive(Queryable, Selectable, Serialize, Clone, Debug)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = tenants)]
+pub struct Tenant {
+    pub name: String,
+    pub is_active: bool,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub last_modified_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateTenantRequest {
+    pub name: String,
+}
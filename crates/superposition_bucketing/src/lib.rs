@@ -0,0 +1,211 @@
+//! Bucketing of units into the variants of experiments, shared by the
+//! experimentation platform and its clients so both serve a unit the same
+//! variant.
+
+/// Buckets units are tossed into, a bucket is one percent of traffic.
+pub const TOTAL_BUCKETS: i32 = 100;
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// What bucketing needs of an experiment.
+pub trait BucketedExperiment {
+    fn layer(&self) -> Option<&str>;
+    /// Percent of traffic each variant serves
+    fn traffic_percentage(&self) -> i32;
+    /// Percent of the buckets left by the global holdout held out of the
+    /// experiment, the last ones
+    fn holdout_percentage(&self) -> i32;
+    /// First bucket of the traffic the experiment serves
+    fn slice_start(&self) -> i32;
+}
+
+/// What bucketing needs of a variant.
+pub trait BucketedVariant {
+    fn is_experimental(&self) -> bool;
+    /// Added to an experiment in progress and serving no traffic until the
+    /// experiment is ramped again
+    fn awaiting_ramp(&self) -> bool;
+}
+
+/// FNV-1a, which unlike the std hasher is the same on every platform and
+/// release, so every client buckets a unit the same way.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Toss of the unit `identifier` names, like a user id, always the same
+/// bucket for the same identifier.
+pub fn toss_of(identifier: &str) -> i8 {
+    (fnv1a(identifier.as_bytes()) % TOTAL_BUCKETS as u64) as i8
+}
+
+/// Whether `toss` is past the `buckets` experiments serve, in the global
+/// holdout.
+pub fn globally_held_out(toss: i8, buckets: i32) -> bool {
+    (buckets..TOTAL_BUCKETS).contains(&i32::from(toss))
+}
+
+/// Bucket `toss` falls in for the experiments of `layer`, out of the
+/// `buckets` experiments serve. Every layer shuffles the buckets its own way,
+/// so which experiment of one layer a unit is in says nothing about the
+/// others. Tosses outside the buckets are kept as is.
+pub fn layer_bucket(layer: Option<&str>, toss: i8, buckets: i32) -> i32 {
+    let toss = i32::from(toss);
+    let Some(layer) = layer else {
+        return toss;
+    };
+    if !(0..buckets).contains(&toss) {
+        return toss;
+    }
+    let hash = fnv1a(layer.as_bytes());
+    // multipliers coprime with the buckets make the shuffle a permutation
+    let modulus = buckets as u64;
+    let mut multiplier = (hash % modulus) as i32 | 1;
+    while gcd(multiplier, buckets) != 1 {
+        multiplier += 2;
+    }
+    let offset = ((hash >> 32) % modulus) as i32;
+    (toss * multiplier + offset) % buckets
+}
+
+/// Whether `toss` is in the holdout of `experiment`, the last buckets of the
+/// ones experiments serve.
+pub fn held_out_of(experiment: &impl BucketedExperiment, toss: i8, buckets: i32) -> bool {
+    let bucket = layer_bucket(experiment.layer(), toss, buckets);
+    (buckets - experiment.holdout_percentage()..buckets).contains(&bucket)
+}
+
+/// Variant of `experiment` the unit of `toss` is served, out of its
+/// `variants`. A negative toss always gets the first experimental variant,
+/// for testing.
+pub fn choose_variant<'a, V: BucketedVariant>(
+    experiment: &impl BucketedExperiment,
+    variants: &'a [V],
+    toss: i8,
+    buckets: i32,
+) -> Option<&'a V> {
+    // variants added while the experiment ran serve nothing until its next ramp
+    let variants = variants
+        .iter()
+        .filter(|variant| !variant.awaiting_ramp())
+        .collect::<Vec<&V>>();
+    if toss < 0 {
+        return variants
+            .into_iter()
+            .find(|variant| variant.is_experimental());
+    }
+    if globally_held_out(toss, buckets) || held_out_of(experiment, toss, buckets) {
+        return None;
+    }
+    // experiments of a layer serve disjoint slices of its buckets
+    let bucket =
+        layer_bucket(experiment.layer(), toss, buckets) - experiment.slice_start();
+    let traffic = experiment.traffic_percentage();
+    let range = traffic * variants.len() as i32;
+    if bucket < 0 || bucket >= range {
+        return None;
+    }
+    variants.get((bucket / traffic) as usize).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Experiment {
+        layer: Option<&'static str>,
+        traffic_percentage: i32,
+        holdout_percentage: i32,
+        slice_start: i32,
+    }
+
+    impl BucketedExperiment for Experiment {
+        fn layer(&self) -> Option<&str> {
+            self.layer
+        }
+
+        fn traffic_percentage(&self) -> i32 {
+            self.traffic_percentage
+        }
+
+        fn holdout_percentage(&self) -> i32 {
+            self.holdout_percentage
+        }
+
+        fn slice_start(&self) -> i32 {
+            self.slice_start
+        }
+    }
+
+    struct Variant(&'static str, bool, bool);
+
+    impl BucketedVariant for Variant {
+        fn is_experimental(&self) -> bool {
+            self.1
+        }
+
+        fn awaiting_ramp(&self) -> bool {
+            self.2
+        }
+    }
+
+    #[test]
+    fn test_toss_of() {
+        // pinned, a change moves units between variants on every client
+        assert_eq!(toss_of("user-1"), 8);
+        assert_eq!(toss_of("user-2"), 41);
+        assert_eq!(toss_of("42"), 91);
+
+        let mut shuffled = (0..90)
+            .map(|toss| layer_bucket(Some("checkout"), toss, 90))
+            .collect::<Vec<i32>>();
+        shuffled.sort();
+        assert_eq!(shuffled, (0..90).collect::<Vec<i32>>());
+        assert_eq!(layer_bucket(None, 42, 90), 42);
+        assert_eq!(layer_bucket(Some("checkout"), 95, 90), 95);
+    }
+
+    #[test]
+    fn test_choose_variant() {
+        let experiment = Experiment {
+            layer: None,
+            traffic_percentage: 10,
+            holdout_percentage: 10,
+            slice_start: 0,
+        };
+        let chosen = |variants: &[Variant], toss: i8| {
+            choose_variant(&experiment, variants, toss, 100).map(|variant| variant.0)
+        };
+        let variants = [
+            Variant("control", false, false),
+            Variant("test", true, false),
+        ];
+        assert_eq!(chosen(&variants, 5), Some("control"));
+        assert_eq!(chosen(&variants, 15), Some("test"));
+        assert_eq!(chosen(&variants, 25), None);
+        assert_eq!(chosen(&variants, 95), None);
+        assert_eq!(chosen(&variants, -1), Some("test"));
+
+        // a negative toss is served nothing without an experimental variant
+        assert_eq!(chosen(&variants[..1], -1), None);
+
+        let variants = [
+            Variant("control", false, false),
+            Variant("late", true, true),
+            Variant("test", true, false),
+        ];
+        assert_eq!(chosen(&variants, 15), Some("test"));
+        assert_eq!(chosen(&variants, 25), None);
+        assert_eq!(chosen(&variants, -1), Some("test"));
+    }
+}
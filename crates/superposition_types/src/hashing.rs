@@ -0,0 +1,30 @@
+use serde_json::Value;
+
+/// Renders `v` with object keys and array items sorted, so that equivalent
+/// conditions render the same way regardless of the order they were written in.
+pub fn json_to_sorted_string(v: &Value) -> String {
+    match v {
+        Value::Object(m) => {
+            let mut keys = m.keys().collect::<Vec<&String>>();
+            keys.sort();
+            keys.into_iter()
+                .map(|key| format!("{key}:{}$", json_to_sorted_string(&m[key])))
+                .collect()
+        }
+        Value::String(m) => m.to_string(),
+        Value::Number(m) => m.to_string(),
+        Value::Bool(m) => m.to_string(),
+        Value::Null => String::from("null"),
+        Value::Array(m) => {
+            let mut items = m.iter().map(json_to_sorted_string).collect::<Vec<String>>();
+            items.sort();
+            items.join(",")
+        }
+    }
+}
+
+/// Hash used as the id of contexts and overrides, the same for every client
+/// that hashes them.
+pub fn hash_json(v: &Value) -> String {
+    blake3::hash(json_to_sorted_string(v).as_bytes()).to_string()
+}
@@ -16,6 +16,7 @@ async fn main() -> std::io::Result<()> {
                 "dev".to_string(),
                 Duration::new(10, 0),
                 "http://localhost:8080".into(),
+                std::env::var("SUPERPOSITION_API_KEY").ok(),
             )
             .await
             .expect(format!("{}: Failed to acquire cac_client", "dev").as_str())
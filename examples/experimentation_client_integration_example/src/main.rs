@@ -13,6 +13,8 @@ async fn main() -> std::io::Result<()> {
         tenant: "dev".to_string(),
         hostname: "http://localhost:8080".to_string(),
         poll_frequency: 10,
+        api_key: std::env::var("SUPERPOSITION_API_KEY").ok(),
+        key_prefixes: None,
     };
     let client = std::sync::Arc::new(exp::Client::new(client_configuration));
     rt::spawn(client.clone().run_polling_updates());
@@ -11,7 +11,11 @@ const char *expt_last_error_message(void);
 
 void expt_free_string(char *s);
 
-int expt_new_client(const char *tenant, unsigned long update_frequency, const char *hostname);
+int expt_new_client(const char *tenant,
+                    unsigned long update_frequency,
+                    const char *hostname,
+                    const char *api_key,
+                    const char *key_prefixes);
 
 void expt_start_polling_update(const char *tenant);
 
@@ -21,6 +25,14 @@ struct Arc_Client *expt_get_client(const char *tenant);
 
 char *expt_get_applicable_variant(struct Arc_Client *client, const char *c_context, short toss);
 
+char *expt_get_holdout_status(struct Arc_Client *client, const char *c_context, short toss);
+
+int expt_report_exposures(struct Arc_Client *client,
+                          const char *c_unit_id,
+                          const char *c_variant_ids);
+
+int expt_toss_of(const char *c_identifier, short *toss);
+
 char *expt_get_satisfied_experiments(struct Arc_Client *client,
                                      const char *c_context,
                                      const char *filter_prefix);